@@ -0,0 +1,129 @@
+use crate::analysis::schedule::scheduled_frames;
+use crate::parsers::encoding::{DatabaseType, LDFScheduleCommand, Name};
+use crate::{Database, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/*
+ * What a responder declares it can do in its node capability file, checked against a cluster it's composed
+ * into. NCFs can't be parsed yet, so capabilities are given by the caller.
+ */
+
+const BITRATE_TOLERANCE: f64 = 0.5; // bps, LDFs give kbps with a few decimals
+
+// bps
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BitrateCapability {
+    Automatic { min: f64, max: f64 },
+    Select(Vec<f64>),
+    Fixed(f64),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeCapability {
+    pub bitrate: BitrateCapability,
+    pub diagnostic_class: u8,      // 1 to 3
+    pub max_frames: Option<usize>, // scheduled frames it can publish or subscribe
+}
+
+impl BitrateCapability {
+    pub fn supports(&self, bitrate: f64) -> bool {
+        let eq = |a: f64, b: f64| (a - b).abs() < BITRATE_TOLERANCE;
+        match self {
+            BitrateCapability::Automatic { min, max } => {
+                (min - BITRATE_TOLERANCE..max + BITRATE_TOLERANCE).contains(&bitrate)
+            }
+            BitrateCapability::Select(rates) => rates.iter().any(|r| eq(*r, bitrate)),
+            BitrateCapability::Fixed(rate) => eq(*rate, bitrate),
+        }
+    }
+}
+
+impl std::fmt::Display for BitrateCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BitrateCapability::Automatic { min, max } => write!(f, "{}-{} bps", min, max),
+            BitrateCapability::Select(rates) => {
+                let rates: Vec<String> = rates.iter().map(|r| r.to_string()).collect();
+                write!(f, "{} bps", rates.join(", "))
+            }
+            BitrateCapability::Fixed(rate) => write!(f, "{} bps", rate),
+        }
+    }
+}
+
+impl Database {
+    // issues by node with the LDF's LIN_speed and schedules, diagnostic_class is what the cluster needs, class 2
+    // and up also need MasterReq and SlaveResp scheduled
+    pub fn check_capabilities(
+        &self,
+        nodes: &BTreeMap<Name, NodeCapability>,
+        diagnostic_class: u8,
+    ) -> Result<Vec<(Name, Error)>, Error> {
+        let DatabaseType::LDF(data) = &self.extra else {
+            return Err(Error::IncompatibleDatabase);
+        };
+        let scheduled = scheduled_frames(data);
+        let commands: Vec<&LDFScheduleCommand> = data
+            .schedule_tables
+            .values()
+            .flatten()
+            .map(|(c, _)| c)
+            .collect();
+        let diagnostics = commands.contains(&&LDFScheduleCommand::CommanderReq)
+            && commands.contains(&&LDFScheduleCommand::ResponderResp);
+
+        let mut ret = Vec::new();
+        for (node, capability) in nodes {
+            let mut issue = |hint: String, e: Error| {
+                ret.push((node.clone(), Error::Hint(hint, Box::new(e))));
+            };
+            if !data.responders.contains_key(node) {
+                issue("not a responder".to_string(), Error::UnknownNode);
+                continue;
+            }
+            if !capability.bitrate.supports(data.bitrate) {
+                issue(
+                    format!(
+                        "LIN_speed is {} bps, supports {}",
+                        data.bitrate, capability.bitrate
+                    ),
+                    Error::UnsupportedBitrate,
+                );
+            }
+            if capability.diagnostic_class < diagnostic_class {
+                issue(
+                    format!(
+                        "class {} needed, supports {}",
+                        diagnostic_class, capability.diagnostic_class
+                    ),
+                    Error::UnsupportedDiagnosticClass,
+                );
+            } else if capability.diagnostic_class >= 2 && !diagnostics {
+                issue(
+                    format!(
+                        "class {} needs MasterReq and SlaveResp scheduled",
+                        capability.diagnostic_class
+                    ),
+                    Error::UnsupportedDiagnosticClass,
+                );
+            }
+            if let Some(max) = capability.max_frames {
+                let frames: HashSet<&Name> = self
+                    .frames_published_by(node)
+                    .chain(self.frames_subscribed_by(node))
+                    .map(|(n, _)| n)
+                    .filter(|n| scheduled.contains(n))
+                    .collect();
+                if frames.len() > max {
+                    issue(
+                        format!("{} frames scheduled, supports {}", frames.len(), max),
+                        Error::TooManyFrames,
+                    );
+                }
+            }
+        }
+        Ok(ret)
+    }
+}
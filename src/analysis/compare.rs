@@ -0,0 +1,212 @@
+use crate::codec::replay::LogFrame;
+use crate::parsers::encoding::Name;
+use crate::Database;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// choices for Database::compare_logs
+#[derive(Clone, Debug)]
+pub struct CompareOptions {
+    pub tolerance: f64,                   // physical, for signals not in tolerances
+    pub tolerances: HashMap<String, f64>, // by signal name
+    pub timing_tolerance: f64,            // mean periods further apart than this fraction drift
+    pub channel: Option<String>,          // only frames from this channel, in both logs
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.0,
+            tolerances: HashMap::new(),
+            timing_tolerance: 0.1,
+            channel: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LogDifference {
+    FrameCount {
+        message: Name,
+        first: usize,
+        second: usize,
+    },
+    Period {
+        message: Name,
+        first: f64, // mean ms between frames
+        second: f64,
+    },
+    Value {
+        message: Name,
+        signal: Name,
+        count: usize, // frames where it differs, or is only in one because of multiplexing
+        max_delta: f64, // over frames where both have a value
+        time: f64,    // ms into the first log of the first difference
+    },
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LogComparison {
+    pub differences: Vec<LogDifference>,
+    pub frames: (usize, usize),  // frames of known messages
+    pub unknown: (usize, usize), // frames with ids not in the database
+}
+
+impl LogComparison {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+impl std::fmt::Display for LogComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for d in &self.differences {
+            match d {
+                LogDifference::FrameCount {
+                    message,
+                    first,
+                    second,
+                } => writeln!(f, "~ frame {}: {} frames -> {}", message, first, second)?,
+                LogDifference::Period {
+                    message,
+                    first,
+                    second,
+                } => writeln!(
+                    f,
+                    "~ frame {}: period {:.3} ms -> {:.3} ms",
+                    message, first, second
+                )?,
+                LogDifference::Value {
+                    message,
+                    signal,
+                    count,
+                    max_delta,
+                    time,
+                } => writeln!(
+                    f,
+                    "~ signal {} in {}: {} value(s) differ by up to {}, first at {:.6} s",
+                    signal,
+                    message,
+                    count,
+                    max_delta,
+                    time / 1000.0
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn mean_period(frames: &[&LogFrame]) -> Option<f64> {
+    let (first, last) = (frames.first()?, frames.last()?);
+    (frames.len() > 1).then(|| (last.time - first.time) / (frames.len() - 1) as f64)
+}
+
+impl Database {
+    // decodes both logs, matching each message's frames in order, for checking that a change left the bus
+    // behaving the same
+    pub fn compare_logs(
+        &self,
+        first: &[LogFrame],
+        second: &[LogFrame],
+        options: &CompareOptions,
+    ) -> LogComparison {
+        let mut ids: HashMap<u32, Option<&Name>> = HashMap::new();
+        let [first, second] = [first, second].map(|frames| {
+            let mut by_message: HashMap<&Name, Vec<&LogFrame>> = HashMap::new();
+            let mut unknown = 0;
+            for frame in frames {
+                if options
+                    .channel
+                    .as_ref()
+                    .is_some_and(|c| *c != frame.channel)
+                {
+                    continue;
+                }
+                let name = *ids
+                    .entry(frame.id)
+                    .or_insert_with(|| self.message_by_id(frame.id).map(|(n, _)| n));
+                match name {
+                    Some(name) => by_message.entry(name).or_default().push(frame),
+                    None => unknown += 1,
+                }
+            }
+            (by_message, unknown)
+        });
+        let ((first, unknown_first), (second, unknown_second)) = (first, second);
+        let count = |m: &HashMap<&Name, Vec<&LogFrame>>| m.values().map(Vec::len).sum();
+        let mut ret = LogComparison {
+            frames: (count(&first), count(&second)),
+            unknown: (unknown_first, unknown_second),
+            ..Default::default()
+        };
+
+        let mut names: Vec<&Name> = first.keys().chain(second.keys()).copied().collect();
+        names.sort_by(|a, b| (self.messages[*a].id, a).cmp(&(self.messages[*b].id, b)));
+        names.dedup();
+        for name in names {
+            let msg = &self.messages[name];
+            let a = first.get(name).map_or(&[][..], Vec::as_slice);
+            let b = second.get(name).map_or(&[][..], Vec::as_slice);
+            if a.len() != b.len() {
+                ret.differences.push(LogDifference::FrameCount {
+                    message: name.clone(),
+                    first: a.len(),
+                    second: b.len(),
+                });
+            }
+            if let (Some(pa), Some(pb)) = (mean_period(a), mean_period(b)) {
+                if (pa - pb).abs() > pa * options.timing_tolerance {
+                    ret.differences.push(LogDifference::Period {
+                        message: name.clone(),
+                        first: pa,
+                        second: pb,
+                    });
+                }
+            }
+
+            // per signal, (count, max delta, first time), in frame order
+            let mut values: Vec<(&Name, (usize, f64, f64))> = Vec::new();
+            for (fa, fb) in a.iter().zip(b) {
+                let va = self.frame_values(msg, &fa.data);
+                let vb = self.frame_values(msg, &fb.data);
+                let only_b = vb.iter().filter(|(n, _)| !va.iter().any(|(m, _)| m == n));
+                let signals = va.iter().chain(only_b).map(|(n, _)| *n);
+                for signal in signals {
+                    let get =
+                        |v: &[(&Name, f64)]| v.iter().find(|(n, _)| *n == signal).map(|v| v.1);
+                    let tolerance = options
+                        .tolerances
+                        .get(&**signal)
+                        .unwrap_or(&options.tolerance);
+                    let delta = match (get(&va), get(&vb)) {
+                        (Some(x), Some(y)) if (x - y).abs() > *tolerance => Some((x - y).abs()),
+                        (Some(_), Some(_)) => continue,
+                        _ => None, // multiplexed differently
+                    };
+                    let at = match values.iter().position(|(n, _)| *n == signal) {
+                        Some(at) => at,
+                        None => {
+                            values.push((signal, (0, 0.0, fa.time)));
+                            values.len() - 1
+                        }
+                    };
+                    let (count, max, _) = &mut values[at].1;
+                    *count += 1;
+                    *max = max.max(delta.unwrap_or(0.0));
+                }
+            }
+            for (signal, (count, max_delta, time)) in values {
+                ret.differences.push(LogDifference::Value {
+                    message: name.clone(),
+                    signal: signal.clone(),
+                    count,
+                    max_delta,
+                    time,
+                });
+            }
+        }
+        ret
+    }
+}
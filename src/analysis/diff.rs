@@ -0,0 +1,352 @@
+use crate::parsers::encoding::{
+    AttributeValue, DatabaseType, Encoding, LDFData, Name, Node, SignalGroup,
+};
+use crate::Database;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Database,
+    Node,
+    Signal,
+    Frame,
+    SporadicFrame,
+    EventFrame,
+    ScheduleTable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Change {
+    pub entity: EntityKind,
+    pub name: String,
+    pub kind: ChangeKind,
+    pub fields: Vec<FieldChange>, // only for modified
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DatabaseDiff {
+    pub changes: Vec<Change>,
+}
+
+impl DatabaseDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl std::fmt::Display for DatabaseDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for c in &self.changes {
+            let entity = match c.entity {
+                EntityKind::Database => "database",
+                EntityKind::Node => "node",
+                EntityKind::Signal => "signal",
+                EntityKind::Frame => "frame",
+                EntityKind::SporadicFrame => "sporadic frame",
+                EntityKind::EventFrame => "event triggered frame",
+                EntityKind::ScheduleTable => "schedule table",
+            };
+            let sym = match c.kind {
+                ChangeKind::Added => '+',
+                ChangeKind::Removed => '-',
+                ChangeKind::Modified => '~',
+            };
+            writeln!(f, "{} {} {}", sym, entity, c.name)?;
+            for field in &c.fields {
+                writeln!(f, "    {}: {} -> {}", field.field, field.old, field.new)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Fields(Vec<FieldChange>);
+
+impl Fields {
+    fn cmp<T: PartialEq + Display>(&mut self, field: &str, old: T, new: T) {
+        if old != new {
+            self.0.push(FieldChange {
+                field: field.to_string(),
+                old: old.to_string(),
+                new: new.to_string(),
+            });
+        }
+    }
+
+    fn cmp_list<T: PartialEq + Display>(&mut self, field: &str, old: &[T], new: &[T]) {
+        if old != new {
+            let join = |l: &[T]| {
+                let l: Vec<String> = l.iter().map(|i| i.to_string()).collect();
+                format!("[{}]", l.join(", "))
+            };
+            self.cmp(field, join(old), join(new));
+        }
+    }
+}
+
+fn diff_maps<T>(
+    changes: &mut Vec<Change>,
+    entity: EntityKind,
+    old: &HashMap<Name, T>,
+    new: &HashMap<Name, T>,
+    compare: impl Fn(&mut Fields, &T, &T),
+) {
+    let names: BTreeSet<&Name> = old.keys().chain(new.keys()).collect();
+    for name in names {
+        let (kind, fields) = match (old.get(name), new.get(name)) {
+            (Some(o), Some(n)) => {
+                let mut fields = Fields(Vec::new());
+                compare(&mut fields, o, n);
+                if fields.0.is_empty() {
+                    continue;
+                }
+                (ChangeKind::Modified, fields.0)
+            }
+            (Some(_), None) => (ChangeKind::Removed, Vec::new()),
+            _ => (ChangeKind::Added, Vec::new()),
+        };
+        changes.push(Change {
+            entity,
+            name: name.to_string(),
+            kind,
+            fields,
+        });
+    }
+}
+
+fn opt<T: Display>(v: &Option<T>) -> String {
+    v.as_ref().map_or("none".to_string(), |v| v.to_string())
+}
+
+fn signal_groups(g: &HashMap<Name, SignalGroup>) -> String {
+    let mut ret: Vec<String> = g
+        .iter()
+        .map(|(k, v)| format!("{} = [{}] x{}", k, v.signals.join(", "), v.repetitions))
+        .collect();
+    ret.sort();
+    ret.join(", ")
+}
+
+fn attributes(a: &HashMap<String, AttributeValue>) -> String {
+    let mut ret: Vec<String> = a.iter().map(|(k, v)| format!("{} = {}", k, v)).collect();
+    ret.sort();
+    ret.join(", ")
+}
+
+fn encodings(e: &Option<Vec<Encoding>>) -> String {
+    match e {
+        Some(e) => e
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("; "),
+        None => "none".to_string(),
+    }
+}
+
+fn diff_ldf(changes: &mut Vec<Change>, old: &LDFData, new: &LDFData) {
+    let mut fields = Fields(Vec::new());
+    fields.cmp("bitrate", old.bitrate, new.bitrate);
+    fields.cmp("channel name", &old.postfix, &new.postfix);
+    fields.cmp("commander", &old.commander, &new.commander);
+    fields.cmp("time base", old.time_base, new.time_base);
+    fields.cmp("jitter", old.jitter, new.jitter);
+    if !fields.0.is_empty() {
+        changes.push(Change {
+            entity: EntityKind::Database,
+            name: String::new(),
+            kind: ChangeKind::Modified,
+            fields: fields.0,
+        });
+    }
+
+    diff_maps(
+        changes,
+        EntityKind::Node,
+        &old.responders,
+        &new.responders,
+        |f, o, n| {
+            f.cmp("protocol", &o.protocol, &n.protocol);
+            f.cmp("configured NAD", o.configured_nad, n.configured_nad);
+            f.cmp("initial NAD", opt(&o.initial_nad), opt(&n.initial_nad));
+            let product_id = |p: &Option<(u16, u16, u8)>| {
+                p.map_or("none".to_string(), |(s, f, v)| {
+                    format!("0x{:04X}, 0x{:04X}, {}", s, f, v)
+                })
+            };
+            f.cmp(
+                "product id",
+                product_id(&o.product_id),
+                product_id(&n.product_id),
+            );
+            f.cmp(
+                "response error",
+                opt(&o.response_error),
+                opt(&n.response_error),
+            );
+            f.cmp_list(
+                "subscribed signals",
+                &o.subscribed_signals,
+                &n.subscribed_signals,
+            );
+            let frames = |l: &[(Name, Option<u16>)]| -> Vec<String> {
+                l.iter()
+                    .map(|(f, id)| match id {
+                        Some(id) => format!("{} = 0x{:02X}", f, id),
+                        None => f.to_string(),
+                    })
+                    .collect()
+            };
+            f.cmp_list(
+                "configurable frames",
+                &frames(&o.configurable_frames),
+                &frames(&n.configurable_frames),
+            );
+        },
+    );
+    diff_maps(
+        changes,
+        EntityKind::SporadicFrame,
+        &old.sporadic_frames,
+        &new.sporadic_frames,
+        |f, o, n| f.cmp_list("frames", o, n),
+    );
+    diff_maps(
+        changes,
+        EntityKind::EventFrame,
+        &old.event_frames,
+        &new.event_frames,
+        |f, o, n| {
+            f.cmp("collision resolver", &o.0, &n.0);
+            f.cmp("id", format!("0x{:02X}", o.1), format!("0x{:02X}", n.1));
+            f.cmp_list("frames", &o.2, &n.2);
+        },
+    );
+    diff_maps(
+        changes,
+        EntityKind::ScheduleTable,
+        &old.schedule_tables,
+        &new.schedule_tables,
+        |f, o, n| {
+            for i in 0..o.len().max(n.len()) {
+                let entry = |t: &Vec<_>| {
+                    t.get(i).map_or("none".to_string(), |(cmd, delay)| {
+                        format!("{} delay {} ms", cmd, delay)
+                    })
+                };
+                f.cmp(&format!("entry {}", i), entry(o), entry(n));
+            }
+        },
+    );
+}
+
+pub fn diff(old: &Database, new: &Database) -> DatabaseDiff {
+    let mut changes = Vec::new();
+    match (&old.extra, &new.extra) {
+        (DatabaseType::LDF(o), DatabaseType::LDF(n)) => diff_ldf(&mut changes, o, n),
+        (DatabaseType::DBC(_), DatabaseType::DBC(_)) => {
+            let nodes = |db: &Database| -> HashMap<Name, Node> {
+                db.nodes()
+                    .into_iter()
+                    .map(|n| (n.name.clone(), n))
+                    .collect()
+            };
+            diff_maps(
+                &mut changes,
+                EntityKind::Node,
+                &nodes(old),
+                &nodes(new),
+                |f, o, n| {
+                    f.cmp("description", opt(&o.description), opt(&n.description));
+                    f.cmp(
+                        "attributes",
+                        attributes(&o.attributes),
+                        attributes(&n.attributes),
+                    );
+                },
+            );
+        }
+        (o, n) => {
+            let mut fields = Fields(Vec::new());
+            fields.cmp("type", o.name(), n.name());
+            if !fields.0.is_empty() {
+                changes.push(Change {
+                    entity: EntityKind::Database,
+                    name: String::new(),
+                    kind: ChangeKind::Modified,
+                    fields: fields.0,
+                });
+            }
+        }
+    }
+    diff_maps(
+        &mut changes,
+        EntityKind::Signal,
+        &old.signals,
+        &new.signals,
+        |f, o, n| {
+            f.cmp("kind", o.kind, n.kind);
+            f.cmp("little endian", o.little_endian, n.little_endian);
+            f.cmp("bit start", o.bit_start, n.bit_start);
+            f.cmp("bit width", o.bit_width, n.bit_width);
+            f.cmp("init value", o.init_value, n.init_value);
+            f.cmp(
+                "encodings",
+                encodings(&o.encodings),
+                encodings(&n.encodings),
+            );
+            f.cmp("description", opt(&o.description), opt(&n.description));
+            f.cmp(
+                "attributes",
+                attributes(&o.attributes),
+                attributes(&n.attributes),
+            );
+        },
+    );
+    diff_maps(
+        &mut changes,
+        EntityKind::Frame,
+        &old.messages,
+        &new.messages,
+        |f, o, n| {
+            f.cmp("id", format!("0x{:02X}", o.id), format!("0x{:02X}", n.id));
+            f.cmp("sender", &o.sender, &n.sender);
+            f.cmp("byte width", o.byte_width, n.byte_width);
+            f.cmp("cycle time", opt(&o.cycle_time), opt(&n.cycle_time));
+            f.cmp("send type", opt(&o.send_type), opt(&n.send_type));
+            f.cmp("protocol", o.protocol, n.protocol);
+            f.cmp("description", opt(&o.description), opt(&n.description));
+            f.cmp(
+                "attributes",
+                attributes(&o.attributes),
+                attributes(&n.attributes),
+            );
+            f.cmp(
+                "signal groups",
+                signal_groups(&o.signal_groups),
+                signal_groups(&n.signal_groups),
+            );
+            f.cmp_list("signals", &o.signals, &n.signals);
+        },
+    );
+    changes.sort_by(|a, b| (a.entity, &a.name).cmp(&(b.entity, &b.name)));
+    DatabaseDiff { changes }
+}
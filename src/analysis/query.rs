@@ -0,0 +1,179 @@
+use crate::parsers::encoding::{DatabaseType, Message, Name, Node, NodeRole};
+use crate::transform::filter::glob_match;
+use crate::writers::ldf::sorted;
+use crate::{Database, Error};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// glob with '*' and '?', or a regex between slashes, e.g. /^Door.*(Left|Right)$/
+pub(crate) enum Pattern {
+    Glob(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub(crate) fn parse(pattern: &str) -> Result<Pattern, Error> {
+        match pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+            Some(re) => Regex::new(re)
+                .map(Pattern::Regex)
+                .map_err(|e| Error::Hint(e.to_string(), Box::new(Error::InvalidPattern))),
+            None => Ok(Pattern::Glob(pattern.to_string())),
+        }
+    }
+
+    pub(crate) fn matches(&self, text: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => glob_match(glob, text),
+            Pattern::Regex(re) => re.is_match(text),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignalMatch<'a> {
+    pub signal: &'a Name,
+    pub message: Option<&'a Name>, // none if not in a frame
+    pub id: Option<u32>,
+    pub bit_start: u16,
+    pub bit_width: u16,
+}
+
+impl Database {
+    // by id then name
+    fn frames_where(
+        &self,
+        f: impl Fn(&Message) -> bool,
+    ) -> impl Iterator<Item = (&Name, &Message)> {
+        let mut frames: Vec<_> = self.messages.iter().filter(|(_, m)| f(m)).collect();
+        frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+        frames.into_iter()
+    }
+
+    pub fn frames_published_by<'a>(
+        &'a self,
+        node: &'a str,
+    ) -> impl Iterator<Item = (&'a Name, &'a Message)> {
+        self.frames_where(move |m| &*m.sender == node)
+    }
+
+    // frames with at least one signal the node receives, the LIN commander receives everything it doesn't publish
+    pub fn frames_subscribed_by<'a>(
+        &'a self,
+        node: &'a str,
+    ) -> impl Iterator<Item = (&'a Name, &'a Message)> {
+        self.frames_where(move |m| {
+            let signals = || m.signals.iter().chain(m.mux_signals.keys());
+            match &self.extra {
+                DatabaseType::LDF(data) => {
+                    (&*data.commander == node && &*m.sender != node)
+                        || data
+                            .responders
+                            .get(node)
+                            .is_some_and(|r| signals().any(|s| r.subscribed_signals.contains(s)))
+                }
+                DatabaseType::DBC(data) => signals().any(|s| {
+                    data.receivers
+                        .get(s)
+                        .is_some_and(|r| r.iter().any(|n| &**n == node))
+                }),
+                DatabaseType::NCF => false,
+            }
+        })
+    }
+
+    // LIN commander then responders by name, DBC nodes in file order
+    pub fn nodes(&self) -> Vec<Node> {
+        let nodes: Vec<(&Name, NodeRole)> = match &self.extra {
+            DatabaseType::LDF(data) => std::iter::once((&data.commander, NodeRole::Commander))
+                .chain(
+                    sorted(&data.responders)
+                        .into_iter()
+                        .map(|(n, _)| (n, NodeRole::Responder)),
+                )
+                .collect(),
+            DatabaseType::DBC(data) => data.nodes.iter().map(|n| (n, NodeRole::Ecu)).collect(),
+            DatabaseType::NCF => Vec::new(),
+        };
+        nodes
+            .into_iter()
+            .map(|(name, role)| {
+                let frames = |it: &mut dyn Iterator<Item = (&Name, &Message)>| {
+                    it.map(|(n, _)| n.clone()).collect()
+                };
+                let (description, attributes) = match &self.extra {
+                    DatabaseType::LDF(data) => (
+                        data.responders
+                            .get(name)
+                            .and_then(|r| r.description.clone()),
+                        HashMap::new(),
+                    ),
+                    DatabaseType::DBC(data) => (
+                        data.node_descriptions.get(name).cloned(),
+                        data.node_attributes.get(name).cloned().unwrap_or_default(),
+                    ),
+                    DatabaseType::NCF => (None, HashMap::new()),
+                };
+                Node {
+                    name: name.clone(),
+                    role,
+                    tx_frames: frames(&mut self.frames_published_by(name)),
+                    rx_frames: frames(&mut self.frames_subscribed_by(name)),
+                    description,
+                    attributes,
+                }
+            })
+            .collect()
+    }
+
+    pub fn node(&self, name: &str) -> Option<Node> {
+        self.nodes().into_iter().find(|n| *n.name == *name)
+    }
+
+    // by id then name
+    pub fn find_messages(&self, pattern: &str) -> Result<Vec<(&Name, &Message)>, Error> {
+        let pattern = Pattern::parse(pattern)?;
+        Ok(self
+            .frames_where(|_| true)
+            .filter(|(name, _)| pattern.matches(name))
+            .collect())
+    }
+
+    // by message id then name, signals in several frames are listed once per frame
+    pub fn find_signals(&self, pattern: &str) -> Result<Vec<SignalMatch<'_>>, Error> {
+        let pattern = Pattern::parse(pattern)?;
+        let mut ret = Vec::new();
+        for (message, msg) in self.frames_where(|_| true) {
+            let mut signals: Vec<&Name> = msg.signals.iter().collect();
+            let mut muxed: Vec<&Name> = msg.mux_signals.keys().collect();
+            muxed.sort();
+            signals.extend(muxed);
+            for name in signals.into_iter().filter(|s| pattern.matches(s)) {
+                let signal = &self.signals[name];
+                ret.push(SignalMatch {
+                    signal: name,
+                    message: Some(message),
+                    id: Some(msg.id),
+                    bit_start: signal.bit_start,
+                    bit_width: signal.bit_width,
+                });
+            }
+        }
+        let mut unused: Vec<_> = self
+            .signals
+            .iter()
+            .filter(|(name, _)| pattern.matches(name) && !ret.iter().any(|m| m.signal == *name))
+            .collect();
+        unused.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, signal) in unused {
+            ret.push(SignalMatch {
+                signal: name,
+                message: None,
+                id: None,
+                bit_start: signal.bit_start,
+                bit_width: signal.bit_width,
+            });
+        }
+        Ok(ret)
+    }
+}
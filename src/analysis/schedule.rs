@@ -0,0 +1,349 @@
+use crate::parsers::encoding::{DatabaseType, LDFData, LDFScheduleCommand, Name};
+use crate::parsers::ids::Pid;
+use crate::writers::ldf::sorted;
+use crate::{Database, Error};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+const LIN_DIAG_LENGTH: u16 = 8;
+const LIN_COMMANDER_REQ_ID: u8 = 0x3C;
+const LIN_RESPONDER_RESP_ID: u8 = 0x3D;
+const LIN_SID_ASSIGN_FRAME_ID: u8 = 0xB1;
+const LIN_SUPPLIER_WILDCARD: u16 = 0x7FFF;
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleSlot<'a> {
+    pub start: f64, // ms
+    pub command: &'a LDFScheduleCommand,
+    pub sent: Option<&'a Name>, // frame sent by a sporadic slot, none if nothing was pending
+    pub id: Option<u8>,         // none for sporadic slots with nothing pending
+    pub pid: Option<u8>,
+    pub byte_width: u16,
+    pub frame_time: f64, // ms, worst case including jitter
+    pub delay: f64,      // ms
+    pub overrun: bool,
+}
+
+// one entry of a schedule table for commander firmware
+#[derive(Debug, Serialize)]
+pub struct TickSlot {
+    pub command: String,
+    pub id: Option<u8>,
+    pub pid: Option<u8>,
+    pub ticks: u32, // until the next slot
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleTicks {
+    pub table: Name,
+    pub tick_period: f64, // ms
+    pub time_base: f64,   // ms
+    pub slots: Vec<TickSlot>,
+}
+
+// a frame one schedule table can send, directly or through a sporadic or event triggered frame
+#[derive(Debug, Serialize)]
+pub struct FramePeriod {
+    pub table: Name,
+    pub frame: Name,
+    pub period: f64, // ms, longest time between two slots that can send it
+}
+
+// where the schedule tables leave frames out or send them too rarely
+#[derive(Debug, Default, Serialize)]
+pub struct ScheduleCoverage {
+    pub unscheduled: Vec<Name>, // unconditional frames no table can send
+    pub unused: Vec<Name>,      // sporadic and event triggered frames in no table
+    pub slow: Vec<FramePeriod>, // longer than the maximum period
+}
+
+// unconditional frames a slot of frame can send
+fn sent_by<'a>(data: &'a LDFData, frame: &'a Name) -> Vec<&'a Name> {
+    if let Some(frames) = data.sporadic_frames.get(frame) {
+        frames.iter().collect()
+    } else if let Some((_, _, frames)) = data.event_frames.get(frame) {
+        frames.iter().collect()
+    } else {
+        vec![frame]
+    }
+}
+
+// unconditional frames some schedule table sends, through sporadic and event triggered frames too
+pub(crate) fn scheduled_frames(data: &LDFData) -> HashSet<&Name> {
+    let mut ret = HashSet::new();
+    for (cmd, _) in data.schedule_tables.values().flatten() {
+        if let LDFScheduleCommand::Frame(frame) = cmd {
+            ret.extend(sent_by(data, frame));
+        }
+    }
+    ret
+}
+
+// n such that x = n * of, within float noise
+fn multiple(x: f64, of: f64) -> Option<u32> {
+    let n = (x / of).round();
+    ((n * of - x).abs() <= 1e-9 * x.abs().max(1.0)).then_some(n as u32)
+}
+
+// protected identifier, id with parity bits
+pub fn lin_pid(id: u8) -> u8 {
+    Pid::from_id(id).get()
+}
+
+// nominal and maximum (140%) frame time in ms, LIN 2.2A section 2.3.2
+pub fn lin_frame_time(bitrate: f64, byte_width: u16) -> (f64, f64) {
+    let nominal = (34.0 + 10.0 * (byte_width as f64 + 1.0)) * 1000.0 / bitrate;
+    (nominal, 1.4 * nominal)
+}
+
+impl Database {
+    // MasterReq data of a LIN 2.0 AssignFrameId, the frame is addressed by its message id in the node
+    pub fn assign_frame_id_request(&self, node: &str, frame: &str) -> Result<[u8; 8], Error> {
+        let data = match &self.extra {
+            DatabaseType::LDF(data) => data,
+            _ => return Err(Error::IncompatibleDatabase),
+        };
+        let resp = data.responders.get(node).ok_or(Error::UnknownNode)?;
+        let id = self
+            .messages
+            .get(frame)
+            .map(|m| m.id)
+            .or(data.event_frames.get(frame).map(|e| e.1))
+            .ok_or(Error::UnknownFrame)?;
+        let message_id = resp.message_id(frame).ok_or(Error::Hint(
+            format!("{} has no message id for {}", node, frame),
+            Box::new(Error::MissingMessageId),
+        ))?;
+        let supplier = resp.product_id.map_or(LIN_SUPPLIER_WILDCARD, |p| p.0);
+        let [supplier_lo, supplier_hi] = supplier.to_le_bytes();
+        let [message_lo, message_hi] = message_id.to_le_bytes();
+        Ok([
+            resp.configured_nad,
+            0x06, // PCI, single frame with 6 bytes
+            LIN_SID_ASSIGN_FRAME_ID,
+            supplier_lo,
+            supplier_hi,
+            message_lo,
+            message_hi,
+            lin_pid(id as u8),
+        ])
+    }
+
+    // id and length of the frame sent for a schedule command
+    pub(crate) fn command_frame(
+        &self,
+        data: &LDFData,
+        cmd: &LDFScheduleCommand,
+    ) -> Result<(Option<u8>, u16), Error> {
+        Ok(match cmd {
+            LDFScheduleCommand::Frame(name) => {
+                if let Some(msg) = self.messages.get(name) {
+                    (Some(msg.id as u8), msg.byte_width)
+                } else if let Some((_, id, frames)) = data.event_frames.get(name) {
+                    let width = frames
+                        .first()
+                        .and_then(|f| self.messages.get(f))
+                        .map_or(0, |m| m.byte_width);
+                    (Some(*id as u8), width)
+                } else if let Some(frames) = data.sporadic_frames.get(name) {
+                    let width = frames
+                        .iter()
+                        .filter_map(|f| self.messages.get(f))
+                        .map(|m| m.byte_width)
+                        .max()
+                        .unwrap_or(0);
+                    (None, width)
+                } else {
+                    return Err(Error::UnknownFrame);
+                }
+            }
+            LDFScheduleCommand::ResponderResp => (Some(LIN_RESPONDER_RESP_ID), LIN_DIAG_LENGTH),
+            _ => (Some(LIN_COMMANDER_REQ_ID), LIN_DIAG_LENGTH), // all sent as requests
+        })
+    }
+
+    // delays as ticks of a firmware timer, every delay must be a multiple of the time base and the time
+    // base a multiple of the tick
+    pub fn schedule_ticks(
+        &self,
+        table: &str,
+        tick_period: f64,       // ms
+        time_base: Option<f64>, // ms, the database's if not given
+    ) -> Result<ScheduleTicks, Error> {
+        let data = match &self.extra {
+            DatabaseType::LDF(data) => data,
+            _ => return Err(Error::IncompatibleDatabase),
+        };
+        let (name, commands) = data
+            .schedule_tables
+            .get_key_value(table)
+            .ok_or(Error::UnknownScheduleTable)?;
+        let time_base = time_base.unwrap_or(data.time_base);
+        if tick_period <= 0.0 || time_base <= 0.0 || multiple(time_base, tick_period).is_none() {
+            return Err(Error::Hint(
+                format!(
+                    "time base {} ms is not a multiple of the {} ms tick",
+                    time_base, tick_period
+                ),
+                Box::new(Error::InvalidScheduleTiming),
+            ));
+        }
+
+        let mut slots = Vec::new();
+        for (cmd, delay) in commands {
+            if multiple(*delay, time_base).is_none() {
+                return Err(Error::Hint(
+                    format!(
+                        "{}: {} delay {} ms is not a multiple of the {} ms time base",
+                        name, cmd, delay, time_base
+                    ),
+                    Box::new(Error::InvalidScheduleTiming),
+                ));
+            }
+            let (id, _) = self.command_frame(data, cmd)?;
+            slots.push(TickSlot {
+                command: cmd.to_string(),
+                id,
+                pid: id.map(lin_pid),
+                ticks: multiple(*delay, tick_period).unwrap(), // tick divides the time base
+            });
+        }
+        Ok(ScheduleTicks {
+            table: name.clone(),
+            tick_period,
+            time_base,
+            slots,
+        })
+    }
+
+    pub fn simulate_schedule(
+        &self,
+        table: &str,
+        duration: f64, // ms
+    ) -> Result<Vec<ScheduleSlot<'_>>, Error> {
+        self.simulate_schedule_pending(table, duration, &[])
+    }
+
+    // pending frames are sent once, by the first sporadic slot carrying them in priority order
+    pub fn simulate_schedule_pending(
+        &self,
+        table: &str,
+        duration: f64, // ms
+        pending: &[Name],
+    ) -> Result<Vec<ScheduleSlot<'_>>, Error> {
+        let data = match &self.extra {
+            DatabaseType::LDF(data) => data,
+            _ => return Err(Error::IncompatibleDatabase),
+        };
+        let table = data
+            .schedule_tables
+            .get(table)
+            .ok_or(Error::UnknownScheduleTable)?;
+        let mut slots = Vec::new();
+        if table.iter().map(|(_, delay)| delay).sum::<f64>() <= 0.0 {
+            return Ok(slots);
+        }
+
+        let mut pending = pending.to_vec();
+        let mut time = 0.0;
+        while time < duration {
+            for (cmd, delay) in table {
+                if time >= duration {
+                    break;
+                }
+                let (mut id, mut byte_width) = self.command_frame(data, cmd)?;
+                let mut sent = None;
+                if let LDFScheduleCommand::Frame(name) = cmd {
+                    if let Some((_, frame)) = data
+                        .sporadic_priority(name)
+                        .find(|(_, f)| pending.contains(f))
+                    {
+                        pending.retain(|f| f != frame);
+                        let msg = self.messages.get(frame).ok_or(Error::UnknownFrame)?;
+                        (id, byte_width) = (Some(msg.id as u8), msg.byte_width);
+                        sent = Some(frame);
+                    }
+                }
+                let frame_time = lin_frame_time(data.bitrate, byte_width).1 + data.jitter;
+                slots.push(ScheduleSlot {
+                    start: time,
+                    command: cmd,
+                    sent,
+                    id,
+                    pid: id.map(lin_pid),
+                    byte_width,
+                    frame_time,
+                    delay: *delay,
+                    overrun: frame_time > *delay,
+                });
+                time += delay;
+            }
+        }
+        Ok(slots)
+    }
+
+    // cross-references frames with the schedule tables, periods are checked per table when max_period (ms)
+    // is given, they count every slot that can send a frame, a sporadic one may be taken by another frame
+    pub fn schedule_coverage(&self, max_period: Option<f64>) -> Result<ScheduleCoverage, Error> {
+        let data = match &self.extra {
+            DatabaseType::LDF(data) => data,
+            _ => return Err(Error::IncompatibleDatabase),
+        };
+        let mut ret = ScheduleCoverage::default();
+        let scheduled = scheduled_frames(data);
+        ret.unscheduled = self
+            .messages
+            .keys()
+            .filter(|f| !scheduled.contains(f))
+            .cloned()
+            .collect();
+        ret.unscheduled.sort();
+        let in_tables: HashSet<&Name> = data
+            .schedule_tables
+            .values()
+            .flatten()
+            .filter_map(|(cmd, _)| match cmd {
+                LDFScheduleCommand::Frame(frame) => Some(frame),
+                _ => None,
+            })
+            .collect();
+        ret.unused = data
+            .sporadic_frames
+            .keys()
+            .chain(data.event_frames.keys())
+            .filter(|f| !in_tables.contains(f))
+            .cloned()
+            .collect();
+        ret.unused.sort();
+
+        let Some(max_period) = max_period else {
+            return Ok(ret);
+        };
+        for (table, commands) in sorted(&data.schedule_tables) {
+            let cycle: f64 = commands.iter().map(|(_, delay)| delay).sum();
+            let mut starts: BTreeMap<&Name, Vec<f64>> = BTreeMap::new();
+            let mut time = 0.0;
+            for (cmd, delay) in commands {
+                if let LDFScheduleCommand::Frame(frame) = cmd {
+                    for sent in sent_by(data, frame) {
+                        starts.entry(sent).or_default().push(time);
+                    }
+                }
+                time += delay;
+            }
+            for (frame, starts) in starts {
+                // around the end of the table back to the first slot
+                let wrap = cycle - starts[starts.len() - 1] + starts[0];
+                let period = starts.windows(2).map(|w| w[1] - w[0]).fold(wrap, f64::max);
+                if period > max_period {
+                    ret.slow.push(FramePeriod {
+                        table: table.clone(),
+                        frame: frame.clone(),
+                        period,
+                    });
+                }
+            }
+        }
+        Ok(ret)
+    }
+}
@@ -0,0 +1,667 @@
+use crate::analysis::schedule::lin_frame_time;
+use crate::parsers::encoding::{
+    DatabaseType, LDFData, LDFScheduleCommand, Message, Name, ValueKind,
+};
+use crate::parsers::ids::{BitPos, Nad};
+use crate::writers::ldf::sorted;
+use crate::{Database, Error};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const LIN_MAX_ID: u32 = 59; // unconditional and event triggered frames
+const LIN_DIAG_IDS: [u32; 2] = [0x3C, 0x3D]; // MasterReq, SlaveResp
+const LIN_RESERVED_IDS: [u32; 2] = [0x3E, 0x3F];
+const LIN_IDENTIFIER_MAX: usize = 31; // significant chars of a C identifier
+
+// LDF keywords, plus C ones since generated code uses the names as is
+const LIN_KEYWORDS: [&str; 43] = [
+    "LIN_description_file",
+    "LIN_protocol_version",
+    "LIN_language_version",
+    "LIN_speed",
+    "Channel_name",
+    "Nodes",
+    "Master",
+    "Slaves",
+    "Node_composition",
+    "Signals",
+    "Diagnostic_signals",
+    "Frames",
+    "Sporadic_frames",
+    "Event_triggered_frames",
+    "Diagnostic_frames",
+    "Node_attributes",
+    "Schedule_tables",
+    "Signal_groups",
+    "Signal_encoding_types",
+    "Signal_representation",
+    "LIN_protocol",
+    "configured_NAD",
+    "initial_NAD",
+    "product_id",
+    "response_error",
+    "fault_state_signals",
+    "P2_min",
+    "ST_min",
+    "N_As_timeout",
+    "N_Cr_timeout",
+    "configurable_frames",
+    "composite",
+    "logical_value",
+    "physical_value",
+    "bcd_value",
+    "ascii_value",
+    "AssignNAD",
+    "AssignFrameId",
+    "AssignFrameIdRange",
+    "ConditionalChangeNAD",
+    "DataDump",
+    "SaveConfiguration",
+    "AssignNADviaSNPD",
+];
+const C_KEYWORDS: [&str; 32] = [
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "int", "long", "register", "return", "short",
+    "signed", "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+    "volatile", "while",
+];
+
+// two signals of a frame sharing bits, multiplexed signals never selected together don't count
+#[derive(Debug, Serialize)]
+pub struct Overlap {
+    pub message: Name,
+    pub signals: (Name, Name),
+    pub bits: Vec<(BitPos, BitPos)>, // inclusive ranges
+}
+
+fn issue(hint: String, e: Error) -> Error {
+    Error::Hint(hint, Box::new(e))
+}
+
+// sorted bits as inclusive ranges
+fn ranges(mut bits: Vec<BitPos>) -> Vec<(BitPos, BitPos)> {
+    bits.sort();
+    let mut ret: Vec<(BitPos, BitPos)> = Vec::new();
+    for bit in bits {
+        match ret.last_mut() {
+            Some((_, end)) if end.get() + 1 == bit.get() => *end = bit,
+            _ => ret.push((bit, bit)),
+        }
+    }
+    ret
+}
+
+fn format_ranges(ranges: &[(BitPos, BitPos)]) -> String {
+    ranges
+        .iter()
+        .map(|(a, b)| {
+            if a == b {
+                a.to_string()
+            } else {
+                format!("{}..={}", a, b)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn validate_layout(db: &Database, errors: &mut Vec<Error>) {
+    for (message, msg) in sorted(&db.messages) {
+        let size = msg.byte_width * 8;
+        for name in msg
+            .signals
+            .iter()
+            .chain(sorted(&msg.mux_signals).into_iter().map(|(n, _)| n))
+        {
+            let Some(signal) = db.signals.get(name) else {
+                errors.push(issue(
+                    format!("{}: {} isn't defined", message, name),
+                    Error::UnknownSignal,
+                ));
+                continue;
+            };
+            let outside: Vec<BitPos> = signal
+                .bit_positions()
+                .into_iter()
+                .filter(|b| b.get() >= size)
+                .collect();
+            if !outside.is_empty() {
+                errors.push(issue(
+                    format!(
+                        "{}: {} uses bits {} of a {} byte frame",
+                        message,
+                        name,
+                        format_ranges(&ranges(outside)),
+                        msg.byte_width
+                    ),
+                    Error::SignalOutsideFrame,
+                ));
+            }
+        }
+    }
+    for o in db.overlaps() {
+        errors.push(issue(
+            format!(
+                "{}: {} and {} share bits {}",
+                o.message,
+                o.signals.0,
+                o.signals.1,
+                format_ranges(&o.bits)
+            ),
+            Error::SignalOverlap,
+        ));
+    }
+}
+
+// floats are exactly as wide as their IEEE 754 format, byte arrays are whole bytes
+fn validate_kinds(db: &Database, errors: &mut Vec<Error>) {
+    for (name, signal) in sorted(&db.signals) {
+        if let Some(width) = signal.kind.width().filter(|w| *w != signal.bit_width) {
+            errors.push(issue(
+                format!(
+                    "{} is {} but {} bits wide, needs {}",
+                    name, signal.kind, signal.bit_width, width
+                ),
+                Error::InvalidValueKind,
+            ));
+        } else if signal.kind == ValueKind::ByteArray && !signal.bit_width.is_multiple_of(8) {
+            errors.push(issue(
+                format!("{} is a byte array of {} bits", name, signal.bit_width),
+                Error::InvalidValueKind,
+            ));
+        } else if signal.is_ascii() && signal.kind != ValueKind::ByteArray {
+            errors.push(issue(
+                format!("{} has an ASCII encoding but is {}", name, signal.kind),
+                Error::InvalidValueKind,
+            ));
+        }
+    }
+}
+
+// groups only hold signals of their own frame
+fn validate_signal_groups(db: &Database, errors: &mut Vec<Error>) {
+    for (message, msg) in sorted(&db.messages) {
+        for (group, g) in sorted(&msg.signal_groups) {
+            for name in &g.signals {
+                if !msg.signals.contains(name) && !msg.mux_signals.contains_key(name) {
+                    errors.push(issue(
+                        format!(
+                            "{}: group {} has {}, not one of its signals",
+                            message, group, name
+                        ),
+                        Error::UnknownSignal,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// (multiplexor, values selecting) for a signal and, with extended multiplexing, each multiplexor up the chain
+pub(crate) fn selectors<'a>(
+    db: &Database,
+    msg: &'a Message,
+    signal: &'a Name,
+) -> Vec<(&'a Name, Vec<(u64, u64)>)> {
+    let mut ret = Vec::new();
+    let mut signal = signal;
+    while let Some((value, multiplexors)) = msg.mux_signals.get(signal) {
+        if ret.len() > msg.mux_signals.len() {
+            break; // a loop, reported by validate_multiplexing
+        }
+        let ranges = db.mux_ranges(signal, *value);
+        ret.extend(multiplexors.iter().map(|m| (m, ranges.clone())));
+        let Some(next) = multiplexors
+            .iter()
+            .find(|m| msg.mux_signals.contains_key(*m))
+        else {
+            break;
+        };
+        signal = next;
+    }
+    ret
+}
+
+// different values of a shared multiplexor select a and b
+fn exclusive(a: &[(&Name, Vec<(u64, u64)>)], b: &[(&Name, Vec<(u64, u64)>)]) -> bool {
+    a.iter().any(|(m, ra)| {
+        b.iter().any(|(n, rb)| {
+            m == n
+                && !ra
+                    .iter()
+                    .any(|(a0, a1)| rb.iter().any(|(b0, b1)| a0 <= b1 && b0 <= a1))
+        })
+    })
+}
+
+// multiplexed frames have one multiplexor selecting each signal, plus the ones it selects for extended
+// multiplexing, and a multiplexor given value ranges selects something for every value from lowest to highest
+fn validate_multiplexing(db: &Database, errors: &mut Vec<Error>) {
+    for (message, msg) in sorted(&db.messages) {
+        let mut top: Vec<&Name> = Vec::new();
+        let mut reported = false;
+        for (signal, (_, multiplexors)) in sorted(&msg.mux_signals) {
+            if multiplexors.len() != 1 {
+                reported = true;
+                let multiplexors: Vec<&str> = multiplexors.iter().map(|m| &**m).collect();
+                errors.push(issue(
+                    format!(
+                        "{}: {} has multiplexors [{}]",
+                        message,
+                        signal,
+                        multiplexors.join(", ")
+                    ),
+                    Error::MultiplexorCount,
+                ));
+            }
+            for m in multiplexors {
+                if msg.signals.contains(m) {
+                    if !top.contains(&m) {
+                        top.push(m);
+                    }
+                } else if !msg.mux_signals.contains_key(m) {
+                    errors.push(issue(
+                        format!(
+                            "{}: {} has multiplexor {}, not in the frame",
+                            message, signal, m
+                        ),
+                        Error::UnknownSignal,
+                    ));
+                }
+            }
+            if selectors(db, msg, signal).len() > msg.mux_signals.len() {
+                errors.push(issue(
+                    format!("{}: {} multiplexes itself", message, signal),
+                    Error::MultiplexorCount,
+                ));
+            }
+        }
+        if top.len() > 1 && !reported {
+            top.sort();
+            let top: Vec<&str> = top.iter().map(|m| &***m).collect();
+            errors.push(issue(
+                format!("{}: multiplexors {}", message, top.join(", ")),
+                Error::MultiplexorCount,
+            ));
+        }
+
+        let DatabaseType::DBC(data) = &db.extra else {
+            continue;
+        };
+        let mut values: BTreeMap<&Name, Vec<(u64, u64)>> = BTreeMap::new();
+        let mut declared: HashSet<&Name> = HashSet::new(); // multiplexors given ranges
+        for (signal, (value, multiplexors)) in &msg.mux_signals {
+            for m in multiplexors {
+                values
+                    .entry(m)
+                    .or_default()
+                    .extend(db.mux_ranges(signal, *value));
+                if data.mux_ranges.contains_key(signal) {
+                    declared.insert(m);
+                }
+            }
+        }
+        for (multiplexor, mut ranges) in values {
+            if !declared.contains(multiplexor) {
+                continue;
+            }
+            ranges.sort();
+            let mut end = ranges[0].1;
+            for (min, max) in &ranges[1..] {
+                if *min > end.saturating_add(1) {
+                    let gap = match (end + 1, min - 1) {
+                        (a, b) if a == b => a.to_string(),
+                        (a, b) => format!("{}-{}", a, b),
+                    };
+                    errors.push(issue(
+                        format!("{}: {} {} selects nothing", message, multiplexor, gap),
+                        Error::MuxRangeGap,
+                    ));
+                }
+                end = end.max(*max);
+            }
+        }
+    }
+}
+
+fn validate_nads(data: &LDFData, errors: &mut Vec<Error>) {
+    let legal = |nad: u8| Nad::new(nad).is_ok();
+    let responders = sorted(&data.responders);
+
+    // each responder is addressed by its own NAD, before and after configuration
+    let mut configured = HashMap::new();
+    let mut initial = HashMap::new();
+    for &(name, resp) in &responders {
+        if !legal(resp.configured_nad) {
+            errors.push(issue(
+                format!("{} configured NAD 0x{:02X}", name, resp.configured_nad),
+                Error::InvalidNAD,
+            ));
+        }
+        if let Some(nad) = resp.initial_nad.filter(|n| !legal(*n)) {
+            errors.push(issue(
+                format!("{} initial NAD 0x{:02X}", name, nad),
+                Error::InvalidNAD,
+            ));
+        }
+        if let Some(other) = configured.insert(resp.configured_nad, name) {
+            errors.push(issue(
+                format!(
+                    "{} and {} both have configured NAD 0x{:02X}",
+                    other, name, resp.configured_nad
+                ),
+                Error::DuplicateNAD,
+            ));
+        }
+        let nad = resp.initial_nad.unwrap_or(resp.configured_nad);
+        if let Some(other) = initial.insert(nad, name).filter(|other| {
+            resp.initial_nad.is_some() || data.responders[*other].initial_nad.is_some()
+        }) {
+            errors.push(issue(
+                format!("{} and {} both have initial NAD 0x{:02X}", other, name, nad),
+                Error::DuplicateNAD,
+            ));
+        }
+    }
+
+    // configuration commands must address a declared NAD and assign legal ones
+    for (table, commands) in sorted(&data.schedule_tables) {
+        for (cmd, _) in commands {
+            match cmd {
+                LDFScheduleCommand::AssignNAD(node) => {
+                    let Some(resp) = data.responders.get(node) else {
+                        continue; // checked when parsing
+                    };
+                    let nad = resp.initial_nad.unwrap_or(resp.configured_nad);
+                    if initial.get(&nad).is_some_and(|n| *n != node) {
+                        errors.push(issue(
+                            format!(
+                                "{}: AssignNAD {{{}}} reaches {} too",
+                                table, node, initial[&nad]
+                            ),
+                            Error::DuplicateNAD,
+                        ));
+                    }
+                }
+                LDFScheduleCommand::ConditionalChangeNAD { nad, new_nad, .. } => {
+                    if *nad != Nad::BROADCAST.get()
+                        && !configured.contains_key(nad)
+                        && !initial.contains_key(nad)
+                    {
+                        errors.push(issue(
+                            format!(
+                                "{}: ConditionalChangeNAD addresses NAD 0x{:02X}",
+                                table, nad
+                            ),
+                            Error::UnknownNode,
+                        ));
+                    }
+                    if !legal(*new_nad) {
+                        errors.push(issue(
+                            format!(
+                                "{}: ConditionalChangeNAD assigns NAD 0x{:02X}",
+                                table, new_nad
+                            ),
+                            Error::InvalidNAD,
+                        ));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+fn frame_id_issue(kind: &str, name: &str, id: u32) -> Option<Error> {
+    if id <= LIN_MAX_ID {
+        None
+    } else if LIN_DIAG_IDS.contains(&id) {
+        Some(issue(
+            format!(
+                "{} {} uses 0x{:02X}, only for diagnostic frames",
+                kind, name, id
+            ),
+            Error::ReservedFrameId,
+        ))
+    } else if LIN_RESERVED_IDS.contains(&id) {
+        Some(issue(
+            format!("{} {} uses 0x{:02X}", kind, name, id),
+            Error::ReservedFrameId,
+        ))
+    } else {
+        Some(issue(
+            format!("{} {} uses 0x{:02X}", kind, name, id),
+            Error::InvalidFrameId,
+        ))
+    }
+}
+
+fn validate_frame_ids(db: &Database, data: &LDFData, errors: &mut Vec<Error>) {
+    let mut frames: Vec<(&str, &Name, u32)> = sorted(&db.messages)
+        .into_iter()
+        .map(|(name, msg)| ("frame", name, msg.id))
+        .collect();
+    frames.extend(
+        sorted(&data.event_frames)
+            .into_iter()
+            .map(|(name, (_, id, _))| ("event triggered frame", name, *id)),
+    );
+
+    // unconditional and event triggered frames share the id space
+    let mut ids: HashMap<u32, (&str, &Name)> = HashMap::new();
+    for (kind, name, id) in frames {
+        errors.extend(frame_id_issue(kind, name, id));
+        if let Some((other_kind, other)) = ids.insert(id, (kind, name)) {
+            errors.push(issue(
+                format!(
+                    "{} {} and {} {} both use 0x{:02X}",
+                    other_kind, other, kind, name, id
+                ),
+                Error::DuplicateFrameId,
+            ));
+        }
+    }
+
+    // a frame is either sent sporadically or on an event, not both
+    for (sporadic, frames) in sorted(&data.sporadic_frames) {
+        for frame in frames {
+            for (event, _) in sorted(&data.event_frames)
+                .into_iter()
+                .filter(|(_, (_, _, carried))| carried.contains(frame))
+            {
+                errors.push(issue(
+                    format!(
+                        "{} is in sporadic frame {} and event triggered frame {}",
+                        frame, sporadic, event
+                    ),
+                    Error::SporadicFrameInEventFrame,
+                ));
+            }
+        }
+    }
+}
+
+// the first byte of frames carried by an event triggered frame holds the PID
+fn validate_event_frames(db: &Database, data: &LDFData, errors: &mut Vec<Error>) {
+    for (event, (_, _, frames)) in sorted(&data.event_frames) {
+        for frame in frames {
+            let Some(msg) = db.messages.get(frame) else {
+                continue; // checked when parsing
+            };
+            for name in &msg.signals {
+                let Some(signal) = db.signals.get(name) else {
+                    continue; // reported by validate_layout
+                };
+                if signal.bit_start < 8 {
+                    errors.push(issue(
+                        format!(
+                            "{} in {} of {} starts at bit {}",
+                            name, frame, event, signal.bit_start
+                        ),
+                        Error::EventFrameFirstByteUsed,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// LIN 2.0 AssignFrameId needs the message id from the node's configurable frames
+fn validate_message_ids(db: &Database, data: &LDFData, errors: &mut Vec<Error>) {
+    for (table, commands) in sorted(&data.schedule_tables) {
+        for (cmd, _) in commands {
+            if let LDFScheduleCommand::AssignFrameId { node, frame } = cmd {
+                match db.assign_frame_id_request(node, frame) {
+                    Ok(_) => (),
+                    Err(Error::Hint(hint, e)) => {
+                        errors.push(issue(format!("{}: {}", table, hint), *e))
+                    }
+                    Err(e) => errors.push(issue(format!("{}: {}", table, cmd), e)),
+                }
+            }
+        }
+    }
+}
+
+// each slot must fit the worst case frame time plus jitter
+fn validate_schedules(db: &Database, data: &LDFData, errors: &mut Vec<Error>) {
+    for (table, commands) in sorted(&data.schedule_tables) {
+        for (i, (cmd, delay)) in commands.iter().enumerate() {
+            let Ok((_, byte_width)) = db.command_frame(data, cmd) else {
+                continue; // checked when parsing
+            };
+            let needed = lin_frame_time(data.bitrate, byte_width).1 + data.jitter;
+            if needed > *delay {
+                errors.push(issue(
+                    format!(
+                        "{} slot {} ({}) is {} ms, needs {:.3} ms",
+                        table, i, cmd, delay, needed
+                    ),
+                    Error::ScheduleSlotTooShort,
+                ));
+            }
+        }
+    }
+}
+
+fn identifier_issue(kind: &str, name: &str) -> Option<String> {
+    let mut chars = name.chars();
+    let legal = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !legal {
+        Some(format!("{} {} isn't [A-Za-z_][A-Za-z0-9_]*", kind, name))
+    } else if name.len() > LIN_IDENTIFIER_MAX {
+        Some(format!(
+            "{} {} is {} chars, at most {}",
+            kind,
+            name,
+            name.len(),
+            LIN_IDENTIFIER_MAX
+        ))
+    } else if LIN_KEYWORDS.contains(&name) || C_KEYWORDS.contains(&name) {
+        Some(format!("{} {} is a reserved keyword", kind, name))
+    } else {
+        None
+    }
+}
+
+// names must be C style identifiers, and strict tools keep one namespace for all of them
+fn validate_identifiers(db: &Database, data: &LDFData, errors: &mut Vec<Error>) {
+    let mut names: Vec<(&str, &Name)> = vec![("node", &data.commander)];
+    names.extend(
+        sorted(&data.responders)
+            .into_iter()
+            .map(|(n, _)| ("node", n)),
+    );
+    names.extend(sorted(&db.signals).into_iter().map(|(n, _)| ("signal", n)));
+    names.extend(sorted(&db.messages).into_iter().map(|(n, _)| ("frame", n)));
+    names.extend(
+        sorted(&data.sporadic_frames)
+            .into_iter()
+            .map(|(n, _)| ("sporadic frame", n)),
+    );
+    names.extend(
+        sorted(&data.event_frames)
+            .into_iter()
+            .map(|(n, _)| ("event triggered frame", n)),
+    );
+    names.extend(
+        sorted(&data.schedule_tables)
+            .into_iter()
+            .map(|(n, _)| ("schedule table", n)),
+    );
+
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for (kind, name) in names {
+        if let Some(hint) = identifier_issue(kind, name) {
+            errors.push(issue(hint, Error::InvalidIdentifier));
+        }
+        if let Some(other) = seen.insert(name, kind) {
+            errors.push(issue(
+                format!("{} is both a {} and a {}", name, other, kind),
+                Error::DuplicateIdentifier,
+            ));
+        }
+    }
+}
+
+impl Database {
+    pub fn overlaps(&self) -> Vec<Overlap> {
+        let mut ret = Vec::new();
+        for (message, msg) in sorted(&self.messages) {
+            let mut names: Vec<&Name> = msg.signals.iter().collect();
+            names.extend(sorted(&msg.mux_signals).into_iter().map(|(n, _)| n));
+            names.retain(|n| self.signals.contains_key(*n)); // others are reported by validate
+            let selectors: Vec<_> = names.iter().map(|n| selectors(self, msg, n)).collect();
+            for (i, a) in names.iter().enumerate() {
+                let bits = self.signals[*a].bit_positions();
+                for (j, b) in names.iter().enumerate().skip(i + 1) {
+                    if exclusive(&selectors[i], &selectors[j]) {
+                        continue;
+                    }
+                    let shared: Vec<BitPos> = self.signals[*b]
+                        .bit_positions()
+                        .into_iter()
+                        .filter(|bit| bits.contains(bit))
+                        .collect();
+                    if !shared.is_empty() {
+                        ret.push(Overlap {
+                            message: message.clone(),
+                            signals: ((*a).clone(), (*b).clone()),
+                            bits: ranges(shared),
+                        });
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    // problems that parse fine but break a real bus, each with a hint naming the entities involved
+    pub fn validate(&self) -> Vec<Error> {
+        let mut errors = Vec::new();
+        validate_layout(self, &mut errors);
+        validate_kinds(self, &mut errors);
+        validate_signal_groups(self, &mut errors);
+        validate_multiplexing(self, &mut errors);
+        if let DatabaseType::LDF(data) = &self.extra {
+            validate_nads(data, &mut errors);
+            validate_frame_ids(self, data, &mut errors);
+            validate_event_frames(self, data, &mut errors);
+            validate_message_ids(self, data, &mut errors);
+            validate_schedules(self, data, &mut errors);
+        }
+        errors
+    }
+
+    // validate() plus the LIN spec's rules for identifiers, which plenty of tools don't enforce
+    pub fn validate_strict(&self) -> Vec<Error> {
+        let mut errors = self.validate();
+        if let DatabaseType::LDF(data) = &self.extra {
+            validate_identifiers(self, data, &mut errors);
+        }
+        errors
+    }
+}
@@ -0,0 +1,7 @@
+fn main() {
+    env_logger::init();
+    if let Err(e) = autodbconv::serve_lsp_stdio() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,16 @@
+use std::net::SocketAddr;
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let addr: SocketAddr = std::env::args()
+        .nth(1)
+        .unwrap_or("127.0.0.1:8080".to_string())
+        .parse()
+        .expect("invalid listen address");
+    log::info!("listening on {}", addr);
+    if let Err(e) = autodbconv::serve_http(addr).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,36 @@
+use autodbconv::{Error, Project, Severity, MANIFEST_NAME};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Manifest, or the directory holding it
+    #[arg(default_value = MANIFEST_NAME)]
+    manifest: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let project = Project::load(&args.manifest)?;
+    let report = project.build()?;
+    for (input, severity, issue) in &report.issues {
+        let level = match severity {
+            Severity::Error => "error",
+            _ => "warning",
+        };
+        println!("{}: {}: {}", input, level, issue);
+    }
+    for path in &report.written {
+        eprintln!("wrote {}", path.display());
+    }
+    let failed = report
+        .issues
+        .iter()
+        .filter(|(_, s, _)| *s == Severity::Error)
+        .count();
+    if failed > 0 {
+        return Err(Error::IO(format!(
+            "{} issue(s) found, nothing written",
+            failed
+        )));
+    }
+    Ok(())
+}
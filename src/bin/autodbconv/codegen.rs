@@ -0,0 +1,82 @@
+use crate::load;
+use autodbconv::{generate_c_with, Accessors, CodegenOptions, Error, Format, NameCase};
+use clap::ValueEnum;
+use std::path::PathBuf;
+
+// each backend writes its output directly, there's no template engine and so no template to select
+#[derive(Clone, Copy, ValueEnum)]
+enum Lang {
+    C,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database to generate code for
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output language
+    #[arg(long, value_enum, default_value_t = Lang::C)]
+    lang: Lang,
+    /// Only generate frames published or subscribed by this node, packing the ones it publishes and
+    /// unpacking the ones it subscribes
+    #[arg(long)]
+    node: Option<String>,
+    /// Only generate frames matching this glob or /regex/, can be repeated
+    #[arg(long = "message")]
+    messages: Vec<String>,
+    /// Only generate signals matching this glob or /regex/, can be repeated
+    #[arg(long = "signal")]
+    signals: Vec<String>,
+    /// Put before type, function and macro names
+    #[arg(long, default_value = "")]
+    prefix: String,
+    /// Case of type, function and field names
+    #[arg(long, value_enum, default_value_t = NameCase::Preserve)]
+    case: NameCase,
+    /// Whether fields hold raw or physical values
+    #[arg(long, value_enum, default_value_t = Accessors::Raw)]
+    accessors: Accessors,
+    /// Output directory
+    #[arg(long, default_value = ".")]
+    out: PathBuf,
+    /// Base name of generated files, defaults to the database name
+    #[arg(long)]
+    name: Option<String>,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    let name = match &args.name {
+        Some(name) => name.clone(),
+        None => args
+            .db
+            .file_stem()
+            .map_or("db".to_string(), |s| s.to_string_lossy().to_string()),
+    };
+
+    let options = CodegenOptions {
+        node: args.node,
+        messages: args.messages,
+        signals: args.signals,
+        prefix: args.prefix,
+        case: args.case,
+        accessors: args.accessors,
+        ..Default::default()
+    };
+
+    std::fs::create_dir_all(&args.out)?;
+    match args.lang {
+        Lang::C => {
+            let (header, source) = generate_c_with(&db, &name, &options)?;
+            for (ext, content) in [("h", header), ("c", source)] {
+                let path = args.out.join(format!("{}.{}", name, ext));
+                std::fs::write(&path, content)?;
+                eprintln!("wrote {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
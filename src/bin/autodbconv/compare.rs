@@ -0,0 +1,81 @@
+use crate::replay::read_log;
+use crate::{load, print_serialized, OutputFormat};
+use autodbconv::{CompareOptions, Error, Format, LogFormat};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database describing the bus
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Reference log: ASC, BLF or candump -l
+    first: PathBuf,
+    /// Log to check against it
+    second: PathBuf,
+    /// Log format (asc, blf or candump), inferred from extension if not given
+    #[arg(long)]
+    log_format: Option<LogFormat>,
+    /// Physical values further apart than this differ
+    #[arg(long, default_value_t = 0.0)]
+    tolerance: f64,
+    /// Tolerance for one signal, e.g. VehicleSpeed=0.5
+    #[arg(long, value_parser = parse_signal_tolerance)]
+    signal: Vec<(String, f64)>,
+    /// Mean frame periods further apart than this fraction drift
+    #[arg(long, default_value_t = 0.1)]
+    timing_tolerance: f64,
+    /// Only frames from this channel, e.g. 1 or can0
+    #[arg(long)]
+    channel: Option<String>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+fn parse_signal_tolerance(s: &str) -> Result<(String, f64), String> {
+    let (name, tolerance) = s
+        .split_once('=')
+        .ok_or(format!("expected SIGNAL=TOLERANCE: {}", s))?;
+    let tolerance = tolerance
+        .parse()
+        .map_err(|_| format!("invalid tolerance: {}", tolerance))?;
+    Ok((name.to_string(), tolerance))
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    for (name, _) in &args.signal {
+        if db.signal(name).is_none() {
+            return Err(Error::Hint(name.clone(), Box::new(Error::UnknownSignal)));
+        }
+    }
+    let first = read_log(&args.first, args.log_format)?;
+    let second = read_log(&args.second, args.log_format)?;
+    let options = CompareOptions {
+        tolerance: args.tolerance,
+        tolerances: args.signal.into_iter().collect(),
+        timing_tolerance: args.timing_tolerance,
+        channel: args.channel,
+    };
+    let comparison = db.compare_logs(&first, &second, &options);
+    if args.format != OutputFormat::Table {
+        return print_serialized(args.format, &comparison);
+    }
+    print!("{}", comparison);
+    if comparison.is_empty() {
+        println!(
+            "{} and {} match, {} frames",
+            args.first.display(),
+            args.second.display(),
+            comparison.frames.0
+        );
+        return Ok(());
+    }
+    Err(Error::IO(format!(
+        "{} difference(s) found",
+        comparison.differences.len()
+    )))
+}
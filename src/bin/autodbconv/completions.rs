@@ -0,0 +1,17 @@
+use crate::Cli;
+use autodbconv::Error;
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Shell to generate completions for
+    shell: Shell,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(args.shell, &mut cmd, name, &mut std::io::stdout());
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use crate::watch::watch;
+use crate::{load, save};
+use autodbconv::{
+    is_compressed, open_decompressed, DatabaseType, Error, Format, SkeletonOptions, WriteOptions,
+};
+use clap::error::ErrorKind;
+use clap::CommandFactory;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Input databases, directories, or glob patterns (e.g. 'db/**/*.ldf'), "-" for stdin
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Output database for a single input, "-" for stdout (default)
+    #[arg(short, long, conflicts_with = "out_dir")]
+    output: Option<PathBuf>,
+    /// Output directory, mirrors the input directory structure
+    #[arg(long, requires = "to")]
+    out_dir: Option<PathBuf>,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output format, inferred from extension if not given
+    #[arg(long)]
+    to: Option<Format>,
+    /// Commander node when converting a DBC to LDF, inferred if not given
+    #[arg(long)]
+    commander: Option<String>,
+    /// Rescale signals into these units (e.g. km/h,degC), other known units get canonical spellings
+    #[arg(long, value_delimiter = ',', num_args = 0..)]
+    normalize_units: Option<Vec<String>>,
+    /// Append the LDF channel name to signal, frame and schedule table names
+    #[arg(long)]
+    apply_postfix: bool,
+    /// Write entities in a stable sorted order, independent of the input's order
+    #[arg(long)]
+    sorted: bool,
+    /// Note the input path, format and parse time in the output
+    #[arg(long)]
+    provenance: bool,
+    /// Define Vector's standard DBC attributes (BusType, GenMsgCycleTime, ...) even when unused
+    #[arg(long)]
+    vector_attributes: bool,
+    /// Re-run whenever an input changes
+    #[arg(long)]
+    watch: bool,
+}
+
+// by extension, compressed files by the database they hold as when given alone
+fn is_database(path: &Path) -> bool {
+    if !is_compressed(path) {
+        return Format::from_path(path).is_some();
+    }
+    open_decompressed(path).is_ok_and(|(_, inner)| Format::from_path(inner).is_some())
+}
+
+// returns (path, path relative to output directory)
+fn expand(input: &str) -> Result<Vec<(PathBuf, PathBuf)>, Error> {
+    let path = Path::new(input);
+    let mut ret = Vec::new();
+    if input.contains(['*', '?', '[']) {
+        let base: PathBuf = path
+            .components()
+            .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+            .collect();
+        let paths = glob::glob(input).map_err(|e| Error::IO(e.to_string()))?;
+        for p in paths {
+            let p = p.map_err(|e| Error::IO(e.to_string()))?;
+            if p.is_file() {
+                let rel = p.strip_prefix(&base).unwrap_or(&p).to_path_buf();
+                ret.push((p, rel));
+            }
+        }
+    } else if input != "-" && path.is_dir() {
+        let mut dirs = vec![path.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(dir)? {
+                let p = entry?.path();
+                if p.is_dir() {
+                    dirs.push(p);
+                } else if is_database(&p) {
+                    let rel = p.strip_prefix(path).unwrap_or(&p).to_path_buf();
+                    ret.push((p, rel));
+                }
+            }
+        }
+        ret.sort();
+    } else {
+        let name = path.file_name().map_or(path.into(), PathBuf::from);
+        ret.push((path.to_path_buf(), name));
+    }
+    Ok(ret)
+}
+
+fn output_path(out_dir: &Path, rel: &Path, to: Format) -> PathBuf {
+    // body.ldf.gz gives body.dbc, not body.ldf.dbc
+    let rel = match is_compressed(rel) {
+        true => rel.with_extension(""),
+        false => rel.to_path_buf(),
+    };
+    let rel: PathBuf = rel
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect();
+    out_dir.join(rel).with_extension(to.extension())
+}
+
+// returns notes on the conversion for the caller to print
+fn convert(
+    input: &Path,
+    output: &Path,
+    args: &Args,
+    to: Option<Format>,
+) -> Result<Vec<String>, Error> {
+    let mut notes = Vec::new();
+    let mut db = load(input, args.from)?;
+    let to_ldf = to.or(Format::from_path(output)) == Some(Format::LDF);
+    if to_ldf && matches!(db.extra, DatabaseType::DBC(_)) {
+        let options = SkeletonOptions {
+            commander: args.commander.clone(),
+            ..Default::default()
+        };
+        let report;
+        (db, report) = db.to_ldf_skeleton(&options)?;
+        for note in report {
+            notes.push(format!("note: {}: {}", input.display(), note));
+        }
+    }
+    if args.apply_postfix {
+        db = db.apply_postfix();
+    }
+    if let Some(units) = &args.normalize_units {
+        let units: Vec<&str> = units.iter().map(|u| u.as_str()).collect();
+        db.normalize_units(&units)?;
+    }
+    if let Some(dir) = output.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let options = WriteOptions {
+        sorted: args.sorted,
+        provenance: args.provenance,
+        vector_attributes: args.vector_attributes,
+    };
+    save(&db, output, to, &options)?;
+    Ok(notes)
+}
+
+fn convert_batch(jobs: &[(PathBuf, PathBuf)], args: &Args, to: Format) -> usize {
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    std::thread::scope(|s| {
+        for _ in 0..threads.min(jobs.len()) {
+            s.spawn(|| {
+                while let Some((input, output)) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = convert(input, output, args, Some(to));
+                    // a file's lines stay together
+                    let (mut out, mut err) = (std::io::stdout().lock(), std::io::stderr().lock());
+                    let _ = match result {
+                        Ok(notes) => {
+                            for note in notes {
+                                let _ = writeln!(err, "{}", note);
+                            }
+                            writeln!(out, "ok    {} -> {}", input.display(), output.display())
+                        }
+                        Err(e) => {
+                            failed.fetch_add(1, Ordering::Relaxed);
+                            writeln!(out, "FAIL  {}: {}", input.display(), e)
+                        }
+                    };
+                }
+            });
+        }
+    });
+    failed.into_inner()
+}
+
+// exits as clap does for its own checks, the number of inputs is only known once globs are expanded
+fn usage_error(kind: ErrorKind, message: &str) -> ! {
+    let mut cli = crate::Cli::command();
+    cli.build();
+    let convert = cli.find_subcommand_mut("convert").unwrap();
+    convert.error(kind, message).exit()
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let mut inputs = Vec::new();
+    for i in &args.inputs {
+        inputs.extend(expand(i)?);
+    }
+    let watched: Vec<&Path> = inputs.iter().map(|(p, _)| p.as_path()).collect();
+
+    let Some(out_dir) = &args.out_dir else {
+        if inputs.len() != 1 {
+            usage_error(
+                ErrorKind::MissingRequiredArgument,
+                "multiple inputs require --out-dir",
+            );
+        }
+        let output = args.output.as_deref().unwrap_or(Path::new("-"));
+        let input = &inputs[0].0;
+        let run = || {
+            for note in convert(input, output, &args, args.to)? {
+                eprintln!("{}", note);
+            }
+            if output != Path::new("-") {
+                eprintln!("wrote {}", output.display());
+            }
+            Ok(())
+        };
+        return if args.watch {
+            watch(&watched, run)
+        } else {
+            run()
+        };
+    };
+    let to = args.to.unwrap(); // required by --out-dir
+    let jobs: Vec<(PathBuf, PathBuf)> = inputs
+        .iter()
+        .map(|(p, rel)| (p.clone(), output_path(out_dir, rel, to)))
+        .collect();
+    let run = || {
+        let failed = convert_batch(&jobs, &args, to);
+        println!("{} converted, {} failed", jobs.len() - failed, failed);
+        if failed > 0 {
+            Err(Error::IO(format!("{} conversion(s) failed", failed)))
+        } else {
+            Ok(())
+        }
+    };
+    if args.watch {
+        watch(&watched, run)
+    } else {
+        run()
+    }
+}
@@ -0,0 +1,90 @@
+use crate::load;
+use autodbconv::{parse_hex, Database, Error, Format};
+use std::io::BufRead;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database used for decoding
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Frame ID, reads frames from stdin if not given
+    #[arg(long, value_parser = parse_id, requires = "data")]
+    id: Option<u32>,
+    /// Payload as hex bytes
+    #[arg(long, requires = "id")]
+    data: Option<String>,
+}
+
+fn parse_id(s: &str) -> Result<u32, String> {
+    match s.strip_prefix("0x").or(s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn print_frame(db: &Database, id: u32, data: &[u8]) -> Result<(), Error> {
+    let (name, signals) = db.decode_frame(id, data)?;
+    println!("{} (0x{:02X})", name, id);
+    for s in signals {
+        let mut line = match (&s.text, &s.bytes, s.signed) {
+            (Some(text), _, _) => format!("  {} = \"{}\"", s.name, text.escape_default()),
+            (None, Some(bytes), _) => format!(
+                "  {} = {{{}}}",
+                s.name,
+                bytes
+                    .iter()
+                    .map(|b| format!("0x{:02X}", b))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            (None, None, Some(value)) => format!("  {} = {}", s.name, value),
+            (None, None, None) => format!("  {} = {}", s.name, s.raw),
+        };
+        if let Some(phys) = s.physical {
+            line += &format!(", {} {}", phys, s.unit.unwrap_or_default());
+        }
+        if let Some(label) = s.label {
+            line += &format!(", \"{}\"", label);
+        }
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+// accepts "ID#DATA" anywhere in the line (candump) or "ID DATA..."
+fn parse_line(line: &str) -> Option<(u32, Vec<u8>)> {
+    if let Some(frame) = line.split_whitespace().find(|t| t.contains('#')) {
+        let (id, data) = frame.split_once('#')?;
+        Some((u32::from_str_radix(id, 16).ok()?, parse_hex(data).ok()?))
+    } else {
+        let (id, data) = line.trim().split_once(char::is_whitespace)?;
+        Some((parse_id(id).ok()?, parse_hex(data).ok()?))
+    }
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    if let (Some(id), Some(data)) = (args.id, args.data) {
+        return print_frame(&db, id, &parse_hex(&data)?);
+    }
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line(&line) {
+            Some((id, data)) => {
+                if let Err(e) = print_frame(&db, id, &data) {
+                    eprintln!("{}: {}", line, e);
+                }
+            }
+            None => eprintln!("{}: unrecognized frame", line),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::{load, print_serialized, OutputFormat};
+use autodbconv::Error;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Old database
+    old: PathBuf,
+    /// New database
+    new: PathBuf,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let old = load(&args.old, None)?;
+    let new = load(&args.new, None)?;
+    let diff = autodbconv::diff(&old, &new);
+    if args.format == OutputFormat::Table {
+        print!("{}", diff);
+        Ok(())
+    } else {
+        print_serialized(args.format, &diff)
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{load, save};
+use autodbconv::{Error, Filter, Format};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Input database, "-" for stdin
+    input: PathBuf,
+    /// Output database, "-" for stdout
+    #[arg(short, long, default_value = "-")]
+    output: PathBuf,
+    /// Keep frames published or subscribed by this node, can be repeated
+    #[arg(long = "node")]
+    nodes: Vec<String>,
+    /// Keep frames matching this glob or /regex/, can be repeated
+    #[arg(long)]
+    messages: Vec<String>,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output format, inferred from extension if not given
+    #[arg(long)]
+    to: Option<Format>,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let filter = Filter {
+        nodes: args.nodes,
+        messages: args.messages,
+    };
+    let db = load(&args.input, args.from)?;
+    for pattern in &filter.messages {
+        db.find_messages(pattern)?; // reports invalid patterns
+    }
+    let db = db.filter(&filter);
+    save(&db, &args.output, args.to, &Default::default())
+}
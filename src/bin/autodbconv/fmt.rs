@@ -0,0 +1,54 @@
+use autodbconv::{format_ldf, Error};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// LDF files to format, "-" for stdin
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// Rewrite the files in place instead of printing them
+    #[arg(short, long)]
+    write: bool,
+    /// Only list files that aren't formatted, failing if there are any
+    #[arg(long, conflicts_with = "write")]
+    check: bool,
+}
+
+fn format(path: &Path) -> Result<(String, String), Error> {
+    let mut src = String::new();
+    if path == Path::new("-") {
+        std::io::stdin().lock().read_to_string(&mut src)?;
+    } else {
+        src = std::fs::read_to_string(path)?;
+    }
+    let formatted = format_ldf(&src).inspect_err(|e| {
+        if e.span().is_some() {
+            eprint!("{}", e.render(&path.display().to_string(), &src));
+        }
+    })?;
+    Ok((src, formatted))
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let mut unformatted = 0;
+    for path in &args.inputs {
+        let (src, formatted) = format(path)?;
+        if args.check {
+            if src != formatted {
+                println!("{}", path.display());
+                unformatted += 1;
+            }
+        } else if args.write && path != Path::new("-") {
+            if src != formatted {
+                std::fs::write(path, formatted)?;
+            }
+        } else {
+            print!("{}", formatted);
+        }
+    }
+    if unformatted > 0 {
+        return Err(Error::IO(format!("{} file(s) not formatted", unformatted)));
+    }
+    Ok(())
+}
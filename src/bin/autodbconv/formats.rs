@@ -0,0 +1,49 @@
+use crate::inspect::print_table;
+use crate::{print_serialized, OutputFormat};
+use autodbconv::{Error, Format};
+use serde::Serialize;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+#[derive(Serialize)]
+struct FormatInfo {
+    name: &'static str,
+    extensions: Vec<&'static str>,
+    read: bool,
+    write: bool,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let formats: Vec<FormatInfo> = Format::all()
+        .into_iter()
+        .map(|f| FormatInfo {
+            name: f.name(),
+            extensions: f.extensions(),
+            read: f.can_read(),
+            write: f.can_write(),
+        })
+        .collect();
+    if args.format != OutputFormat::Table {
+        return print_serialized(args.format, &formats);
+    }
+    let yes = |b: bool| if b { "yes" } else { "no" }.to_string();
+    let rows: Vec<Vec<String>> = formats
+        .iter()
+        .map(|f| {
+            let extensions: Vec<String> = f.extensions.iter().map(|e| format!(".{}", e)).collect();
+            vec![
+                f.name.to_string(),
+                extensions.join(" "),
+                yes(f.read),
+                yes(f.write),
+            ]
+        })
+        .collect();
+    print_table("Formats", &["Name", "Extensions", "Read", "Write"], &rows);
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use crate::{load, print_serialized, OutputFormat};
+use autodbconv::{Database, DatabaseType, Error, Format, Name};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database to inspect
+    input: PathBuf,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+pub fn print_table(title: &str, headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<w$}", c, w = w))
+            .collect();
+        println!("  {}", cells.join("  ").trim_end());
+    };
+    println!("{}", title);
+    line(headers.to_vec());
+    line(
+        widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<_>>()
+            .iter()
+            .map(|s| s.as_str())
+            .collect(),
+    );
+    for row in rows {
+        line(row.iter().map(|s| s.as_str()).collect());
+    }
+    println!();
+}
+
+pub fn sorted_keys<T>(map: &std::collections::HashMap<Name, T>) -> Vec<&Name> {
+    let mut keys: Vec<&Name> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.input, args.from)?;
+    if args.format == OutputFormat::Table {
+        print_database(&db);
+        Ok(())
+    } else {
+        print_serialized(args.format, &db)
+    }
+}
+
+fn print_database(db: &Database) {
+    if let DatabaseType::LDF(data) = &db.extra {
+        println!(
+            "LDF, {} kbps, channel {}",
+            data.bitrate / 1000.0,
+            if data.postfix.is_empty() {
+                "-"
+            } else {
+                &data.postfix
+            }
+        );
+        println!();
+        let mut rows = vec![vec![
+            data.commander.to_string(),
+            "commander".to_string(),
+            "-".to_string(),
+            "-".to_string(),
+            "-".to_string(),
+            format!("time base {} ms, jitter {} ms", data.time_base, data.jitter),
+        ]];
+        for name in sorted_keys(&data.responders) {
+            let r = &data.responders[name];
+            rows.push(vec![
+                name.to_string(),
+                "responder".to_string(),
+                r.protocol.clone(),
+                format!("0x{:02X}", r.configured_nad),
+                r.initial_nad
+                    .map_or("-".to_string(), |n| format!("0x{:02X}", n)),
+                r.product_id.map_or("-".to_string(), |(s, f, v)| {
+                    format!("product 0x{:04X}:0x{:04X}:{}", s, f, v)
+                }),
+            ]);
+        }
+        print_table(
+            "Nodes",
+            &["NAME", "ROLE", "PROTOCOL", "NAD", "INITIAL NAD", "INFO"],
+            &rows,
+        );
+    } else {
+        println!("{}", db.extra.name());
+        println!();
+        let rows: Vec<Vec<String>> = db
+            .nodes()
+            .iter()
+            .map(|n| {
+                vec![
+                    n.name.to_string(),
+                    n.role.to_string(),
+                    n.tx_frames.join(", "),
+                    n.rx_frames.join(", "),
+                ]
+            })
+            .collect();
+        if !rows.is_empty() {
+            print_table("Nodes", &["NAME", "ROLE", "SENDS", "RECEIVES"], &rows);
+        }
+    }
+
+    let mut frames: Vec<_> = db.messages.iter().collect();
+    frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+    let mut rows: Vec<Vec<String>> = frames
+        .iter()
+        .map(|(name, msg)| {
+            vec![
+                name.to_string(),
+                "unconditional".to_string(),
+                format!("0x{:02X}", msg.id),
+                msg.byte_width.to_string(),
+                msg.sender.to_string(),
+                msg.signals.join(", "),
+            ]
+        })
+        .collect();
+    if let DatabaseType::LDF(data) = &db.extra {
+        for name in sorted_keys(&data.event_frames) {
+            let (resolver, id, list) = &data.event_frames[name];
+            rows.push(vec![
+                name.to_string(),
+                "event triggered".to_string(),
+                format!("0x{:02X}", id),
+                list.first()
+                    .map_or("-".to_string(), |f| db.messages[f].byte_width.to_string()),
+                format!("resolver {}", resolver),
+                list.join(", "),
+            ]);
+        }
+        for name in sorted_keys(&data.sporadic_frames) {
+            rows.push(vec![
+                name.to_string(),
+                "sporadic".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+                data.commander.to_string(),
+                data.sporadic_frames[name].join(" > "), // priority order
+            ]);
+        }
+    }
+    print_table(
+        "Frames",
+        &["NAME", "TYPE", "ID", "LENGTH", "PUBLISHER", "CONTENTS"],
+        &rows,
+    );
+
+    let mut rows = Vec::new();
+    for (frame, msg) in &frames {
+        for name in &msg.signals {
+            let sig = &db.signals[name];
+            let scaling: Vec<String> = sig
+                .encodings
+                .iter()
+                .flatten()
+                .map(|e| e.to_string())
+                .collect();
+            rows.push(vec![
+                name.to_string(),
+                frame.to_string(),
+                sig.bit_start.to_string(),
+                sig.bit_width.to_string(),
+                sig.init_value.to_string(),
+                scaling.join("; "),
+            ]);
+        }
+    }
+    for name in sorted_keys(&db.signals) {
+        if !frames.iter().any(|(_, m)| m.signals.contains(name)) {
+            let sig = &db.signals[name];
+            rows.push(vec![
+                name.to_string(),
+                "-".to_string(),
+                "-".to_string(),
+                sig.bit_width.to_string(),
+                sig.init_value.to_string(),
+                String::new(),
+            ]);
+        }
+    }
+    print_table(
+        "Signals",
+        &["NAME", "FRAME", "BIT START", "WIDTH", "INIT", "SCALING"],
+        &rows,
+    );
+
+    if let DatabaseType::LDF(data) = &db.extra {
+        for name in sorted_keys(&data.schedule_tables) {
+            let table = &data.schedule_tables[name];
+            let mut time = 0.0;
+            let rows: Vec<Vec<String>> = table
+                .iter()
+                .enumerate()
+                .map(|(i, (cmd, delay))| {
+                    let row = vec![
+                        i.to_string(),
+                        format!("{} ms", time),
+                        cmd.to_string(),
+                        format!("{} ms", delay),
+                    ];
+                    time += delay;
+                    row
+                })
+                .collect();
+            print_table(
+                &format!("Schedule table {} ({} ms)", name, time),
+                &["#", "START", "COMMAND", "DELAY"],
+                &rows,
+            );
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use crate::load;
+use autodbconv::{render_layout, render_layout_svg, Error, Format};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database containing the frames
+    input: PathBuf,
+    /// Frames to draw, all if not given
+    messages: Vec<String>,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Write a single frame as SVG instead
+    #[arg(long)]
+    svg: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.input, args.from)?;
+    if let Some(path) = &args.svg {
+        let [message] = args.messages.as_slice() else {
+            return Err(Error::IO("--svg requires a single frame".to_string()));
+        };
+        std::fs::write(path, render_layout_svg(&db, message)?)?;
+        eprintln!("wrote {}", path.display());
+        return Ok(());
+    }
+
+    let mut messages = args.messages.clone();
+    if messages.is_empty() {
+        let mut frames: Vec<_> = db.messages.iter().collect();
+        frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+        messages = frames.into_iter().map(|(n, _)| n.to_string()).collect();
+    }
+    for message in &messages {
+        println!("{}", render_layout(&db, message)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use autodbconv::{open_decompressed, Database, Error, Format, Warning, WriteOptions};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+mod build;
+mod codegen;
+mod compare;
+mod completions;
+mod convert;
+mod decode;
+mod diff;
+mod filter;
+mod fmt;
+mod formats;
+mod inspect;
+mod layout;
+mod man;
+mod migrate;
+#[cfg(feature = "tui")]
+mod monitor;
+mod rename;
+mod replay;
+mod routes;
+mod schedule;
+mod stats;
+mod traffic;
+mod validate;
+mod vectors;
+mod watch;
+
+#[derive(Parser)]
+#[command(version, about = "Automotive database converter")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Regenerate every target of an autodbconv.toml manifest
+    Build(build::Args),
+    /// Generate code for packing and unpacking frames
+    Codegen(codegen::Args),
+    /// Compare the signals of two bus logs decoded with the same database
+    Compare(compare::Args),
+    /// Convert a database between formats
+    Convert(convert::Args),
+    /// Decode a frame payload, or frames read from stdin
+    Decode(decode::Args),
+    /// Show semantic differences between two databases
+    Diff(diff::Args),
+    /// Extract a subset of a database by node or frame name
+    Filter(filter::Args),
+    /// Rewrite LDF files in canonical form, keeping comments
+    Fmt(fmt::Args),
+    /// List the database formats that can be read or written
+    Formats(formats::Args),
+    /// Pretty-print the contents of a database
+    #[command(alias = "dump")]
+    Inspect(inspect::Args),
+    /// Draw which signal occupies each bit of a frame
+    Layout(layout::Args),
+    /// Upgrade an LDF to LIN 2.2, reporting assumptions made
+    Migrate(migrate::Args),
+    /// Show live signal values from SocketCAN, a LIN serial port or candump on stdin
+    #[cfg(feature = "tui")]
+    Monitor(monitor::Args),
+    /// Rename signals, frames, nodes or schedule tables and every reference to them
+    Rename(rename::Args),
+    /// Decode a bus log into a CSV of signal values over time
+    Replay(replay::Args),
+    /// Print the signal routing table of a project
+    Routes(routes::Args),
+    /// Print the timeline of a schedule table
+    Schedule(schedule::Args),
+    /// Summarize the size of a database
+    Stats(stats::Args),
+    /// Generate a synthetic bus log, for testing log tooling
+    Traffic(traffic::Args),
+    /// Check that a database is valid
+    Validate(validate::Args),
+    /// Generate known answer vectors for testing frame codecs
+    Vectors(vectors::Args),
+    /// Print shell completions
+    #[command(hide = true)]
+    Completions(completions::Args),
+    /// Print or write man pages
+    #[command(hide = true)]
+    Man(man::Args),
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    #[value(alias = "text")]
+    Table,
+    Json,
+    Yaml,
+}
+
+// machine-readable output, tables are printed by each subcommand
+fn print_serialized(format: OutputFormat, value: &impl Serialize) -> Result<(), Error> {
+    match format {
+        OutputFormat::Table => unreachable!(),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        OutputFormat::Yaml => print!(
+            "{}",
+            serde_yaml::to_string(value).map_err(|e| Error::IO(e.to_string()))?
+        ),
+    }
+    Ok(())
+}
+
+// "-" reads from stdin, the format is detected from the content if not given or implied by the extension
+// parse errors are rendered with their source here, see report()
+fn load(path: &Path, format: Option<Format>) -> Result<Database, Error> {
+    Ok(load_with_warnings(path, format)?.0)
+}
+
+// "-" reads stdin, .gz and .zip files are decompressed, also returns the path naming what was read
+fn read_input(path: &Path) -> Result<(String, PathBuf), Error> {
+    let mut src = String::new();
+    if path == Path::new("-") {
+        std::io::stdin().lock().read_to_string(&mut src)?;
+        return Ok((src, path.to_path_buf()));
+    }
+    let (mut reader, inner) = open_decompressed(path)?;
+    reader.read_to_string(&mut src)?;
+    Ok((src, inner))
+}
+
+// the format given, else the one the path read or the text implies
+fn input_format(src: &str, inner: &Path, format: Option<Format>) -> Result<Format, Error> {
+    format
+        .or(Format::from_path(inner))
+        .or(Format::detect(src.as_bytes()))
+        .ok_or(Error::UnknownFormat)
+}
+
+fn load_with_warnings(
+    path: &Path,
+    format: Option<Format>,
+) -> Result<(Database, Vec<Warning>), Error> {
+    let (src, inner) = read_input(path)?;
+    let format = input_format(&src, &inner, format)?;
+    parse_with_warnings(path, &src, format)
+}
+
+// src as read from path by read_input, errors with a location are printed against it
+fn parse_with_warnings(
+    path: &Path,
+    src: &str,
+    format: Format,
+) -> Result<(Database, Vec<Warning>), Error> {
+    let (db, warnings) = format
+        .parse_reader_with_warnings(src.as_bytes())
+        .inspect_err(|e| {
+            if e.span().is_some() {
+                eprint!("{}", e.render(&path.display().to_string(), src));
+            }
+        })?;
+    if path == Path::new("-") {
+        return Ok((db, warnings));
+    }
+    Ok((db.with_source(path), warnings))
+}
+
+// "-" writes to stdout, format must then be given
+fn save(
+    db: &Database,
+    path: &Path,
+    format: Option<Format>,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let format = format
+        .or(Format::from_path(path))
+        .ok_or(Error::UnknownFormat)?;
+    if path == Path::new("-") {
+        format.write_writer_with(db, std::io::stdout().lock(), options)
+    } else {
+        format.write_with(db, path, options)
+    }
+}
+
+fn report(e: &Error) {
+    if e.span().is_none() {
+        eprintln!("error: {}", e);
+    }
+}
+
+fn main() {
+    env_logger::init();
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Build(args) => build::run(args),
+        Command::Codegen(args) => codegen::run(args),
+        Command::Compare(args) => compare::run(args),
+        Command::Convert(args) => convert::run(args),
+        Command::Decode(args) => decode::run(args),
+        Command::Diff(args) => diff::run(args),
+        Command::Filter(args) => filter::run(args),
+        Command::Fmt(args) => fmt::run(args),
+        Command::Formats(args) => formats::run(args),
+        Command::Inspect(args) => inspect::run(args),
+        Command::Layout(args) => layout::run(args),
+        Command::Migrate(args) => migrate::run(args),
+        #[cfg(feature = "tui")]
+        Command::Monitor(args) => monitor::run(args),
+        Command::Rename(args) => rename::run(args),
+        Command::Replay(args) => replay::run(args),
+        Command::Routes(args) => routes::run(args),
+        Command::Schedule(args) => schedule::run(args),
+        Command::Stats(args) => stats::run(args),
+        Command::Traffic(args) => traffic::run(args),
+        Command::Validate(args) => validate::run(args),
+        Command::Vectors(args) => vectors::run(args),
+        Command::Completions(args) => completions::run(args),
+        Command::Man(args) => man::run(args),
+    };
+    if let Err(e) = result {
+        report(&e);
+        std::process::exit(1);
+    }
+}
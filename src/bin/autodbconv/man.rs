@@ -0,0 +1,23 @@
+use crate::Cli;
+use autodbconv::Error;
+use clap::CommandFactory;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Directory to write a page per subcommand to, otherwise prints the main page
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let cmd = Cli::command();
+    match args.out_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)?;
+            clap_mangen::generate_to(cmd, &dir)?;
+        }
+        None => clap_mangen::Man::new(cmd).render(&mut std::io::stdout())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use crate::{load, save};
+use autodbconv::{Error, Format};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Input LDF, "-" for stdin
+    input: PathBuf,
+    /// Output database, "-" for stdout
+    #[arg(short, long, default_value = "-")]
+    output: PathBuf,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output format, inferred from extension if not given
+    #[arg(long)]
+    to: Option<Format>,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let (db, report) = load(&args.input, args.from)?.migrate_lin22()?;
+    for note in &report {
+        eprintln!("note: {}", note);
+    }
+    save(
+        &db,
+        &args.output,
+        args.to.or(args.from),
+        &Default::default(),
+    )
+}
@@ -0,0 +1,240 @@
+use crate::load;
+use autodbconv::{
+    parse_asc, parse_candump, Error, Format, LinSniffer, LogFrame, SignalMonitor, SignalState,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Row, Table, TableState};
+use ratatui::Frame;
+use std::io::{BufRead, Read};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+const REFRESH: Duration = Duration::from_millis(100);
+const STALE: f64 = 1000.0; // ms, signals not seen for this long or three periods are dimmed
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database describing the bus
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// SocketCAN interface to read, e.g. can0
+    #[arg(long, conflicts_with = "serial")]
+    can: Option<String>,
+    /// Serial port wired to a LIN transceiver, e.g. /dev/ttyUSB0
+    #[arg(long)]
+    serial: Option<String>,
+    /// Baud rate of the LIN bus
+    #[arg(long, default_value_t = 19200)]
+    baud: u32,
+    /// Only show signals or frames containing this, editable while running
+    #[arg(long, default_value = "")]
+    filter: String,
+}
+
+// stdin takes candump -L or ASC lines, e.g. candump -L can0 | autodbconv monitor --db body.dbc
+fn read_stdin(start: Instant, tx: Sender<LogFrame>) -> Result<(), Error> {
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let frames = match parse_candump(&line) {
+            frames if frames.is_empty() => parse_asc(&line),
+            frames => frames,
+        };
+        for mut frame in frames {
+            frame.time = start.elapsed().as_secs_f64() * 1000.0;
+            if tx.send(frame).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_serial(
+    mut sniffer: LinSniffer,
+    path: &str,
+    baud: u32,
+    start: Instant,
+    tx: Sender<LogFrame>,
+) -> Result<(), Error> {
+    let mut port = serialport::new(path, baud)
+        .timeout(REFRESH)
+        .open()
+        .map_err(|e| Error::Hint(path.to_string(), Box::new(Error::IO(e.to_string()))))?;
+    let mut buf = [0; 256];
+    loop {
+        let n = match port.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let time = start.elapsed().as_secs_f64() * 1000.0;
+        for frame in sniffer.push(&buf[..n], time) {
+            if tx.send(frame).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_socketcan(interface: &str, start: Instant, tx: Sender<LogFrame>) -> Result<(), Error> {
+    let mut socket = autodbconv::SocketCan::open(interface)?;
+    loop {
+        let mut frame = socket.recv()?;
+        frame.time = start.elapsed().as_secs_f64() * 1000.0;
+        if tx.send(frame).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_socketcan(_: &str, _: Instant, _: Sender<LogFrame>) -> Result<(), Error> {
+    Err(Error::Hint(
+        "SocketCAN needs Linux".to_string(),
+        Box::new(Error::NotImplemented),
+    ))
+}
+
+fn row<'a>(state: &'a SignalState, now: f64) -> Row<'a> {
+    let age = state.age(now);
+    let period = match state.rate() {
+        0.0 => 0.0,
+        rate => 1000.0 / rate,
+    };
+    let style = if state.out_of_range {
+        Style::new().fg(Color::Red)
+    } else if age > STALE.max(3.0 * period) {
+        Style::new().fg(Color::DarkGray)
+    } else {
+        Style::new()
+    };
+    Row::new([
+        state.signal.to_string(),
+        state.message.to_string(),
+        format!("{}", state.value),
+        state.unit.clone(),
+        format!("{:.1}", state.rate()),
+        format!("{:.1}", age / 1000.0),
+    ])
+    .style(style)
+}
+
+fn draw(
+    frame: &mut Frame,
+    monitor: &SignalMonitor,
+    source: &str,
+    filter: &str,
+    now: f64,
+    table: &mut TableState,
+) {
+    let [status, body] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(frame.area());
+    let signals = monitor.signals(filter);
+    frame.render_widget(
+        Line::from(format!(
+            "{}: {} frames, {} unknown, {} signals, filter: {}_   (type to filter, q quits)",
+            source,
+            monitor.frames,
+            monitor.unknown,
+            signals.len(),
+            filter
+        ))
+        .reversed(),
+        status,
+    );
+    let widths = [
+        Constraint::Fill(3),
+        Constraint::Fill(3),
+        Constraint::Fill(2),
+        Constraint::Fill(1),
+        Constraint::Length(9),
+        Constraint::Length(8),
+    ];
+    let header = Row::new(["Signal", "Frame", "Value", "Unit", "Rate (Hz)", "Age (s)"]).bold();
+    let rows = signals.iter().map(|s| row(s, now));
+    frame.render_stateful_widget(
+        Table::new(rows, widths)
+            .header(header)
+            .row_highlight_style(Style::new().reversed()),
+        body,
+        table,
+    );
+}
+
+fn run_tui(
+    args: &mut Args,
+    db: &autodbconv::Database,
+    rx: Receiver<LogFrame>,
+    start: Instant,
+) -> Result<(), Error> {
+    let source = match (&args.can, &args.serial) {
+        (Some(interface), _) => interface.clone(),
+        (_, Some(path)) => path.clone(),
+        _ => "stdin".to_string(),
+    };
+    let mut monitor = SignalMonitor::new(db);
+    let mut table = TableState::default();
+    let mut terminal = ratatui::init();
+    let result = loop {
+        for frame in rx.try_iter() {
+            monitor.update(&frame);
+        }
+        let now = start.elapsed().as_secs_f64() * 1000.0;
+        if let Err(e) = terminal.draw(|f| draw(f, &monitor, &source, &args.filter, now, &mut table))
+        {
+            break Err(e.into());
+        }
+        match event::poll(REFRESH).and_then(|ready| ready.then(event::read).transpose()) {
+            Ok(Some(Event::Key(key))) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break Ok(()),
+                KeyCode::Char('q') if args.filter.is_empty() => break Ok(()),
+                KeyCode::Esc if args.filter.is_empty() => break Ok(()),
+                KeyCode::Esc => args.filter.clear(),
+                KeyCode::Char(c) => args.filter.push(c),
+                KeyCode::Backspace => {
+                    args.filter.pop();
+                }
+                KeyCode::Down => table.select_next(),
+                KeyCode::Up => table.select_previous(),
+                _ => (),
+            },
+            Ok(_) => (),
+            Err(e) => break Err(e.into()),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+pub fn run(mut args: Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    let start = Instant::now();
+    let (tx, rx) = channel();
+    let reader = match (&args.can, &args.serial) {
+        (Some(interface), _) => {
+            let interface = interface.clone();
+            std::thread::spawn(move || read_socketcan(&interface, start, tx))
+        }
+        (_, Some(path)) => {
+            let sniffer = LinSniffer::new(&db, path)?;
+            let (path, baud) = (path.clone(), args.baud);
+            std::thread::spawn(move || read_serial(sniffer, &path, baud, start, tx))
+        }
+        _ => std::thread::spawn(move || read_stdin(start, tx)),
+    };
+    run_tui(&mut args, &db, rx, start)?;
+
+    // a reader that stopped on an error is done, one still waiting for input is left behind
+    if reader.is_finished() {
+        reader.join().unwrap()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::{read_input, save};
+use autodbconv::{rename_ldf, Database, DatabaseType, EntityKind, Error, Format, Name};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database to rename in, "-" for stdin
+    #[arg(long)]
+    db: PathBuf,
+    /// Output database, "-" for stdout, LDFs keep their comments
+    #[arg(short, long, default_value = "-")]
+    output: PathBuf,
+    /// Rename a signal, e.g. LSMerror=LSM_Error, can be repeated
+    #[arg(long = "signal", value_parser = parse_rename)]
+    signals: Vec<(Name, Name)>,
+    /// Rename a frame, including sporadic and event triggered ones, can be repeated
+    #[arg(long = "frame", value_parser = parse_rename)]
+    frames: Vec<(Name, Name)>,
+    /// Rename a node, can be repeated
+    #[arg(long = "node", value_parser = parse_rename)]
+    nodes: Vec<(Name, Name)>,
+    /// Rename a schedule table, can be repeated
+    #[arg(long = "schedule-table", value_parser = parse_rename)]
+    schedule_tables: Vec<(Name, Name)>,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output format, the input's if not given or implied by the extension
+    #[arg(long)]
+    to: Option<Format>,
+}
+
+fn parse_rename(s: &str) -> Result<(Name, Name), String> {
+    match s.split_once('=') {
+        Some((old, new)) if !old.is_empty() && !new.is_empty() => Ok((old.into(), new.into())),
+        _ => Err(format!("expected OLD=NEW: {}", s)),
+    }
+}
+
+fn exists(db: &Database, kind: EntityKind, name: &str) -> bool {
+    let ldf = match &db.extra {
+        DatabaseType::LDF(data) => Some(data),
+        _ => None,
+    };
+    match kind {
+        EntityKind::Signal => db.signals.contains_key(name),
+        EntityKind::Node => db.node(name).is_some(),
+        EntityKind::ScheduleTable => ldf.is_some_and(|d| d.schedule_tables.contains_key(name)),
+        _ => {
+            db.messages.contains_key(name)
+                || ldf.is_some_and(|d| {
+                    d.sporadic_frames.contains_key(name) || d.event_frames.contains_key(name)
+                })
+        }
+    }
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let (src, inner) = read_input(&args.db)?;
+    let from = args
+        .from
+        .or(Format::from_path(inner))
+        .or(Format::detect(src.as_bytes()))
+        .ok_or(Error::UnknownFormat)?;
+    let render = |e: &Error| {
+        if e.span().is_some() {
+            eprint!("{}", e.render(&args.db.display().to_string(), &src));
+        }
+    };
+    let mut db = from.parse_reader(src.as_bytes()).inspect_err(render)?;
+
+    let renames: Vec<(EntityKind, HashMap<Name, Name>)> = [
+        (EntityKind::Node, &args.nodes, Error::UnknownNode),
+        (EntityKind::Frame, &args.frames, Error::UnknownFrame),
+        (EntityKind::Signal, &args.signals, Error::UnknownSignal),
+        (
+            EntityKind::ScheduleTable,
+            &args.schedule_tables,
+            Error::UnknownScheduleTable,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, renames, _)| !renames.is_empty())
+    .map(|(kind, renames, error)| {
+        if let Some((old, _)) = renames.iter().find(|(old, _)| !exists(&db, kind, old)) {
+            return Err(Error::Hint(old.to_string(), Box::new(error)));
+        }
+        Ok((kind, renames.iter().cloned().collect()))
+    })
+    .collect::<Result<_, _>>()?;
+
+    // LDFs are re-emitted from their source to keep comments
+    let to = args.to.or(Format::from_path(&args.output)).unwrap_or(from);
+    if from == Format::LDF && to == Format::LDF {
+        let renamed = rename_ldf(&src, &renames)?;
+        if args.output == Path::new("-") {
+            print!("{}", renamed);
+        } else {
+            std::fs::write(&args.output, renamed)?;
+        }
+        return Ok(());
+    }
+    for (kind, renames) in &renames {
+        db.rename(*kind, renames)?;
+    }
+    save(&db, &args.output, Some(to), &Default::default())
+}
@@ -0,0 +1,82 @@
+use crate::load;
+use autodbconv::{write_series_csv, Error, Format, LogFormat, LogFrame, SeriesOptions};
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database describing the bus
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Bus log: ASC, BLF or candump -l
+    log: PathBuf,
+    /// Log format (asc, blf or candump), inferred from extension if not given
+    #[arg(long)]
+    log_format: Option<LogFormat>,
+    /// Signals to write, comma separated, every signal of a logged frame if not given
+    #[arg(long, value_delimiter = ',')]
+    signals: Vec<String>,
+    /// Only frames from this channel, e.g. 1 or can0
+    #[arg(long)]
+    channel: Option<String>,
+    /// Repeat a signal's last value in rows where its frame wasn't received
+    #[arg(long)]
+    hold: bool,
+    /// Output CSV, or Parquet if it ends in .parquet, stdout if not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Rows per Parquet row group
+    #[cfg(feature = "parquet")]
+    #[arg(long, default_value_t = autodbconv::PARQUET_ROW_GROUP_ROWS)]
+    row_group_rows: usize,
+}
+
+pub(crate) fn read_log(path: &Path, format: Option<LogFormat>) -> Result<Vec<LogFrame>, Error> {
+    let format = format
+        .or_else(|| LogFormat::from_path(path))
+        .ok_or(Error::Hint(
+            format!("{}, use --log-format", path.display()),
+            Box::new(Error::UnknownFormat),
+        ))?;
+    format.read(path)
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    let frames = read_log(&args.log, args.log_format)?;
+    let options = SeriesOptions {
+        signals: args.signals,
+        channel: args.channel,
+        hold: args.hold,
+    };
+    let series = db.signal_series(&frames, &options)?;
+    match &args.output {
+        Some(path) if path.extension().is_some_and(|e| e == "parquet") => {
+            #[cfg(feature = "parquet")]
+            autodbconv::write_series_parquet(
+                &db,
+                &series,
+                std::fs::File::create(path)?,
+                args.row_group_rows,
+            )?;
+            #[cfg(not(feature = "parquet"))]
+            return Err(Error::Hint(
+                "built without the parquet feature".to_string(),
+                Box::new(Error::NotImplemented),
+            ));
+        }
+        Some(path) => write_series_csv(&series, std::fs::File::create(path)?)?,
+        None => write_series_csv(&series, std::io::stdout().lock())?,
+    }
+    eprintln!(
+        "{} frames, {} rows, {} unknown, {} too short",
+        frames.len(),
+        series.rows.len(),
+        series.unknown,
+        series.invalid
+    );
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use crate::inspect::print_table;
+use crate::{print_serialized, OutputFormat};
+use autodbconv::{generate_routing_c, Error, NetworkProject, RouteEndpoint};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Project file declaring buses and routes
+    project: PathBuf,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+    /// Also write the table as a C header
+    #[arg(long)]
+    c: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let project = NetworkProject::load(&args.project)?;
+    let table = project.routing_table()?;
+
+    if let Some(path) = &args.c {
+        let name = path
+            .file_stem()
+            .map_or("routes".to_string(), |s| s.to_string_lossy().to_string());
+        std::fs::write(path, generate_routing_c(&table, &name)?)?;
+        eprintln!("wrote {}", path.display());
+    }
+    if args.format != OutputFormat::Table {
+        return print_serialized(args.format, &table);
+    }
+    let rows: Vec<Vec<String>> = table
+        .iter()
+        .map(|e| {
+            let endpoint = |p: &RouteEndpoint| {
+                format!(
+                    "{}.{} ({} 0x{:X}, bits {}+{})",
+                    p.bus, p.signal, p.message, p.id, p.bit_start, p.bit_width
+                )
+            };
+            vec![
+                endpoint(&e.from),
+                endpoint(&e.to),
+                format!("{} * raw + {}", e.scale, e.offset),
+            ]
+        })
+        .collect();
+    print_table("Routes", &["FROM", "TO", "CONVERSION"], &rows);
+    Ok(())
+}
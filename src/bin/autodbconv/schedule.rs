@@ -0,0 +1,187 @@
+use crate::inspect::print_table;
+use crate::{load, print_serialized, OutputFormat};
+use autodbconv::{generate_schedule_c, Error, Format, Name};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database containing the schedule table
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Schedule table to run
+    #[arg(long, required_unless_present = "coverage")]
+    table: Option<String>,
+    /// Instead list frames no schedule table sends, failing if there are any
+    #[arg(long, conflicts_with_all = ["table", "tick_period"])]
+    coverage: bool,
+    /// Also list frames a table sends less often than this, e.g. 100ms
+    #[arg(long, value_parser = parse_duration, requires = "coverage")]
+    max_period: Option<f64>,
+    /// How long to run for, e.g. 5s or 250ms (bare numbers are ms)
+    #[arg(long, value_parser = parse_duration, default_value = "1s")]
+    duration: f64,
+    /// Frames waiting to be sent by sporadic slots, e.g. CEM_Frm1,LSM_Frm1
+    #[arg(long, value_delimiter = ',')]
+    pending: Vec<String>,
+    /// Export delays as ticks of this period (e.g. 1ms) instead of simulating
+    #[arg(long, value_parser = parse_duration)]
+    tick_period: Option<f64>,
+    /// Time base for tick export, the database's if not given
+    #[arg(long, value_parser = parse_duration, requires = "tick_period")]
+    time_base: Option<f64>,
+    /// Also write the ticks as a C header
+    #[arg(long, requires = "tick_period")]
+    c: Option<PathBuf>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+// returns ms
+pub(crate) fn parse_duration(s: &str) -> Result<f64, String> {
+    let (num, scale) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1000.0)
+    } else {
+        (s, 1.0)
+    };
+    num.trim()
+        .parse::<f64>()
+        .map(|n| n * scale)
+        .map_err(|_| format!("invalid duration: {}", s))
+}
+
+fn export_ticks(args: &Args, tick_period: f64) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    let table = args.table.as_deref().unwrap_or_default();
+    let schedule = db.schedule_ticks(table, tick_period, args.time_base)?;
+    if let Some(path) = &args.c {
+        let name = path
+            .file_stem()
+            .map_or("schedule".to_string(), |s| s.to_string_lossy().to_string());
+        std::fs::write(path, generate_schedule_c(&schedule, &name)?)?;
+        eprintln!("wrote {}", path.display());
+    }
+    if args.format != OutputFormat::Table {
+        return print_serialized(args.format, &schedule);
+    }
+
+    let hex = |v: Option<u8>| v.map_or("-".to_string(), |v| format!("0x{:02X}", v));
+    let rows: Vec<Vec<String>> = schedule
+        .slots
+        .iter()
+        .map(|s| {
+            vec![
+                s.command.clone(),
+                hex(s.id),
+                hex(s.pid),
+                s.ticks.to_string(),
+            ]
+        })
+        .collect();
+    print_table(
+        &format!("{} ({} ms ticks)", table, tick_period),
+        &["COMMAND", "ID", "PID", "TICKS"],
+        &rows,
+    );
+    Ok(())
+}
+
+fn coverage(args: &Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    let coverage = db.schedule_coverage(args.max_period)?;
+    let issues = coverage.unscheduled.len() + coverage.unused.len() + coverage.slow.len();
+    if args.format != OutputFormat::Table {
+        print_serialized(args.format, &coverage)?;
+    } else {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        for frame in &coverage.unscheduled {
+            rows.push(vec![
+                frame.to_string(),
+                "-".to_string(),
+                "never scheduled".to_string(),
+            ]);
+        }
+        for frame in &coverage.unused {
+            rows.push(vec![
+                frame.to_string(),
+                "-".to_string(),
+                "in no table".to_string(),
+            ]);
+        }
+        for slow in &coverage.slow {
+            rows.push(vec![
+                slow.frame.to_string(),
+                slow.table.to_string(),
+                format!("every {} ms", slow.period),
+            ]);
+        }
+        print_table("schedule coverage", &["FRAME", "TABLE", "ISSUE"], &rows);
+    }
+    if issues > 0 {
+        return Err(Error::IO(format!("{} schedule issue(s)", issues)));
+    }
+    Ok(())
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    if args.coverage {
+        return coverage(&args);
+    }
+    if let Some(tick_period) = args.tick_period {
+        return export_ticks(&args, tick_period);
+    }
+    let db = load(&args.db, args.from)?;
+    let table = args.table.as_deref().unwrap_or_default();
+    let pending: Vec<Name> = args.pending.iter().map(|f| f.as_str().into()).collect();
+    let slots = db.simulate_schedule_pending(table, args.duration, &pending)?;
+    if args.format != OutputFormat::Table {
+        return print_serialized(args.format, &slots);
+    }
+
+    let hex = |v: Option<u8>| v.map_or("-".to_string(), |v| format!("0x{:02X}", v));
+    let rows: Vec<Vec<String>> = slots
+        .iter()
+        .map(|s| {
+            vec![
+                format!("{} ms", s.start),
+                match s.sent {
+                    Some(frame) => format!("{} ({})", s.command, frame),
+                    None => s.command.to_string(),
+                },
+                hex(s.id),
+                hex(s.pid),
+                s.byte_width.to_string(),
+                format!("{:.3} ms", s.frame_time),
+                format!("{} ms", s.delay),
+                if s.overrun { "OVERRUN" } else { "" }.to_string(),
+            ]
+        })
+        .collect();
+    print_table(
+        table,
+        &[
+            "TIME",
+            "COMMAND",
+            "ID",
+            "PID",
+            "LENGTH",
+            "FRAME TIME",
+            "DELAY",
+            "",
+        ],
+        &rows,
+    );
+    let overruns = slots.iter().filter(|s| s.overrun).count();
+    if overruns > 0 {
+        println!(
+            "{} slot(s) shorter than their worst case frame time",
+            overruns
+        );
+    }
+    Ok(())
+}
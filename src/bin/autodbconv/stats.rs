@@ -0,0 +1,95 @@
+use crate::inspect::print_table;
+use crate::{load, print_serialized, OutputFormat};
+use autodbconv::{DatabaseType, Error, Format};
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database to summarize
+    input: PathBuf,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+#[derive(Serialize)]
+struct Stats {
+    format: &'static str,
+    nodes: usize,
+    frames: usize,
+    sporadic_frames: usize,
+    event_frames: usize,
+    signals: usize,
+    encoded_signals: usize,
+    unused_signals: usize,
+    payload_bytes: usize,
+    signal_bits: usize,
+    schedule_tables: usize,
+    schedule_entries: usize,
+    fingerprint: String,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.input, args.from)?;
+    let mut stats = Stats {
+        format: db.extra.name(),
+        nodes: db.nodes().len(),
+        frames: db.messages.len(),
+        sporadic_frames: 0,
+        event_frames: 0,
+        signals: db.signals.len(),
+        encoded_signals: db
+            .signals
+            .values()
+            .filter(|s| s.encodings.is_some())
+            .count(),
+        unused_signals: db
+            .signals
+            .keys()
+            .filter(|s| !db.messages.values().any(|m| m.signals.contains(s)))
+            .count(),
+        payload_bytes: db.messages.values().map(|m| m.byte_width as usize).sum(),
+        signal_bits: db.signals.values().map(|s| s.bit_width as usize).sum(),
+        schedule_tables: 0,
+        schedule_entries: 0,
+        fingerprint: format!("{:016x}", db.fingerprint()),
+    };
+    if let DatabaseType::LDF(data) = &db.extra {
+        stats.sporadic_frames = data.sporadic_frames.len();
+        stats.event_frames = data.event_frames.len();
+        stats.schedule_tables = data.schedule_tables.len();
+        stats.schedule_entries = data.schedule_tables.values().map(|t| t.len()).sum();
+    }
+
+    if args.format != OutputFormat::Table {
+        return print_serialized(args.format, &stats);
+    }
+    let rows: Vec<Vec<String>> = [
+        ("format", stats.format.to_string()),
+        ("nodes", stats.nodes.to_string()),
+        ("frames", stats.frames.to_string()),
+        ("sporadic frames", stats.sporadic_frames.to_string()),
+        ("event triggered frames", stats.event_frames.to_string()),
+        ("signals", stats.signals.to_string()),
+        ("encoded signals", stats.encoded_signals.to_string()),
+        ("unused signals", stats.unused_signals.to_string()),
+        ("payload bytes", stats.payload_bytes.to_string()),
+        ("signal bits", stats.signal_bits.to_string()),
+        ("schedule tables", stats.schedule_tables.to_string()),
+        ("schedule entries", stats.schedule_entries.to_string()),
+        ("fingerprint", stats.fingerprint.clone()),
+    ]
+    .into_iter()
+    .map(|(k, v)| vec![k.to_string(), v])
+    .collect();
+    print_table(
+        &args.input.display().to_string(),
+        &["STATISTIC", "VALUE"],
+        &rows,
+    );
+    Ok(())
+}
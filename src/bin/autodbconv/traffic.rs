@@ -0,0 +1,102 @@
+use crate::load;
+use crate::schedule::parse_duration;
+use autodbconv::{write_asc, write_candump, Error, Format, TrafficOptions, ValuePattern};
+use clap::ValueEnum;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum LogFormat {
+    Candump,
+    Asc,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database describing the bus
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// How long to log for, e.g. 5s or 250ms (bare numbers are ms)
+    #[arg(long, value_parser = parse_duration, default_value = "1s")]
+    duration: f64,
+    /// LIN schedule table to run, the first by name if not given
+    #[arg(long)]
+    table: Option<String>,
+    /// Cycle time for CAN frames without one, they're left out if not given
+    #[arg(long, value_parser = parse_duration)]
+    cycle_time: Option<f64>,
+    /// Signal values: init, constant:VALUE, ramp:PERIOD (e.g. ramp:2s) or random
+    #[arg(long, value_parser = parse_pattern, default_value = "init")]
+    pattern: ValuePattern,
+    /// Pattern for one signal, e.g. VehicleSpeed=ramp:10s
+    #[arg(long, value_parser = parse_signal_pattern)]
+    signal: Vec<(String, ValuePattern)>,
+    /// Seed for random values, the same seed gives the same log
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Log format
+    #[arg(long, value_enum, default_value_t = LogFormat::Candump)]
+    format: LogFormat,
+    /// Interface name in candump logs
+    #[arg(long, default_value = "can0")]
+    interface: String,
+    /// Channel number in ASC logs
+    #[arg(long, default_value_t = 1)]
+    channel: u8,
+    /// Output file, stdout if not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn parse_pattern(s: &str) -> Result<ValuePattern, String> {
+    let (kind, arg) = s.split_once(':').unwrap_or((s, ""));
+    match (kind, arg) {
+        ("init", "") => Ok(ValuePattern::Init),
+        ("random", "") => Ok(ValuePattern::Random),
+        ("constant", value) => value
+            .parse()
+            .map(ValuePattern::Constant)
+            .map_err(|_| format!("invalid value: {}", value)),
+        ("ramp", period) => parse_duration(period).map(ValuePattern::Ramp),
+        _ => Err(format!("invalid pattern: {}", s)),
+    }
+}
+
+fn parse_signal_pattern(s: &str) -> Result<(String, ValuePattern), String> {
+    let (name, pattern) = s
+        .split_once('=')
+        .ok_or(format!("expected SIGNAL=PATTERN: {}", s))?;
+    Ok((name.to_string(), parse_pattern(pattern)?))
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    for (name, _) in &args.signal {
+        if db.signal(name).is_none() {
+            return Err(Error::Hint(name.clone(), Box::new(Error::UnknownSignal)));
+        }
+    }
+    let options = TrafficOptions {
+        duration: args.duration,
+        table: args.table,
+        default_cycle_time: args.cycle_time,
+        pattern: args.pattern,
+        patterns: args.signal.into_iter().collect(),
+        seed: args.seed,
+    };
+    let frames = db.generate_traffic(&options)?;
+    let log = match args.format {
+        LogFormat::Candump => write_candump(&frames, &args.interface)?,
+        LogFormat::Asc => write_asc(&frames, args.channel)?,
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, log)?;
+            eprintln!("wrote {} frames to {}", frames.len(), path.display());
+        }
+        None => print!("{}", log),
+    }
+    Ok(())
+}
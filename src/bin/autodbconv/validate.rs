@@ -0,0 +1,199 @@
+use crate::watch::watch;
+use crate::{input_format, parse_with_warnings, print_serialized, read_input, OutputFormat};
+use autodbconv::{
+    parse_ldf_str_with_spans, render_layout, Database, Error, Format, Severity, ValidationOptions,
+    Warning,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database to validate
+    input: PathBuf,
+    /// Input format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+    /// Fail on warnings too, e.g. unsupported sections, ignored encodings or rules set to warning
+    #[arg(long)]
+    deny_warnings: bool,
+    /// Also check LDF names against the LIN spec's identifier rules
+    #[arg(long)]
+    strict: bool,
+    /// Severity of a kind of issue, e.g. MuxRangeGap=warning or SignalOverlap=ignore
+    #[arg(long, value_parser = parse_severity)]
+    severity: Vec<(String, Severity)>,
+    /// Re-run whenever the input changes
+    #[arg(long)]
+    watch: bool,
+}
+
+#[derive(Serialize)]
+struct Report {
+    path: String,
+    valid: bool,
+    error: Option<String>,
+    line: Option<usize>, // one-based
+    column: Option<usize>,
+    issues: Vec<String>,   // rules checked after parsing
+    warnings: Vec<String>, // what the parser ignored or approximated, and rules set to warning
+}
+
+// with its one-based position in src
+fn describe(src: &str, warning: &Warning) -> String {
+    let (line, col) = warning.span.line_col(src);
+    format!("{}:{}: warning: {}", line + 1, col + 1, warning)
+}
+
+// layout of each frame with overlapping signals, and where the signals are declared in an LDF
+fn explain_overlaps(args: &Args, db: &Database, src: &str, format: Format) -> Result<(), Error> {
+    let overlaps = db.overlaps();
+    let mut messages: Vec<_> = overlaps.iter().map(|o| &o.message).collect();
+    messages.dedup();
+    for message in messages {
+        println!("\n{}", render_layout(db, message)?.trim_end());
+    }
+    if format != Format::LDF {
+        return Ok(());
+    }
+    let path = args.input.display().to_string();
+    let (_, spans) = parse_ldf_str_with_spans(src)?;
+    let mut signals: Vec<_> = overlaps
+        .iter()
+        .flat_map(|o| [&o.signals.0, &o.signals.1])
+        .collect();
+    signals.sort();
+    signals.dedup();
+    for signal in signals {
+        if let Some(span) = spans.signals.get(signal) {
+            let e = Error::Hint(
+                format!("{} declared here", signal),
+                Box::new(Error::SignalOverlap),
+            );
+            print!(
+                "\n{}",
+                Error::Located(*span, Box::new(e)).render(&path, src)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn parse_severity(s: &str) -> Result<(String, Severity), String> {
+    let (kind, severity) = s
+        .split_once('=')
+        .ok_or(format!("expected KIND=SEVERITY: {}", s))?;
+    let severity = severity.parse().map_err(|e: Error| e.to_string())?;
+    Ok((kind.to_string(), severity))
+}
+
+// issues that fail validation, and ones only warned about
+fn check(args: &Args, db: &Database) -> (Vec<Error>, Vec<Error>) {
+    let options = ValidationOptions {
+        strict: args.strict,
+        severity: args.severity.iter().cloned().collect(),
+    };
+    let issues = if args.strict {
+        db.validate_strict()
+    } else {
+        db.validate()
+    };
+    let (mut errors, mut warnings) = (Vec::new(), Vec::new());
+    for issue in issues {
+        match options.severity(&issue) {
+            Severity::Error => errors.push(issue),
+            Severity::Warning => warnings.push(issue),
+            Severity::Ignore => (),
+        }
+    }
+    (errors, warnings)
+}
+
+fn validate(args: &Args) -> Result<(), Error> {
+    if args.format == OutputFormat::Table {
+        let (src, inner) = read_input(&args.input)?;
+        let format = input_format(&src, &inner, args.from)?;
+        let (db, warnings) = parse_with_warnings(&args.input, &src, format)?;
+        for warning in &warnings {
+            println!("{}:{}", args.input.display(), describe(&src, warning));
+        }
+        let (issues, lints) = check(args, &db);
+        for lint in &lints {
+            println!("{}: warning: {}", args.input.display(), lint);
+        }
+        let failed = issues.len()
+            + if args.deny_warnings {
+                warnings.len() + lints.len()
+            } else {
+                0
+            };
+        if failed == 0 {
+            println!("{}: ok", args.input.display());
+            return Ok(());
+        }
+        for issue in &issues {
+            println!("{}: {}", args.input.display(), issue);
+        }
+        if issues
+            .iter()
+            .any(|e| matches!(e.kind(), Error::SignalOverlap))
+        {
+            explain_overlaps(args, &db, &src, format)?;
+        }
+        return Err(Error::IO(format!("{} issue(s) found", failed)));
+    }
+
+    let (src, result) = match read_input(&args.input) {
+        Ok((src, inner)) => {
+            let result = input_format(&src, &inner, args.from)
+                .and_then(|format| parse_with_warnings(&args.input, &src, format));
+            (src, result)
+        }
+        Err(e) => (String::new(), Err(e)),
+    };
+    let (issues, lints) = result
+        .as_ref()
+        .map(|(db, _)| check(args, db))
+        .unwrap_or_default();
+    let mut issues: Vec<String> = issues.iter().map(|e| e.to_string()).collect();
+    let mut warnings: Vec<String> = result
+        .as_ref()
+        .map(|(_, w)| w.iter().map(|w| describe(&src, w)).collect())
+        .unwrap_or_default();
+    warnings.extend(lints.iter().map(|e| format!("warning: {}", e)));
+    if args.deny_warnings {
+        issues.extend(warnings.iter().cloned());
+    }
+    let position = result.as_ref().err().and_then(|e| {
+        let (line, col) = e.span()?.line_col(&src);
+        Some((line + 1, col + 1))
+    });
+    print_serialized(
+        args.format,
+        &Report {
+            path: args.input.display().to_string(),
+            valid: result.is_ok() && issues.is_empty(),
+            error: result.as_ref().err().map(|e| e.kind().to_string()),
+            line: position.map(|(l, _)| l),
+            column: position.map(|(_, c)| c),
+            issues: issues.clone(),
+            warnings,
+        },
+    )?;
+    result?;
+    if !issues.is_empty() {
+        return Err(Error::IO(format!("{} issue(s) found", issues.len())));
+    }
+    Ok(())
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    if args.watch {
+        watch(&[&args.input], || validate(&args))
+    } else {
+        validate(&args)
+    }
+}
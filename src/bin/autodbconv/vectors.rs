@@ -0,0 +1,68 @@
+use crate::inspect::print_table;
+use crate::{load, print_serialized, OutputFormat};
+use autodbconv::{generate_vectors_c, Error, Format};
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Database to generate vectors for
+    #[arg(long)]
+    db: PathBuf,
+    /// Database format, inferred from extension if not given
+    #[arg(long)]
+    from: Option<Format>,
+    /// Random vectors per frame, on top of initial, zero, maximum and minimum values
+    #[arg(long, default_value_t = 8)]
+    count: usize,
+    /// Only these frames
+    #[arg(long, value_delimiter = ',')]
+    frames: Vec<String>,
+    /// Also write the vectors as a C header
+    #[arg(long)]
+    c: Option<PathBuf>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+pub fn run(args: Args) -> Result<(), Error> {
+    let db = load(&args.db, args.from)?;
+    let mut vectors = db.test_vectors(args.count)?;
+    if !args.frames.is_empty() {
+        vectors.retain(|v| args.frames.iter().any(|f| **f == *v.message));
+    }
+    if let Some(path) = &args.c {
+        let name = path
+            .file_stem()
+            .map_or("vectors".to_string(), |s| s.to_string_lossy().to_string());
+        std::fs::write(path, generate_vectors_c(&vectors, &name)?)?;
+        eprintln!("wrote {}", path.display());
+    }
+    if args.format != OutputFormat::Table {
+        return print_serialized(args.format, &vectors);
+    }
+
+    let rows: Vec<Vec<String>> = vectors
+        .iter()
+        .map(|v| {
+            let payload: Vec<String> = v.payload.iter().map(|b| format!("{:02X}", b)).collect();
+            let values: Vec<String> = v
+                .signals
+                .iter()
+                .map(|s| format!("{}={}", s.name, s.physical))
+                .collect();
+            vec![
+                v.message.to_string(),
+                v.case.clone(),
+                payload.join(" "),
+                values.join(", "),
+            ]
+        })
+        .collect();
+    print_table(
+        &args.db.display().to_string(),
+        &["FRAME", "CASE", "PAYLOAD", "VALUES"],
+        &rows,
+    );
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::report;
+use autodbconv::Error;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// runs f once, then again whenever any of the paths change, never returns
+pub fn watch(paths: &[&Path], mut f: impl FnMut() -> Result<(), Error>) -> Result<(), Error> {
+    let mut last: Vec<_> = paths.iter().map(|p| modified(p)).collect();
+    loop {
+        if let Err(e) = f() {
+            report(&e);
+        }
+        eprintln!("watching for changes...");
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let now: Vec<_> = paths.iter().map(|p| modified(p)).collect();
+            if now != last {
+                last = now;
+                break;
+            }
+        }
+    }
+}
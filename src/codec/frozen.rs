@@ -0,0 +1,80 @@
+use crate::codec::plan::DecodePlan;
+use crate::parsers::encoding::{Message, Name, Signal};
+use crate::parsers::ids::FrameId;
+use crate::Database;
+use std::collections::HashMap;
+
+// the model is plain owned data, so it can be shared across threads behind an Arc
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Database>();
+    assert_send_sync::<FrozenDatabase>();
+};
+
+// read-only database indexed for concurrent lookups, iteration is in a stable order
+pub struct FrozenDatabase {
+    db: Database,
+    messages: Vec<Name>, // sorted by (id, name)
+    signals: Vec<Name>,  // sorted by name
+    by_id: HashMap<u32, usize>,
+    by_pgn: HashMap<u32, usize>,    // J1939 frames
+    plans: Vec<Option<DecodePlan>>, // none if the message can't be planned
+}
+
+impl Database {
+    pub fn freeze(self) -> FrozenDatabase {
+        let mut messages: Vec<Name> = self.messages.keys().cloned().collect();
+        messages.sort_by(|a, b| (self.messages[a].id, a).cmp(&(self.messages[b].id, b)));
+        let mut signals: Vec<Name> = self.signals.keys().cloned().collect();
+        signals.sort();
+        let mut by_id = HashMap::new();
+        let mut by_pgn = HashMap::new();
+        for (i, name) in messages.iter().enumerate() {
+            by_id.entry(self.messages[name].id).or_insert(i);
+            if let Some(pgn) = self.messages[name].pgn() {
+                by_pgn.entry(pgn).or_insert(i);
+            }
+        }
+        let plans = messages.iter().map(|m| self.decode_plan(m).ok()).collect();
+        FrozenDatabase {
+            db: self,
+            messages,
+            signals,
+            by_id,
+            by_pgn,
+            plans,
+        }
+    }
+}
+
+impl FrozenDatabase {
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = (&Name, &Message)> {
+        self.messages.iter().map(|n| (n, &self.db.messages[n]))
+    }
+
+    pub fn signals(&self) -> impl Iterator<Item = (&Name, &Signal)> {
+        self.signals.iter().map(|n| (n, &self.db.signals[n]))
+    }
+
+    // same matching as Database::message_by_id
+    fn index(&self, id: u32) -> Option<usize> {
+        self.by_id.get(&id).copied().or_else(|| {
+            let pgn = FrameId::can(id).ok().filter(|id| id.is_extended())?.pgn();
+            self.by_pgn.get(&pgn).copied()
+        })
+    }
+
+    pub fn message_by_id(&self, id: u32) -> Option<(&Name, &Message)> {
+        let name = &self.messages[self.index(id)?];
+        Some((name, &self.db.messages[name]))
+    }
+
+    // none for frames with byte arrays or multiplexed signals, decode those with decode_message
+    pub fn decode_plan(&self, id: u32) -> Option<&DecodePlan> {
+        self.plans[self.index(id)?].as_ref()
+    }
+}
@@ -0,0 +1,185 @@
+use crate::codec::replay::LogFrame;
+use crate::codec::traffic::lin_checksum;
+use crate::parsers::encoding::{ChecksumModel, DatabaseType, Encoding, Name};
+use crate::parsers::ids::Pid;
+use crate::{Database, Error};
+use serde::Serialize;
+use std::collections::HashMap;
+
+const LIN_SYNC: u8 = 0x55;
+const LIN_DIAGNOSTIC_IDS: [u32; 2] = [0x3C, 0x3D]; // always 8 bytes with classic checksums
+
+// latest of a signal seen on a live bus
+#[derive(Clone, Debug, Serialize)]
+pub struct SignalState {
+    pub signal: Name,
+    pub message: Name,
+    pub value: f64, // physical, numeric for signals without a scalar encoding
+    pub unit: String,
+    pub count: usize,
+    pub first: f64, // ms
+    pub last: f64,
+    pub out_of_range: bool, // outside the physical limits or every scalar range
+}
+
+impl SignalState {
+    // Hz, over everything seen so far
+    pub fn rate(&self) -> f64 {
+        if self.count < 2 || self.last <= self.first {
+            return 0.0;
+        }
+        (self.count - 1) as f64 * 1000.0 / (self.last - self.first)
+    }
+
+    // ms since it was last received
+    pub fn age(&self, now: f64) -> f64 {
+        (now - self.last).max(0.0)
+    }
+}
+
+// decodes frames as they arrive, keeping the latest value of each signal
+pub struct SignalMonitor<'a> {
+    db: &'a Database,
+    ids: HashMap<u32, Option<&'a Name>>,
+    states: HashMap<&'a Name, SignalState>,
+    pub frames: usize,
+    pub unknown: usize, // frames with ids not in the database
+}
+
+impl<'a> SignalMonitor<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self {
+            db,
+            ids: HashMap::new(),
+            states: HashMap::new(),
+            frames: 0,
+            unknown: 0,
+        }
+    }
+
+    pub fn update(&mut self, frame: &LogFrame) {
+        self.frames += 1;
+        let db = self.db;
+        let name = *self
+            .ids
+            .entry(frame.id)
+            .or_insert_with(|| db.message_by_id(frame.id).map(|(n, _)| n));
+        let Some(name) = name else {
+            self.unknown += 1;
+            return;
+        };
+        let msg = &db.messages[name];
+        if frame.data.len() < msg.byte_width as usize {
+            return;
+        }
+        for (sig, value) in db.frame_values(msg, &frame.data) {
+            let signal = &db.signals[sig];
+            let state = self.states.entry(sig).or_insert_with(|| SignalState {
+                signal: sig.clone(),
+                message: name.clone(),
+                value,
+                unit: signal
+                    .encodings
+                    .iter()
+                    .flatten()
+                    .find_map(|e| match e {
+                        Encoding::Scalar { unit, .. } => Some(unit.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default(),
+                count: 0,
+                first: frame.time,
+                last: frame.time,
+                out_of_range: false,
+            });
+            state.message.clone_from(name);
+            state.value = value;
+            state.count += 1;
+            state.last = frame.time;
+            state.out_of_range = signal.check_physical(value).is_err();
+        }
+    }
+
+    // by signal name, those whose signal or frame name contains filter, ignoring case
+    pub fn signals(&self, filter: &str) -> Vec<&SignalState> {
+        let filter = filter.to_lowercase();
+        let mut ret: Vec<&SignalState> = self
+            .states
+            .values()
+            .filter(|s| {
+                s.signal.to_lowercase().contains(&filter)
+                    || s.message.to_lowercase().contains(&filter)
+            })
+            .collect();
+        ret.sort_by(|a, b| a.signal.cmp(&b.signal));
+        ret
+    }
+}
+
+// finds LIN frames in what a UART behind a LIN transceiver receives, the break reads as 0x00 and is skipped,
+// then come sync, PID, the frame's bytes and checksum, only frames with a valid checksum are kept
+pub struct LinSniffer {
+    frames: HashMap<u8, (usize, ChecksumModel)>, // by id, byte width and checksum model
+    buf: Vec<u8>,
+    pub channel: String,
+}
+
+impl LinSniffer {
+    pub fn new(db: &Database, channel: &str) -> Result<Self, Error> {
+        let DatabaseType::LDF(ldf) = &db.extra else {
+            return Err(Error::IncompatibleDatabase);
+        };
+        let mut frames: HashMap<u8, (usize, ChecksumModel)> = db
+            .messages
+            .values()
+            .filter(|m| m.id < 0x40)
+            .map(|m| {
+                let model = m.checksum.unwrap_or_else(|| ldf.checksum_model(m));
+                (m.id as u8, (m.byte_width as usize, model))
+            })
+            .collect();
+        for id in LIN_DIAGNOSTIC_IDS {
+            frames.insert(id as u8, (8, ChecksumModel::Classic));
+        }
+        Ok(Self {
+            frames,
+            buf: Vec::new(),
+            channel: channel.to_string(),
+        })
+    }
+
+    // frames completed by bytes, all stamped with time (ms)
+    pub fn push(&mut self, bytes: &[u8], time: f64) -> Vec<LogFrame> {
+        self.buf.extend_from_slice(bytes);
+        let mut ret = Vec::new();
+        let mut pos = 0;
+        while pos + 1 < self.buf.len() {
+            let pid = self.buf[pos + 1];
+            let id = pid & 0x3F;
+            let known = self.frames.get(&id).copied();
+            let Some((len, model)) =
+                known.filter(|_| self.buf[pos] == LIN_SYNC && Pid::from_id(id).get() == pid)
+            else {
+                pos += 1;
+                continue;
+            };
+            let Some(frame) = self.buf.get(pos + 2..pos + 3 + len) else {
+                break; // wait for the rest
+            };
+            let (data, checksum) = frame.split_at(len);
+            if lin_checksum(id as u32, data, model) != checksum[0] {
+                pos += 1;
+                continue;
+            }
+            ret.push(LogFrame {
+                time,
+                channel: self.channel.clone(),
+                id: id as u32,
+                data: data.to_vec(),
+            });
+            pos += 3 + len;
+        }
+        self.buf.drain(..pos);
+        ret
+    }
+}
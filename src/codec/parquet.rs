@@ -0,0 +1,138 @@
+use crate::codec::replay::SignalSeries;
+use crate::parsers::encoding::{Encoding, Signal, ValueKind};
+use crate::{Database, Error};
+use parquet::basic::{Repetition, Type as PhysicalType};
+use parquet::data_type::{BoolType, DoubleType, FloatType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+use parquet::schema::types::Type;
+use std::io::Write;
+use std::sync::Arc;
+
+pub const PARQUET_ROW_GROUP_ROWS: usize = 1 << 20;
+
+impl From<parquet::errors::ParquetError> for Error {
+    fn from(item: parquet::errors::ParquetError) -> Self {
+        Error::IO(item.to_string())
+    }
+}
+
+// narrowest column type holding every physical value of the signal
+fn column_type(signal: Option<&Signal>) -> PhysicalType {
+    let Some(signal) = signal else {
+        return PhysicalType::DOUBLE;
+    };
+    let integral = signal.encodings.iter().flatten().all(|e| match e {
+        Encoding::Scalar { scale, offset, .. } => scale.fract() == 0.0 && offset.fract() == 0.0,
+        _ => true,
+    });
+    let scaled = signal.physical_range().is_some();
+    match signal.kind {
+        ValueKind::Float32 if !scaled => PhysicalType::FLOAT,
+        ValueKind::Unsigned if !scaled && signal.bit_width == 1 => PhysicalType::BOOLEAN,
+        ValueKind::Unsigned | ValueKind::Signed if integral && signal.bit_width < 64 => {
+            PhysicalType::INT64
+        }
+        _ => PhysicalType::DOUBLE,
+    }
+}
+
+fn write_column(
+    column: &mut SerializedColumnWriter,
+    kind: PhysicalType,
+    values: &[Option<f64>],
+) -> Result<(), Error> {
+    let levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+    let present = values.iter().flatten();
+    match kind {
+        PhysicalType::BOOLEAN => {
+            let values: Vec<bool> = present.map(|v| *v != 0.0).collect();
+            column
+                .typed::<BoolType>()
+                .write_batch(&values, Some(&levels), None)?
+        }
+        PhysicalType::INT64 => {
+            let values: Vec<i64> = present.map(|v| *v as i64).collect();
+            column
+                .typed::<Int64Type>()
+                .write_batch(&values, Some(&levels), None)?
+        }
+        PhysicalType::FLOAT => {
+            let values: Vec<f32> = present.map(|v| *v as f32).collect();
+            column
+                .typed::<FloatType>()
+                .write_batch(&values, Some(&levels), None)?
+        }
+        _ => {
+            let values: Vec<f64> = present.copied().collect();
+            column
+                .typed::<DoubleType>()
+                .write_batch(&values, Some(&levels), None)?
+        }
+    };
+    Ok(())
+}
+
+// time in seconds then an optional column per signal typed from db, a row group every row_group_rows rows
+pub fn write_series_parquet(
+    db: &Database,
+    series: &SignalSeries,
+    writer: impl Write + Send,
+    row_group_rows: usize,
+) -> Result<(), Error> {
+    let mut kinds = vec![PhysicalType::DOUBLE];
+    kinds.extend(
+        series
+            .signals
+            .iter()
+            .map(|s| column_type(db.signals.get(s))),
+    );
+    let mut fields = vec![Arc::new(
+        Type::primitive_type_builder("time", PhysicalType::DOUBLE)
+            .with_repetition(Repetition::REQUIRED)
+            .build()?,
+    )];
+    for (name, kind) in series.signals.iter().zip(&kinds[1..]) {
+        fields.push(Arc::new(
+            Type::primitive_type_builder(name, *kind)
+                .with_repetition(Repetition::OPTIONAL)
+                .build()?,
+        ));
+    }
+    let schema = Type::group_type_builder("series")
+        .with_fields(fields)
+        .build()?;
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(row_group_rows.max(1))
+        .build();
+    let mut file = SerializedFileWriter::new(writer, Arc::new(schema), Arc::new(properties))?;
+
+    let rows: Vec<usize> = (0..series.rows.len()).collect();
+    for chunk in rows.chunks(row_group_rows.max(1)) {
+        let mut group = file.next_row_group()?;
+        let mut i = 0;
+        while let Some(mut column) = group.next_column()? {
+            let values: Vec<Option<f64>> = match i {
+                0 => chunk
+                    .iter()
+                    .map(|r| Some(series.times[*r] / 1000.0))
+                    .collect(),
+                _ => chunk.iter().map(|r| series.rows[*r][i - 1]).collect(),
+            };
+            if i == 0 {
+                // required, so no definition levels
+                let values: Vec<f64> = values.into_iter().flatten().collect();
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&values, None, None)?;
+            } else {
+                write_column(&mut column, kinds[i], &values)?;
+            }
+            column.close()?;
+            i += 1;
+        }
+        group.close()?;
+    }
+    file.close()?;
+    Ok(())
+}
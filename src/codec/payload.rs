@@ -0,0 +1,672 @@
+use crate::parsers::encoding::{
+    Encoding, Message, Name, Signal, SignalGroup, ValueKind, BIT_START_INVALID,
+};
+use crate::parsers::ids::{BitPos, FrameId};
+use crate::writers::ldf::sorted;
+use crate::{Database, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignalValue {
+    Raw(u64),
+    Physical(f64),
+    Logical(String),
+    Bytes(Vec<u8>), // byte arrays, byte 0 first
+    Text(String),   // byte arrays as ASCII
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodedSignal {
+    pub name: String,
+    pub raw: u64,
+    pub physical: Option<f64>,
+    pub unit: Option<String>,
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed: Option<i64>, // two's complement value of raw, signed signals only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<Vec<u8>>, // byte arrays only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>, // byte arrays with an ASCII encoding, up to the first NUL
+}
+
+// outcome of checking a proposed value before it's encoded
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum ValueVerdict {
+    Valid { raw: u64 },     // within a scalar range or a defined logical value
+    Undefined { raw: u64 }, // fits the signal but no encoding covers it
+    OutOfRange { range: Option<(f64, f64)> }, // physical value outside the limits or every scalar range
+    TooWide { max_raw: u64 },
+    UnknownLabel,
+}
+
+impl ValueVerdict {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ValueVerdict::Valid { .. })
+    }
+}
+
+impl Signal {
+    pub fn extract(&self, data: &[u8]) -> Result<u64, Error> {
+        let bits = self.check_layout(data)?;
+        let mut raw = 0;
+        for (i, bit) in bits.into_iter().take(64).enumerate() {
+            if get_bit(data, bit) {
+                raw |= 1 << i;
+            }
+        }
+        Ok(raw)
+    }
+
+    // bits of a byte array in frame order, LSB first for little-endian and MSB first for big-endian
+    fn byte_bits(&self) -> Vec<(BitPos, u32)> {
+        self.bit_positions()
+            .into_iter()
+            .enumerate()
+            .map(|(i, bit)| {
+                let shift = if self.little_endian { i % 8 } else { 7 - i % 8 };
+                (bit, (i / 8 * 8 + shift) as u32)
+            })
+            .collect()
+    }
+
+    pub fn extract_bytes(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        self.check_layout(data)?;
+        let mut ret = vec![0u8; (self.bit_width as usize).div_ceil(8)];
+        for (bit, i) in self.byte_bits() {
+            if get_bit(data, bit) {
+                ret[i as usize / 8] |= 1 << (i % 8);
+            }
+        }
+        Ok(ret)
+    }
+
+    // shorter arrays are padded with zeros
+    pub fn insert_bytes(&self, data: &mut [u8], bytes: &[u8]) -> Result<(), Error> {
+        self.check_layout(data)?;
+        if bytes.len() * 8 > self.bit_width as usize {
+            return Err(Error::Hint(
+                format!("{} bytes in {} bits", bytes.len(), self.bit_width),
+                Box::new(Error::ValueOutOfRange),
+            ));
+        }
+        for (bit, i) in self.byte_bits() {
+            let set = bytes
+                .get(i as usize / 8)
+                .is_some_and(|b| (b >> (i % 8)) & 1 != 0);
+            set_bit(data, bit, set);
+        }
+        Ok(())
+    }
+
+    pub fn is_ascii(&self) -> bool {
+        self.encodings
+            .iter()
+            .flatten()
+            .any(|e| matches!(e, Encoding::Ascii))
+    }
+
+    // initial bytes of a byte array, only the first 8 can be set
+    pub fn init_bytes(&self) -> Vec<u8> {
+        let mut ret = self.init_value.to_le_bytes().to_vec();
+        ret.resize((self.bit_width as usize).div_ceil(8), 0);
+        ret
+    }
+
+    pub fn insert(&self, data: &mut [u8], raw: u64) -> Result<(), Error> {
+        let bits = self.check_layout(data)?;
+        for (i, bit) in bits.into_iter().enumerate() {
+            set_bit(data, bit, i < 64 && (raw >> i) & 1 != 0);
+        }
+        Ok(())
+    }
+
+    // the initial value when none is given
+    fn insert_value(&self, data: &mut [u8], value: Option<&SignalValue>) -> Result<(), Error> {
+        if self.kind == ValueKind::ByteArray {
+            let bytes = match value {
+                Some(v) => self.to_bytes(v)?,
+                None => self.init_bytes(),
+            };
+            return self.insert_bytes(data, &bytes);
+        }
+        let raw = match value {
+            Some(v) => self.to_raw(v)?,
+            None => self.init_value,
+        };
+        self.insert(data, raw)
+    }
+
+    pub fn max_raw(&self) -> u64 {
+        if self.bit_width >= 64 {
+            u64::MAX
+        } else {
+            (1 << self.bit_width) - 1
+        }
+    }
+
+    // two's complement value of the raw bits, raw for unsigned signals
+    pub fn sign_extend(&self, raw: u64) -> i64 {
+        let sext = if self.kind == ValueKind::Signed && self.bit_width < 64 {
+            64 - self.bit_width as u32
+        } else {
+            0
+        };
+        ((raw << sext) as i64) >> sext
+    }
+
+    // raw bits of a signed value, none if it doesn't fit
+    pub fn from_signed(&self, value: i64) -> Option<u64> {
+        let (min, max) = if self.kind != ValueKind::Signed {
+            (0, self.max_raw().min(i64::MAX as u64) as i64)
+        } else if self.bit_width >= 64 {
+            (i64::MIN, i64::MAX)
+        } else {
+            (
+                -(1 << (self.bit_width - 1)),
+                (1 << (self.bit_width - 1)) - 1,
+            )
+        };
+        (min..=max)
+            .contains(&value)
+            .then_some(value as u64 & self.max_raw())
+    }
+
+    // numeric value of raw bits, sign-extended or reinterpreted as IEEE 754 according to the kind
+    pub fn raw_value(&self, raw: u64) -> f64 {
+        match self.kind {
+            ValueKind::Signed => self.sign_extend(raw) as f64,
+            ValueKind::Float32 => f32::from_bits(raw as u32) as f64,
+            ValueKind::Float64 => f64::from_bits(raw),
+            ValueKind::Unsigned | ValueKind::ByteArray => raw as f64,
+        }
+    }
+
+    // raw bits of a numeric value, none if it doesn't fit
+    pub fn from_value(&self, value: f64) -> Option<u64> {
+        match self.kind {
+            ValueKind::Signed => self.from_signed(value.round() as i64),
+            ValueKind::Float32 => Some((value as f32).to_bits() as u64),
+            ValueKind::Float64 => Some(value.to_bits()),
+            ValueKind::Unsigned | ValueKind::ByteArray => {
+                let raw = value.round();
+                (raw >= 0.0 && raw <= self.max_raw() as f64).then_some(raw as u64)
+            }
+        }
+    }
+
+    // smallest and largest numeric value of a range of raw bits, the upper half is negative if signed
+    pub(crate) fn raw_span(&self, raw_min: u64, raw_max: u64) -> (f64, f64) {
+        let half = 1u64 << (self.bit_width.clamp(1, 64) - 1);
+        match self.kind {
+            ValueKind::Float32 => return (f32::MIN as f64, f32::MAX as f64),
+            ValueKind::Float64 => return (f64::MIN, f64::MAX),
+            _ => (),
+        }
+        if self.kind != ValueKind::Signed || raw_max < half || raw_min >= half {
+            let (a, b) = (self.raw_value(raw_min), self.raw_value(raw_max));
+            (a.min(b), a.max(b))
+        } else {
+            (self.raw_value(raw_min.max(half)), (half - 1) as f64)
+        }
+    }
+
+    // bits covered as byte * 8 + bit, starting at bit_start, big-endian follows the sawtooth in encoding.rs
+    pub fn bit_positions(&self) -> Vec<BitPos> {
+        if self.bit_start == BIT_START_INVALID {
+            return Vec::new();
+        }
+        let mut bit = self.bit_start;
+        let mut ret = Vec::with_capacity(self.bit_width as usize);
+        for _ in 0..self.bit_width {
+            ret.push(BitPos::unchecked(bit));
+            if self.little_endian {
+                bit += 1;
+            } else if bit.is_multiple_of(8) {
+                bit += 15; // MSB of the next byte
+            } else {
+                bit -= 1;
+            }
+        }
+        ret
+    }
+
+    pub fn to_physical(&self, raw: u64) -> Option<(f64, &str)> {
+        for e in self.encodings.iter().flatten() {
+            if let Encoding::Scalar {
+                raw_min,
+                raw_max,
+                scale,
+                offset,
+                unit,
+                ..
+            } = e
+            {
+                if (*raw_min..=*raw_max).contains(&raw) {
+                    return Some((scale * self.raw_value(raw) + offset, unit));
+                }
+            }
+        }
+        None
+    }
+
+    // explicit limits, or the span of all scalar ranges
+    pub fn physical_range(&self) -> Option<(f64, f64)> {
+        if let Some((min, max)) = self.physical_limits {
+            return Some((min.min(max), min.max(max)));
+        }
+        self.encodings
+            .iter()
+            .flatten()
+            .filter_map(|e| match e {
+                Encoding::Scalar {
+                    raw_min,
+                    raw_max,
+                    scale,
+                    offset,
+                    ..
+                } => {
+                    let (lo, hi) = self.raw_span(*raw_min, *raw_max);
+                    let (a, b) = (scale * lo + offset, scale * hi + offset);
+                    Some((a.min(b), a.max(b)))
+                }
+                _ => None,
+            })
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+    }
+
+    pub fn clamp_physical(&self, value: f64) -> f64 {
+        match self.physical_range() {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        }
+    }
+
+    pub fn check_physical(&self, value: f64) -> Result<(), Error> {
+        match self.physical_range() {
+            Some((min, max)) if !(min..=max).contains(&value) => Err(Error::Hint(
+                format!("{} not in {}..={}", value, min, max),
+                Box::new(Error::ValueOutOfRange),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn to_logical(&self, raw: u64) -> Option<&str> {
+        for e in self.encodings.iter().flatten() {
+            if let Encoding::Enum { rev_map, .. } = e {
+                if let Some(s) = rev_map.get(&raw) {
+                    return Some(s);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn to_raw(&self, value: &SignalValue) -> Result<u64, Error> {
+        let raw = match value {
+            SignalValue::Raw(raw) => Some(*raw),
+            SignalValue::Physical(phys) => {
+                self.check_physical(*phys)?;
+                self.encodings.iter().flatten().find_map(|e| {
+                    if let Encoding::Scalar {
+                        raw_min,
+                        raw_max,
+                        scale,
+                        offset,
+                        ..
+                    } = e
+                    {
+                        let raw = self.from_value((phys - offset) / scale)?;
+                        if (*raw_min..=*raw_max).contains(&raw) {
+                            return Some(raw);
+                        }
+                    }
+                    None
+                })
+            }
+            SignalValue::Logical(label) => self.encodings.iter().flatten().find_map(|e| {
+                if let Encoding::Enum { map, .. } = e {
+                    map.get(label).copied()
+                } else {
+                    None
+                }
+            }),
+            SignalValue::Bytes(_) | SignalValue::Text(_) => {
+                let bytes = self.to_bytes(value)?;
+                let mut raw = [0; 8];
+                (bytes.len() <= 8).then(|| {
+                    raw[..bytes.len()].copy_from_slice(&bytes);
+                    u64::from_le_bytes(raw)
+                })
+            }
+        };
+        match raw {
+            Some(raw) if raw <= self.max_raw() => Ok(raw),
+            _ => Err(Error::ValueOutOfRange),
+        }
+    }
+
+    // bytes of a byte array value, numeric values are split little-endian
+    pub fn to_bytes(&self, value: &SignalValue) -> Result<Vec<u8>, Error> {
+        let len = (self.bit_width as usize).div_ceil(8);
+        let bytes = match value {
+            SignalValue::Bytes(bytes) => bytes.clone(),
+            SignalValue::Text(text) if text.is_ascii() => text.as_bytes().to_vec(),
+            SignalValue::Text(text) => {
+                return Err(Error::Hint(
+                    format!("\"{}\" isn't ASCII", text),
+                    Box::new(Error::ValueOutOfRange),
+                ))
+            }
+            _ => {
+                let mut bytes = self.to_raw(value)?.to_le_bytes().to_vec();
+                bytes.truncate(len);
+                bytes
+            }
+        };
+        if bytes.len() > len {
+            return Err(Error::Hint(
+                format!("{} bytes, at most {}", bytes.len(), len),
+                Box::new(Error::ValueOutOfRange),
+            ));
+        }
+        Ok(bytes)
+    }
+
+    pub fn check_value(&self, value: &SignalValue) -> ValueVerdict {
+        match value {
+            SignalValue::Raw(raw) => {
+                let covered = self.encodings.iter().flatten().any(|e| match e {
+                    Encoding::Scalar {
+                        raw_min, raw_max, ..
+                    } => (*raw_min..=*raw_max).contains(raw),
+                    Encoding::Enum { rev_map, .. } => rev_map.contains_key(raw),
+                    Encoding::Ascii => true,
+                });
+                if *raw > self.max_raw() {
+                    ValueVerdict::TooWide {
+                        max_raw: self.max_raw(),
+                    }
+                } else if covered || self.encodings.is_none() {
+                    ValueVerdict::Valid { raw: *raw }
+                } else {
+                    ValueVerdict::Undefined { raw: *raw }
+                }
+            }
+            // to_raw also checks the physical limits
+            SignalValue::Physical(_) => match self.to_raw(value) {
+                Ok(raw) => ValueVerdict::Valid { raw },
+                Err(_) => ValueVerdict::OutOfRange {
+                    range: self.physical_range(),
+                },
+            },
+            SignalValue::Logical(_) => match self.to_raw(value) {
+                Ok(raw) => ValueVerdict::Valid { raw },
+                Err(_) => ValueVerdict::UnknownLabel,
+            },
+            // raw holds the first 8 bytes
+            SignalValue::Bytes(_) | SignalValue::Text(_) => match self.to_bytes(value) {
+                Ok(bytes) => {
+                    let mut raw = [0; 8];
+                    let n = bytes.len().min(8);
+                    raw[..n].copy_from_slice(&bytes[..n]);
+                    ValueVerdict::Valid {
+                        raw: u64::from_le_bytes(raw),
+                    }
+                }
+                Err(_) => ValueVerdict::TooWide {
+                    max_raw: self.max_raw(),
+                },
+            },
+        }
+    }
+
+    // bit positions from the LSB up
+    fn check_layout(&self, data: &[u8]) -> Result<Vec<BitPos>, Error> {
+        let mut bits = self.bit_positions();
+        if bits.is_empty() || bits.iter().any(|b| b.byte() as usize >= data.len()) {
+            return Err(Error::InvalidPayload);
+        }
+        if !self.little_endian {
+            bits.reverse(); // bit_start is the MSB
+        }
+        Ok(bits)
+    }
+}
+
+// bits checked against the payload by check_layout
+fn get_bit(data: &[u8], bit: BitPos) -> bool {
+    (data[bit.byte() as usize] >> bit.bit()) & 1 != 0
+}
+
+fn set_bit(data: &mut [u8], bit: BitPos, set: bool) {
+    if set {
+        data[bit.byte() as usize] |= 1 << bit.bit();
+    } else {
+        data[bit.byte() as usize] &= !(1 << bit.bit());
+    }
+}
+
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, Error> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return Err(Error::InvalidPayload);
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+        .collect::<Result<_, _>>()
+        .map_err(|_| Error::InvalidPayload)
+}
+
+// group members without a value
+fn missing_signals<'a>(
+    group: &'a SignalGroup,
+    values: &HashMap<String, SignalValue>,
+) -> Vec<&'a str> {
+    group
+        .signals
+        .iter()
+        .filter(|s| !values.contains_key(&***s))
+        .map(|s| &**s)
+        .collect()
+}
+
+impl Database {
+    // J1939 frames also match on their PGN, whatever the priority and addresses
+    pub fn message_by_id(&self, id: u32) -> Option<(&Name, &Message)> {
+        self.messages.iter().find(|(_, m)| m.id == id).or_else(|| {
+            let pgn = FrameId::can(id).ok().filter(|id| id.is_extended())?.pgn();
+            self.messages.iter().find(|(_, m)| m.pgn() == Some(pgn))
+        })
+    }
+
+    pub fn check_value(&self, signal: &str, value: &SignalValue) -> Result<ValueVerdict, Error> {
+        let signal = self.signals.get(signal).ok_or(Error::UnknownSignal)?;
+        Ok(signal.check_value(value))
+    }
+
+    pub fn decode_frame(&self, id: u32, data: &[u8]) -> Result<(&str, Vec<DecodedSignal>), Error> {
+        let (name, _) = self.message_by_id(id).ok_or(Error::UnknownFrame)?;
+        Ok((name, self.decode_message(name, data)?))
+    }
+
+    // a signal a frame refers to, which may be missing when edited through the fields
+    fn frame_signal(&self, name: &Name) -> Result<&Signal, Error> {
+        self.signals.get(name).ok_or(Error::Hint(
+            name.to_string(),
+            Box::new(Error::UnknownSignal),
+        ))
+    }
+
+    // whether a frame's multiplexors select a signal, with extended multiplexing its multiplexors must be selected
+    // too, signals in a multiplexor loop never are
+    pub(crate) fn mux_active(&self, msg: &Message, signal: &Name, data: &[u8]) -> bool {
+        let mut signal = signal;
+        for _ in 0..=msg.mux_signals.len() {
+            let Some((value, multiplexors)) = msg.mux_signals.get(signal) else {
+                return true;
+            };
+            let ranges = self.mux_ranges(signal, *value);
+            let selected = |m: &Name| {
+                let raw = self.signals.get(m).map(|m| m.extract(data));
+                raw.is_some_and(|raw| {
+                    raw.is_ok_and(|raw| ranges.iter().any(|(a, b)| (*a..=*b).contains(&raw)))
+                })
+            };
+            if !multiplexors.iter().all(selected) {
+                return false;
+            }
+            match multiplexors
+                .iter()
+                .find(|m| msg.mux_signals.contains_key(*m))
+            {
+                Some(m) => signal = m,
+                None => return true,
+            }
+        }
+        false
+    }
+
+    // plain signals of a frame in order, then the multiplexed ones its multiplexors select by name
+    pub(crate) fn active_signals<'a>(&self, msg: &'a Message, data: &[u8]) -> Vec<&'a Name> {
+        let mut muxed: Vec<&Name> = msg
+            .mux_signals
+            .keys()
+            .filter(|n| self.mux_active(msg, n, data))
+            .collect();
+        muxed.sort();
+        msg.signals.iter().chain(muxed).collect()
+    }
+
+    pub fn decode_message(&self, message: &str, data: &[u8]) -> Result<Vec<DecodedSignal>, Error> {
+        let msg = self.messages.get(message).ok_or(Error::UnknownFrame)?;
+        if data.len() < msg.byte_width as usize {
+            return Err(Error::InvalidPayload);
+        }
+        let mut ret = Vec::new();
+        for name in self.active_signals(msg, data) {
+            let signal = self.frame_signal(name)?;
+            let raw = signal.extract(data)?;
+            let physical = signal.to_physical(raw);
+            let bytes = match signal.kind {
+                ValueKind::ByteArray => Some(signal.extract_bytes(data)?),
+                _ => None,
+            };
+            let text = bytes.as_ref().filter(|_| signal.is_ascii()).map(|b| {
+                let end = b.iter().position(|c| *c == 0).unwrap_or(b.len());
+                String::from_utf8_lossy(&b[..end]).into_owned()
+            });
+            ret.push(DecodedSignal {
+                name: name.to_string(),
+                raw,
+                physical: physical.map(|(p, _)| p),
+                unit: physical.map(|(_, u)| u.to_string()),
+                label: signal.to_logical(raw).map(|s| s.to_string()),
+                signed: (signal.kind == ValueKind::Signed).then(|| signal.sign_extend(raw)),
+                bytes,
+                text,
+            });
+        }
+        Ok(ret)
+    }
+
+    // signals not in values keep their initial value, a signal group is given whole or not at all
+    pub fn encode_message(
+        &self,
+        message: &str,
+        values: &HashMap<String, SignalValue>,
+    ) -> Result<Vec<u8>, Error> {
+        let msg = self.messages.get(message).ok_or(Error::UnknownFrame)?;
+        if let Some(name) = values.keys().find(|k| {
+            !msg.signals.iter().any(|s| **s == ***k) && !msg.mux_signals.contains_key(k.as_str())
+        }) {
+            return Err(Error::Hint(name.clone(), Box::new(Error::UnknownSignal)));
+        }
+        for (group, g) in sorted(&msg.signal_groups) {
+            let missing = missing_signals(g, values);
+            if !missing.is_empty() && missing.len() < g.signals.len() {
+                return Err(Error::Hint(
+                    format!("{} also needs {}", group, missing.join(", ")),
+                    Box::new(Error::IncompleteSignalGroup),
+                ));
+            }
+        }
+        let mut data = vec![0; msg.byte_width as usize];
+        for name in &msg.signals {
+            self.frame_signal(name)?
+                .insert_value(&mut data, values.get(&**name))?;
+        }
+        // multiplexed signals once the values packed so far select them, multiplexors first
+        let mut muxed: Vec<&Name> = sorted(&msg.mux_signals)
+            .into_iter()
+            .map(|(n, _)| n)
+            .filter(|n| values.contains_key(&***n))
+            .collect();
+        while !muxed.is_empty() {
+            let (active, rest): (Vec<&Name>, Vec<&Name>) = muxed
+                .into_iter()
+                .partition(|n| self.mux_active(msg, n, &data));
+            if active.is_empty() {
+                return Err(Error::Hint(
+                    rest.iter()
+                        .map(|n| n.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    Box::new(Error::UnselectedSignal),
+                ));
+            }
+            for name in active {
+                self.frame_signal(name)?
+                    .insert_value(&mut data, values.get(&**name))?;
+            }
+            muxed = rest;
+        }
+        Ok(data)
+    }
+
+    // updates every signal of a group in an existing payload, leaving it untouched on error
+    pub fn encode_group(
+        &self,
+        message: &str,
+        group: &str,
+        values: &HashMap<String, SignalValue>,
+        data: &mut [u8],
+    ) -> Result<(), Error> {
+        let msg = self.messages.get(message).ok_or(Error::UnknownFrame)?;
+        let g = msg.signal_groups.get(group).ok_or(Error::Hint(
+            format!("{} in {}", group, message),
+            Box::new(Error::UnknownSignalGroup),
+        ))?;
+        if let Some(name) = values
+            .keys()
+            .find(|k| !g.signals.iter().any(|s| **s == ***k))
+        {
+            return Err(Error::Hint(
+                format!("{} is not in {}", name, group),
+                Box::new(Error::UnknownSignal),
+            ));
+        }
+        let missing = missing_signals(g, values);
+        if !missing.is_empty() {
+            return Err(Error::Hint(
+                format!("{} also needs {}", group, missing.join(", ")),
+                Box::new(Error::IncompleteSignalGroup),
+            ));
+        }
+        if data.len() < msg.byte_width as usize {
+            return Err(Error::InvalidPayload);
+        }
+        let mut out = data.to_vec();
+        for name in &g.signals {
+            let signal = self.signals.get(name).ok_or(Error::UnknownSignal)?;
+            signal.insert_value(&mut out, values.get(&**name))?;
+        }
+        data.copy_from_slice(&out);
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use crate::parsers::encoding::{Encoding, Name, ValueKind, MAX_SIGNAL_WIDTH};
+use crate::{Database, Error};
+
+struct SignalPlan {
+    name: Name,
+    byte: usize,      // first byte holding the signal
+    bytes: usize,     // number of bytes spanned
+    shift: u32,       // of the LSB within the byte holding it
+    big_endian: bool, // first byte is the most significant
+    mask: u64,
+    sext: u32, // shift pair for sign-extension, 0 if unsigned
+    kind: ValueKind,
+    ranges: Vec<(u64, u64, f64, f64)>, // raw_min, raw_max, scale, offset
+}
+
+// precomputed layout of one message for fast repeated decoding
+pub struct DecodePlan {
+    byte_width: usize,
+    signals: Vec<SignalPlan>,
+}
+
+impl DecodePlan {
+    pub fn len(&self) -> usize {
+        self.signals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signals.is_empty()
+    }
+
+    // in the same order as decoded values
+    pub fn signals(&self) -> impl Iterator<Item = &Name> {
+        self.signals.iter().map(|s| &s.name)
+    }
+
+    // sign-extended raw values
+    pub fn decode_raw(&self, data: &[u8], out: &mut [i64]) -> Result<(), Error> {
+        if data.len() < self.byte_width || out.len() < self.signals.len() {
+            return Err(Error::InvalidPayload);
+        }
+        for (s, out) in self.signals.iter().zip(out) {
+            *out = s.extract(data);
+        }
+        Ok(())
+    }
+
+    // physical values, raw value if no scaling applies
+    pub fn decode(&self, data: &[u8], out: &mut [f64]) -> Result<(), Error> {
+        if data.len() < self.byte_width || out.len() < self.signals.len() {
+            return Err(Error::InvalidPayload);
+        }
+        for (s, out) in self.signals.iter().zip(out) {
+            let value = s.extract(data);
+            let raw = value as u64 & s.mask;
+            let value = match s.kind {
+                ValueKind::Float32 => f32::from_bits(raw as u32) as f64,
+                ValueKind::Float64 => f64::from_bits(raw),
+                _ => value as f64,
+            };
+            *out = value;
+            for (min, max, scale, offset) in &s.ranges {
+                if (*min..=*max).contains(&raw) {
+                    *out = scale * value + offset;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SignalPlan {
+    #[inline]
+    fn extract(&self, data: &[u8]) -> i64 {
+        let mut word = 0u128;
+        for (i, b) in data[self.byte..self.byte + self.bytes].iter().enumerate() {
+            let i = if self.big_endian {
+                self.bytes - 1 - i
+            } else {
+                i
+            };
+            word |= (*b as u128) << (8 * i);
+        }
+        let raw = (word >> self.shift) as u64 & self.mask;
+        ((raw << self.sext) as i64) >> self.sext
+    }
+}
+
+impl Database {
+    pub fn decode_plan(&self, message: &str) -> Result<DecodePlan, Error> {
+        let msg = self.messages.get(message).ok_or(Error::UnknownFrame)?;
+        if !msg.mux_signals.is_empty() {
+            // a fixed set of outputs can't say which signals the multiplexors select
+            return Err(Error::Hint(
+                format!("{} is multiplexed, use decode_message", message),
+                Box::new(Error::NotImplemented),
+            ));
+        }
+        let mut signals = Vec::new();
+        for name in &msg.signals {
+            let signal = self.signals.get(name).ok_or(Error::Hint(
+                name.to_string(),
+                Box::new(Error::UnknownSignal),
+            ))?;
+            if signal.bit_width > MAX_SIGNAL_WIDTH {
+                return Err(Error::Hint(
+                    format!("{} is a byte array, use decode_message", name),
+                    Box::new(Error::SignalTooWide),
+                ));
+            }
+            // LSB and MSB for little-endian, MSB and LSB for big-endian
+            let bits = signal.bit_positions();
+            let (Some(first), Some(last)) = (bits.first(), bits.last()) else {
+                return Err(Error::InvalidPayload);
+            };
+            if bits.iter().any(|b| b.get() >= msg.byte_width * 8) {
+                return Err(Error::InvalidPayload);
+            }
+            let (first, last) = (first.get() as usize, last.get() as usize);
+            let width = signal.bit_width as usize;
+            let lsb = if signal.little_endian { first } else { last };
+            let ranges = signal
+                .encodings
+                .iter()
+                .flatten()
+                .filter_map(|e| match e {
+                    Encoding::Scalar {
+                        raw_min,
+                        raw_max,
+                        scale,
+                        offset,
+                        ..
+                    } => Some((*raw_min, *raw_max, *scale, *offset)),
+                    _ => None,
+                })
+                .collect();
+            signals.push(SignalPlan {
+                name: name.clone(),
+                byte: first.min(last) / 8,
+                bytes: first.max(last) / 8 - first.min(last) / 8 + 1,
+                shift: (lsb % 8) as u32,
+                big_endian: !signal.little_endian,
+                mask: signal.max_raw(),
+                sext: if signal.kind == ValueKind::Signed {
+                    64 - width as u32
+                } else {
+                    0
+                },
+                kind: signal.kind,
+                ranges,
+            });
+        }
+        Ok(DecodePlan {
+            byte_width: msg.byte_width as usize,
+            signals,
+        })
+    }
+}
@@ -0,0 +1,483 @@
+use crate::parsers::encoding::{Message, Name, Signal, ValueKind};
+use crate::{Database, Error};
+use flate2::read::ZlibDecoder;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+const CAN_EXTENDED_FLAG: u32 = 0x80000000; // as in DBC files and BLF
+const BLF_LOG_CONTAINER: u32 = 10;
+const BLF_CAN_MESSAGE: u32 = 1;
+const BLF_LIN_MESSAGE: u32 = 11;
+const BLF_CAN_MESSAGE2: u32 = 86;
+const BLF_CAN_FD_MESSAGE: u32 = 100;
+const BLF_CAN_FD_MESSAGE_64: u32 = 101;
+const BLF_TIME_TEN_MICS: u32 = 1; // object flags, nanoseconds otherwise
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Asc,
+    Blf,
+    Candump,
+}
+
+// one frame read from a log
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogFrame {
+    pub time: f64,       // ms
+    pub channel: String, // as the log names it, e.g. 1 or can0
+    pub id: u32,         // as in Message::id, extended CAN ids get the DBC flag
+    pub data: Vec<u8>,
+}
+
+// choices for Database::signal_series
+#[derive(Clone, Debug, Default)]
+pub struct SeriesOptions {
+    pub signals: Vec<String>, // columns in this order, every numeric signal of a logged frame if empty
+    pub channel: Option<String>, // only frames from this channel
+    pub hold: bool,           // a signal keeps its last value in rows where it wasn't received
+}
+
+// decoded signals over a log, one row per frame carrying any of them
+#[derive(Clone, Debug, Default)]
+pub struct SignalSeries {
+    pub signals: Vec<Name>,
+    pub times: Vec<f64>,             // ms
+    pub rows: Vec<Vec<Option<f64>>>, // physical values, numeric for signals without a scalar encoding
+    pub unknown: usize,              // frames with ids not in the database
+    pub invalid: usize,              // frames shorter than their message
+}
+
+impl LogFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> Option<LogFormat> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        ext.parse().ok()
+    }
+
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Vec<LogFrame>, Error> {
+        let bytes = std::fs::read(path)?;
+        match self {
+            LogFormat::Blf => parse_blf(&bytes),
+            LogFormat::Asc => Ok(parse_asc(&String::from_utf8_lossy(&bytes))),
+            LogFormat::Candump => Ok(parse_candump(&String::from_utf8_lossy(&bytes))),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asc" => Ok(LogFormat::Asc),
+            "blf" => Ok(LogFormat::Blf),
+            "candump" | "log" => Ok(LogFormat::Candump),
+            _ => Err(Error::UnknownFormat),
+        }
+    }
+}
+
+fn parse_hex_bytes<'a>(tokens: impl Iterator<Item = &'a str>, radix: u32) -> Option<Vec<u8>> {
+    tokens.map(|t| u8::from_str_radix(t, radix).ok()).collect()
+}
+
+// Vector ASC, CAN, CAN FD and LIN frames, everything else (events, errors, statistics) is skipped
+pub fn parse_asc(asc: &str) -> Vec<LogFrame> {
+    let mut radix = 16;
+    let mut relative = false;
+    let mut last = 0.0;
+    let mut ret = Vec::new();
+    for line in asc.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["base", base, "timestamps", timestamps, ..] => {
+                radix = if *base == "dec" { 10 } else { 16 };
+                relative = *timestamps == "relative";
+                continue;
+            }
+            [time, ..] if time.parse::<f64>().is_err() => continue,
+            _ if tokens.len() < 5 => continue,
+            _ => (),
+        }
+        let parse_id = |id: &str| match id.strip_suffix('x') {
+            Some(id) => u32::from_str_radix(id, radix)
+                .ok()
+                .map(|i| i | CAN_EXTENDED_FLAG),
+            None => u32::from_str_radix(id, radix).ok(),
+        };
+        let frame = match tokens[1] {
+            // <time> CANFD <ch> <dir> <id> [name] <brs> <esi> <dlc> <length> <data>
+            "CANFD" => (|| {
+                let mut at = 5;
+                if !matches!(tokens.get(at), Some(&"0") | Some(&"1")) {
+                    at += 1; // symbolic name
+                }
+                let len: usize = tokens.get(at + 3)?.parse().ok()?;
+                let data =
+                    parse_hex_bytes(tokens.get(at + 4..at + 4 + len)?.iter().copied(), radix)?;
+                Some((tokens[2].to_string(), parse_id(tokens[4])?, data))
+            })(),
+            // <time> Li<ch> <id> <dir> <dlc> <data> checksum = ...
+            ch if ch.starts_with("Li") => (|| {
+                let len: usize = tokens[4].parse().ok()?;
+                let data = parse_hex_bytes(tokens.get(5..5 + len)?.iter().copied(), radix)?;
+                Some((ch[2..].to_string(), parse_id(tokens[2])?, data))
+            })(),
+            // <time> <ch> <id> <dir> d <dlc> <data>, remote frames have r instead
+            ch if ch.parse::<u16>().is_ok() && tokens.get(4) == Some(&"d") => (|| {
+                let len = (u8::from_str_radix(tokens.get(5)?, 16).ok()? as usize).min(8);
+                let data = parse_hex_bytes(tokens.get(6..6 + len)?.iter().copied(), radix)?;
+                Some((ch.to_string(), parse_id(tokens[2])?, data))
+            })(),
+            _ => None,
+        };
+        let Some((channel, id, data)) = frame else {
+            continue;
+        };
+        let mut time = tokens[0].parse::<f64>().unwrap() * 1000.0;
+        if relative {
+            time += last;
+            last = time;
+        }
+        ret.push(LogFrame {
+            time,
+            channel,
+            id,
+            data,
+        });
+    }
+    ret
+}
+
+// candump -l lines "(time) can0 123#0102" with ## for CAN FD, or candump's "can0 123 [2] 01 02", remote
+// frames are skipped
+pub fn parse_candump(log: &str) -> Vec<LogFrame> {
+    let mut ret = Vec::new();
+    for (i, line) in log.lines().enumerate() {
+        let mut tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut time = None;
+        if let Some(t) = tokens.first().and_then(|t| t.strip_prefix('(')) {
+            time = t.trim_end_matches(')').parse::<f64>().ok();
+            tokens.remove(0);
+        }
+        let frame = match tokens.as_slice() {
+            [channel, frame, ..] if frame.contains('#') => (|| {
+                let (id, data) = frame.split_once('#')?;
+                let data = match data.strip_prefix('#') {
+                    Some(fd) => fd.get(1..)?, // flags nibble
+                    None if data.starts_with('R') => return None,
+                    None => data,
+                };
+                let bytes = (0..data.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(data.get(i..i + 2)?, 16).ok())
+                    .collect::<Option<Vec<u8>>>()?;
+                Some((channel.to_string(), id, bytes))
+            })(),
+            [channel, id, len, data @ ..] if len.starts_with('[') => (|| {
+                let len: usize = len.trim_matches(['[', ']']).parse().ok()?;
+                let bytes = parse_hex_bytes(data.get(..len)?.iter().copied(), 16)?;
+                Some((channel.to_string(), *id, bytes))
+            })(),
+            _ => None,
+        };
+        let Some((channel, id, data)) = frame else {
+            continue;
+        };
+        let Ok(mut id) = u32::from_str_radix(id, 16) else {
+            continue;
+        };
+        if id > 0x7FF || frame_id_len(line) == 8 {
+            id |= CAN_EXTENDED_FLAG;
+        }
+        ret.push(LogFrame {
+            time: time.map_or(i as f64, |t| t * 1000.0), // line number without timestamps
+            channel,
+            id,
+            data,
+        });
+    }
+    ret
+}
+
+// candump writes extended ids with 8 digits, even small ones
+fn frame_id_len(line: &str) -> usize {
+    line.split_whitespace()
+        .find_map(|t| t.split_once('#').map(|(id, _)| id.len()))
+        .or_else(|| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let at = tokens.iter().position(|t| t.starts_with('['))?;
+            Some(tokens.get(at.checked_sub(1)?)?.len())
+        })
+        .unwrap_or(0)
+}
+
+fn le_u16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u64(b: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(b.get(at..at + 8)?.try_into().ok()?))
+}
+
+// (type, object) of each LOBJ in data, objects are padded to 4 bytes but not all writers agree how
+fn blf_objects(data: &[u8]) -> Vec<(u32, &[u8])> {
+    let mut ret = Vec::new();
+    let mut pos = 0;
+    while let Some(at) = data
+        .get(pos..(pos + 8).min(data.len()))
+        .and_then(|d| d.windows(4).position(|w| w == b"LOBJ"))
+    {
+        pos += at;
+        let (Some(size), Some(kind)) = (le_u32(data, pos + 8), le_u32(data, pos + 12)) else {
+            break;
+        };
+        let Some(object) = data.get(pos..pos + size as usize) else {
+            break; // cut short, e.g. a log still being written
+        };
+        ret.push((kind, object));
+        pos += (size as usize).max(16);
+    }
+    ret
+}
+
+fn blf_frame(kind: u32, object: &[u8]) -> Option<LogFrame> {
+    let header = le_u16(object, 4)? as usize;
+    let flags = le_u32(object, 16)?;
+    let timestamp = le_u64(object, 24)? as f64;
+    let time = if flags == BLF_TIME_TEN_MICS {
+        timestamp / 100.0
+    } else {
+        timestamp / 1e6
+    };
+    let body = object.get(header..)?;
+    let (channel, id, data) = match kind {
+        BLF_CAN_MESSAGE | BLF_CAN_MESSAGE2 => {
+            if body.get(2)? & 0x80 != 0 {
+                return None; // remote frame
+            }
+            let len = (*body.get(3)? as usize).min(8);
+            (le_u16(body, 0)?, le_u32(body, 4)?, body.get(8..8 + len)?)
+        }
+        BLF_CAN_FD_MESSAGE => {
+            if body.get(2)? & 0x80 != 0 {
+                return None;
+            }
+            let len = *body.get(14)? as usize;
+            (le_u16(body, 0)?, le_u32(body, 4)?, body.get(20..20 + len)?)
+        }
+        BLF_CAN_FD_MESSAGE_64 => {
+            if le_u32(body, 12)? & 0x10 != 0 {
+                return None;
+            }
+            let len = *body.get(2)? as usize;
+            (
+                *body.first()? as u16,
+                le_u32(body, 4)?,
+                body.get(40..40 + len)?,
+            )
+        }
+        BLF_LIN_MESSAGE => {
+            let len = (*body.get(3)? as usize).min(8);
+            (
+                le_u16(body, 0)?,
+                *body.get(2)? as u32,
+                body.get(4..4 + len)?,
+            )
+        }
+        _ => return None,
+    };
+    Some(LogFrame {
+        time,
+        channel: channel.to_string(),
+        id,
+        data: data.to_vec(),
+    })
+}
+
+// Vector BLF, CAN, CAN FD and LIN frames from plain or zlib compressed log containers
+pub fn parse_blf(blf: &[u8]) -> Result<Vec<LogFrame>, Error> {
+    let invalid = || {
+        Error::Hint(
+            "not a BLF file".to_string(),
+            Box::new(Error::InvalidPayload),
+        )
+    };
+    if blf.get(..4) != Some(b"LOGG") {
+        return Err(invalid());
+    }
+    let start = le_u32(blf, 4).ok_or_else(invalid)? as usize;
+
+    // objects can span containers, so they're joined first
+    let mut inner = Vec::new();
+    let mut ret = Vec::new();
+    for (kind, object) in blf_objects(blf.get(start..).ok_or_else(invalid)?) {
+        if kind != BLF_LOG_CONTAINER {
+            ret.extend(blf_frame(kind, object));
+            continue;
+        }
+        let header = le_u16(object, 4).ok_or_else(invalid)? as usize;
+        let data = object.get(header + 16..).ok_or_else(invalid)?;
+        match le_u16(object, header).ok_or_else(invalid)? {
+            0 => inner.extend_from_slice(data),
+            2 => {
+                ZlibDecoder::new(data).read_to_end(&mut inner)?;
+            }
+            method => {
+                return Err(Error::Hint(
+                    format!("BLF compression method {}", method),
+                    Box::new(Error::NotImplemented),
+                ))
+            }
+        }
+    }
+    for (kind, object) in blf_objects(&inner) {
+        ret.extend(blf_frame(kind, object));
+    }
+    Ok(ret)
+}
+
+// numeric value of a signal, physical if it has a scalar encoding
+fn series_value(signal: &Signal, data: &[u8]) -> Option<f64> {
+    let raw = signal.extract(data).ok()?;
+    Some(
+        signal
+            .to_physical(raw)
+            .map_or(signal.raw_value(raw), |(p, _)| p),
+    )
+}
+
+impl Database {
+    // values of a frame's numeric signals, multiplexed ones only while their multiplexors select them
+    pub(crate) fn frame_values<'a>(
+        &'a self,
+        msg: &'a Message,
+        data: &[u8],
+    ) -> Vec<(&'a Name, f64)> {
+        self.active_signals(msg, data)
+            .into_iter()
+            .filter_map(|n| {
+                let signal = self.signals.get(n)?;
+                if signal.kind == ValueKind::ByteArray {
+                    return None;
+                }
+                Some((n, series_value(signal, data)?))
+            })
+            .collect()
+    }
+
+    pub fn signal_series(
+        &self,
+        frames: &[LogFrame],
+        options: &SeriesOptions,
+    ) -> Result<SignalSeries, Error> {
+        let mut ids: HashMap<u32, Option<&Name>> = HashMap::new();
+        let frames: Vec<&LogFrame> = frames
+            .iter()
+            .filter(|f| options.channel.as_ref().is_none_or(|c| *c == f.channel))
+            .collect();
+        let mut message = |id: u32| {
+            *ids.entry(id)
+                .or_insert_with(|| self.message_by_id(id).map(|(n, _)| n))
+        };
+
+        let mut signals: Vec<Name> = Vec::new();
+        for name in &options.signals {
+            let (name, _) = self
+                .signals
+                .get_key_value(name.as_str())
+                .ok_or(Error::Hint(name.clone(), Box::new(Error::UnknownSignal)))?;
+            signals.push(name.clone());
+        }
+        if signals.is_empty() {
+            for frame in &frames {
+                let Some(msg) = message(frame.id).map(|n| &self.messages[n]) else {
+                    continue;
+                };
+                signals.extend(msg.signals.iter().chain(msg.mux_signals.keys()).cloned());
+            }
+            signals.retain(|s| self.signals[s].kind != ValueKind::ByteArray);
+            signals.sort();
+            signals.dedup();
+        }
+
+        // per message, (column, signal name, signal)
+        let mut columns: HashMap<&Name, Vec<(usize, &Name, &Signal)>> = HashMap::new();
+        for (name, msg) in &self.messages {
+            let mut cols = Vec::new();
+            for (i, sig) in signals.iter().enumerate() {
+                if msg.signals.contains(sig) || msg.mux_signals.contains_key(sig) {
+                    let (sig, signal) = self.signals.get_key_value(sig).unwrap();
+                    cols.push((i, sig, signal));
+                }
+            }
+            if !cols.is_empty() {
+                columns.insert(name, cols);
+            }
+        }
+
+        let mut ret = SignalSeries {
+            signals,
+            ..Default::default()
+        };
+        let mut last = vec![None; ret.signals.len()];
+        for frame in frames {
+            let Some(name) = message(frame.id) else {
+                ret.unknown += 1;
+                continue;
+            };
+            let Some(cols) = columns.get(name) else {
+                continue;
+            };
+            if frame.data.len() < self.messages[name].byte_width as usize {
+                ret.invalid += 1;
+                continue;
+            }
+            let mut row = if options.hold {
+                last.clone()
+            } else {
+                vec![None; ret.signals.len()]
+            };
+            let msg = &self.messages[name];
+            for (i, sig, signal) in cols {
+                if self.mux_active(msg, sig, &frame.data) {
+                    row[*i] = series_value(signal, &frame.data);
+                }
+            }
+            last.clone_from(&row);
+            ret.times.push(frame.time);
+            ret.rows.push(row);
+        }
+        Ok(ret)
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// time in seconds then a column per signal, empty where a signal has no value
+pub fn write_series_csv(series: &SignalSeries, mut writer: impl Write) -> Result<(), Error> {
+    let header: Vec<String> = series.signals.iter().map(|s| csv_field(s)).collect();
+    writeln!(writer, "time,{}", header.join(","))?;
+    for (time, row) in series.times.iter().zip(&series.rows) {
+        write!(writer, "{:.6}", time / 1000.0)?;
+        for value in row {
+            match value {
+                Some(v) => write!(writer, ",{}", v)?,
+                None => write!(writer, ",")?,
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(writer.flush()?)
+}
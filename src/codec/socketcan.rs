@@ -0,0 +1,106 @@
+use crate::codec::replay::LogFrame;
+use crate::Error;
+use std::fs::File;
+use std::io::Read;
+use std::os::fd::{AsRawFd, FromRawFd};
+
+// struct can_frame or canfd_frame
+const FRAME_BYTES: usize = size_of::<libc::canfd_frame>();
+
+// raw socket taking CAN FD frames too, bound to one interface
+pub struct SocketCan {
+    socket: File,
+    buf: [u8; FRAME_BYTES],
+    pub interface: String,
+}
+
+impl SocketCan {
+    pub fn open(interface: &str) -> Result<Self, Error> {
+        let hint = |e: std::io::Error| Error::Hint(interface.to_string(), Box::new(e.into()));
+        let name = std::ffi::CString::new(interface).map_err(|_| Error::InvalidIdentifier)?;
+        let socket = unsafe {
+            let fd = libc::socket(libc::PF_CAN, libc::SOCK_RAW, libc::CAN_RAW);
+            if fd < 0 {
+                return Err(hint(std::io::Error::last_os_error()));
+            }
+            let socket = File::from_raw_fd(fd);
+            let enable: libc::c_int = 1;
+            libc::setsockopt(
+                fd,
+                libc::SOL_CAN_RAW,
+                libc::CAN_RAW_FD_FRAMES,
+                &enable as *const libc::c_int as *const libc::c_void,
+                size_of::<libc::c_int>() as libc::socklen_t,
+            );
+            let index = libc::if_nametoindex(name.as_ptr());
+            if index == 0 {
+                return Err(hint(std::io::Error::last_os_error()));
+            }
+            let mut addr: libc::sockaddr_can = std::mem::zeroed();
+            addr.can_family = libc::AF_CAN as libc::sa_family_t;
+            addr.can_ifindex = index as libc::c_int;
+            let bound = libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_can as *const libc::sockaddr,
+                size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            );
+            if bound < 0 {
+                return Err(hint(std::io::Error::last_os_error()));
+            }
+            socket
+        };
+        Ok(Self {
+            socket,
+            buf: [0; FRAME_BYTES],
+            interface: interface.to_string(),
+        })
+    }
+
+    // reads return WouldBlock instead of waiting, for polling it from an event loop
+    pub fn set_nonblocking(&self) -> Result<(), Error> {
+        let fd = self.socket.as_raw_fd();
+        let ok = unsafe {
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            flags >= 0 && libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) >= 0
+        };
+        match ok {
+            true => Ok(()),
+            false => Err(std::io::Error::last_os_error().into()),
+        }
+    }
+
+    // next data frame, time is left at 0, the id's top bit marks extended ids as in DBC files, remote and
+    // error frames are skipped
+    pub fn recv(&mut self) -> Result<LogFrame, Error> {
+        loop {
+            if let Some(frame) = self.recv_one()? {
+                return Ok(frame);
+            }
+        }
+    }
+
+    // one read, none for frames that are skipped
+    pub(crate) fn recv_one(&mut self) -> std::io::Result<Option<LogFrame>> {
+        let n = self.socket.read(&mut self.buf)?;
+        if n < size_of::<libc::can_frame>() {
+            return Ok(None);
+        }
+        let id = u32::from_ne_bytes(self.buf[..4].try_into().unwrap());
+        if id & (libc::CAN_RTR_FLAG | libc::CAN_ERR_FLAG) != 0 {
+            return Ok(None);
+        }
+        let len = (self.buf[4] as usize).min(n - 8);
+        Ok(Some(LogFrame {
+            time: 0.0,
+            channel: self.interface.clone(),
+            id: id & (libc::CAN_EFF_FLAG | libc::CAN_EFF_MASK),
+            data: self.buf[8..8 + len].to_vec(),
+        }))
+    }
+}
+
+impl AsRawFd for SocketCan {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.socket.as_raw_fd()
+    }
+}
@@ -0,0 +1,123 @@
+use crate::codec::monitor::LinSniffer;
+use crate::codec::payload::DecodedSignal;
+use crate::codec::replay::{parse_asc, parse_candump, LogFrame};
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+use crate::codec::socketcan::SocketCan;
+use crate::{Database, Error};
+use std::collections::VecDeque;
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, Lines};
+
+/*
+ * Live frames for async code, what the reader threads behind `autodbconv monitor` do without a thread each.
+ * Frames are stamped with the ms since the stream was made.
+ */
+
+const READ_BYTES: usize = 256;
+
+enum Source {
+    Lines(Lines<Box<dyn AsyncBufRead + Unpin + Send>>),
+    Lin(Box<dyn AsyncRead + Unpin + Send>, LinSniffer),
+    #[cfg(all(feature = "socketcan", target_os = "linux"))]
+    SocketCan(tokio::io::unix::AsyncFd<SocketCan>),
+}
+
+pub struct FrameStream {
+    source: Source,
+    pending: VecDeque<LogFrame>,
+    start: Instant,
+}
+
+impl FrameStream {
+    fn new(source: Source) -> Self {
+        Self {
+            source,
+            pending: VecDeque::new(),
+            start: Instant::now(),
+        }
+    }
+
+    // candump -L or ASC lines, e.g. a child process's stdout or a TCP connection
+    pub fn lines(reader: impl AsyncBufRead + Unpin + Send + 'static) -> Self {
+        let reader: Box<dyn AsyncBufRead + Unpin + Send> = Box::new(reader);
+        Self::new(Source::Lines(reader.lines()))
+    }
+
+    // what a UART behind a LIN transceiver receives, e.g. a serial port opened with tokio-serial
+    pub fn lin(reader: impl AsyncRead + Unpin + Send + 'static, sniffer: LinSniffer) -> Self {
+        Self::new(Source::Lin(Box::new(reader), sniffer))
+    }
+
+    // must be called within a tokio runtime
+    #[cfg(all(feature = "socketcan", target_os = "linux"))]
+    pub fn socketcan(interface: &str) -> Result<Self, Error> {
+        let socket = SocketCan::open(interface)?;
+        socket.set_nonblocking()?;
+        Ok(Self::new(Source::SocketCan(tokio::io::unix::AsyncFd::new(
+            socket,
+        )?)))
+    }
+
+    // none once the input ends, a socket never does
+    pub async fn next(&mut self) -> Result<Option<LogFrame>, Error> {
+        let start = self.start;
+        let time = move || start.elapsed().as_secs_f64() * 1000.0;
+        loop {
+            if let Some(frame) = self.pending.pop_front() {
+                return Ok(Some(frame));
+            }
+            match &mut self.source {
+                Source::Lines(lines) => {
+                    let Some(line) = lines.next_line().await? else {
+                        return Ok(None);
+                    };
+                    let frames = match parse_candump(&line) {
+                        frames if frames.is_empty() => parse_asc(&line),
+                        frames => frames,
+                    };
+                    for mut frame in frames {
+                        frame.time = time();
+                        self.pending.push_back(frame);
+                    }
+                }
+                Source::Lin(reader, sniffer) => {
+                    let mut buf = [0; READ_BYTES];
+                    let n = reader.read(&mut buf).await?;
+                    if n == 0 {
+                        return Ok(None);
+                    }
+                    self.pending.extend(sniffer.push(&buf[..n], time()));
+                }
+                #[cfg(all(feature = "socketcan", target_os = "linux"))]
+                Source::SocketCan(socket) => {
+                    let mut guard = socket.readable_mut().await?;
+                    // Err when it would block, readiness is cleared and waited for again
+                    if let Ok(frame) = guard.try_io(|s| s.get_mut().recv_one()) {
+                        if let Some(mut frame) = frame? {
+                            frame.time = time();
+                            self.pending.push_back(frame);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // next frame of db with its signals, frames not in it or shorter than it says are skipped
+    pub async fn next_decoded(
+        &mut self,
+        db: &Database,
+    ) -> Result<Option<(LogFrame, Vec<DecodedSignal>)>, Error> {
+        while let Some(frame) = self.next().await? {
+            let Some((name, msg)) = db.message_by_id(frame.id) else {
+                continue;
+            };
+            if frame.data.len() < msg.byte_width as usize {
+                continue;
+            }
+            let signals = db.decode_message(name, &frame.data)?;
+            return Ok(Some((frame, signals)));
+        }
+        Ok(None)
+    }
+}
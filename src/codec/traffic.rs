@@ -0,0 +1,272 @@
+use crate::codec::payload::SignalValue;
+use crate::codec::vectors::next_random;
+use crate::parsers::encoding::{
+    ChecksumModel, DatabaseType, LDFScheduleCommand, Name, Signal, ValueKind,
+};
+use crate::parsers::ids::{FrameId, Pid};
+use crate::{Database, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const SEED: u64 = 0x9E3779B97F4A7C15; // xorshift state can't be 0
+const FD_LENGTHS: [usize; 8] = [8, 12, 16, 20, 24, 32, 48, 64]; // by DLC from 8
+const FLOAT_SPAN: f64 = 1000.0; // float signals without a physical range move within +-this
+
+// how a signal's value moves over a generated log
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValuePattern {
+    #[default]
+    Init, // the initial value throughout
+    Constant(f64), // physical, numeric for signals without a scalar encoding
+    Ramp(f64),     // across the signal's range every so many ms, then from the start again
+    Random,        // anywhere in the signal's range, a new value each frame
+}
+
+// choices for Database::generate_traffic
+#[derive(Clone, Debug)]
+pub struct TrafficOptions {
+    pub duration: f64,                           // ms
+    pub table: Option<String>, // LIN schedule table, the first by name if not given
+    pub default_cycle_time: Option<f64>, // ms, for CAN frames without one, unsent if not given
+    pub pattern: ValuePattern, // for signals not in patterns
+    pub patterns: HashMap<String, ValuePattern>, // by signal name
+    pub seed: u64,
+}
+
+impl Default for TrafficOptions {
+    fn default() -> Self {
+        Self {
+            duration: 1000.0,
+            table: None,
+            default_cycle_time: None,
+            pattern: ValuePattern::Init,
+            patterns: HashMap::new(),
+            seed: 0,
+        }
+    }
+}
+
+// one frame on the bus
+#[derive(Clone, Debug, Serialize)]
+pub struct TrafficFrame {
+    pub time: f64, // ms
+    pub message: Name,
+    pub id: u32, // as in Message::id, extended CAN ids keep the DBC flag
+    pub data: Vec<u8>,
+    pub checksum: Option<u8>, // LIN
+}
+
+// numeric value at phase 0-1 of the signal's range, physical if it has a range
+fn value_at(signal: &Signal, phase: f64) -> Option<u64> {
+    if let Some((min, max)) = signal.physical_range().filter(|(min, max)| min < max) {
+        let value = min + (max - min) * phase;
+        return signal.to_raw(&SignalValue::Physical(value)).ok();
+    }
+    let (min, max) = match signal.kind {
+        ValueKind::Float32 | ValueKind::Float64 => (-FLOAT_SPAN, FLOAT_SPAN),
+        _ => signal.raw_span(0, signal.max_raw()),
+    };
+    signal.from_value(min + (max - min) * phase)
+}
+
+fn pattern_raw(signal: &Signal, pattern: &ValuePattern, time: f64, state: &mut u64) -> u64 {
+    let raw = match pattern {
+        ValuePattern::Init => None,
+        ValuePattern::Constant(value) => signal
+            .to_raw(&SignalValue::Physical(*value))
+            .ok()
+            .or_else(|| signal.from_value(*value)),
+        ValuePattern::Ramp(period) if *period > 0.0 => value_at(signal, time % period / period),
+        ValuePattern::Ramp(_) => None,
+        ValuePattern::Random => value_at(signal, next_random(state) as f64 / u64::MAX as f64),
+    };
+    raw.unwrap_or(signal.init_value & signal.max_raw()) // ends of a range can round outside it
+}
+
+// LIN 2.2A section 2.3.1.5, the enhanced checksum covers the PID too
+pub(crate) fn lin_checksum(id: u32, data: &[u8], model: ChecksumModel) -> u8 {
+    let mut sum: u16 = match model {
+        ChecksumModel::Enhanced => Pid::from_id(id as u8).get() as u16,
+        ChecksumModel::Classic => 0,
+    };
+    for byte in data {
+        sum += *byte as u16;
+        if sum > 0xFF {
+            sum -= 0xFF;
+        }
+    }
+    !(sum as u8)
+}
+
+impl Database {
+    // frames a bus would carry for duration, LIN ones following a schedule table and CAN ones their cycle
+    // times, the same options always give the same log
+    pub fn generate_traffic(&self, options: &TrafficOptions) -> Result<Vec<TrafficFrame>, Error> {
+        let mut sent: Vec<(f64, &Name)> = Vec::new();
+        if let DatabaseType::LDF(data) = &self.extra {
+            let mut tables: Vec<&Name> = data.schedule_tables.keys().collect();
+            tables.sort();
+            let table = match &options.table {
+                Some(table) => table.as_str(),
+                None => tables.first().ok_or(Error::UnknownScheduleTable)?,
+            };
+            for slot in self.simulate_schedule(table, options.duration)? {
+                // nothing is known about diagnostic payloads or event triggered responses
+                let frame = match (slot.sent, slot.command) {
+                    (Some(frame), _) => frame,
+                    (None, LDFScheduleCommand::Frame(frame))
+                        if self.messages.contains_key(frame) =>
+                    {
+                        frame
+                    }
+                    _ => continue,
+                };
+                sent.push((slot.start, frame));
+            }
+        } else {
+            let mut frames: Vec<_> = self.messages.iter().collect();
+            frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+            for (name, msg) in frames {
+                let Some(period) = msg.cycle_time.or(options.default_cycle_time) else {
+                    continue;
+                };
+                if period <= 0.0 {
+                    continue;
+                }
+                let mut time = 0.0;
+                while time < options.duration {
+                    sent.push((time, name));
+                    time += period;
+                }
+            }
+            sent.sort_by(|a, b| {
+                let id = |n| self.messages[n].id;
+                (a.0, id(a.1), a.1)
+                    .partial_cmp(&(b.0, id(b.1), b.1))
+                    .unwrap()
+            });
+        }
+
+        let mut state = options.seed ^ SEED;
+        let mut ret = Vec::with_capacity(sent.len());
+        for (time, name) in sent {
+            let msg = &self.messages[name];
+            let mut values = HashMap::new();
+            for sig in &msg.signals {
+                let signal = self.signals.get(sig).ok_or(Error::UnknownSignal)?;
+                if signal.kind == ValueKind::ByteArray {
+                    continue;
+                }
+                let pattern = options.patterns.get(&**sig).unwrap_or(&options.pattern);
+                let raw = pattern_raw(signal, pattern, time, &mut state);
+                values.insert(sig.to_string(), SignalValue::Raw(raw));
+            }
+            let data = self
+                .encode_message(name, &values)
+                .map_err(|e| Error::Hint(name.to_string(), Box::new(e)))?;
+            let checksum = match &self.extra {
+                DatabaseType::LDF(ldf) => {
+                    let model = msg.checksum.unwrap_or_else(|| ldf.checksum_model(msg));
+                    Some(lin_checksum(msg.id, &data, model))
+                }
+                _ => None,
+            };
+            ret.push(TrafficFrame {
+                time,
+                message: name.clone(),
+                id: msg.id,
+                data,
+                checksum,
+            });
+        }
+        Ok(ret)
+    }
+}
+
+fn hex(data: &[u8], sep: &str) -> String {
+    data.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+// SocketCAN candump -l format, LIN frames are written as CAN ones
+pub fn write_candump(frames: &[TrafficFrame], interface: &str) -> Result<String, Error> {
+    let mut s = String::new();
+    for frame in frames {
+        let id = if FrameId::can(frame.id).is_ok_and(FrameId::is_extended) {
+            format!("{:08X}", frame.id & 0x1FFFFFFF)
+        } else {
+            format!("{:03X}", frame.id)
+        };
+        writeln!(
+            s,
+            "({:.6}) {} {}#{}",
+            frame.time / 1000.0,
+            interface,
+            id,
+            hex(&frame.data, "")
+        )?;
+    }
+    Ok(s)
+}
+
+// Vector ASC, timestamps from the start of measurement
+pub fn write_asc(frames: &[TrafficFrame], channel: u8) -> Result<String, Error> {
+    let mut s = String::new();
+    s += "date Mon Jan 1 00:00:00.000 am 2024\n";
+    s += "base hex  timestamps absolute\n";
+    s += "internal events logged\n";
+    s += "Begin Triggerblock Mon Jan 1 00:00:00.000 am 2024\n";
+    s += "   0.000000 Start of measurement\n";
+    for frame in frames {
+        let time = frame.time / 1000.0;
+        match frame.checksum {
+            Some(checksum) => writeln!(
+                s,
+                "{:>11.6} Li{} {:X} Rx {} {} checksum = {:X}",
+                time,
+                channel,
+                frame.id,
+                frame.data.len(),
+                hex(&frame.data, " "),
+                checksum
+            ),
+            None => {
+                let id = if FrameId::can(frame.id).is_ok_and(FrameId::is_extended) {
+                    format!("{:X}x", frame.id & 0x1FFFFFFF)
+                } else {
+                    format!("{:X}", frame.id)
+                };
+                let len = frame.data.len();
+                if len > 8 {
+                    let dlc = FD_LENGTHS.iter().position(|l| *l >= len).unwrap_or(15) + 8;
+                    writeln!(
+                        s,
+                        "{:>11.6} CANFD {:>3} Rx {:>9} 1 0 {:x} {:>2} {}",
+                        time,
+                        channel,
+                        id,
+                        dlc,
+                        len,
+                        hex(&frame.data, " ")
+                    )
+                } else {
+                    writeln!(
+                        s,
+                        "{:>11.6} {}  {:<16}Rx   d {} {}",
+                        time,
+                        channel,
+                        id,
+                        len,
+                        hex(&frame.data, " ")
+                    )
+                }
+            }
+        }?;
+    }
+    s += "End TriggerBlock\n";
+    Ok(s)
+}
@@ -0,0 +1,112 @@
+use crate::codec::payload::SignalValue;
+use crate::parsers::encoding::{Name, Signal, ValueKind};
+use crate::{Database, Error};
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub(crate) const SEED: u64 = 0x9E3779B97F4A7C15;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VectorSignal {
+    pub name: Name,
+    pub raw: u64,
+    pub physical: f64, // the numeric raw value for signals without a scalar encoding
+}
+
+// known answer for a codec, packing the raw values gives payload and unpacking payload gives them back
+#[derive(Clone, Debug, Serialize)]
+pub struct TestVector {
+    pub message: Name,
+    pub case: String,
+    pub signals: Vec<VectorSignal>, // in frame order, byte arrays left at their initial value
+    pub payload: Vec<u8>,
+}
+
+// xorshift64*, the same database and count always give the same vectors
+pub(crate) fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    state.wrapping_mul(0x2545F4914F6CDD1D)
+}
+
+fn case_raw(signal: &Signal, case: &str, random: u64) -> u64 {
+    let max = signal.max_raw();
+    match (case, signal.kind) {
+        ("init", _) => signal.init_value & max,
+        ("max", ValueKind::Signed) => max >> 1,
+        ("max", ValueKind::Float32) => f32::MAX.to_bits() as u64,
+        ("max", ValueKind::Float64) => f64::MAX.to_bits(),
+        ("max", _) => max,
+        ("min", ValueKind::Signed) => (max >> 1) + 1,
+        ("min", ValueKind::Float32) => f32::MIN.to_bits() as u64,
+        ("min", ValueKind::Float64) => f64::MIN.to_bits(),
+        // random bits could be NaN, keep floats finite and readable
+        ("random", ValueKind::Float32) => ((random as i32 as f32) / 256.0).to_bits() as u64,
+        ("random", ValueKind::Float64) => ((random as i32 as f64) / 256.0).to_bits(),
+        ("random", _) => random & max,
+        _ => 0,
+    }
+}
+
+impl Database {
+    // per frame by id: initial values, zero, maximum, minimum if any signal has one, then count random ones
+    pub fn test_vectors(&self, count: usize) -> Result<Vec<TestVector>, Error> {
+        let mut frames: Vec<_> = self.messages.iter().collect();
+        frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+        let mut ret = Vec::new();
+        for (name, msg) in frames {
+            let hint = |e: Error| Error::Hint(name.to_string(), Box::new(e));
+            let signals: Vec<(&Name, &Signal)> = msg
+                .signals
+                .iter()
+                .map(|n| Ok((n, self.signals.get(n).ok_or(Error::UnknownSignal)?)))
+                .filter(|s| !matches!(s, Ok((_, s)) if s.kind == ValueKind::ByteArray))
+                .collect::<Result<_, Error>>()
+                .map_err(hint)?;
+            if signals.is_empty() {
+                continue;
+            }
+            let mut cases = vec!["init", "zero", "max"];
+            if signals.iter().any(|(_, s)| s.kind != ValueKind::Unsigned) {
+                cases.push("min");
+            }
+            let fixed = cases.len();
+            cases.extend(std::iter::repeat_n("random", count));
+
+            let mut state = SEED ^ msg.id as u64;
+            for (i, case) in cases.into_iter().enumerate() {
+                let values: HashMap<String, SignalValue> = signals
+                    .iter()
+                    .map(|(n, s)| {
+                        let raw = case_raw(s, case, next_random(&mut state));
+                        (n.to_string(), SignalValue::Raw(raw))
+                    })
+                    .collect();
+                let payload = self.encode_message(name, &values).map_err(hint)?;
+                let decoded = self.decode_message(name, &payload).map_err(hint)?;
+                let signals = signals
+                    .iter()
+                    .map(|(n, s)| {
+                        let d = decoded.iter().find(|d| *d.name == ***n).unwrap();
+                        VectorSignal {
+                            name: (*n).clone(),
+                            raw: d.raw,
+                            physical: d.physical.unwrap_or(s.raw_value(d.raw)),
+                        }
+                    })
+                    .collect();
+                ret.push(TestVector {
+                    message: name.clone(),
+                    case: match case {
+                        "random" => format!("random {}", i + 1 - fixed),
+                        _ => case.to_string(),
+                    },
+                    signals,
+                    payload,
+                });
+            }
+        }
+        Ok(ret)
+    }
+}
@@ -0,0 +1,828 @@
+use crate::analysis::query::Pattern;
+use crate::analysis::schedule::ScheduleTicks;
+use crate::analysis::validate::selectors;
+use crate::codec::vectors::TestVector;
+use crate::parsers::encoding::{
+    ChecksumModel, Encoding, Message, Name, Signal, ValueKind, BIT_START_INVALID,
+};
+use crate::project::manifest::{Accessors, CodegenOptions, NameCase};
+use crate::project::routing::{RouteEndpoint, RoutingEntry};
+use crate::{Database, Error, Filter};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+fn sorted<T>(map: &HashMap<Name, T>) -> Vec<(&Name, &T)> {
+    let mut ret: Vec<_> = map.iter().collect();
+    ret.sort_by(|a, b| a.0.cmp(b.0));
+    ret
+}
+
+fn c_type(width: u16, kind: ValueKind) -> &'static str {
+    match (width, kind) {
+        (_, ValueKind::Float32) => "float",
+        (_, ValueKind::Float64) => "double",
+        (0..=8, ValueKind::Signed) => "int8_t",
+        (9..=16, ValueKind::Signed) => "int16_t",
+        (17..=32, ValueKind::Signed) => "int32_t",
+        (_, ValueKind::Signed) => "int64_t",
+        (0..=8, _) => "uint8_t",
+        (9..=16, _) => "uint16_t",
+        (17..=32, _) => "uint32_t",
+        (_, _) => "uint64_t",
+    }
+}
+
+fn c_ident(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// one // line per line of text
+fn c_comment(indent: &str, text: &str) -> String {
+    text.lines()
+        .map(|l| format!("{}// {}\n", indent, l.trim_end()))
+        .collect()
+}
+
+// "LSMErrorBit" and "lsm_error_bit" both split into lsm, error, bit
+fn words(s: &str) -> Vec<String> {
+    let chars: Vec<char> = c_ident(s).chars().collect();
+    let mut ret: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = ch.is_ascii_uppercase()
+            && (prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+                || (prev.is_some_and(|p| p.is_ascii_uppercase())
+                    && next.is_some_and(|n| n.is_ascii_lowercase())));
+        if ch == '_' || boundary {
+            ret.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+        }
+        if ch != '_' {
+            word.push(ch.to_ascii_lowercase());
+        }
+    }
+    ret.extend((!word.is_empty()).then_some(word));
+    ret
+}
+
+// identifiers for frames, fields and macros
+struct Names<'a>(&'a CodegenOptions);
+
+impl Names<'_> {
+    fn field(&self, name: &str) -> String {
+        match self.0.case {
+            NameCase::Preserve => c_ident(name),
+            NameCase::Snake => words(name).join("_"),
+            NameCase::Camel => {
+                let mut ret = String::new();
+                for (i, word) in words(name).into_iter().enumerate() {
+                    let mut chars = word.chars();
+                    if let (false, Some(first)) = (i == 0, chars.next()) {
+                        ret.push(first.to_ascii_uppercase());
+                        ret.extend(chars);
+                    } else {
+                        ret.push_str(&word);
+                    }
+                }
+                ret
+            }
+        }
+    }
+
+    fn frame(&self, name: &str) -> String {
+        c_ident(&format!("{}{}", self.0.prefix, self.field(name)))
+    }
+
+    fn constant(&self, name: &str) -> String {
+        let name = match self.0.case {
+            NameCase::Preserve => c_ident(name),
+            _ => words(name).join("_"),
+        };
+        c_ident(&format!("{}{}", self.0.prefix, name)).to_uppercase()
+    }
+}
+
+// (multiplexor, values selecting) a multiplexed signal must all match, see selectors()
+type Guard<'a> = Vec<(&'a Name, Vec<(u64, u64)>)>;
+
+// a frame with the signals and functions generated for it, multiplexed signals follow their multiplexors
+struct Frame<'a> {
+    name: &'a Name,
+    msg: &'a Message,
+    signals: Vec<&'a Name>,
+    guards: HashMap<&'a Name, Guard<'a>>,
+    pack: bool,
+    unpack: bool,
+}
+
+impl Frame<'_> {
+    // multiplexors are compared raw, so they're never physical
+    fn is_multiplexor(&self, name: &Name) -> bool {
+        self.guards.values().flatten().any(|(m, _)| *m == name)
+    }
+}
+
+// C condition on the struct fields selecting a multiplexed signal
+fn guard_condition(names: &Names, guard: &Guard, var: &str) -> String {
+    let conditions: Vec<String> = guard
+        .iter()
+        .map(|(m, ranges)| {
+            let field = format!("{}{}", var, names.field(m));
+            let ranges: Vec<String> = ranges
+                .iter()
+                .map(|(a, b)| match (a, b) {
+                    (a, b) if a == b => format!("{} == {}u", field, a),
+                    (0, b) => format!("{} <= {}u", field, b),
+                    (a, b) => format!("({} >= {}u && {} <= {}u)", field, a, field, b),
+                })
+                .collect();
+            match ranges.len() {
+                1 => ranges[0].clone(),
+                _ => format!("({})", ranges.join(" || ")),
+            }
+        })
+        .collect();
+    conditions.join(" && ")
+}
+
+// frames by id, db is already filtered to the node and messages
+fn frames<'a>(db: &'a Database, options: &CodegenOptions) -> Result<Vec<Frame<'a>>, Error> {
+    let patterns = options
+        .signals
+        .iter()
+        .map(|p| Pattern::parse(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    let subscribed: Vec<&Name> = match &options.node {
+        Some(node) => db.frames_subscribed_by(node).map(|(n, _)| n).collect(),
+        None => Vec::new(),
+    };
+    let mut ret = Vec::new();
+    for (name, msg) in &db.messages {
+        let selected = |s: &Name| patterns.is_empty() || patterns.iter().any(|p| p.matches(s));
+
+        // multiplexed signals bring their multiplexors along
+        let mut guards = HashMap::new();
+        let mut needed = Vec::new();
+        for sig in msg.mux_signals.keys().filter(|s| selected(s)) {
+            let guard = selectors(db, msg, sig);
+            if guard.len() > msg.mux_signals.len() {
+                return Err(Error::Hint(
+                    format!("{} multiplexes itself", sig),
+                    Box::new(Error::MultiplexorCount),
+                ));
+            }
+            needed.extend(guard.iter().map(|(m, _)| *m));
+            guards.insert(sig, guard);
+        }
+        let mut muxed: Vec<&Name> = msg
+            .mux_signals
+            .keys()
+            .filter(|s| guards.contains_key(s) || needed.contains(s))
+            .collect();
+        for sig in &muxed {
+            guards.entry(sig).or_insert_with(|| selectors(db, msg, sig));
+        }
+        muxed.sort_by_key(|s| (guards[s].len(), *s)); // a multiplexor's chain is shorter
+        let signals: Vec<&Name> = msg
+            .signals
+            .iter()
+            .filter(|s| selected(s) || needed.contains(s))
+            .chain(muxed)
+            .collect();
+        if signals.is_empty() && !patterns.is_empty() {
+            continue;
+        }
+        if let Some(s) = signals.iter().find(|s| !db.signals.contains_key(**s)) {
+            return Err(Error::Hint(s.to_string(), Box::new(Error::UnknownSignal)));
+        }
+        let (pack, unpack) = match &options.node {
+            Some(node) => (*msg.sender == **node, subscribed.contains(&name)),
+            None => (true, true),
+        };
+        ret.push(Frame {
+            name,
+            msg,
+            signals,
+            guards,
+            pack,
+            unpack,
+        });
+    }
+    ret.sort_by(|a, b| (a.msg.id, a.name).cmp(&(b.msg.id, b.name)));
+    Ok(ret)
+}
+
+// (scale, offset) when the field holds a physical value
+fn physical(options: &CodegenOptions, signal: &Signal) -> Option<(f64, f64)> {
+    if options.accessors != Accessors::Physical
+        || !matches!(signal.kind, ValueKind::Unsigned | ValueKind::Signed)
+    {
+        return None;
+    }
+    signal.encodings.iter().flatten().find_map(|e| match e {
+        Encoding::Scalar { scale, offset, .. } => Some((*scale, *offset)),
+        _ => None,
+    })
+}
+
+// statements of a signal, only run when its multiplexors select it
+fn guarded(c: &mut String, names: &Names, guard: Option<&Guard>, body: &str) {
+    let Some(guard) = guard else {
+        c.push_str(body);
+        return;
+    };
+    c.push_str(&format!(
+        "    if ({}) {{\n",
+        guard_condition(names, guard, "msg->")
+    ));
+    for line in body.lines() {
+        c.push_str(&format!("    {}\n", line));
+    }
+    c.push_str("    }\n");
+}
+
+// returns (header, source), name is used for the include guard and #include
+pub fn generate_c(db: &Database, name: &str) -> Result<(String, String), Error> {
+    generate_c_with(db, name, &CodegenOptions::default())
+}
+
+// generate_c for one node's view of the database, with its own names and accessors
+pub fn generate_c_with(
+    db: &Database,
+    name: &str,
+    options: &CodegenOptions,
+) -> Result<(String, String), Error> {
+    for pattern in &options.messages {
+        db.find_messages(pattern)?; // reports invalid patterns
+    }
+    let filtered;
+    let db = if options.node.is_some() || !options.messages.is_empty() {
+        filtered = db.clone().filter(&Filter {
+            nodes: options.node.iter().cloned().collect(),
+            messages: options.messages.clone(),
+        });
+        &filtered
+    } else {
+        db
+    };
+    let frames = frames(db, options)?;
+    let names = Names(options);
+    let guard = format!("{}_H", c_ident(name).to_uppercase());
+    let mut h = String::new();
+    let mut c = String::new();
+
+    writeln!(h, "// generated by autodbconv, do not edit")?;
+    writeln!(h, "#ifndef {}\n#define {}\n", guard, guard)?;
+    writeln!(h, "#include <stdint.h>\n")?;
+
+    // signal encodings
+    let included: Vec<&Name> = frames.iter().flat_map(|f| &f.signals).copied().collect();
+    for (sig_name, signal) in sorted(&db.signals) {
+        if !included.contains(&sig_name) {
+            continue;
+        }
+        let prefix = names.constant(sig_name);
+        for e in signal.encodings.iter().flatten() {
+            match e {
+                Encoding::Enum {
+                    rev_map,
+                    description,
+                    ..
+                } => {
+                    if let Some(text) = description {
+                        h.push_str(&c_comment("", text));
+                    }
+                    let mut values: Vec<_> = rev_map.iter().collect();
+                    values.sort();
+                    for (raw, label) in values {
+                        writeln!(
+                            h,
+                            "#define {}_{} ({}u)",
+                            prefix,
+                            c_ident(label).to_uppercase(),
+                            raw
+                        )?;
+                    }
+                }
+                Encoding::Scalar {
+                    raw_min,
+                    raw_max,
+                    scale,
+                    offset,
+                    ..
+                } => {
+                    // only the first range, others need the full table
+                    writeln!(h, "#define {}_RAW_MIN ({}u)", prefix, raw_min)?;
+                    writeln!(h, "#define {}_RAW_MAX ({}u)", prefix, raw_max)?;
+                    writeln!(h, "#define {}_SCALE ({:?})", prefix, scale)?;
+                    writeln!(h, "#define {}_OFFSET ({:?})", prefix, offset)?;
+                    break;
+                }
+                Encoding::Ascii => (),
+            }
+        }
+    }
+    writeln!(h)?;
+
+    // frames
+    for frame in &frames {
+        let msg = frame.msg;
+        let ident = names.frame(frame.name);
+        let upper = names.constant(frame.name);
+        if let Some(text) = &msg.description {
+            h.push_str(&c_comment("", text));
+        }
+        writeln!(h, "#define {}_ID (0x{:02X}u)", upper, msg.id)?;
+        writeln!(h, "#define {}_LEN ({}u)", upper, msg.byte_width)?;
+        if let Some(checksum) = msg.checksum {
+            writeln!(
+                h,
+                "#define {}_ENHANCED_CHECKSUM ({}u)",
+                upper,
+                (checksum == ChecksumModel::Enhanced) as u8
+            )?;
+        }
+        writeln!(h)?;
+        writeln!(h, "typedef struct {{")?;
+        for sig in &frame.signals {
+            let signal = db.signals.get(*sig).ok_or(Error::UnknownSignal)?;
+            if let Some(text) = &signal.description {
+                h.push_str(&c_comment("    ", text));
+            }
+            if let Some(guard) = frame.guards.get(sig) {
+                let condition = guard_condition(&names, guard, "");
+                h.push_str(&c_comment("    ", &format!("only when {}", condition)));
+            }
+            let field = names.field(sig);
+            if signal.kind == ValueKind::ByteArray {
+                writeln!(
+                    h,
+                    "    uint8_t {}[{}];",
+                    field,
+                    signal.bit_width.div_ceil(8)
+                )?;
+            } else if physical(options, signal).is_some() && !frame.is_multiplexor(sig) {
+                writeln!(h, "    double {};", field)?;
+            } else {
+                writeln!(
+                    h,
+                    "    {} {};",
+                    c_type(signal.bit_width, signal.kind),
+                    field
+                )?;
+            }
+        }
+        writeln!(h, "}} {}_t;\n", ident)?;
+        if frame.pack {
+            writeln!(
+                h,
+                "void {}_pack(const {}_t *msg, uint8_t data[{}_LEN]);",
+                ident, ident, upper
+            )?;
+        }
+        if frame.unpack {
+            writeln!(
+                h,
+                "void {}_unpack({}_t *msg, const uint8_t data[{}_LEN]);",
+                ident, ident, upper
+            )?;
+        }
+        writeln!(h)?;
+    }
+    writeln!(h, "#endif // {}", guard)?;
+
+    let signals = || {
+        frames
+            .iter()
+            .flat_map(|f| &f.signals)
+            .map(|s| &db.signals[*s])
+    };
+    let uses = |kind: ValueKind| signals().any(|s| s.kind == kind);
+    let floats = uses(ValueKind::Float32) || uses(ValueKind::Float64);
+
+    writeln!(c, "// generated by autodbconv, do not edit")?;
+    if floats {
+        writeln!(c, "#include <string.h>")?;
+    }
+    writeln!(c, "#include \"{}.h\"\n", name)?;
+    writeln!(
+        c,
+        "static uint64_t get_bits(const uint8_t *data, unsigned start, unsigned width) {{"
+    )?;
+    writeln!(c, "    uint64_t raw = 0;")?;
+    writeln!(c, "    for (unsigned i = 0; i < width; i++) {{")?;
+    writeln!(c, "        unsigned bit = start + i;")?;
+    writeln!(
+        c,
+        "        raw |= (uint64_t)((data[bit / 8] >> (bit % 8)) & 1u) << i;"
+    )?;
+    writeln!(c, "    }}\n    return raw;\n}}\n")?;
+    writeln!(
+        c,
+        "static void set_bits(uint8_t *data, unsigned start, unsigned width, uint64_t raw) {{"
+    )?;
+    writeln!(c, "    for (unsigned i = 0; i < width; i++) {{")?;
+    writeln!(c, "        unsigned bit = start + i;")?;
+    writeln!(c, "        if ((raw >> i) & 1u) {{")?;
+    writeln!(
+        c,
+        "            data[bit / 8] |= (uint8_t)(1u << (bit % 8));"
+    )?;
+    writeln!(c, "        }} else {{")?;
+    writeln!(
+        c,
+        "            data[bit / 8] &= (uint8_t)~(1u << (bit % 8));"
+    )?;
+    writeln!(c, "        }}\n    }}\n}}")?;
+
+    // big-endian start is the MSB, the next bit down follows the sawtooth
+    if signals().any(|s| !s.little_endian) {
+        writeln!(
+            c,
+            "\nstatic unsigned next_bit_be(unsigned bit) {{\n    return bit % 8 == 0 ? bit + 15 : bit - 1;\n}}"
+        )?;
+        writeln!(
+            c,
+            "\nstatic uint64_t get_bits_be(const uint8_t *data, unsigned start, unsigned width) {{"
+        )?;
+        writeln!(c, "    uint64_t raw = 0;")?;
+        writeln!(
+            c,
+            "    for (unsigned i = 0, bit = start; i < width; i++, bit = next_bit_be(bit)) {{"
+        )?;
+        writeln!(
+            c,
+            "        raw = (raw << 1) | ((data[bit / 8] >> (bit % 8)) & 1u);"
+        )?;
+        writeln!(c, "    }}\n    return raw;\n}}\n")?;
+        writeln!(
+            c,
+            "static void set_bits_be(uint8_t *data, unsigned start, unsigned width, uint64_t raw) {{"
+        )?;
+        writeln!(
+            c,
+            "    for (unsigned i = 0, bit = start; i < width; i++, bit = next_bit_be(bit)) {{"
+        )?;
+        writeln!(c, "        if ((raw >> (width - 1 - i)) & 1u) {{")?;
+        writeln!(
+            c,
+            "            data[bit / 8] |= (uint8_t)(1u << (bit % 8));"
+        )?;
+        writeln!(c, "        }} else {{")?;
+        writeln!(
+            c,
+            "            data[bit / 8] &= (uint8_t)~(1u << (bit % 8));"
+        )?;
+        writeln!(c, "        }}\n    }}\n}}")?;
+    }
+
+    // two's complement, the sign bit is the top one of the signal
+    if uses(ValueKind::Signed) {
+        writeln!(
+            c,
+            "\nstatic int64_t sign_extend(uint64_t raw, unsigned width) {{"
+        )?;
+        writeln!(c, "    uint64_t sign = (uint64_t)1 << (width - 1);")?;
+        writeln!(c, "    return (int64_t)((raw ^ sign) - sign);\n}}")?;
+    }
+
+    // IEEE 754 bits, memcpy is the portable way to reinterpret them
+    if uses(ValueKind::Float32) {
+        writeln!(c, "\nstatic float to_float(uint64_t raw) {{")?;
+        writeln!(c, "    uint32_t bits = (uint32_t)raw;\n    float value;")?;
+        writeln!(
+            c,
+            "    memcpy(&value, &bits, sizeof(value));\n    return value;\n}}"
+        )?;
+        writeln!(c, "\nstatic uint64_t from_float(float value) {{")?;
+        writeln!(
+            c,
+            "    uint32_t bits;\n    memcpy(&bits, &value, sizeof(bits));\n    return bits;\n}}"
+        )?;
+    }
+    if uses(ValueKind::Float64) {
+        writeln!(c, "\nstatic double to_double(uint64_t raw) {{")?;
+        writeln!(
+            c,
+            "    double value;\n    memcpy(&value, &raw, sizeof(value));\n    return value;\n}}"
+        )?;
+        writeln!(c, "\nstatic uint64_t from_double(double value) {{")?;
+        writeln!(
+            c,
+            "    uint64_t bits;\n    memcpy(&bits, &value, sizeof(bits));\n    return bits;\n}}"
+        )?;
+    }
+
+    // nearest raw value, half away from zero
+    if signals().any(|s| physical(options, s).is_some()) {
+        writeln!(
+            c,
+            "\nstatic int64_t to_raw(double value, double scale, double offset) {{"
+        )?;
+        writeln!(c, "    double raw = (value - offset) / scale;")?;
+        writeln!(
+            c,
+            "    return (int64_t)(raw < 0 ? raw - 0.5 : raw + 0.5);\n}}"
+        )?;
+    }
+
+    for frame in &frames {
+        let ident = names.frame(frame.name);
+        let upper = names.constant(frame.name);
+        let mut layout = Vec::new();
+        for sig in &frame.signals {
+            let signal = &db.signals[*sig];
+            if signal.bit_start == BIT_START_INVALID {
+                return Err(Error::InvalidPayload);
+            }
+            let physical = physical(options, signal).filter(|_| !frame.is_multiplexor(sig));
+            let guard = frame.guards.get(sig);
+            layout.push((names.field(sig), signal, physical, guard));
+        }
+
+        if frame.pack {
+            writeln!(
+                c,
+                "\nvoid {}_pack(const {}_t *msg, uint8_t data[{}_LEN]) {{",
+                ident, ident, upper
+            )?;
+        }
+        for (sig, signal, physical, guard) in layout.iter().filter(|_| frame.pack) {
+            let mut body = String::new();
+            if signal.kind == ValueKind::ByteArray {
+                for (i, start) in signal.bit_positions().iter().step_by(8).enumerate() {
+                    writeln!(
+                        body,
+                        "    set_bits{}(data, {}, 8, msg->{}[{}]);",
+                        if signal.little_endian { "" } else { "_be" },
+                        start,
+                        sig,
+                        i
+                    )?;
+                }
+            } else {
+                let value = match (signal.kind, physical) {
+                    (_, Some((scale, offset))) => {
+                        format!("(uint64_t)to_raw(msg->{}, {:?}, {:?})", sig, scale, offset)
+                    }
+                    (ValueKind::Signed, _) => format!("(uint64_t)msg->{}", sig),
+                    (ValueKind::Float32, _) => format!("from_float(msg->{})", sig),
+                    (ValueKind::Float64, _) => format!("from_double(msg->{})", sig),
+                    _ => format!("msg->{}", sig),
+                };
+                writeln!(
+                    body,
+                    "    set_bits{}(data, {}, {}, {});",
+                    if signal.little_endian { "" } else { "_be" },
+                    signal.bit_start,
+                    signal.bit_width,
+                    value
+                )?;
+            }
+            guarded(&mut c, &names, *guard, &body);
+        }
+        if frame.pack {
+            writeln!(c, "}}")?;
+        }
+        if !frame.unpack {
+            continue;
+        }
+        writeln!(
+            c,
+            "\nvoid {}_unpack({}_t *msg, const uint8_t data[{}_LEN]) {{",
+            ident, ident, upper
+        )?;
+        for (sig, signal, physical, guard) in &layout {
+            let mut body = String::new();
+            if signal.kind == ValueKind::ByteArray {
+                for (i, start) in signal.bit_positions().iter().step_by(8).enumerate() {
+                    writeln!(
+                        body,
+                        "    msg->{}[{}] = (uint8_t)get_bits{}(data, {}, 8);",
+                        sig,
+                        i,
+                        if signal.little_endian { "" } else { "_be" },
+                        start
+                    )?;
+                }
+            } else {
+                let get = format!(
+                    "get_bits{}(data, {}, {})",
+                    if signal.little_endian { "" } else { "_be" },
+                    signal.bit_start,
+                    signal.bit_width
+                );
+                let value = match signal.kind {
+                    ValueKind::Signed => format!(
+                        "({})sign_extend({}, {})",
+                        c_type(signal.bit_width, signal.kind),
+                        get,
+                        signal.bit_width
+                    ),
+                    ValueKind::Float32 => format!("to_float({})", get),
+                    ValueKind::Float64 => format!("to_double({})", get),
+                    _ => format!("({}){}", c_type(signal.bit_width, signal.kind), get),
+                };
+                let value = match physical {
+                    Some((scale, offset)) => {
+                        format!("(double){} * {:?} + {:?}", value, scale, offset)
+                    }
+                    None => value,
+                };
+                writeln!(body, "    msg->{} = {};", sig, value)?;
+            }
+            guarded(&mut c, &names, *guard, &body);
+        }
+        writeln!(c, "}}")?;
+    }
+    Ok((h, c))
+}
+
+// header with a constant table for a gateway, raw values are converted as dst = scale * src + offset
+pub fn generate_routing_c(entries: &[RoutingEntry], name: &str) -> Result<String, Error> {
+    let ident = c_ident(name);
+    let guard = format!("{}_H", ident.to_uppercase());
+    let mut buses: Vec<&Name> = entries
+        .iter()
+        .flat_map(|e| [&e.from.bus, &e.to.bus])
+        .collect();
+    buses.sort();
+    buses.dedup();
+    let bus = |n: &Name| buses.iter().position(|b| *b == n).unwrap();
+    let mut h = String::new();
+
+    writeln!(h, "// generated by autodbconv, do not edit")?;
+    writeln!(h, "#ifndef {}\n#define {}\n", guard, guard)?;
+    writeln!(h, "#include <stdbool.h>\n#include <stdint.h>\n")?;
+    for (i, b) in buses.iter().enumerate() {
+        writeln!(
+            h,
+            "#define {}_BUS_{} ({}u)",
+            ident.to_uppercase(),
+            c_ident(b).to_uppercase(),
+            i
+        )?;
+    }
+    writeln!(h)?;
+    writeln!(h, "typedef struct {{")?;
+    writeln!(h, "    uint8_t bus;")?;
+    writeln!(h, "    uint32_t id;")?;
+    writeln!(h, "    uint16_t bit_start;")?;
+    writeln!(h, "    uint8_t bit_width;")?;
+    writeln!(h, "    bool little_endian;")?;
+    writeln!(h, "}} {}_endpoint_t;\n", ident)?;
+    writeln!(h, "typedef struct {{")?;
+    writeln!(h, "    {}_endpoint_t from;", ident)?;
+    writeln!(h, "    {}_endpoint_t to;", ident)?;
+    writeln!(h, "    double scale;")?;
+    writeln!(h, "    double offset;")?;
+    writeln!(h, "}} {}_route_t;\n", ident)?;
+
+    writeln!(
+        h,
+        "static const {}_route_t {}_routes[{}] = {{",
+        ident,
+        ident,
+        entries.len()
+    )?;
+    for e in entries {
+        let endpoint = |p: &RouteEndpoint| {
+            format!(
+                "{{{}u, 0x{:X}u, {}u, {}u, {}}}",
+                bus(&p.bus),
+                p.id,
+                p.bit_start,
+                p.bit_width,
+                p.little_endian
+            )
+        };
+        writeln!(
+            h,
+            "    {{{}, {}, {:?}, {:?}}}, // {}.{} -> {}.{}",
+            endpoint(&e.from),
+            endpoint(&e.to),
+            e.scale,
+            e.offset,
+            e.from.bus,
+            e.from.signal,
+            e.to.bus,
+            e.to.signal
+        )?;
+    }
+    writeln!(h, "}};\n")?;
+    writeln!(h, "#endif")?;
+    Ok(h)
+}
+
+// schedule table for a commander, sporadic slots have id 0xFF since the frame is chosen at runtime
+pub fn generate_schedule_c(schedule: &ScheduleTicks, name: &str) -> Result<String, Error> {
+    let ident = c_ident(name);
+    let upper = ident.to_uppercase();
+    let table = c_ident(&schedule.table);
+    let guard = format!("{}_H", upper);
+    let mut h = String::new();
+
+    writeln!(h, "// generated by autodbconv, do not edit")?;
+    writeln!(h, "#ifndef {}\n#define {}\n", guard, guard)?;
+    writeln!(h, "#include <stdint.h>\n")?;
+    writeln!(
+        h,
+        "#define {}_TICK_PERIOD_MS ({:?})",
+        upper, schedule.tick_period
+    )?;
+    writeln!(
+        h,
+        "#define {}_TIME_BASE_MS ({:?})",
+        upper, schedule.time_base
+    )?;
+    writeln!(h, "#define {}_SPORADIC_ID (0xFFu)\n", upper)?;
+    writeln!(h, "typedef struct {{")?;
+    writeln!(h, "    uint8_t id;")?;
+    writeln!(h, "    uint8_t pid;")?;
+    writeln!(h, "    uint32_t ticks;")?;
+    writeln!(h, "}} {}_slot_t;\n", ident)?;
+    writeln!(
+        h,
+        "static const {}_slot_t {}_{}[{}] = {{",
+        ident,
+        ident,
+        table,
+        schedule.slots.len()
+    )?;
+    for slot in &schedule.slots {
+        writeln!(
+            h,
+            "    {{0x{:02X}u, 0x{:02X}u, {}u}}, // {}",
+            slot.id.unwrap_or(0xFF),
+            slot.pid.unwrap_or(0xFF),
+            slot.ticks,
+            slot.command
+        )?;
+    }
+    writeln!(h, "}};\n")?;
+    writeln!(h, "#endif")?;
+    Ok(h)
+}
+
+// known answers as constant arrays, one set per frame in the order given
+pub fn generate_vectors_c(vectors: &[TestVector], name: &str) -> Result<String, Error> {
+    let ident = c_ident(name);
+    let upper = ident.to_uppercase();
+    let guard = format!("{}_H", upper);
+    let mut h = String::new();
+
+    writeln!(h, "// generated by autodbconv, do not edit")?;
+    writeln!(h, "#ifndef {}\n#define {}\n", guard, guard)?;
+    writeln!(h, "#include <stdint.h>\n")?;
+    for frame in vectors.chunk_by(|a, b| a.message == b.message) {
+        let first = &frame[0];
+        let prefix = format!("{}_{}", ident, c_ident(&first.message));
+        let signals: Vec<&str> = first.signals.iter().map(|s| &*s.name).collect();
+        let (n, width, len) = (frame.len(), signals.len(), first.payload.len());
+        writeln!(h, "// {}: {}", first.message, signals.join(", "))?;
+        writeln!(h, "#define {}_VECTORS ({}u)", prefix.to_uppercase(), n)?;
+        writeln!(
+            h,
+            "static const uint8_t {}_payload[{}][{}] = {{",
+            prefix, n, len
+        )?;
+        for v in frame {
+            let bytes: Vec<String> = v.payload.iter().map(|b| format!("0x{:02X}u", b)).collect();
+            writeln!(h, "    {{{}}}, // {}", bytes.join(", "), v.case)?;
+        }
+        writeln!(h, "}};")?;
+        writeln!(
+            h,
+            "static const uint64_t {}_raw[{}][{}] = {{",
+            prefix, n, width
+        )?;
+        for v in frame {
+            let raw: Vec<String> = v
+                .signals
+                .iter()
+                .map(|s| format!("UINT64_C({})", s.raw))
+                .collect();
+            writeln!(h, "    {{{}}}, // {}", raw.join(", "), v.case)?;
+        }
+        writeln!(h, "}};")?;
+        writeln!(
+            h,
+            "static const double {}_physical[{}][{}] = {{",
+            prefix, n, width
+        )?;
+        for v in frame {
+            let physical: Vec<String> = v
+                .signals
+                .iter()
+                .map(|s| format!("{:?}", s.physical))
+                .collect();
+            writeln!(h, "    {{{}}}, // {}", physical.join(", "), v.case)?;
+        }
+        writeln!(h, "}};\n")?;
+    }
+    writeln!(h, "#endif // {}", guard)?;
+    Ok(h)
+}
@@ -0,0 +1,71 @@
+use crate::{Database, Error, Format};
+use std::path::Path;
+
+const CACHE_MAGIC: &[u8; 4] = b"ADBC";
+const CACHE_VERSION: u32 = 14; // bump whenever the model changes
+
+// FNV-1a, stable across platforms and compiler versions unlike DefaultHasher
+fn source_hash(source: &[u8]) -> u64 {
+    source.iter().fold(0xCBF29CE484222325, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001B3)
+    })
+}
+
+fn header(source: &[u8]) -> Vec<u8> {
+    let mut ret = CACHE_MAGIC.to_vec();
+    ret.extend(CACHE_VERSION.to_le_bytes());
+    ret.extend(source_hash(source).to_le_bytes());
+    ret
+}
+
+impl Database {
+    // content hash, the same for databases that only differ in parse order, formatting or provenance
+    pub fn fingerprint(&self) -> u64 {
+        let mut db = self.clone();
+        db.sort();
+        db.provenance = None;
+        // maps become sorted objects in serde_json values
+        let value = serde_json::to_value(&db).unwrap_or_default();
+        source_hash(value.to_string().as_bytes())
+    }
+
+    pub fn save_cache(&self, cache: impl AsRef<Path>, source: &[u8]) -> Result<(), Error> {
+        let mut data = header(source);
+        data.extend(postcard::to_stdvec(self).map_err(|e| Error::IO(e.to_string()))?);
+        std::fs::write(cache, data)?;
+        Ok(())
+    }
+
+    // none if the cache is missing or was built from a different source
+    pub fn load_cache(cache: impl AsRef<Path>, source: &[u8]) -> Result<Option<Database>, Error> {
+        let data = match std::fs::read(cache) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let header = header(source);
+        match data.strip_prefix(header.as_slice()) {
+            Some(payload) => Ok(Some(
+                postcard::from_bytes(payload).map_err(|e| Error::IO(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Format {
+    // parses through a binary cache, rebuilding it if stale
+    pub fn parse_cached(
+        &self,
+        path: impl AsRef<Path>,
+        cache: impl AsRef<Path>,
+    ) -> Result<Database, Error> {
+        let source = std::fs::read(path)?;
+        if let Some(db) = Database::load_cache(&cache, &source)? {
+            return Ok(db);
+        }
+        let db = self.parse_reader(source.as_slice())?;
+        db.save_cache(&cache, &source)?;
+        Ok(db)
+    }
+}
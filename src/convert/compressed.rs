@@ -0,0 +1,62 @@
+use crate::{Error, Format};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+
+/*
+ * Databases shipped compressed, e.g. body.ldf.gz or an OEM release bundle. A .gz is decompressed as it's
+ * read, a .zip must hold exactly one file with a database extension and is decompressed into memory.
+ */
+
+fn extension(path: &Path) -> Option<String> {
+    Some(path.extension()?.to_str()?.to_ascii_lowercase())
+}
+
+pub fn is_compressed(path: impl AsRef<Path>) -> bool {
+    matches!(extension(path.as_ref()).as_deref(), Some("gz" | "zip"))
+}
+
+// what's in a file read from path, with a path naming it for Format::from_path, e.g. body.ldf for
+// body.ldf.gz and bundle.zip/LIN/body.ldf for a zip holding LIN/body.ldf
+pub(crate) fn decompress(
+    path: &Path,
+    reader: impl Read + Seek + 'static,
+) -> Result<(Box<dyn Read>, PathBuf), Error> {
+    match extension(path).as_deref() {
+        Some("gz") => Ok((
+            Box::new(MultiGzDecoder::new(reader)),
+            path.with_extension(""),
+        )),
+        Some("zip") => {
+            let mut archive = zip::ZipArchive::new(reader)?;
+            let names: Vec<String> = archive
+                .file_names()
+                .filter(|n| !n.ends_with('/') && Format::from_path(n).is_some())
+                .map(String::from)
+                .collect();
+            let [name] = names.as_slice() else {
+                let hint = match names.len() {
+                    0 => format!("no database in {}", path.display()),
+                    n => format!(
+                        "{} databases in {}: {}",
+                        n,
+                        path.display(),
+                        names.join(", ")
+                    ),
+                };
+                return Err(Error::Hint(hint, Box::new(Error::UnknownFormat)));
+            };
+            let mut data = Vec::new();
+            archive.by_name(name)?.read_to_end(&mut data)?;
+            Ok((Box::new(Cursor::new(data)), path.join(name)))
+        }
+        _ => Ok((Box::new(reader), path.to_path_buf())),
+    }
+}
+
+// opens a database file, decompressing .gz and .zip ones, see decompress
+pub fn open_decompressed(path: impl AsRef<Path>) -> Result<(Box<dyn Read>, PathBuf), Error> {
+    let path = path.as_ref();
+    decompress(path, BufReader::new(File::open(path)?))
+}
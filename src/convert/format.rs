@@ -0,0 +1,318 @@
+use crate::convert::compressed::open_decompressed;
+use crate::convert::plugin;
+use crate::writers::dbc::write_dbc_string_with;
+use crate::writers::ldf::write_ldf_string_with;
+use crate::{
+    parse_dbc_reader, parse_dbc_reader_with, parse_ldf_reader, parse_ldf_reader_with, Database,
+    DiagnosticSink, Error, LogSink, Warning,
+};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WriteOptions {
+    pub sorted: bool, // entities in (id, name) order whatever the parse order, for reproducible output
+    pub provenance: bool, // where the database came from, as a comment or JSON field
+    pub vector_attributes: bool, // DBC: Vector's standard attribute definitions even when unused, CANdb++ wants them
+}
+
+const DETECT_BYTES: usize = 64 * 1024; // of a file read to detect its format
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    LDF,
+    DBC,
+    NCF,
+    JSON,
+    Plugin(&'static str), // registered with register_reader or register_writer, by name
+}
+
+// what parse reads, a file or its content
+#[derive(Clone, Copy, Debug)]
+pub enum ParseInput<'a> {
+    Path(&'a Path),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> From<&'a Path> for ParseInput<'a> {
+    fn from(path: &'a Path) -> Self {
+        ParseInput::Path(path)
+    }
+}
+
+impl<'a> From<&'a PathBuf> for ParseInput<'a> {
+    fn from(path: &'a PathBuf) -> Self {
+        ParseInput::Path(path)
+    }
+}
+
+impl<'a> From<&'a [u8]> for ParseInput<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        ParseInput::Bytes(bytes)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for ParseInput<'a> {
+    fn from(bytes: &'a Vec<u8>) -> Self {
+        ParseInput::Bytes(bytes)
+    }
+}
+
+// text after a BOM, whitespace and comments, as both formats and JSON allow them before the first token
+fn skip_comments(mut text: &str) -> &str {
+    text = text.trim_start_matches('\u{FEFF}');
+    loop {
+        text = text.trim_start();
+        if let Some(rest) = text.strip_prefix("//") {
+            text = rest.split_once('\n').map_or("", |(_, r)| r);
+        } else if let Some(rest) = text.strip_prefix("/*") {
+            text = rest.split_once("*/").map_or("", |(_, r)| r);
+        } else {
+            return text;
+        }
+    }
+}
+
+// head as text, up to a character cut off at its end
+fn head_text(head: &[u8]) -> &str {
+    match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&head[..e.valid_up_to()]).unwrap(),
+    }
+}
+
+// name of the root element of an XML document, without its namespace prefix
+pub fn xml_root(head: &[u8]) -> Option<&str> {
+    let mut rest = head_text(head).trim_start_matches('\u{FEFF}').trim_start();
+    while let Some(tag) = rest.strip_prefix('<') {
+        if let Some(comment) = tag.strip_prefix("!--") {
+            rest = comment.split_once("-->")?.1.trim_start();
+            continue;
+        }
+        if tag.starts_with('?') || tag.starts_with('!') {
+            // declaration or doctype
+            rest = tag.split_once('>')?.1.trim_start();
+            continue;
+        }
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()?;
+        return name.rsplit(':').next().filter(|n| !n.is_empty());
+    }
+    None
+}
+
+fn undetected(head: &[u8]) -> Error {
+    match xml_root(head) {
+        Some(root) => Error::Hint(
+            format!(
+                "XML with root element {}, no reader registered for it",
+                root
+            ),
+            Box::new(Error::UnknownFormat),
+        ),
+        None => Error::UnknownFormat,
+    }
+}
+
+// parses a file or bytes in whatever format they're in, see Format::detect
+pub fn parse<'a>(input: impl Into<ParseInput<'a>>) -> Result<(Format, Database), Error> {
+    match input.into() {
+        ParseInput::Path(path) => {
+            let mut head = Vec::new();
+            let (reader, inner) = open_decompressed(path)?;
+            reader.take(DETECT_BYTES as u64).read_to_end(&mut head)?;
+            let format = Format::detect(&head)
+                .or(Format::from_path(inner))
+                .ok_or_else(|| undetected(&head))?;
+            Ok((format, format.parse(path)?))
+        }
+        ParseInput::Bytes(bytes) => {
+            let head = &bytes[..bytes.len().min(DETECT_BYTES)];
+            let format = Format::detect(head).ok_or_else(|| undetected(head))?;
+            Ok((format, format.parse_reader(bytes)?))
+        }
+    }
+}
+
+impl Format {
+    // from the start of a file: LIN_description_file, node_capability_file, DBC's VERSION or first keyword,
+    // a JSON object or a registered reader recognizing it, e.g. by its XML root element
+    pub fn detect(head: &[u8]) -> Option<Format> {
+        if let Some(name) = plugin::detect(head) {
+            return Some(Format::Plugin(name));
+        }
+        let text = skip_comments(head_text(head));
+        let keyword = text
+            .split(|c: char| c.is_whitespace() || c == ';' || c == ':')
+            .next()
+            .unwrap_or("");
+        match keyword {
+            "LIN_description_file" => Some(Format::LDF),
+            "node_capability_file" => Some(Format::NCF),
+            "VERSION" | "NS_" | "BS_" | "BU_" | "BO_" => Some(Format::DBC),
+            _ if text.starts_with('{') => Some(Format::JSON),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Format> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        ext.parse()
+            .ok()
+            .or_else(|| plugin::find_extension(ext).map(Format::Plugin))
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::LDF => "ldf",
+            Format::DBC => "dbc",
+            Format::NCF => "ncf",
+            Format::JSON => "json",
+            Format::Plugin(name) => plugin::extension(name),
+        }
+    }
+
+    pub fn parse(&self, path: impl AsRef<Path>) -> Result<Database, Error> {
+        #[cfg(feature = "mmap")]
+        if *self == Format::LDF && !crate::is_compressed(&path) {
+            return crate::parse_ldf_mmap(path);
+        }
+        let (reader, _) = open_decompressed(&path)?;
+        let db = self.parse_reader(BufReader::new(reader))?;
+        Ok(db.with_source(path.as_ref()))
+    }
+
+    pub fn parse_reader(&self, mut reader: impl Read) -> Result<Database, Error> {
+        match self {
+            Format::LDF => parse_ldf_reader(reader),
+            Format::DBC => parse_dbc_reader(reader),
+            Format::JSON => Ok(serde_json::from_reader(reader)?),
+            Format::NCF => Err(Error::NotImplemented),
+            Format::Plugin(name) => plugin::reader(name)
+                .ok_or(Error::NotImplemented)?
+                .read(&mut reader, &mut LogSink),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn parse_async(&self, path: impl AsRef<Path>) -> Result<Database, Error> {
+        let data = tokio::fs::read(&path).await?;
+        let data = std::io::Cursor::new(data);
+        let (reader, _) = crate::convert::compressed::decompress(path.as_ref(), data)?;
+        Ok(self.parse_reader(reader)?.with_source(path.as_ref()))
+    }
+
+    // reads everything before parsing, which doesn't wait on anything
+    #[cfg(feature = "tokio")]
+    pub async fn parse_reader_async(
+        &self,
+        mut reader: impl tokio::io::AsyncRead + Unpin,
+    ) -> Result<Database, Error> {
+        let mut data = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut reader, &mut data).await?;
+        self.parse_reader(data.as_slice())
+    }
+
+    // reports what the parser ignored or approximated to sink
+    pub fn parse_reader_with(
+        &self,
+        mut reader: impl Read,
+        sink: &mut dyn DiagnosticSink,
+    ) -> Result<Database, Error> {
+        match self {
+            Format::LDF => parse_ldf_reader_with(reader, sink),
+            Format::DBC => parse_dbc_reader_with(reader, sink),
+            Format::Plugin(name) => plugin::reader(name)
+                .ok_or(Error::NotImplemented)?
+                .read(&mut reader, sink),
+            _ => self.parse_reader(reader),
+        }
+    }
+
+    pub fn parse_reader_with_warnings(
+        &self,
+        reader: impl Read,
+    ) -> Result<(Database, Vec<Warning>), Error> {
+        let mut warnings = Vec::new();
+        let db = self.parse_reader_with(reader, &mut |w| warnings.push(w))?;
+        Ok((db, warnings))
+    }
+
+    pub fn write(&self, db: &Database, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.write_with(db, path, &WriteOptions::default())
+    }
+
+    pub fn write_writer(&self, db: &Database, writer: impl Write) -> Result<(), Error> {
+        self.write_writer_with(db, writer, &WriteOptions::default())
+    }
+
+    pub fn write_with(
+        &self,
+        db: &Database,
+        path: impl AsRef<Path>,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        self.write_writer_with(db, BufWriter::new(File::create(path)?), options)
+    }
+
+    pub fn write_writer_with(
+        &self,
+        db: &Database,
+        mut writer: impl Write,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        let owned;
+        let db = if options.sorted || (*self == Format::JSON && !options.provenance) {
+            let mut db = db.clone();
+            if options.sorted {
+                db.sort();
+            }
+            if !options.provenance {
+                db.provenance = None; // timestamps would make every write differ
+            }
+            owned = db;
+            &owned
+        } else {
+            db
+        };
+        match self {
+            Format::LDF => writer.write_all(write_ldf_string_with(db, options)?.as_bytes())?,
+            Format::DBC => writer.write_all(write_dbc_string_with(db, options)?.as_bytes())?,
+            Format::JSON if options.sorted => {
+                // maps go through serde_json's own map, which keeps keys sorted
+                serde_json::to_writer_pretty(&mut writer, &serde_json::to_value(db)?)?;
+                writeln!(writer)?;
+            }
+            Format::JSON => {
+                serde_json::to_writer_pretty(&mut writer, db)?;
+                writeln!(writer)?;
+            }
+            Format::NCF => return Err(Error::NotImplemented),
+            Format::Plugin(name) => plugin::writer(name).ok_or(Error::NotImplemented)?.write(
+                db,
+                &mut writer,
+                options,
+            )?,
+        }
+        Ok(writer.flush()?)
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ldf" => Ok(Format::LDF),
+            "dbc" => Ok(Format::DBC),
+            "ncf" => Ok(Format::NCF),
+            "json" => Ok(Format::JSON),
+            _ => plugin::find_name(s)
+                .map(Format::Plugin)
+                .ok_or(Error::UnknownFormat),
+        }
+    }
+}
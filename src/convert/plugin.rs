@@ -0,0 +1,175 @@
+use crate::{Database, DiagnosticSink, Error, Format, WriteOptions};
+use std::io::{Read, Write};
+use std::sync::{Arc, RwLock};
+
+/*
+ * Formats from other crates. Once registered they're a Format::Plugin like any other: FromStr and from_path
+ * find them, so they're accepted by --from/--to, picked by extension and listed by the formats subcommand
+ * of a binary that registers them before running.
+ */
+
+// a format databases can be read from
+pub trait FormatReader: Send + Sync {
+    fn name(&self) -> &'static str; // as given to Format::from_str, case doesn't matter
+    fn extensions(&self) -> &[&'static str]; // without the dot
+
+    // whether the start of a file is in this format, see xml_root for XML ones
+    fn detect(&self, _head: &[u8]) -> bool {
+        false
+    }
+    fn read(&self, reader: &mut dyn Read, sink: &mut dyn DiagnosticSink)
+        -> Result<Database, Error>;
+}
+
+// a format databases can be written to
+pub trait FormatWriter: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn extensions(&self) -> &[&'static str];
+    fn write(
+        &self,
+        db: &Database,
+        writer: &mut dyn Write,
+        options: &WriteOptions,
+    ) -> Result<(), Error>;
+}
+
+static READERS: RwLock<Vec<Arc<dyn FormatReader>>> = RwLock::new(Vec::new());
+static WRITERS: RwLock<Vec<Arc<dyn FormatWriter>>> = RwLock::new(Vec::new());
+
+const BUILTIN: [Format; 4] = [Format::LDF, Format::DBC, Format::NCF, Format::JSON];
+
+// names are unique across built in and registered formats, one name can have a reader and a writer
+fn check_name(name: &str, taken: bool) -> Result<(), Error> {
+    let builtin = BUILTIN
+        .iter()
+        .any(|f| f.extension().eq_ignore_ascii_case(name));
+    if builtin || taken {
+        return Err(Error::Hint(
+            format!("format {} already exists", name),
+            Box::new(Error::DuplicateIdentifier),
+        ));
+    }
+    Ok(())
+}
+
+pub fn register_reader(reader: impl FormatReader + 'static) -> Result<(), Error> {
+    let mut readers = READERS.write().unwrap();
+    let taken = readers
+        .iter()
+        .any(|r| r.name().eq_ignore_ascii_case(reader.name()));
+    check_name(reader.name(), taken)?;
+    readers.push(Arc::new(reader));
+    Ok(())
+}
+
+pub fn register_writer(writer: impl FormatWriter + 'static) -> Result<(), Error> {
+    let mut writers = WRITERS.write().unwrap();
+    let taken = writers
+        .iter()
+        .any(|w| w.name().eq_ignore_ascii_case(writer.name()));
+    check_name(writer.name(), taken)?;
+    writers.push(Arc::new(writer));
+    Ok(())
+}
+
+pub(crate) fn reader(name: &str) -> Option<Arc<dyn FormatReader>> {
+    let readers = READERS.read().unwrap();
+    readers.iter().find(|r| r.name() == name).cloned()
+}
+
+pub(crate) fn writer(name: &str) -> Option<Arc<dyn FormatWriter>> {
+    let writers = WRITERS.read().unwrap();
+    writers.iter().find(|w| w.name() == name).cloned()
+}
+
+// registered name matching s, whatever its case
+pub(crate) fn find_name(s: &str) -> Option<&'static str> {
+    let readers = READERS.read().unwrap();
+    let writers = WRITERS.read().unwrap();
+    let names = readers.iter().map(|r| r.name());
+    names
+        .chain(writers.iter().map(|w| w.name()))
+        .find(|n| n.eq_ignore_ascii_case(s))
+}
+
+// first registered reader recognizing head
+pub(crate) fn detect(head: &[u8]) -> Option<&'static str> {
+    let readers = READERS.read().unwrap();
+    readers.iter().find(|r| r.detect(head)).map(|r| r.name())
+}
+
+// registered format for a file extension, readers first
+pub(crate) fn find_extension(ext: &str) -> Option<&'static str> {
+    let readers = READERS.read().unwrap();
+    let writers = WRITERS.read().unwrap();
+    let by_reader = readers.iter().map(|r| (r.name(), r.extensions()));
+    by_reader
+        .chain(writers.iter().map(|w| (w.name(), w.extensions())))
+        .find(|(_, exts)| exts.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+        .map(|(name, _)| name)
+}
+
+pub(crate) fn extension(name: &str) -> &'static str {
+    let ext = reader(name)
+        .and_then(|r| r.extensions().first().copied())
+        .or_else(|| writer(name)?.extensions().first().copied());
+    ext.unwrap_or("")
+}
+
+impl Format {
+    // built in formats then registered ones in the order they were registered
+    pub fn all() -> Vec<Format> {
+        let mut ret = BUILTIN.to_vec();
+        let readers = READERS.read().unwrap();
+        let writers = WRITERS.read().unwrap();
+        for name in readers
+            .iter()
+            .map(|r| r.name())
+            .chain(writers.iter().map(|w| w.name()))
+        {
+            if !ret.contains(&Format::Plugin(name)) {
+                ret.push(Format::Plugin(name));
+            }
+        }
+        ret
+    }
+
+    pub fn can_read(&self) -> bool {
+        match self {
+            Format::NCF => false,
+            Format::Plugin(name) => reader(name).is_some(),
+            _ => true,
+        }
+    }
+
+    pub fn can_write(&self) -> bool {
+        match self {
+            Format::NCF => false,
+            Format::Plugin(name) => writer(name).is_some(),
+            _ => true,
+        }
+    }
+
+    // every extension from_path maps to it
+    pub fn extensions(&self) -> Vec<&'static str> {
+        let Format::Plugin(name) = self else {
+            return vec![self.extension()];
+        };
+        let mut ret: Vec<&'static str> = Vec::new();
+        let readers = reader(name).map(|r| r.extensions().to_vec());
+        let writers = writer(name).map(|w| w.extensions().to_vec());
+        for ext in readers.into_iter().chain(writers).flatten() {
+            if !ret.contains(&ext) {
+                ret.push(ext);
+            }
+        }
+        ret
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Plugin(name) => name,
+            _ => self.extension(),
+        }
+    }
+}
@@ -1,9 +1,158 @@
+mod analysis {
+    pub mod capability;
+    pub mod compare;
+    pub mod diff;
+    pub mod query;
+    pub mod schedule;
+    pub mod validate;
+}
+
+mod codegen {
+    pub mod c;
+}
+
+mod codec {
+    pub mod frozen;
+    pub mod monitor;
+    #[cfg(feature = "parquet")]
+    pub mod parquet;
+    pub mod payload;
+    pub mod plan;
+    pub mod replay;
+    #[cfg(all(feature = "socketcan", target_os = "linux"))]
+    pub mod socketcan;
+    #[cfg(feature = "tokio")]
+    pub mod stream;
+    pub mod traffic;
+    pub mod vectors;
+}
+
+mod convert {
+    pub mod cache;
+    pub mod compressed;
+    pub mod format;
+    pub mod plugin;
+}
+
 mod parsers {
+    pub mod dbc;
     pub mod encoding;
     pub mod error;
+    pub mod ids;
+    pub mod ldf;
+}
+
+mod project {
+    pub mod manifest;
+    pub mod network;
+    pub mod routing;
+}
+
+mod server {
+    #[cfg(feature = "server")]
+    pub mod http;
+    #[cfg(feature = "lsp")]
+    pub mod lsp;
+}
+
+mod transform {
+    pub mod edit;
+    pub mod filter;
+    pub mod merge;
+    pub mod migrate;
+    pub mod pack;
+    pub mod pipeline;
+    pub mod postfix;
+    pub mod rename;
+    pub mod skeleton;
+    pub mod sort;
+    pub mod units;
+    pub mod visit;
+}
+
+mod writers {
+    pub mod dbc;
+    pub mod fmt;
+    pub mod layout;
     pub mod ldf;
 }
 
-pub use crate::parsers::encoding::Database;
-pub use crate::parsers::error::Error;
-pub use crate::parsers::ldf::parse_ldf;
+pub mod testing;
+
+pub use crate::analysis::capability::{BitrateCapability, NodeCapability};
+pub use crate::analysis::compare::{CompareOptions, LogComparison, LogDifference};
+pub use crate::analysis::diff::{diff, Change, ChangeKind, DatabaseDiff, EntityKind, FieldChange};
+pub use crate::analysis::query::SignalMatch;
+pub use crate::analysis::schedule::{
+    lin_frame_time, lin_pid, FramePeriod, ScheduleCoverage, ScheduleSlot, ScheduleTicks, TickSlot,
+};
+pub use crate::analysis::validate::Overlap;
+pub use crate::codec::frozen::FrozenDatabase;
+pub use crate::codec::monitor::{LinSniffer, SignalMonitor, SignalState};
+#[cfg(feature = "parquet")]
+pub use crate::codec::parquet::{write_series_parquet, PARQUET_ROW_GROUP_ROWS};
+pub use crate::codec::payload::{parse_hex, DecodedSignal, SignalValue, ValueVerdict};
+pub use crate::codec::plan::DecodePlan;
+pub use crate::codec::replay::{
+    parse_asc, parse_blf, parse_candump, write_series_csv, LogFormat, LogFrame, SeriesOptions,
+    SignalSeries,
+};
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+pub use crate::codec::socketcan::SocketCan;
+#[cfg(feature = "tokio")]
+pub use crate::codec::stream::FrameStream;
+pub use crate::codec::traffic::{
+    write_asc, write_candump, TrafficFrame, TrafficOptions, ValuePattern,
+};
+pub use crate::codec::vectors::{TestVector, VectorSignal};
+pub use crate::codegen::c::{
+    generate_c, generate_c_with, generate_routing_c, generate_schedule_c, generate_vectors_c,
+};
+pub use crate::convert::compressed::{is_compressed, open_decompressed};
+pub use crate::convert::format::{parse, xml_root, Format, ParseInput, WriteOptions};
+pub use crate::convert::plugin::{register_reader, register_writer, FormatReader, FormatWriter};
+#[cfg(feature = "tokio")]
+pub use crate::parsers::dbc::parse_dbc_reader_async;
+pub use crate::parsers::dbc::{
+    parse_dbc, parse_dbc_reader, parse_dbc_reader_with, parse_dbc_reader_with_warnings,
+    parse_dbc_str, parse_dbc_str_with,
+};
+pub use crate::parsers::encoding::{
+    AttributeValue, ChecksumModel, DBCData, Database, DatabaseType, Encoding, FrameProtocol,
+    LDFData, LDFScheduleCommand, LINResponderData, Message, Name, Node, NodeRole, Provenance,
+    SNPDStep, SendType, Signal, SignalGroup, ValueKind, BIT_START_INVALID, MAX_ARRAY_WIDTH,
+    MAX_SIGNAL_WIDTH,
+};
+pub use crate::parsers::error::{
+    DiagnosticSink, Error, LogSink, Span, SpanMap, Warning, WarningKind,
+};
+pub use crate::parsers::ids::{BitPos, BitWidth, FrameId, Nad, Pid};
+#[cfg(feature = "mmap")]
+pub use crate::parsers::ldf::parse_ldf_mmap;
+#[cfg(feature = "tokio")]
+pub use crate::parsers::ldf::parse_ldf_reader_async;
+pub use crate::parsers::ldf::{
+    ldf_sections, parse_ldf, parse_ldf_reader, parse_ldf_reader_with,
+    parse_ldf_reader_with_warnings, parse_ldf_sections, parse_ldf_str, parse_ldf_str_with,
+    parse_ldf_str_with_spans,
+};
+pub use crate::project::manifest::{
+    Accessors, BuildReport, CodegenLang, CodegenOptions, NameCase, Project, Severity, Target,
+    ValidationOptions, MANIFEST_NAME,
+};
+pub use crate::project::network::{Bus, NetworkProject};
+pub use crate::project::routing::{Route, RouteEndpoint, RoutingEntry, SignalRef};
+#[cfg(feature = "server")]
+pub use crate::server::http::{http_router, serve_http};
+#[cfg(feature = "lsp")]
+pub use crate::server::lsp::serve_lsp_stdio;
+pub use crate::transform::filter::Filter;
+pub use crate::transform::pack::PackOptions;
+pub use crate::transform::pipeline::{Pipeline, Step};
+pub use crate::transform::skeleton::SkeletonOptions;
+pub use crate::transform::units::{Quantity, Unit};
+pub use crate::transform::visit::{walk, walk_mut, Mux, SignalSite, Visitor, VisitorMut};
+pub use crate::writers::dbc::{write_dbc, write_dbc_string, write_dbc_writer};
+pub use crate::writers::fmt::{format_ldf, rename_ldf};
+pub use crate::writers::layout::{render_layout, render_layout_svg};
+pub use crate::writers::ldf::{write_ldf, write_ldf_string, write_ldf_writer};
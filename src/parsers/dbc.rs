@@ -0,0 +1,781 @@
+use crate::convert::compressed::open_decompressed;
+use crate::parsers::encoding::{
+    AttributeValue, DBCData, DatabaseType, Encoding, FrameProtocol, Message, Name, Provenance,
+    SendType, Signal, SignalGroup, ValueKind, MAX_SIGNAL_WIDTH,
+};
+use crate::parsers::error::{DiagnosticSink, LogSink, Span, Warning, WarningKind};
+use crate::parsers::ids::FrameId;
+use crate::parsers::ldf::parse_char_string;
+use crate::writers::dbc::TYPED_ATTRIBUTES;
+use crate::{Database, Error};
+use log::debug;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+
+const NO_NODE: &str = "Vector__XXX";
+
+pub fn parse_dbc(dbc: impl AsRef<Path>) -> Result<Database, Error> {
+    let (reader, _) = open_decompressed(&dbc)?;
+    Ok(parse_dbc_reader(reader)?.with_source(dbc.as_ref()))
+}
+
+pub fn parse_dbc_reader(mut reader: impl Read) -> Result<Database, Error> {
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+    parse_dbc_str(&data)
+}
+
+// reads everything before parsing, which doesn't wait on anything
+#[cfg(feature = "tokio")]
+pub async fn parse_dbc_reader_async(
+    mut reader: impl tokio::io::AsyncRead + Unpin,
+) -> Result<Database, Error> {
+    let mut data = Vec::new();
+    tokio::io::AsyncReadExt::read_to_end(&mut reader, &mut data).await?;
+    parse_dbc_reader(data.as_slice())
+}
+
+// reports what was ignored or renamed along the way to sink
+pub fn parse_dbc_reader_with(
+    mut reader: impl Read,
+    sink: &mut dyn DiagnosticSink,
+) -> Result<Database, Error> {
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+    parse_dbc_str_with(&data, sink)
+}
+
+pub fn parse_dbc_reader_with_warnings(
+    reader: impl Read,
+) -> Result<(Database, Vec<Warning>), Error> {
+    let mut warnings = Vec::new();
+    let db = parse_dbc_reader_with(reader, &mut |w| warnings.push(w))?;
+    Ok((db, warnings))
+}
+
+// statements are one per line, except char_strings which can span several
+fn statements(src: &str) -> Vec<(usize, &str)> {
+    let mut ret = Vec::new();
+    let mut start = 0;
+    let (mut quoted, mut escaped) = (false, false);
+    let mut index = 0;
+    for line in src.split_inclusive('\n') {
+        for c in line.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                _ => (),
+            }
+        }
+        index += line.len();
+        if !quoted {
+            ret.push((start, src[start..index].trim_end()));
+            start = index;
+        }
+    }
+    if start < src.len() {
+        ret.push((start, src[start..].trim_end()));
+    }
+    ret
+}
+
+// end of a char string's contents, s starts after the opening quote
+fn char_string_end(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    s.char_indices()
+        .find(|(_, c)| match c {
+            _ if escaped => {
+                escaped = false;
+                false
+            }
+            '\\' => {
+                escaped = true;
+                false
+            }
+            c => *c == '"',
+        })
+        .map(|(i, _)| i)
+}
+
+// value and label pairs of a VAL_ statement, labels unescaped
+fn value_descriptions(mut s: &str) -> Result<Vec<(&str, String)>, Error> {
+    let mut ret = Vec::new();
+    while let Some((value, rest)) = s.split_once('"') {
+        let end = char_string_end(rest).ok_or(Error::ExpectedToken)?;
+        ret.push((value, parse_char_string(&rest[..end])));
+        s = &rest[end + 1..];
+    }
+    Ok(ret)
+}
+
+fn skip_words(mut s: &str, n: usize) -> &str {
+    for _ in 0..n {
+        s = s.trim_start();
+        s = &s[s.find(char::is_whitespace).unwrap_or(s.len())..];
+    }
+    s
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T, Error> {
+    let s = s.trim();
+    s.parse()
+        .map_err(|_| Error::Hint(format!("got {}", s), Box::new(Error::NumberParse)))
+}
+
+// value of a BA_ statement, typed by its BA_DEF_, enums through label()
+fn attribute_value(
+    kind: Option<&String>,
+    value: &str,
+    label: impl Fn(&str) -> Result<String, Error>,
+) -> Result<AttributeValue, Error> {
+    let value = value.trim();
+    Ok(match kind.map(|k| k.as_str()) {
+        Some("ENUM") => AttributeValue::Enum(label(value)?),
+        Some("INT" | "HEX") => AttributeValue::Int(parse_number(value)?),
+        Some("FLOAT") => AttributeValue::Float(parse_number(value)?),
+        _ if value.starts_with('"') => AttributeValue::String(parse_char_string(value)),
+        // no BA_DEF_, guess from the value
+        _ => match parse_number(value) {
+            Ok(v) => AttributeValue::Int(v),
+            Err(_) => AttributeValue::Float(parse_number(value)?),
+        },
+    })
+}
+
+// enum values are indices into the BA_DEF_ labels, some tools write the label itself
+fn enum_label(labels: Option<&Vec<String>>, value: &str) -> Result<String, Error> {
+    if value.starts_with('"') {
+        return Ok(parse_char_string(value));
+    }
+    let index: usize = parse_number(value)?;
+    labels
+        .and_then(|labels| labels.get(index))
+        .cloned()
+        .ok_or(Error::ValueOutOfRange)
+}
+
+// quoted name of a BA_DEF_ or BA_ statement and what follows it
+fn attribute_name(stmt: &str) -> Result<(&str, &str), Error> {
+    let (_, rest) = stmt.split_once('"').ok_or(Error::ExpectedToken)?;
+    rest.split_once('"').ok_or(Error::ExpectedToken)
+}
+
+// mux indicator of a signal, "M" for the multiplexor and "m<value>" for multiplexed signals, "m<value>M" ones
+// multiplex others in turn, given by SG_MUL_VAL_
+enum Mux {
+    None,
+    Multiplexor,
+    Multiplexed(u64),
+}
+
+// SG_ name [mux] : start|width@order sign (scale,offset) [min|max] "unit" receivers
+fn parse_signal(stmt: &str) -> Result<(&str, Mux, Signal, Vec<&str>), Error> {
+    let (left, right) = stmt.split_once(':').ok_or(Error::ExpectedToken)?;
+    let mut left = left.split_whitespace().skip(1); // "SG_"
+    let name = left.next().ok_or(Error::ExpectedToken)?;
+    let mux = match left.next() {
+        None => Mux::None,
+        Some("M") => Mux::Multiplexor,
+        Some(m) => {
+            let value = m
+                .strip_prefix('m')
+                .map(|v| v.trim_end_matches('M'))
+                .ok_or(Error::UnexpectedToken)?;
+            Mux::Multiplexed(parse_number(value)?)
+        }
+    };
+
+    let right = right.trim_start();
+    let (layout, rest) = right
+        .split_once(char::is_whitespace)
+        .ok_or(Error::ExpectedToken)?;
+    let (bit_start, rest_layout) = layout.split_once('|').ok_or(Error::UnexpectedToken)?;
+    let (bit_width, order) = rest_layout.split_once('@').ok_or(Error::UnexpectedToken)?;
+    let little_endian = match order.get(..1) {
+        Some("1") => true,
+        Some("0") => false,
+        _ => return Err(Error::UnexpectedToken),
+    };
+    let kind = match order.get(1..) {
+        Some("+") => ValueKind::Unsigned,
+        Some("-") => ValueKind::Signed,
+        _ => return Err(Error::UnexpectedToken),
+    };
+    let (factor, rest) = rest
+        .trim_start()
+        .strip_prefix('(')
+        .and_then(|r| r.split_once(')'))
+        .ok_or(Error::ExpectedToken)?;
+    let (scale, offset) = factor.split_once(',').ok_or(Error::ExpectedToken)?;
+    let (limits, rest) = rest
+        .trim_start()
+        .strip_prefix('[')
+        .and_then(|r| r.split_once(']'))
+        .ok_or(Error::ExpectedToken)?;
+    let rest = rest
+        .trim_start()
+        .strip_prefix('"')
+        .ok_or(Error::ExpectedToken)?;
+    let end = char_string_end(rest).ok_or(Error::ExpectedToken)?;
+    let (unit, rest) = (parse_char_string(&rest[..end]), &rest[end + 1..]);
+    let receivers = rest
+        .split([',', ' ', '\t'])
+        .filter(|r| !r.is_empty() && *r != NO_NODE)
+        .collect();
+
+    let mut signal = Signal {
+        kind,
+        little_endian,
+        bit_start: parse_number(bit_start)?,
+        bit_width: parse_number(bit_width)?,
+        init_value: 0,
+        encodings: None,
+        physical_limits: None,
+        description: None,
+        attributes: HashMap::new(),
+    };
+    if signal.bit_width == 0 || signal.bit_width > MAX_SIGNAL_WIDTH {
+        return Err(Error::SignalTooWide);
+    }
+    let (scale, offset): (f64, f64) = (parse_number(scale)?, parse_number(offset)?);
+    if scale != 1.0 || offset != 0.0 || !unit.is_empty() {
+        signal.encodings = Some(vec![Encoding::Scalar {
+            raw_min: 0,
+            raw_max: signal.max_raw(),
+            scale,
+            offset,
+            unit,
+            description: None,
+        }]);
+    }
+    // [0|0] is the usual way of leaving them out, limits matching the raw range add nothing
+    let (min, max) = limits.split_once('|').ok_or(Error::ExpectedToken)?;
+    let (min, max): (f64, f64) = (parse_number(min)?, parse_number(max)?);
+    let (lo, hi) = signal.raw_span(0, signal.max_raw());
+    let (lo, hi) = (lo * scale + offset, hi * scale + offset);
+    if (min != 0.0 || max != 0.0) && (min, max) != (lo.min(hi), lo.max(hi)) {
+        signal.physical_limits = Some((min, max));
+    }
+    Ok((name, mux, signal, receivers))
+}
+
+pub fn parse_dbc_str(dbc: &str) -> Result<Database, Error> {
+    parse_dbc_str_with(dbc, &mut LogSink)
+}
+
+pub fn parse_dbc_str_with(dbc: &str, sink: &mut dyn DiagnosticSink) -> Result<Database, Error> {
+    parse(dbc, sink).inspect_err(|e| sink.error(e))
+}
+
+fn parse(dbc: &str, sink: &mut dyn DiagnosticSink) -> Result<Database, Error> {
+    let mut db: Database = Default::default();
+    let mut data: DBCData = Default::default();
+    let mut message: Option<(u32, Name)> = None;
+    let mut names: HashMap<(u32, &str), Name> = HashMap::new(); // (message id, dbc name) -> signal
+    let mut multiplexors: HashMap<Name, Vec<Name>> = HashMap::new(); // message -> signals, one unless invalid
+    let mut multiplexed: HashMap<Name, Vec<(Name, u64, Span)>> = HashMap::new(); // message -> signals
+    let mut extended = Vec::new(); // (message id, [signal, multiplexor], values) from SG_MUL_VAL_
+    let mut enums: HashMap<String, Vec<String>> = HashMap::new(); // attribute -> enum labels
+    let mut types: HashMap<String, String> = HashMap::new(); // attribute -> INT, HEX, FLOAT, STRING or ENUM
+    let mut objects: HashMap<String, String> = HashMap::new(); // attribute -> BU_, BO_, SG_ or "" for the network
+    let mut formats: HashMap<u32, FrameProtocol> = HashMap::new(); // message id -> VFrameFormat
+    let mut j1939 = false; // ProtocolType, extended frames are parameter groups by default
+    let mut start_values: Vec<(Name, f64)> = Vec::new(); // applied once value kinds are known
+    let mut version = None;
+    let mut in_ns = false;
+
+    for (start, stmt) in statements(dbc) {
+        let span = Span {
+            start,
+            end: start + stmt.len(),
+        };
+        let located = |e: Error| Error::Located(span, Box::new(e));
+        // NS_ lists symbol names on indented lines
+        if in_ns && (stmt.is_empty() || stmt.starts_with(char::is_whitespace)) {
+            continue;
+        }
+        in_ns = false;
+        let keyword = stmt.split_whitespace().next().unwrap_or_default();
+        match keyword.trim_end_matches(':') {
+            "" => (),
+            "NS_" => in_ns = true,
+            "BU_" => {
+                let (_, nodes) = stmt.split_once(':').ok_or(located(Error::ExpectedToken))?;
+                data.nodes = nodes.split_whitespace().map(Name::from).collect();
+            }
+            "BO_" => {
+                let (left, right) = stmt.split_once(':').ok_or(located(Error::ExpectedToken))?;
+                let mut left = left.split_whitespace().skip(1);
+                let mut right = right.split_whitespace();
+                let (Some(id), Some(name), Some(dlc), Some(sender)) =
+                    (left.next(), left.next(), right.next(), right.next())
+                else {
+                    return Err(located(Error::ExpectedToken));
+                };
+                let id = parse_number(id).map_err(located)?;
+                let name = Name::from(name);
+                if db.messages.contains_key(&name) {
+                    return Err(located(Error::DuplicateFrame));
+                }
+                db.messages.insert(
+                    name.clone(),
+                    Message {
+                        sender: if sender == NO_NODE { "" } else { sender }.into(),
+                        id,
+                        byte_width: parse_number(dlc).map_err(located)?,
+                        signals: Vec::new(),
+                        mux_signals: HashMap::new(),
+                        checksum: None,
+                        cycle_time: None,
+                        send_type: None,
+                        protocol: Default::default(),
+                        description: None,
+                        attributes: HashMap::new(),
+                        signal_groups: Default::default(),
+                    },
+                );
+                message = Some((id, name));
+            }
+            "SG_" => {
+                let (id, msg) = message.as_ref().ok_or(located(Error::UnknownFrame))?;
+                let (dbc_name, mux, signal, receivers) = parse_signal(stmt).map_err(located)?;
+                // signal names are only unique per message in DBC
+                let mut name = Name::from(dbc_name);
+                if db.signals.contains_key(&name) {
+                    name = format!("{}_{}", msg, dbc_name).into();
+                    let message = format!("signal {} is not unique, renamed to {}", dbc_name, name);
+                    sink.warning(Warning::new(WarningKind::Renamed, span, message));
+                    if db.signals.contains_key(&name) {
+                        return Err(located(Error::DuplicateSignal));
+                    }
+                }
+                match mux {
+                    Mux::Multiplexed(value) => multiplexed.entry(msg.clone()).or_default().push((
+                        name.clone(),
+                        value,
+                        span,
+                    )),
+                    Mux::Multiplexor => {
+                        multiplexors
+                            .entry(msg.clone())
+                            .or_default()
+                            .push(name.clone());
+                        db.messages.get_mut(msg).unwrap().signals.push(name.clone());
+                    }
+                    Mux::None => db.messages.get_mut(msg).unwrap().signals.push(name.clone()),
+                }
+                names.insert((*id, dbc_name), name.clone());
+                data.receivers.insert(
+                    name.clone(),
+                    receivers.into_iter().map(Name::from).collect(),
+                );
+                db.signals.insert(name, signal);
+            }
+            "VAL_" => {
+                let mut tokens = stmt.split_whitespace().skip(1);
+                let (Some(id), Some(signal)) = (tokens.next(), tokens.next()) else {
+                    return Err(located(Error::ExpectedToken));
+                };
+                let Ok(id) = id.parse::<u32>() else {
+                    let message = format!("ignoring environment variable values of {}", id);
+                    sink.warning(Warning::new(WarningKind::UnsupportedSection, span, message));
+                    continue;
+                };
+                let name = names
+                    .get(&(id, signal))
+                    .ok_or(located(Error::UnknownSignal))?
+                    .clone();
+                let signal = db.signals.get_mut(&name).unwrap();
+                let mask = signal.max_raw();
+
+                // pairs of value and description
+                let rest = skip_words(stmt, 3).trim().trim_end_matches(';');
+                let mut map = HashMap::new();
+                let mut rev_map = HashMap::new();
+                for (value, label) in value_descriptions(rest).map_err(located)? {
+                    let value = parse_number::<i64>(value).map_err(located)? as u64 & mask;
+                    map.insert(label.clone(), value);
+                    rev_map.insert(value, label);
+                }
+                signal
+                    .encodings
+                    .get_or_insert_with(Vec::new)
+                    .push(Encoding::Enum {
+                        name: format!("{}_Encoding", name),
+                        map,
+                        rev_map,
+                        description: None,
+                    });
+            }
+            "SIG_VALTYPE_" => {
+                // SIG_VALTYPE_ id signal : 1;
+                let mut tokens = stmt
+                    .split_whitespace()
+                    .skip(1)
+                    .filter(|t| *t != ":" && *t != ";");
+                let (Some(id), Some(signal), Some(kind)) =
+                    (tokens.next(), tokens.next(), tokens.next())
+                else {
+                    return Err(located(Error::ExpectedToken));
+                };
+                let id = parse_number::<u32>(id).map_err(located)?;
+                let name = names
+                    .get(&(id, signal))
+                    .ok_or(located(Error::UnknownSignal))?;
+                let signal = db.signals.get_mut(name).unwrap();
+                signal.kind = match kind.trim_start_matches(':').trim_end_matches(';') {
+                    "0" => continue, // integer
+                    "1" => ValueKind::Float32,
+                    "2" => ValueKind::Float64,
+                    _ => return Err(located(Error::UnexpectedToken)),
+                };
+
+                // floats always have a physical value, even unscaled
+                let raw_max = signal.max_raw();
+                let encodings = signal.encodings.get_or_insert_with(Vec::new);
+                if !encodings
+                    .iter()
+                    .any(|e| matches!(e, Encoding::Scalar { .. }))
+                {
+                    encodings.push(Encoding::Scalar {
+                        raw_min: 0,
+                        raw_max,
+                        scale: 1.0,
+                        offset: 0.0,
+                        unit: String::new(),
+                        description: None,
+                    });
+                }
+            }
+            "BA_DEF_" => {
+                // BA_DEF_ [object] "name" type [min max | "label",...];
+                let (name, rest) = attribute_name(stmt).map_err(located)?;
+                let labels = value_descriptions(rest).map_err(located)?;
+                enums.insert(
+                    name.to_string(),
+                    labels.into_iter().map(|(_, l)| l).collect(),
+                );
+                let kind = rest.split_whitespace().next().unwrap_or_default();
+                types.insert(name.to_string(), kind.trim_end_matches(';').to_string());
+                let object = stmt.split('"').next().unwrap_or_default();
+                let object = object.split_whitespace().nth(1).unwrap_or_default();
+                objects.insert(name.to_string(), object.to_string());
+            }
+            "BA_DEF_DEF_" => {
+                // BA_DEF_DEF_ "name" value; typed attributes get theirs from the writer
+                let (name, rest) = attribute_name(stmt).map_err(located)?;
+                if TYPED_ATTRIBUTES.contains(&name) {
+                    continue;
+                }
+                let rest = rest.trim().trim_end_matches(';');
+                let label = |value: &str| enum_label(enums.get(name), value);
+                let value = attribute_value(types.get(name), rest, label).map_err(located)?;
+                let object = objects.get(name).cloned().unwrap_or_default();
+                data.attribute_defaults
+                    .insert(name.to_string(), (object, value));
+            }
+            "BA_" => {
+                // BA_ "name" [object] value;
+                let (name, rest) = attribute_name(stmt).map_err(located)?;
+                let rest = rest.trim().trim_end_matches(';').trim_end();
+                let mut tokens = rest.split_whitespace();
+                let label = |value: &str| enum_label(enums.get(name), value);
+                match (name, tokens.next()) {
+                    ("ProtocolType", Some(value)) => j1939 = value.trim_matches('"') == "J1939",
+                    ("GenMsgCycleTime" | "GenMsgSendType" | "VFrameFormat", Some("BO_")) => {
+                        let id = tokens.next().ok_or(located(Error::ExpectedToken))?;
+                        let id = parse_number::<u32>(id).map_err(located)?;
+                        let value = skip_words(rest, 2).trim();
+                        let msg = db
+                            .messages
+                            .values_mut()
+                            .find(|m| m.id == id)
+                            .ok_or(located(Error::UnknownFrame))?;
+                        // kept as written too, the writer emits the typed fields instead
+                        let attribute =
+                            attribute_value(types.get(name), value, label).map_err(located)?;
+                        msg.attributes.insert(name.to_string(), attribute);
+                        match name {
+                            "GenMsgCycleTime" => {
+                                let period: f64 = parse_number(value).map_err(located)?;
+                                msg.cycle_time = (period > 0.0).then_some(period);
+                                // 0 for none
+                            }
+                            "GenMsgSendType" => {
+                                msg.send_type =
+                                    Some(SendType::from_label(&label(value).map_err(located)?));
+                            }
+                            _ => {
+                                let label = label(value).map_err(located)?;
+                                match FrameProtocol::from_label(&label) {
+                                    Some(protocol) => {
+                                        formats.insert(id, protocol);
+                                    }
+                                    None => sink.warning(Warning::new(
+                                        WarningKind::UnsupportedAttribute,
+                                        span,
+                                        format!("frame format {} not supported, ignoring", label),
+                                    )),
+                                }
+                            }
+                        }
+                    }
+                    ("GenSigStartValue", Some("SG_")) => {
+                        let (Some(id), Some(signal), Some(value)) =
+                            (tokens.next(), tokens.next(), tokens.next())
+                        else {
+                            return Err(located(Error::ExpectedToken));
+                        };
+                        let id = parse_number::<u32>(id).map_err(located)?;
+                        let sig = names
+                            .get(&(id, signal))
+                            .ok_or(located(Error::UnknownSignal))?;
+                        let attribute =
+                            attribute_value(types.get(name), value, label).map_err(located)?;
+                        let signal = db.signals.get_mut(sig).unwrap();
+                        signal.attributes.insert(name.to_string(), attribute);
+                        start_values.push((sig.clone(), parse_number(value).map_err(located)?));
+                    }
+                    (_, Some("BO_")) => {
+                        let id = tokens.next().ok_or(located(Error::ExpectedToken))?;
+                        let id = parse_number::<u32>(id).map_err(located)?;
+                        let value = attribute_value(types.get(name), skip_words(rest, 2), label)
+                            .map_err(located)?;
+                        let msg = db
+                            .messages
+                            .values_mut()
+                            .find(|m| m.id == id)
+                            .ok_or(located(Error::UnknownFrame))?;
+                        msg.attributes.insert(name.to_string(), value);
+                    }
+                    (_, Some("SG_")) => {
+                        let (Some(id), Some(signal)) = (tokens.next(), tokens.next()) else {
+                            return Err(located(Error::ExpectedToken));
+                        };
+                        let id = parse_number::<u32>(id).map_err(located)?;
+                        let value = attribute_value(types.get(name), skip_words(rest, 3), label)
+                            .map_err(located)?;
+                        let sig = names
+                            .get(&(id, signal))
+                            .ok_or(located(Error::UnknownSignal))?;
+                        let signal = db.signals.get_mut(sig).unwrap();
+                        signal.attributes.insert(name.to_string(), value);
+                    }
+                    (_, Some("BU_")) => {
+                        let node = tokens.next().ok_or(located(Error::ExpectedToken))?;
+                        let value = attribute_value(types.get(name), skip_words(rest, 2), label)
+                            .map_err(located)?;
+                        if !data.nodes.iter().any(|n| **n == *node) {
+                            return Err(located(Error::UnknownNode));
+                        }
+                        data.node_attributes
+                            .entry(node.into())
+                            .or_default()
+                            .insert(name.to_string(), value);
+                    }
+                    (_, Some("EV_")) | (_, None) => sink.warning(Warning::new(
+                        WarningKind::UnsupportedAttribute,
+                        span,
+                        format!("attribute {} not supported yet, ignoring", name),
+                    )),
+                    (_, Some(_)) => {
+                        let value =
+                            attribute_value(types.get(name), rest, label).map_err(located)?;
+                        db.attributes.insert(name.to_string(), value);
+                    }
+                }
+            }
+            "VERSION" => {
+                let (_, rest) = stmt.split_once('"').ok_or(located(Error::ExpectedToken))?;
+                let (text, _) = rest.rsplit_once('"').ok_or(located(Error::ExpectedToken))?;
+                version = Some(text.to_string()).filter(|v| !v.is_empty());
+            }
+            // nothing to carry over
+            "BS_" => debug!("ignoring {}", keyword),
+            "CM_" => {
+                // CM_ [BU_ node | BO_ id | SG_ id signal] "text";
+                let (head, rest) = stmt.split_once('"').ok_or(located(Error::ExpectedToken))?;
+                let (text, _) = rest.rsplit_once('"').ok_or(located(Error::ExpectedToken))?;
+                let text = parse_char_string(text);
+                let mut tokens = head.split_whitespace().skip(1);
+                match (tokens.next(), tokens.next(), tokens.next()) {
+                    (Some("BU_"), Some(node), _) => {
+                        if !data.nodes.iter().any(|n| **n == *node) {
+                            return Err(located(Error::UnknownNode));
+                        }
+                        data.node_descriptions.insert(node.into(), text);
+                    }
+                    (Some("BO_"), Some(id), _) => {
+                        let id = parse_number::<u32>(id).map_err(located)?;
+                        let msg = db
+                            .messages
+                            .values_mut()
+                            .find(|m| m.id == id)
+                            .ok_or(located(Error::UnknownFrame))?;
+                        msg.description = Some(text);
+                    }
+                    (Some("SG_"), Some(id), Some(signal)) => {
+                        let id = parse_number::<u32>(id).map_err(located)?;
+                        let name = names
+                            .get(&(id, signal))
+                            .ok_or(located(Error::UnknownSignal))?;
+                        db.signals.get_mut(name).unwrap().description = Some(text);
+                    }
+                    (object, ..) => {
+                        let message = match object {
+                            Some(object) => {
+                                format!("{} comments not supported yet, ignoring", object)
+                            }
+                            None => "network comment not supported yet, ignoring".to_string(),
+                        };
+                        sink.warning(Warning::new(WarningKind::UnsupportedSection, span, message));
+                    }
+                }
+            }
+            "SIG_GROUP_" => {
+                // SIG_GROUP_ id name repetitions : signal ...;
+                let (left, right) = stmt.split_once(':').ok_or(located(Error::ExpectedToken))?;
+                let mut left = left.split_whitespace().skip(1);
+                let (Some(id), Some(group), Some(repetitions)) =
+                    (left.next(), left.next(), left.next())
+                else {
+                    return Err(located(Error::ExpectedToken));
+                };
+                let id = parse_number::<u32>(id).map_err(located)?;
+                let repetitions = parse_number(repetitions).map_err(located)?;
+                let signals = right
+                    .trim_end()
+                    .trim_end_matches(';')
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| names.get(&(id, s)).cloned().ok_or(Error::UnknownSignal))
+                    .collect::<Result<_, _>>()
+                    .map_err(located)?;
+                let msg = db
+                    .messages
+                    .values_mut()
+                    .find(|m| m.id == id)
+                    .ok_or(located(Error::UnknownFrame))?;
+                msg.signal_groups.insert(
+                    group.into(),
+                    SignalGroup {
+                        signals,
+                        repetitions,
+                    },
+                );
+            }
+            "SG_MUL_VAL_" => {
+                // SG_MUL_VAL_ id signal multiplexor min-max, ...;
+                let mut tokens = stmt.trim_end_matches(';').split_whitespace().skip(1);
+                let (Some(id), Some(signal), Some(multiplexor)) =
+                    (tokens.next(), tokens.next(), tokens.next())
+                else {
+                    return Err(located(Error::ExpectedToken));
+                };
+                let id = parse_number::<u32>(id).map_err(located)?;
+                let name = |s| names.get(&(id, s)).cloned().ok_or(Error::UnknownSignal);
+                let (signal, multiplexor) = (name(signal), name(multiplexor));
+                let ranges = tokens
+                    .collect::<String>()
+                    .split(',')
+                    .filter(|r| !r.is_empty())
+                    .map(|r| {
+                        let (min, max) = r.split_once('-').ok_or(Error::UnexpectedToken)?;
+                        Ok((parse_number(min)?, parse_number(max)?))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+                    .map_err(located)?;
+                if ranges.is_empty() {
+                    return Err(located(Error::ExpectedToken));
+                }
+                let names = [signal.map_err(located)?, multiplexor.map_err(located)?];
+                extended.push((id, names, ranges));
+            }
+            "BA_DEF_REL_" | "BA_DEF_DEF_REL_" | "BA_REL_" | "VAL_TABLE_" | "BO_TX_BU_" | "EV_"
+            | "ENVVAR_DATA_" | "SGTYPE_" | "SIG_TYPE_REF_" | "BU_SG_REL_" | "BU_BO_REL_"
+            | "BU_EV_REL_" | "SGTYPE_VAL_" | "BA_DEF_SGTYPE_" | "CAT_DEF_" | "FILTER" => {
+                let message = format!("{} not supported yet, ignoring", keyword);
+                sink.warning(Warning::new(WarningKind::UnsupportedSection, span, message));
+            }
+            _ => return Err(located(Error::UnexpectedToken)),
+        }
+    }
+
+    // multiplexed signals refer to the message's multiplexor
+    for (msg, signals) in multiplexed {
+        let message = db.messages.get_mut(&msg).unwrap();
+        let Some(multiplexor) = multiplexors.get(&msg) else {
+            let (name, _, span) = &signals[0];
+            return Err(Error::Located(
+                *span,
+                Box::new(Error::Hint(
+                    format!("{} has no multiplexor in {}", name, msg),
+                    Box::new(Error::UnknownSignal),
+                )),
+            ));
+        };
+        for (name, value, _) in signals {
+            message
+                .mux_signals
+                .insert(name, (value, multiplexor.clone()));
+        }
+    }
+
+    // extended multiplexing names each signal's multiplexors, replacing the frame's
+    let mut named: HashSet<Name> = HashSet::new();
+    for (id, [signal, multiplexor], ranges) in extended {
+        let (value, multiplexors) = db
+            .messages
+            .values_mut()
+            .find(|m| m.id == id)
+            .and_then(|m| m.mux_signals.get_mut(&signal))
+            .ok_or(Error::Hint(
+                signal.to_string(),
+                Box::new(Error::UnknownSignal),
+            ))?;
+        if named.insert(signal.clone()) {
+            *value = ranges[0].0;
+            multiplexors.clear();
+            data.mux_ranges.insert(signal, ranges);
+        }
+        multiplexors.push(multiplexor);
+    }
+
+    // frame formats follow the id unless given
+    for msg in db.messages.values_mut() {
+        let extended = FrameId::can(msg.id).is_ok_and(FrameId::is_extended);
+        msg.protocol = match formats.get(&msg.id) {
+            Some(protocol) => *protocol,
+            None if extended && j1939 => FrameProtocol::J1939PG,
+            None if extended => FrameProtocol::Extended,
+            None => FrameProtocol::Standard,
+        };
+    }
+
+    // raw values, floats are only known to be floats by now
+    for (name, value) in start_values {
+        let signal = db.signals.get_mut(&name).unwrap();
+        signal.init_value = signal.from_value(value).ok_or(Error::Hint(
+            format!("start value {} of {}", value, name),
+            Box::new(Error::ValueOutOfRange),
+        ))?;
+    }
+    // encoding descriptions, the value tables come after the attributes
+    for signal in db.signals.values_mut() {
+        for e in signal.encodings.iter_mut().flatten() {
+            let (key, description) = match e {
+                Encoding::Scalar { description, .. } => ("ScaleDescription", description),
+                Encoding::Enum { description, .. } => ("ValueTableDescription", description),
+                Encoding::Ascii => continue,
+            };
+            if let Some(AttributeValue::String(text)) = signal.attributes.get(key) {
+                *description = Some(text.clone());
+            }
+        }
+    }
+    db.extra = DatabaseType::DBC(data);
+    db.provenance = Some(Provenance::new("DBC", version));
+    Ok(db)
+}
@@ -1,9 +1,17 @@
+use crate::parsers::ids::FrameId;
+use crate::Error;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+
+// node, frame, signal and table names, shared so repeated references are cheap to clone and compare
+pub type Name = Arc<str>;
 
 pub const MAX_SIGNAL_WIDTH: u16 = 64;
+pub const MAX_ARRAY_WIDTH: u16 = 64 * 8; // byte arrays, a CAN FD payload
 pub const BIT_START_INVALID: u16 = u16::MAX;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Encoding {
     Scalar {
         raw_min: u64,
@@ -11,12 +19,28 @@ pub enum Encoding {
         scale: f64,
         offset: f64, // actual = scale * raw + offset
         unit: String,
+        #[serde(default)]
+        description: Option<String>,
     },
     Enum {
         name: String,
         map: HashMap<String, u64>,     // encoding
         rev_map: HashMap<u64, String>, // decoding
+        #[serde(default)]
+        description: Option<String>,
     },
+    Ascii, // byte array holding text
+}
+
+// how the raw bits of a signal are read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValueKind {
+    #[default]
+    Unsigned,
+    Signed,  // two's complement
+    Float32, // IEEE 754, 32 bits wide
+    Float64, // IEEE 754, 64 bits wide
+    ByteArray,
 }
 
 /*
@@ -27,41 +51,108 @@ pub enum Encoding {
  * Little-endian counts up as expected since bit_start encodes the LSB, but big-endian counts down in a sawtooth
  * pattern since bit_start encodes the MSB.
  */
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Signal {
-    pub signed: bool,
+    pub kind: ValueKind,
     pub little_endian: bool,
     pub bit_start: u16,
     pub bit_width: u16,
-    pub init_value: u64,
+    pub init_value: u64, // first 8 bytes of a byte array, byte 0 lowest
     pub encodings: Option<Vec<Encoding>>,
+    #[serde(default)]
+    pub physical_limits: Option<(f64, f64)>, // explicit min, max, e.g. from a DBC
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+// LIN checksum, classic covers the data only and enhanced also covers the PID
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChecksumModel {
+    Classic,
+    Enhanced,
+}
+
+// format specific metadata without a typed field, e.g. DBC attributes, kept so it survives conversion
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AttributeValue {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Enum(String), // label
+}
+
+// when a frame goes out, as in the DBC GenMsgSendType attribute
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SendType {
+    Cyclic,
+    Spontaneous,
+    CyclicIfActive,
+    CyclicAndSpontaneous,
+    IfActive,
+    NoSendType,
+    Other(String),
+}
+
+// CAN frame format, as in the DBC VFrameFormat attribute
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameProtocol {
+    #[default]
+    Standard,
+    Extended,
+    J1939PG, // extended id made of priority, parameter group number and source address
+}
+
+// signals of one frame a sender updates together, a receiver never sees some of them new and others stale
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignalGroup {
+    pub signals: Vec<Name>,
+    pub repetitions: u32, // as in DBC SIG_GROUP_, 1 otherwise
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Message {
-    pub sender: String,
+    pub sender: Name,
     pub id: u32,
     pub byte_width: u16,
-    pub signals: Vec<String>,
-    pub mux_signals: HashMap<String, (u64, Vec<String>)>,
+    pub signals: Vec<Name>,
+    pub mux_signals: HashMap<Name, (u64, Vec<Name>)>,
+    #[serde(default)]
+    pub checksum: Option<ChecksumModel>, // LIN only
+    #[serde(default)]
+    pub cycle_time: Option<f64>, // ms
+    #[serde(default)]
+    pub send_type: Option<SendType>,
+    #[serde(default)]
+    pub protocol: FrameProtocol, // CAN only
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub attributes: HashMap<String, AttributeValue>,
+    #[serde(default)]
+    pub signal_groups: HashMap<Name, SignalGroup>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct LINResponderData {
-    pub subscribed_signals: Vec<String>,
+    pub protocol: String,
+    pub subscribed_signals: Vec<Name>,
     pub configured_nad: u8,
     pub initial_nad: Option<u8>,
     pub product_id: Option<(u16, u16, u8)>, // supplier, function, variant
-    pub response_error: Option<String>,
-    pub configurable_frames: Vec<(String, Option<u16>)>,
+    pub response_error: Option<Name>,
+    pub configurable_frames: Vec<(Name, Option<u16>)>,
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum LDFScheduleCommand {
-    Frame(String),
+    Frame(Name),
     CommanderReq,
     ResponderResp,
-    AssignNAD(String),
+    AssignNAD(Name),
     ConditionalChangeNAD {
         nad: u8,
         id: u8,
@@ -71,51 +162,523 @@ pub enum LDFScheduleCommand {
         new_nad: u8,
     },
     DataDump {
-        name: String,
+        name: Name,
         data: [u8; 5], // D1-D5
     },
-    SaveConfiguration(String),
+    SaveConfiguration(Name),
     AssignFrameIdRange {
-        name: String,
+        name: Name,
         index: u8,
         pid: [u8; 4],
     },
     FreeFormat([u8; 8]),
     AssignFrameId {
-        node: String,
-        frame: String,
+        node: Name,
+        frame: Name,
+    },
+    // slave node position detection, an extension some transceivers' LDFs use for auto-addressing
+    AssignNADviaSNPD {
+        step: SNPDStep,
+        supplier: u16,
+        nad: Option<u8>, // the NAD assigned, only for AssignNAD steps
     },
 }
 
-#[derive(Debug, Default)]
+// subfunctions of Assign NAD via SNPD, in the order a commander sends them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SNPDStep {
+    Init,
+    AssignNAD,
+    Store,
+    Finish,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct LDFData {
     pub bitrate: f64, // bps
     pub postfix: String,
-    pub commander: String,
+    pub commander: Name,
     pub time_base: f64, // ms
     pub jitter: f64,    // ms
-    pub responders: HashMap<String, LINResponderData>,
-    pub sporadic_frames: HashMap<String, Vec<String>>,
-    pub event_frames: HashMap<String, (String, u32, Vec<String>)>, // collision resolver, id, list of frames
-    pub schedule_tables: HashMap<String, Vec<(LDFScheduleCommand, f64)>>, // command, delay in ms
+    pub responders: HashMap<Name, LINResponderData>,
+    pub sporadic_frames: HashMap<Name, Vec<Name>>, // in priority order
+    pub event_frames: HashMap<Name, (Name, u32, Vec<Name>)>, // collision resolver, id, list of frames
+    pub schedule_tables: HashMap<Name, Vec<(LDFScheduleCommand, f64)>>, // command, delay in ms
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DBCData {
+    pub nodes: Vec<Name>,
+    pub receivers: HashMap<Name, Vec<Name>>, // signal -> receiving nodes
+    #[serde(default)]
+    pub node_descriptions: HashMap<Name, String>,
+    #[serde(default)]
+    pub node_attributes: HashMap<Name, HashMap<String, AttributeValue>>,
+    #[serde(default)]
+    pub mux_ranges: HashMap<Name, Vec<(u64, u64)>>, // signal -> multiplexor values selecting it, from SG_MUL_VAL_
+    #[serde(default)]
+    pub attribute_defaults: HashMap<String, (String, AttributeValue)>, // attribute -> (BA_DEF_ object, BA_DEF_DEF_ value)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeRole {
+    Commander, // LIN
+    Responder, // LIN
+    Ecu,       // CAN, every node may send
 }
 
-#[derive(Debug)]
+// a node whatever the format, built by Database::nodes() from the format specific data
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub name: Name,
+    pub role: NodeRole,
+    pub tx_frames: Vec<Name>, // by id then name
+    pub rx_frames: Vec<Name>, // by id then name
+    pub description: Option<String>,
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum DatabaseType {
+    #[default]
     NCF,
     LDF(LDFData),
-    DBC,
+    DBC(DBCData),
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Database {
-    pub signals: HashMap<String, Signal>,
-    pub messages: HashMap<String, Message>,
+    pub signals: HashMap<Name, Signal>,
+    pub messages: HashMap<Name, Message>,
     pub extra: DatabaseType,
+    #[serde(default)]
+    pub provenance: Option<Provenance>, // set by the parsers
+    #[serde(default)]
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+// where a database came from, callers comparing databases from different parses leave it out
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub source: Option<String>, // path, if parsed from a file
+    pub format: String,
+    pub version: Option<String>, // as declared by the file, e.g. LIN_protocol_version
+    pub parsed_at: u64,          // seconds since the Unix epoch
+    pub tool_version: String,    // of autodbconv
+}
+
+impl Provenance {
+    pub(crate) fn new(format: &str, version: Option<String>) -> Provenance {
+        Provenance {
+            source: None,
+            format: format.to_string(),
+            version,
+            parsed_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    // parsed_at as RFC 3339 in UTC
+    pub fn timestamp(&self) -> String {
+        let (days, secs) = (self.parsed_at / 86400, self.parsed_at % 86400);
+
+        // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+        let z = days + 719468;
+        let era = z / 146097;
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + (month <= 2) as u64;
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
+
+impl std::fmt::Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "generated from {}",
+            self.source.as_deref().unwrap_or("<input>")
+        )?;
+        match &self.version {
+            Some(version) => write!(f, " ({} {})", self.format, version)?,
+            None => write!(f, " ({})", self.format)?,
+        }
+        write!(
+            f,
+            ", parsed {} by autodbconv {}",
+            self.timestamp(),
+            self.tool_version
+        )
+    }
+}
+
+impl Database {
+    // records the file the database was read from, for parsers given a reader, a JSON database keeps its own
+    pub fn with_source(mut self, path: &std::path::Path) -> Database {
+        if let Some(provenance) = &mut self.provenance {
+            provenance
+                .source
+                .get_or_insert_with(|| path.display().to_string());
+        }
+        self
+    }
+
+    // inclusive ranges of multiplexor values selecting a multiplexed signal, DBC's extended multiplexing can give
+    // several, otherwise it's the signal's one value
+    pub fn mux_ranges(&self, signal: &str, value: u64) -> Vec<(u64, u64)> {
+        match &self.extra {
+            DatabaseType::DBC(data) => data.mux_ranges.get(signal).cloned(),
+            _ => None,
+        }
+        .unwrap_or_else(|| vec![(value, value)])
+    }
+}
+
+impl LINResponderData {
+    // LIN 2.0 message id of a configurable frame
+    pub fn message_id(&self, frame: &str) -> Option<u16> {
+        self.configurable_frames
+            .iter()
+            .find(|(f, _)| **f == *frame)
+            .and_then(|(_, id)| *id)
+    }
+}
+
+impl LDFData {
+    // (priority, frame) of a sporadic frame, 0 is the highest and declared first
+    pub fn sporadic_priority(&self, sporadic: &str) -> impl Iterator<Item = (usize, &Name)> {
+        self.sporadic_frames
+            .get(sporadic)
+            .into_iter()
+            .flatten()
+            .enumerate()
+    }
+
+    // diagnostic frames and LIN 1.x nodes only support the classic checksum
+    pub fn checksum_model(&self, msg: &Message) -> ChecksumModel {
+        let lin1 = self
+            .responders
+            .iter()
+            .filter(|(n, r)| {
+                **n == msg.sender || msg.signals.iter().any(|s| r.subscribed_signals.contains(s))
+            })
+            .any(|(_, r)| r.protocol.starts_with("1."));
+        if lin1 || msg.id >= 0x3C {
+            ChecksumModel::Classic
+        } else {
+            ChecksumModel::Enhanced
+        }
+    }
+}
+
+impl ValueKind {
+    // bit width the kind needs, if fixed
+    pub fn width(&self) -> Option<u16> {
+        match self {
+            ValueKind::Float32 => Some(32),
+            ValueKind::Float64 => Some(64),
+            _ => None,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, ValueKind::Float32 | ValueKind::Float64)
+    }
+}
+
+impl SendType {
+    pub const LABELS: [&'static str; 6] = [
+        "Cyclic",
+        "Spontaneous",
+        "CyclicIfActive",
+        "CyclicAndSpontaneous",
+        "IfActive",
+        "NoMsgSendType",
+    ];
+
+    // DBC enum label, unknown ones are kept as is
+    pub fn from_label(label: &str) -> SendType {
+        match label {
+            "Cyclic" => SendType::Cyclic,
+            "Spontaneous" | "Event" | "OnChange" | "OnWrite" => SendType::Spontaneous,
+            "CyclicIfActive" => SendType::CyclicIfActive,
+            "CyclicAndSpontaneous" => SendType::CyclicAndSpontaneous,
+            "IfActive" => SendType::IfActive,
+            "NoMsgSendType" | "NoSigSendType" => SendType::NoSendType,
+            _ => SendType::Other(label.to_string()),
+        }
+    }
+}
+
+impl FrameProtocol {
+    // DBC enum label, CAN FD formats map to their CAN counterparts
+    pub fn from_label(label: &str) -> Option<FrameProtocol> {
+        match label {
+            "StandardCAN" | "StandardCAN_FD" => Some(FrameProtocol::Standard),
+            "ExtendedCAN" | "ExtendedCAN_FD" => Some(FrameProtocol::Extended),
+            "J1939PG" => Some(FrameProtocol::J1939PG),
+            _ => None,
+        }
+    }
+}
+
+impl Message {
+    // J1939 parameter group number, none for other frames
+    pub fn pgn(&self) -> Option<u32> {
+        if self.protocol != FrameProtocol::J1939PG {
+            return None;
+        }
+        FrameId::can(self.id)
+            .ok()
+            .filter(|id| id.is_extended())
+            .map(FrameId::pgn)
+    }
+
+    // drops group members that left the frame, and groups left empty
+    pub fn prune_signal_groups(&mut self) {
+        let signals = &self.signals;
+        let mux_signals = &self.mux_signals;
+        for group in self.signal_groups.values_mut() {
+            group
+                .signals
+                .retain(|s| signals.contains(s) || mux_signals.contains_key(s));
+        }
+        self.signal_groups.retain(|_, g| !g.signals.is_empty());
+    }
+}
+
+impl SNPDStep {
+    pub const LABELS: [&str; 4] = ["Init", "AssignNAD", "Store", "Finish"];
+
+    // subfunction id sent in D3
+    pub fn subfunction(self) -> u8 {
+        self as u8 + 1
+    }
+}
+
+impl std::str::FromStr for SNPDStep {
+    type Err = Error;
+
+    // by label or subfunction id
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let step = match SNPDStep::LABELS.iter().position(|l| *l == s) {
+            Some(i) => i as u8 + 1,
+            None => s.parse::<u8>().map_err(|_| {
+                Error::Hint(
+                    format!(
+                        "SNPD step {}, not one of {}",
+                        s,
+                        SNPDStep::LABELS.join(", ")
+                    ),
+                    Box::new(Error::IncorrectToken),
+                )
+            })?,
+        };
+        match step {
+            1 => Ok(SNPDStep::Init),
+            2 => Ok(SNPDStep::AssignNAD),
+            3 => Ok(SNPDStep::Store),
+            4 => Ok(SNPDStep::Finish),
+            _ => Err(Error::Hint(
+                format!("SNPD step {}", s),
+                Box::new(Error::ValueOutOfRange),
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SNPDStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", SNPDStep::LABELS[*self as usize])
+    }
+}
+
+impl std::fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NodeRole::Commander => write!(f, "commander"),
+            NodeRole::Responder => write!(f, "responder"),
+            NodeRole::Ecu => write!(f, "ECU"),
+        }
+    }
+}
+
+impl std::fmt::Display for FrameProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FrameProtocol::Standard => write!(f, "StandardCAN"),
+            FrameProtocol::Extended => write!(f, "ExtendedCAN"),
+            FrameProtocol::J1939PG => write!(f, "J1939PG"),
+        }
+    }
+}
+
+impl std::fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AttributeValue::Int(v) => write!(f, "{}", v),
+            AttributeValue::Float(v) => write!(f, "{}", v),
+            AttributeValue::String(v) | AttributeValue::Enum(v) => write!(f, "\"{}\"", v),
+        }
+    }
+}
+
+impl std::fmt::Display for SendType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SendType::Cyclic => write!(f, "{}", SendType::LABELS[0]),
+            SendType::Spontaneous => write!(f, "{}", SendType::LABELS[1]),
+            SendType::CyclicIfActive => write!(f, "{}", SendType::LABELS[2]),
+            SendType::CyclicAndSpontaneous => write!(f, "{}", SendType::LABELS[3]),
+            SendType::IfActive => write!(f, "{}", SendType::LABELS[4]),
+            SendType::NoSendType => write!(f, "{}", SendType::LABELS[5]),
+            SendType::Other(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+impl std::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValueKind::Unsigned => write!(f, "unsigned"),
+            ValueKind::Signed => write!(f, "signed"),
+            ValueKind::Float32 => write!(f, "float32"),
+            ValueKind::Float64 => write!(f, "float64"),
+            ValueKind::ByteArray => write!(f, "byte array"),
+        }
+    }
+}
+
+impl DatabaseType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DatabaseType::NCF => "NCF",
+            DatabaseType::LDF(_) => "LDF",
+            DatabaseType::DBC(_) => "DBC",
+        }
+    }
+}
+
+fn hex_list(data: &[u8]) -> String {
+    data.iter()
+        .map(|d| format!("0x{:02X}", d))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// LDF syntax
+impl std::fmt::Display for LDFScheduleCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LDFScheduleCommand::Frame(frame) => write!(f, "{}", frame),
+            LDFScheduleCommand::CommanderReq => write!(f, "MasterReq"),
+            LDFScheduleCommand::ResponderResp => write!(f, "SlaveResp"),
+            LDFScheduleCommand::AssignNAD(node) => write!(f, "AssignNAD {{{}}}", node),
+            LDFScheduleCommand::ConditionalChangeNAD {
+                nad,
+                id,
+                byte,
+                mask,
+                inv,
+                new_nad,
+            } => write!(
+                f,
+                "ConditionalChangeNAD {{{}}}",
+                hex_list(&[*nad, *id, *byte, *mask, *inv, *new_nad])
+            ),
+            LDFScheduleCommand::DataDump { name, data } => {
+                write!(f, "DataDump {{{}, {}}}", name, hex_list(data))
+            }
+            LDFScheduleCommand::SaveConfiguration(node) => {
+                write!(f, "SaveConfiguration {{{}}}", node)
+            }
+            LDFScheduleCommand::AssignFrameIdRange { name, index, pid } => {
+                if pid.iter().all(|p| *p == 0xFF) {
+                    write!(f, "AssignFrameIdRange {{{}, {}}}", name, index)
+                } else {
+                    write!(
+                        f,
+                        "AssignFrameIdRange {{{}, {}, {}}}",
+                        name,
+                        index,
+                        hex_list(pid)
+                    )
+                }
+            }
+            LDFScheduleCommand::FreeFormat(data) => write!(f, "FreeFormat {{{}}}", hex_list(data)),
+            LDFScheduleCommand::AssignFrameId { node, frame } => {
+                write!(f, "AssignFrameId {{{}, {}}}", node, frame)
+            }
+            LDFScheduleCommand::AssignNADviaSNPD {
+                step,
+                supplier,
+                nad,
+            } => {
+                write!(f, "AssignNADviaSNPD {{{}, 0x{:04X}", step, supplier)?;
+                if let Some(nad) = nad {
+                    write!(f, ", 0x{:02X}", nad)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
 }
 
-impl Default for DatabaseType {
-    fn default() -> Self {
-        DatabaseType::NCF
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Encoding::Scalar {
+                raw_min,
+                raw_max,
+                scale,
+                offset,
+                unit,
+                description,
+            } => {
+                write!(
+                    f,
+                    "{}..={}: {} * raw + {} {}",
+                    raw_min, raw_max, scale, offset, unit
+                )?;
+                match description {
+                    Some(text) => write!(f, " ({})", text),
+                    None => Ok(()),
+                }
+            }
+            Encoding::Enum {
+                name,
+                rev_map,
+                description,
+                ..
+            } => {
+                let mut values: Vec<_> = rev_map.iter().collect();
+                values.sort();
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|(raw, label)| format!("{} = \"{}\"", raw, label))
+                    .collect();
+                write!(f, "{} {{{}}}", name, values.join(", "))?;
+                match description {
+                    Some(text) => write!(f, " ({})", text),
+                    None => Ok(()),
+                }
+            }
+            Encoding::Ascii => write!(f, "ascii"),
+        }
     }
 }
@@ -1,5 +1,50 @@
+use crate::parsers::encoding::Name;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize, // byte-index
+    pub end: usize,
+}
+
+// source locations of parsed definitions, from the name to the closing token
+#[derive(Clone, Debug, Default)]
+pub struct SpanMap {
+    pub signals: HashMap<Name, Span>,
+    pub frames: HashMap<Name, Span>, // includes sporadic and event triggered frames
+    pub nodes: HashMap<Name, Span>,  // node attribute blocks
+    pub schedule_tables: HashMap<Name, Span>,
+    pub schedule_entries: HashMap<Name, Vec<Span>>, // in table order
+    pub encodings: HashMap<String, Span>,
+    pub descriptions: Vec<Span>, // comments taken as encoding descriptions
+}
+
+// something in the source that wasn't carried over as written or looks unintended, parsing still succeeded
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum WarningKind {
+    VersionMismatch,
+    UnsupportedSection,
+    UnsupportedAttribute,
+    IgnoredEncoding,
+    DefaultedValue,
+    Renamed,
+    UnusedSignal,          // in no frame
+    UnusedEncoding,        // given to no signal
+    UncarriedSubscription, // subscribed signal no frame from another node carries
+}
+
 #[derive(Debug)]
 pub enum Error {
+    Located(Span, Box<Error>),
+    Hint(String, Box<Error>), // suggestion for fixing the error
     IO(String),
     ExpectedComment,
     ExpectedToken,
@@ -11,15 +56,233 @@ pub enum Error {
     UnknownFrame,
     UnknownSignal,
     UnknownEncoding,
+    UnknownScheduleTable,
+    UnknownBus,
+    UnknownUnit,
+    UnknownSignalGroup,
     DuplicateSignal,
     DuplicateFrame,
     DuplicateEncoding,
+    DuplicateNode,
+    DuplicateNAD,
+    InvalidNAD,
+    InvalidFrameId,
+    ReservedFrameId,
+    DuplicateFrameId,
+    InvalidScheduleTiming,
+    ScheduleSlotTooShort,
+    UnsupportedBitrate,
+    UnsupportedDiagnosticClass,
+    TooManyFrames,
+    MissingMessageId,
+    InvalidPattern,
+    InvalidIdentifier,
+    DuplicateIdentifier,
+    SignalOverlap,
+    SignalOutsideFrame,
+    InvalidValueKind,
+    InconsistentSignal,
+    IncompleteSignalGroup,
+    MultiplexorCount,
+    MuxRangeGap,
+    UnselectedSignal,
     NotUnconditionalFrame,
     SporadicFrameHasResponder,
+    SporadicFrameInEventFrame,
     EventFrameDifferentLength,
+    EventFrameFirstByteUsed,
+    InvalidPayload,
+    IncompatibleDatabase,
+    UnknownFormat,
+    ValueOutOfRange,
     NotImplemented,
 }
 
+// where parsers report what they run into, closures taking a Warning work too
+pub trait DiagnosticSink {
+    fn warning(&mut self, warning: Warning);
+    // the error parsing stopped at, it's returned as well
+    fn error(&mut self, _error: &Error) {}
+}
+
+// forwards warnings to the log crate, what the plain parse functions use
+pub struct LogSink;
+
+impl DiagnosticSink for LogSink {
+    fn warning(&mut self, warning: Warning) {
+        log::warn!("{}", warning);
+    }
+}
+
+impl<F: FnMut(Warning)> DiagnosticSink for F {
+    fn warning(&mut self, warning: Warning) {
+        self(warning)
+    }
+}
+
+impl Warning {
+    pub(crate) fn new(kind: WarningKind, span: Span, message: String) -> Warning {
+        Warning {
+            kind,
+            span,
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Span {
+    // zero-based line and column (in chars) of start
+    pub fn line_col(&self, src: &str) -> (usize, usize) {
+        let before = &src[..self.start.min(src.len())];
+        let line = before.matches('\n').count();
+        let col = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count();
+        (line, col)
+    }
+}
+
+impl SpanMap {
+    // first definition found for name, looking at signals, frames, nodes then schedule tables
+    pub fn get(&self, name: &str) -> Option<Span> {
+        [
+            &self.signals,
+            &self.frames,
+            &self.nodes,
+            &self.schedule_tables,
+        ]
+        .into_iter()
+        .find_map(|m| m.get(name).copied())
+    }
+}
+
+impl Error {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::Located(span, _) => Some(*span),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> &Error {
+        match self {
+            Error::Located(_, e) | Error::Hint(_, e) => e.kind(),
+            e => e,
+        }
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            Error::Located(_, e) => e.hint(),
+            Error::Hint(hint, _) => Some(hint),
+            _ => None,
+        }
+    }
+
+    // rustc style report with a source snippet, falls back to a single line without a span
+    pub fn render(&self, path: &str, src: &str) -> String {
+        let Some(span) = self.span() else {
+            return format!("error: {}\n", self);
+        };
+        let (line, col) = span.line_col(src);
+        let text = src.lines().nth(line).unwrap_or("");
+        let width = src
+            .get(span.start..span.end.max(span.start))
+            .map_or(1, |s| s.lines().next().unwrap_or("").chars().count())
+            .clamp(1, text.chars().count().saturating_sub(col).max(1));
+        let number = (line + 1).to_string();
+        let pad = " ".repeat(number.len());
+
+        let mut ret = format!("error: {}\n", self.kind());
+        ret += &format!("{}--> {}:{}:{}\n", pad, path, line + 1, col + 1);
+        ret += &format!("{} |\n", pad);
+        ret += &format!("{} | {}\n", number, text);
+        ret += &format!("{} | {}{}\n", pad, " ".repeat(col), "^".repeat(width));
+        if let Some(hint) = self.hint() {
+            ret += &format!("{} = help: {}\n", pad, hint);
+        }
+        ret
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Located(span, e) => write!(f, "{} (bytes {}..{})", e, span.start, span.end),
+            Error::Hint(hint, e) => write!(f, "{}, {}", e, hint),
+            Error::IO(s) => write!(f, "{}", s),
+            Error::ExpectedComment => write!(f, "expected comment"),
+            Error::ExpectedToken => write!(f, "unexpected end of input"),
+            Error::UnexpectedToken => write!(f, "unexpected token"),
+            Error::IncorrectToken => write!(f, "incorrect token"),
+            Error::NumberParse => write!(f, "invalid number"),
+            Error::SignalTooWide => write!(f, "signal too wide"),
+            Error::UnknownNode => write!(f, "unknown node"),
+            Error::UnknownFrame => write!(f, "unknown frame"),
+            Error::UnknownSignal => write!(f, "unknown signal"),
+            Error::UnknownEncoding => write!(f, "unknown encoding"),
+            Error::UnknownScheduleTable => write!(f, "unknown schedule table"),
+            Error::UnknownBus => write!(f, "unknown bus"),
+            Error::UnknownUnit => write!(f, "unknown unit"),
+            Error::UnknownSignalGroup => write!(f, "unknown signal group"),
+            Error::DuplicateSignal => write!(f, "duplicate signal"),
+            Error::DuplicateFrame => write!(f, "duplicate frame"),
+            Error::DuplicateEncoding => write!(f, "duplicate encoding"),
+            Error::DuplicateNode => write!(f, "duplicate node"),
+            Error::DuplicateNAD => write!(f, "duplicate NAD"),
+            Error::InvalidNAD => write!(f, "NAD outside 0x01-0x7D"),
+            Error::InvalidFrameId => write!(f, "frame id outside 0-59"),
+            Error::ReservedFrameId => write!(f, "reserved frame id"),
+            Error::DuplicateFrameId => write!(f, "duplicate frame id"),
+            Error::InvalidScheduleTiming => write!(f, "invalid schedule timing"),
+            Error::ScheduleSlotTooShort => write!(f, "schedule slot shorter than its frame"),
+            Error::UnsupportedBitrate => write!(f, "bitrate not supported by node"),
+            Error::UnsupportedDiagnosticClass => {
+                write!(f, "diagnostic class not supported by node")
+            }
+            Error::TooManyFrames => write!(f, "more frames than node supports"),
+            Error::MissingMessageId => write!(f, "configurable frame has no message id"),
+            Error::InvalidPattern => write!(f, "invalid pattern"),
+            Error::InvalidIdentifier => write!(f, "not a valid LIN identifier"),
+            Error::DuplicateIdentifier => write!(f, "identifier used for more than one kind"),
+            Error::SignalOverlap => write!(f, "signals overlap"),
+            Error::SignalOutsideFrame => write!(f, "signal outside its frame"),
+            Error::InvalidValueKind => write!(f, "value kind doesn't fit the signal width"),
+            Error::InconsistentSignal => write!(f, "inconsistent signal"),
+            Error::IncompleteSignalGroup => write!(f, "signal group only partly given"),
+            Error::MultiplexorCount => write!(f, "not exactly one multiplexor"),
+            Error::MuxRangeGap => write!(f, "gap in multiplexor values"),
+            Error::UnselectedSignal => {
+                write!(f, "multiplexed signal not selected by its multiplexor")
+            }
+            Error::NotUnconditionalFrame => write!(f, "not an unconditional frame"),
+            Error::SporadicFrameHasResponder => write!(f, "sporadic frame published by responder"),
+            Error::SporadicFrameInEventFrame => {
+                write!(f, "frame in both a sporadic and an event triggered frame")
+            }
+            Error::EventFrameDifferentLength => {
+                write!(f, "event triggered frame has frames of different length")
+            }
+            Error::EventFrameFirstByteUsed => {
+                write!(f, "event triggered frame has a signal in its first byte")
+            }
+            Error::InvalidPayload => write!(f, "invalid payload"),
+            Error::IncompatibleDatabase => write!(f, "incompatible database type"),
+            Error::UnknownFormat => write!(f, "unknown format"),
+            Error::ValueOutOfRange => write!(f, "value out of range"),
+            Error::NotImplemented => write!(f, "not implemented"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<std::io::Error> for Error {
     fn from(item: std::io::Error) -> Self {
         Error::IO(item.to_string())
@@ -37,3 +300,27 @@ impl From<std::num::ParseIntError> for Error {
         Error::NumberParse
     }
 }
+
+impl From<std::fmt::Error> for Error {
+    fn from(item: std::fmt::Error) -> Self {
+        Error::IO(item.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(item: serde_json::Error) -> Self {
+        Error::IO(item.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(item: zip::result::ZipError) -> Self {
+        Error::IO(item.to_string())
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(item: toml::de::Error) -> Self {
+        Error::IO(item.to_string())
+    }
+}
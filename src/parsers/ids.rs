@@ -0,0 +1,223 @@
+use crate::parsers::encoding::{MAX_ARRAY_WIDTH, MAX_SIGNAL_WIDTH};
+use crate::Error;
+use serde::{Deserialize, Serialize};
+
+const LIN_ID_MAX: u32 = 0x3F;
+const CAN_STANDARD_MAX: u32 = 0x7FF;
+const CAN_EXTENDED_MAX: u32 = 0x1FFFFFFF;
+const CAN_EXTENDED_FLAG: u32 = 0x80000000; // as in DBC files
+const BIT_POS_MAX: u16 = 64 * 8 - 1; // CAN FD payload
+
+/*
+ * Range-checked values for API boundaries, e.g. add_message takes a FrameId, assign_frame_ids returns Pids and
+ * bit_positions returns BitPos. The model itself keeps plain integers, as the formats and the cache store them, so a
+ * Message or Signal is only checked where it's built through these.
+ */
+
+fn out_of_range(what: &str, value: impl std::fmt::Display) -> Error {
+    Error::Hint(
+        format!("{} {}", what, value),
+        Box::new(Error::ValueOutOfRange),
+    )
+}
+
+// frame id as stored in Message::id, LIN ids or CAN ids with the DBC extended flag
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FrameId(u32);
+
+// LIN node address, 0x7E is functional and 0x7F broadcast
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Nad(u8);
+
+// LIN protected id, the frame id with its two parity bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pid(u8);
+
+// bit of a payload, byte * 8 + bit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BitPos(u16);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BitWidth(u16);
+
+impl FrameId {
+    pub fn lin(id: u32) -> Result<FrameId, Error> {
+        if id > LIN_ID_MAX {
+            return Err(out_of_range("LIN frame id", format!("0x{:02X}", id)));
+        }
+        Ok(FrameId(id))
+    }
+
+    // 11 bit ids, or 29 bit ones with the extended flag
+    pub fn can(id: u32) -> Result<FrameId, Error> {
+        let valid = if FrameId(id).is_extended() {
+            id & !CAN_EXTENDED_FLAG <= CAN_EXTENDED_MAX
+        } else {
+            id <= CAN_STANDARD_MAX
+        };
+        if !valid {
+            return Err(out_of_range("CAN frame id", format!("0x{:X}", id)));
+        }
+        Ok(FrameId(id))
+    }
+
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_extended(self) -> bool {
+        self.0 & CAN_EXTENDED_FLAG != 0
+    }
+
+    // J1939 parameter group number, PDU1 ones (PF below 240) leave out the destination address
+    pub fn pgn(self) -> u32 {
+        let pgn = ((self.0 & CAN_EXTENDED_MAX) >> 8) & 0x3FFFF;
+        if (pgn >> 8) & 0xFF < 240 {
+            pgn & !0xFF
+        } else {
+            pgn
+        }
+    }
+
+    pub fn pid(self) -> Option<Pid> {
+        (self.0 <= LIN_ID_MAX).then(|| Pid::from_id(self.0 as u8))
+    }
+}
+
+impl Nad {
+    pub const FUNCTIONAL: Nad = Nad(0x7E);
+    pub const BROADCAST: Nad = Nad(0x7F);
+
+    // NADs a node can be configured with
+    pub fn new(nad: u8) -> Result<Nad, Error> {
+        if !(0x01..=0x7D).contains(&nad) {
+            return Err(Error::Hint(
+                format!("0x{:02X}", nad),
+                Box::new(Error::InvalidNAD),
+            ));
+        }
+        Ok(Nad(nad))
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl Pid {
+    pub fn from_id(id: u8) -> Pid {
+        let bit = |n: u8| (id >> n) & 1;
+        let p0 = bit(0) ^ bit(1) ^ bit(2) ^ bit(4);
+        let p1 = !(bit(1) ^ bit(3) ^ bit(4) ^ bit(5)) & 1;
+        Pid((id & 0x3F) | (p0 << 6) | (p1 << 7))
+    }
+
+    // checks the parity bits
+    pub fn new(pid: u8) -> Result<Pid, Error> {
+        let ret = Pid::from_id(pid & 0x3F);
+        if ret.0 != pid {
+            return Err(out_of_range(
+                "PID with bad parity",
+                format!("0x{:02X}", pid),
+            ));
+        }
+        Ok(ret)
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    pub fn id(self) -> FrameId {
+        FrameId((self.0 & 0x3F) as u32)
+    }
+}
+
+impl BitPos {
+    pub fn new(pos: u16) -> Result<BitPos, Error> {
+        if pos > BIT_POS_MAX {
+            return Err(out_of_range("bit", pos));
+        }
+        Ok(BitPos(pos))
+    }
+
+    // positions derived from the model, validate reports the ones outside their frame
+    pub(crate) fn unchecked(pos: u16) -> BitPos {
+        BitPos(pos)
+    }
+
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    pub fn byte(self) -> u16 {
+        self.0 / 8
+    }
+
+    // bit within the byte, 0 is the LSB
+    pub fn bit(self) -> u8 {
+        (self.0 % 8) as u8
+    }
+}
+
+impl BitWidth {
+    pub fn new(width: u16) -> Result<BitWidth, Error> {
+        if width == 0 || width > MAX_SIGNAL_WIDTH {
+            return Err(Error::Hint(
+                format!("{} bits", width),
+                Box::new(Error::SignalTooWide),
+            ));
+        }
+        Ok(BitWidth(width))
+    }
+
+    // byte arrays, whole bytes up to a CAN FD payload
+    pub fn array(width: u16) -> Result<BitWidth, Error> {
+        if width == 0 || !width.is_multiple_of(8) || width > MAX_ARRAY_WIDTH {
+            return Err(Error::Hint(
+                format!("{} bits", width),
+                Box::new(Error::SignalTooWide),
+            ));
+        }
+        Ok(BitWidth(width))
+    }
+
+    pub fn get(self) -> u16 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for FrameId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "0x{:02X}", self.0)
+    }
+}
+
+impl std::fmt::Display for Nad {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "0x{:02X}", self.0)
+    }
+}
+
+impl std::fmt::Display for Pid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "0x{:02X}", self.0)
+    }
+}
+
+impl std::fmt::Display for BitPos {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::fmt::Display for BitWidth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -1,20 +1,30 @@
+use crate::convert::compressed::open_decompressed;
 use crate::parsers::encoding::{
-    DatabaseType, Encoding, LDFData, LDFScheduleCommand, Message, Signal, BIT_START_INVALID,
-    MAX_SIGNAL_WIDTH,
+    DatabaseType, Encoding, LDFData, LDFScheduleCommand, Message, Name, Provenance, SNPDStep,
+    Signal, SignalGroup, ValueKind, BIT_START_INVALID, MAX_ARRAY_WIDTH, MAX_SIGNAL_WIDTH,
 };
+use crate::parsers::error::{DiagnosticSink, LogSink, Span, SpanMap, Warning, WarningKind};
+use crate::parsers::ids::Nad;
+use crate::writers::ldf::sorted;
 use crate::{Database, Error};
-use log::{error, warn};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
+use log::debug;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
-use std::str::FromStr;
 
 const LIN_VERSION_STR: &str = "\"2.2\"";
+const READ_CHUNK: usize = 8192; // bytes of lines read at a time when streaming
 
-struct Tokenizer {
-    data: String,
-    index: usize,
+pub(crate) struct Tokenizer<'a> {
+    data: Cow<'a, str>, // window of the input, tokens are slices of it
+    base: usize,        // offset of the window in the input
+    reader: Option<Box<dyn BufRead + 'a>>, // rest of the input when streaming
+    index: usize,       // byte-index into the input
+    span: Span,         // last token
+    consumed: Span,     // last token returned by next()
+    hint: Option<String>,
+    names: HashSet<Name>, // interned
 }
 
 enum TokenizerState {
@@ -22,27 +32,131 @@ enum TokenizerState {
     ExpectComment,
     BlockComment,
     LineComment,
-    CharString(bool),
+    CharString(bool), // after a backslash
     Skip,
     Stop,
     Found(usize, char),
 }
 
-impl Tokenizer {
-    fn new(file: impl AsRef<Path>) -> Result<Self, std::io::Error> {
-        let mut ret = Self {
-            data: String::new(),
-            index: 0, // byte-index
+impl<'a> Tokenizer<'a> {
+    pub(crate) fn new(data: &'a str) -> Self {
+        Self {
+            data: Cow::Borrowed(data),
+            base: 0,
+            reader: None,
+            index: 0,
+            span: Default::default(),
+            consumed: Default::default(),
+            hint: None,
+            names: HashSet::new(),
+        }
+    }
+
+    // reads lines as tokens need them, only the input from the last consumed token on is kept
+    pub(crate) fn from_reader(reader: impl BufRead + 'a) -> Self {
+        Self {
+            data: Cow::Owned(String::new()),
+            reader: Some(Box::new(reader)),
+            ..Self::new("")
+        }
+    }
+
+    pub(crate) fn span(&self) -> Span {
+        self.span
+    }
+
+    // from start to the end of the last consumed token
+    fn since(&self, start: usize) -> Span {
+        Span {
+            start,
+            end: self.consumed.end,
+        }
+    }
+
+    // a comment right after the last consumed token on its line, e.g. "; // ambient light", and its text
+    fn trailing_comment(&self) -> Option<(Span, String)> {
+        let rest = &self.data[self.consumed.end - self.base..];
+        let line = rest.lines().next().unwrap_or_default();
+        let comment = line.trim_start();
+        let start = self.consumed.end + line.len() - comment.len();
+        let (len, text) = match comment.strip_prefix("//") {
+            Some(text) => (comment.trim_end().len(), text),
+            None => {
+                let (text, _) = comment.strip_prefix("/*")?.split_once("*/")?;
+                (text.len() + 4, text)
+            }
         };
-        File::open(file)?.read_to_string(&mut ret.data)?;
-        Ok(ret)
+        let text = text.trim();
+        (!text.is_empty()).then(|| {
+            (
+                Span {
+                    start,
+                    end: start + len,
+                },
+                text.to_string(),
+            )
+        })
+    }
+
+    pub(crate) fn take_hint(&mut self) -> Option<String> {
+        self.hint.take()
+    }
+
+    fn slice(&self, start: usize, end: usize) -> &str {
+        &self.data[start - self.base..end - self.base]
+    }
+
+    fn end(&self) -> usize {
+        self.base + self.data.len()
+    }
+
+    // drops what's no longer needed and appends whole lines, false at the end of the input
+    fn fill(&mut self) -> Result<bool, Error> {
+        let Some(reader) = &mut self.reader else {
+            return Ok(false);
+        };
+        let data = self.data.to_mut();
+        let dropped = self.consumed.start.min(self.index) - self.base;
+        data.drain(..dropped);
+        self.base += dropped;
+        let mut bytes = Vec::new();
+        while bytes.len() < READ_CHUNK && reader.read_until(b'\n', &mut bytes)? > 0 {}
+        if bytes.is_empty() {
+            self.reader = None;
+            return Ok(false);
+        }
+        data.push_str(std::str::from_utf8(&bytes).map_err(|e| Error::IO(e.to_string()))?);
+        Ok(true)
     }
 
     fn parse(&mut self, update: bool) -> Result<&str, Error> {
+        loop {
+            let token = self.scan()?;
+            // tokens and comments running into the end of the window may go on past it
+            if token.is_none_or(|(_, end)| end == self.end()) && self.fill()? {
+                continue;
+            }
+            let Some((start, end)) = token else {
+                self.span = Span {
+                    start: self.end(),
+                    end: self.end(),
+                };
+                return Err(Error::ExpectedToken);
+            };
+            if update {
+                self.index = end;
+            }
+            self.span = Span { start, end };
+            return Ok(self.slice(start, end));
+        }
+    }
+
+    // start and end of the next token in the window
+    fn scan(&mut self) -> Result<Option<(usize, usize)>, Error> {
         // search forward for start of next token
         let mut c_prev = ' ';
         let mut state = TokenizerState::Search;
-        for (i, c) in self.data[self.index..].char_indices() {
+        for (i, c) in self.data[self.index - self.base..].char_indices() {
             match state {
                 TokenizerState::Search => {
                     if c == '/' {
@@ -58,18 +172,18 @@ impl Tokenizer {
                     } else if c == '/' {
                         state = TokenizerState::LineComment;
                     } else {
+                        self.span = Span {
+                            start: self.index + i - 1,
+                            end: self.index + i,
+                        };
                         return Err(Error::ExpectedComment);
                     }
                 }
-                TokenizerState::BlockComment => {
-                    if c_prev == '*' && c == '/' {
-                        state = TokenizerState::Search;
-                    }
+                TokenizerState::BlockComment if c_prev == '*' && c == '/' => {
+                    state = TokenizerState::Search;
                 }
-                TokenizerState::LineComment => {
-                    if c == '\n' {
-                        state = TokenizerState::Search;
-                    }
+                TokenizerState::LineComment if c == '\n' => {
+                    state = TokenizerState::Search;
                 }
                 _ => (),
             }
@@ -80,23 +194,24 @@ impl Tokenizer {
         let is_delimiter = |c: char| [',', ';', ':', '=', '{', '}', '/'].contains(&c);
         if let TokenizerState::Found(start_idx, c_start) = state {
             if let '"' = c_start {
-                state = TokenizerState::CharString(true);
+                state = TokenizerState::CharString(false);
             } else if is_delimiter(c_start) {
                 state = TokenizerState::Skip;
             } else {
                 state = TokenizerState::Search;
             }
-            for (i, c) in self.data[start_idx..].char_indices() {
+            for (i, c) in self.data[start_idx - self.base..].char_indices() {
                 match state {
-                    TokenizerState::Search => {
-                        if is_delimiter(c) || c.is_whitespace() {
-                            state = TokenizerState::Found(start_idx + i, c);
-                            break;
-                        }
+                    TokenizerState::Search if is_delimiter(c) || c.is_whitespace() => {
+                        state = TokenizerState::Found(start_idx + i, c);
+                        break;
                     }
-                    TokenizerState::CharString(start) => {
-                        if start {
+                    // delimiters and comment starts are part of the string, \" and \\ are escapes
+                    TokenizerState::CharString(escaped) => {
+                        if i == 0 || escaped {
                             state = TokenizerState::CharString(false);
+                        } else if c == '\\' {
+                            state = TokenizerState::CharString(true);
                         } else if c == '"' {
                             state = TokenizerState::Stop;
                         }
@@ -112,41 +227,63 @@ impl Tokenizer {
                 }
             }
 
-            let new_index;
-            if let TokenizerState::Found(end_idx, _) = state {
-                new_index = end_idx;
-            } else {
-                new_index = self.data.len();
-            }
-            if update {
-                self.index = new_index;
-            }
-            Ok(&self.data[start_idx..new_index])
+            let end_idx = match state {
+                TokenizerState::Found(end_idx, _) => end_idx,
+                _ => self.end(),
+            };
+            Ok(Some((start_idx, end_idx)))
         } else {
-            Err(Error::ExpectedToken)
+            Ok(None)
         }
     }
 
-    fn next(&mut self) -> Result<&str, Error> {
-        self.parse(true)
+    pub(crate) fn next(&mut self) -> Result<&str, Error> {
+        self.parse(true)?;
+        self.consumed = self.span;
+        Ok(self.slice(self.span.start, self.span.end))
     }
 
-    fn peek(&mut self) -> Result<&str, Error> {
+    pub(crate) fn peek(&mut self) -> Result<&str, Error> {
         self.parse(false)
     }
 
+    // next token as a shared name, identical names share one allocation
+    fn next_name(&mut self) -> Result<Name, Error> {
+        self.parse(true)?;
+        self.consumed = self.span;
+        let token = self.slice(self.span.start, self.span.end);
+        if let Some(name) = self.names.get(token) {
+            return Ok(name.clone());
+        }
+        let name: Name = token.into();
+        self.names.insert(name.clone());
+        Ok(name)
+    }
+
     fn check_equal(&mut self, expected: &[&str]) -> Result<(), Error> {
         for e in expected {
-            let actual = self.next()?;
-            if &actual != e {
-                error!("expected: {}, actual: {}", e, actual);
-                return Err(Error::IncorrectToken);
+            let prev = self.consumed;
+            let result = self.next().map(|actual| actual == *e);
+            if !matches!(result, Ok(true)) {
+                let hint = if prev.end > prev.start {
+                    format!(
+                        "expected '{}' after '{}'",
+                        e,
+                        self.slice(prev.start, prev.end)
+                    )
+                } else {
+                    format!("expected '{}'", e)
+                };
+                debug!("{}", hint);
+                self.hint = Some(hint);
+                return Err(result.err().unwrap_or(Error::IncorrectToken));
             }
         }
         Ok(())
     }
 }
 
+#[derive(Clone, Copy)]
 enum ParserState {
     Header,
     ProtocolVersion,
@@ -169,718 +306,1348 @@ enum ParserState {
     Done,
 }
 
-fn parse_real_or_integer(s: &str) -> Result<f64, <f64 as FromStr>::Err> {
-    if s.starts_with("0x") {
-        if let Ok(i) = u64::from_str_radix(&s[2..], 16) {
-            Ok(i as f64)
-        } else {
-            "z".parse() // create ParseFloatError
+// optional sign, then what follows
+fn split_sign(s: &str) -> (bool, &str) {
+    match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    }
+}
+
+// magnitude of a decimal or 0x hex integer, without sign
+fn parse_magnitude(digits: &str) -> Option<u64> {
+    match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) if !hex.is_empty() && hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            u64::from_str_radix(hex, 16).ok()
         }
-    } else {
-        s.parse()
+        Some(_) => None,
+        None if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            digits.parse().ok()
+        }
+        None => None,
     }
 }
 
-fn parse_integer(s: &str) -> Result<u64, <u64 as FromStr>::Err> {
-    if s.starts_with("0x") {
-        u64::from_str_radix(&s[2..], 16)
+fn invalid_number(s: &str) -> Error {
+    Error::Hint(format!("got {}", s), Box::new(Error::NumberParse))
+}
+
+// signed decimal, scientific notation or 0x hex
+pub(crate) fn parse_real(s: &str) -> Result<f64, Error> {
+    let (negative, digits) = split_sign(s);
+    let value = if digits.starts_with("0x") || digits.starts_with("0X") {
+        parse_magnitude(digits).ok_or_else(|| invalid_number(s))? as f64
+    } else if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        // f64's own parser would take inf and nan, and a second sign
+        digits.parse::<f64>().map_err(|_| invalid_number(s))?
     } else {
-        s.parse()
+        return Err(invalid_number(s));
+    };
+    Ok(if negative { -value } else { value })
+}
+
+// signed decimal or 0x hex, checked against the range of T
+pub(crate) fn parse_integer<T: TryFrom<i128>>(s: &str) -> Result<T, Error> {
+    let (negative, digits) = split_sign(s);
+    let magnitude = parse_magnitude(digits).ok_or_else(|| invalid_number(s))? as i128;
+    T::try_from(if negative { -magnitude } else { magnitude })
+        .map_err(|_| Error::Hint(format!("got {}", s), Box::new(Error::ValueOutOfRange)))
+}
+
+// contents of a quoted token, unescaped
+pub(crate) fn parse_char_string(s: &str) -> String {
+    let s = s
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s);
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => ret.extend(chars.next()),
+            _ => ret.push(c),
+        }
     }
+    ret
 }
 
 pub fn parse_ldf(ldf: impl AsRef<Path>) -> Result<Database, Error> {
-    let mut tokens = Tokenizer::new(ldf)?;
+    let (reader, _) = open_decompressed(&ldf)?;
+    Ok(parse_ldf_reader(reader)?.with_source(ldf.as_ref()))
+}
+
+// maps the file instead of reading it into memory, file must not be modified while parsing
+#[cfg(feature = "mmap")]
+pub fn parse_ldf_mmap(ldf: impl AsRef<Path>) -> Result<Database, Error> {
+    let file = std::fs::File::open(&ldf)?;
+    // SAFETY: mapping is read-only and dropped before returning, concurrent writes are documented as unsupported
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    let data = std::str::from_utf8(&map).map_err(|e| Error::IO(e.to_string()))?;
+    Ok(parse(Tokenizer::new(data))?.with_source(ldf.as_ref()))
+}
+
+pub fn parse_ldf_reader(reader: impl Read) -> Result<Database, Error> {
+    parse(Tokenizer::from_reader(BufReader::new(reader)))
+}
+
+// reads everything before parsing, which doesn't wait on anything
+#[cfg(feature = "tokio")]
+pub async fn parse_ldf_reader_async(
+    mut reader: impl tokio::io::AsyncRead + Unpin,
+) -> Result<Database, Error> {
+    let mut data = Vec::new();
+    tokio::io::AsyncReadExt::read_to_end(&mut reader, &mut data).await?;
+    parse_ldf_reader(data.as_slice())
+}
+
+// reports what was ignored or approximated along the way to sink
+pub fn parse_ldf_reader_with(
+    reader: impl Read,
+    sink: &mut dyn DiagnosticSink,
+) -> Result<Database, Error> {
+    let tokens = Tokenizer::from_reader(BufReader::new(reader));
+    parse_spans(tokens, &mut SpanMap::default(), sink)
+}
+
+pub fn parse_ldf_reader_with_warnings(
+    reader: impl Read,
+) -> Result<(Database, Vec<Warning>), Error> {
+    let mut warnings = Vec::new();
+    let db = parse_ldf_reader_with(reader, &mut |w| warnings.push(w))?;
+    Ok((db, warnings))
+}
+
+pub fn parse_ldf_str(ldf: &str) -> Result<Database, Error> {
+    parse(Tokenizer::new(ldf))
+}
+
+pub fn parse_ldf_str_with(ldf: &str, sink: &mut dyn DiagnosticSink) -> Result<Database, Error> {
+    parse_spans(Tokenizer::new(ldf), &mut SpanMap::default(), sink)
+}
+
+// also returns where each definition is in the source
+pub fn parse_ldf_str_with_spans(ldf: &str) -> Result<(Database, SpanMap), Error> {
+    let mut spans = SpanMap::default();
+    let db = parse_spans(Tokenizer::new(ldf), &mut spans, &mut LogSink)?;
+    Ok((db, spans))
+}
+
+fn parse(tokens: Tokenizer) -> Result<Database, Error> {
+    parse_spans(tokens, &mut SpanMap::default(), &mut LogSink)
+}
+
+fn parse_spans(
+    mut tokens: Tokenizer,
+    spans: &mut SpanMap,
+    sink: &mut dyn DiagnosticSink,
+) -> Result<Database, Error> {
+    parse_tokens(&mut tokens, spans, sink).map_err(|e| {
+        let e = locate(&mut tokens, e);
+        sink.error(&e);
+        e
+    })
+}
+
+// at the token it stopped at, with the tokenizer's hint if any
+fn locate(tokens: &mut Tokenizer, e: Error) -> Error {
+    let e = match tokens.take_hint() {
+        Some(hint) => Error::Hint(hint, Box::new(e)),
+        None => e,
+    };
+    Error::Located(tokens.span(), Box::new(e))
+}
+
+// keyword starting each top-level section, in file order, and where the parser picks it up
+const SECTIONS: [(&str, ParserState); 14] = [
+    ("LIN_description_file", ParserState::Header),
+    ("Nodes", ParserState::Node),
+    ("composite", ParserState::NodeComposition),
+    ("Signals", ParserState::Signal),
+    ("Diagnostic_signals", ParserState::DiagnosticSignal),
+    ("Frames", ParserState::Frame),
+    ("Sporadic_frames", ParserState::SporadicFrame),
+    ("Event_triggered_frames", ParserState::EventTriggeredFrame),
+    ("Diagnostic_frames", ParserState::DiagnosticFrame),
+    ("Node_attributes", ParserState::NodeAttributes),
+    ("Schedule_tables", ParserState::ScheduleTable),
+    ("Signal_groups", ParserState::SignalGroups),
+    ("Signal_encoding_types", ParserState::SignalEncodingTypes),
+    ("Signal_representation", ParserState::SignalRepresentation),
+];
+
+// sections whose definitions a section refers to
+fn section_dependencies(section: &str) -> &'static [&'static str] {
+    match section {
+        "Signals" => &["Nodes"],
+        "Frames" => &["Signals"],
+        "Sporadic_frames" | "Event_triggered_frames" | "Signal_groups" => &["Frames"],
+        "Node_attributes" => &["Frames", "Event_triggered_frames"],
+        "Schedule_tables" => &["Frames", "Sporadic_frames", "Event_triggered_frames"],
+        "Signal_representation" => &["Signals", "Signal_encoding_types"],
+        _ => &[],
+    }
+}
+
+// top-level sections as (keyword, span), found from braces alone so the rest of the file needn't be valid, a
+// section keyword followed by '{' starts a new section even if an earlier one wasn't closed, a tokenizer error
+// drops the section it's in and scanning picks up at the next line starting with a section keyword
+pub fn ldf_sections(ldf: &str) -> (Vec<(String, Span)>, Vec<Error>) {
+    let mut tokens = Tokenizer::new(ldf);
+    let mut ret: Vec<(String, Span)> = Vec::new();
+    let mut errors = Vec::new();
+    let mut depth = 0usize;
+    let mut closed = true; // last section ended with its brace
+    loop {
+        let token = match tokens.next().map(str::to_string) {
+            Ok(token) => token,
+            // only whitespace and comments left
+            Err(Error::ExpectedToken) if tokens.span().start == ldf.len() => break,
+            Err(e) => {
+                errors.push(locate(&mut tokens, e));
+                if !closed {
+                    ret.pop();
+                }
+                match resync(ldf, tokens.span().start) {
+                    Some(index) => tokens.index = index,
+                    None => break,
+                }
+                (depth, closed) = (0, true);
+                continue;
+            }
+        };
+        let start = tokens.consumed.start;
+        let keyword = SECTIONS.iter().any(|(k, _)| *k == token);
+        if keyword && (depth == 0 || tokens.peek().is_ok_and(|t| t == "{")) {
+            (depth, closed) = (0, false);
+            ret.push((token.clone(), Span { start, end: start }));
+        }
+        match token.as_str() {
+            "{" => depth += 1,
+            "}" => {
+                depth = depth.saturating_sub(1);
+                closed |= depth == 0;
+            }
+            _ => (),
+        }
+        if let Some(last) = ret.last_mut() {
+            last.1.end = tokens.consumed.end;
+        }
+    }
+    (ret, errors)
+}
+
+// start of the first line after index that begins with a section keyword
+fn resync(ldf: &str, index: usize) -> Option<usize> {
+    let mut line = index;
+    loop {
+        line += ldf.get(line..)?.find('\n')? + 1;
+        let rest = &ldf[line..];
+        let word = rest.trim_start();
+        let found = SECTIONS.iter().any(|(k, _)| {
+            word.strip_prefix(k)
+                .is_some_and(|r| r.starts_with(|c: char| c.is_whitespace() || c == '{' || c == ';'))
+        });
+        if found {
+            return Some(line + rest.len() - word.len());
+        }
+    }
+}
+
+// parses only these sections, e.g. "Schedule_tables", and the ones they refer to, each on its own so an error in
+// one doesn't stop the others, returns what was parsed and where each failed section stopped
+pub fn parse_ldf_sections(ldf: &str, sections: &[&str]) -> Result<(Database, Vec<Error>), Error> {
+    let mut wanted: Vec<&str> = Vec::new();
+    let mut queue = sections.to_vec();
+    while let Some(section) = queue.pop() {
+        if !SECTIONS.iter().any(|(k, _)| *k == section) {
+            return Err(Error::Hint(
+                format!("no section {}", section),
+                Box::new(Error::UnexpectedToken),
+            ));
+        }
+        if !wanted.contains(&section) {
+            wanted.push(section);
+            queue.extend(section_dependencies(section));
+        }
+    }
+
+    let mut ctx = ParserContext::default();
+    let (sections, mut errors) = ldf_sections(ldf);
+    let mut sink = LogSink;
+    for (keyword, span) in sections {
+        if !wanted.contains(&keyword.as_str()) {
+            continue;
+        }
+        let mut tokens = Tokenizer::new(ldf);
+        tokens.index = span.start;
+        let (_, mut state) = SECTIONS.into_iter().find(|(k, _)| *k == keyword).unwrap();
+        // the header is several statements, other sections end with their closing brace
+        loop {
+            match parse_section(
+                state,
+                &mut tokens,
+                &mut ctx,
+                &mut SpanMap::default(),
+                &mut sink,
+            ) {
+                Ok(
+                    next @ (ParserState::ProtocolVersion
+                    | ParserState::LanguageVersion
+                    | ParserState::Speed
+                    | ParserState::ChannelName),
+                ) => state = next,
+                Ok(_) => break,
+                Err(e) => {
+                    errors.push(locate(&mut tokens, e));
+                    break;
+                }
+            }
+        }
+    }
+    Ok((finish(ctx), errors))
+}
+
+#[derive(Default)]
+struct ParserContext {
+    db: Database,
+    data: LDFData,
+    version: Option<String>,
+    encodings: HashMap<String, Vec<Encoding>>,
+    represented: HashSet<String>, // encodings Signal_representation gives to signals
+}
+
+fn parse_tokens(
+    tokens: &mut Tokenizer,
+    spans: &mut SpanMap,
+    sink: &mut dyn DiagnosticSink,
+) -> Result<Database, Error> {
     let mut state = ParserState::Header;
-    let mut db: Database = Default::default();
-    let mut data: LDFData = Default::default();
-    let mut encodings: HashMap<String, Vec<Encoding>> = HashMap::new();
+    let mut ctx = ParserContext::default();
 
     // first pass parse data
     while !matches!(state, ParserState::Done) {
-        match state {
-            ParserState::Header => {
-                tokens.check_equal(&["LIN_description_file", ";"])?;
-                state = ParserState::ProtocolVersion;
+        state = parse_section(state, tokens, &mut ctx, spans, sink)?;
+    }
+    warn_unused(&ctx, spans, sink);
+
+    // TODO second pass validation
+    /*
+     * - resolver schedule tables exist, no event triggered frames in it!
+     * - no event triggered frames and associated frame in same schedule table
+     */
+    Ok(finish(ctx))
+}
+
+// definitions nothing refers to, where they're written
+fn warn_unused(ctx: &ParserContext, spans: &SpanMap, sink: &mut dyn DiagnosticSink) {
+    let mut warn = |kind, span: Option<&Span>, message| {
+        sink.warning(Warning::new(
+            kind,
+            span.copied().unwrap_or_default(),
+            message,
+        ));
+    };
+    let db = &ctx.db;
+    for (name, signal) in sorted(&db.signals) {
+        if signal.bit_start == BIT_START_INVALID {
+            warn(
+                WarningKind::UnusedSignal,
+                spans.signals.get(name),
+                format!("signal {} is in no frame", name),
+            );
+        }
+    }
+    let mut encodings: Vec<&String> = ctx.encodings.keys().collect();
+    encodings.sort();
+    for name in encodings {
+        if !ctx.represented.contains(name) {
+            warn(
+                WarningKind::UnusedEncoding,
+                spans.encodings.get(name),
+                format!("encoding {} is given to no signal", name),
+            );
+        }
+    }
+    for (node, responder) in sorted(&ctx.data.responders) {
+        for signal in &responder.subscribed_signals {
+            let placed = db
+                .signals
+                .get(signal)
+                .is_some_and(|s| s.bit_start != BIT_START_INVALID);
+            let carried = db
+                .messages
+                .values()
+                .any(|m| m.sender != *node && m.signals.contains(signal));
+            if placed && !carried {
+                warn(
+                    WarningKind::UncarriedSubscription,
+                    spans.signals.get(signal),
+                    format!(
+                        "{} subscribes to {} but no frame from another node carries it",
+                        node, signal
+                    ),
+                );
             }
-            ParserState::ProtocolVersion => {
-                tokens.check_equal(&["LIN_protocol_version", "="])?;
-                if tokens.next()? != LIN_VERSION_STR {
-                    warn!("protocol version not {}", LIN_VERSION_STR);
-                }
-                tokens.check_equal(&[";"])?;
-                state = ParserState::LanguageVersion;
+        }
+    }
+}
+
+fn finish(ctx: ParserContext) -> Database {
+    let ParserContext {
+        mut db,
+        data,
+        version,
+        ..
+    } = ctx;
+    for msg in db.messages.values_mut() {
+        msg.checksum = Some(data.checksum_model(msg));
+    }
+    db.extra = DatabaseType::LDF(data);
+    db.provenance = Some(Provenance::new("LDF", version));
+    db
+}
+
+// one state of the parser, returns the next
+// name, id and signal prefix of the diagnostic frames, fixed by the spec
+const DIAGNOSTIC_FRAMES: [(&str, u32, &str); 2] = [
+    ("MasterReq", 0x3C, "MasterReqB"),
+    ("SlaveResp", 0x3D, "SlaveRespB"),
+];
+
+// frame and byte of a diagnostic signal, e.g. MasterReqB3
+fn diagnostic_byte(name: &str) -> Option<(usize, u16)> {
+    DIAGNOSTIC_FRAMES
+        .iter()
+        .enumerate()
+        .find_map(|(frame, (_, _, prefix))| {
+            let byte = name.strip_prefix(prefix)?;
+            match byte.as_bytes() {
+                [b @ b'0'..=b'7'] => Some((frame, (b - b'0') as u16)),
+                _ => None,
             }
-            ParserState::LanguageVersion => {
-                tokens.check_equal(&["LIN_language_version", "="])?;
-                if tokens.next()? != LIN_VERSION_STR {
-                    warn!("language version not {}", LIN_VERSION_STR);
-                }
-                tokens.check_equal(&[";"])?;
-                state = ParserState::Speed;
-            }
-            ParserState::Speed => {
-                tokens.check_equal(&["LIN_speed", "="])?;
-                data.bitrate = parse_real_or_integer(tokens.next()?)?;
-                data.bitrate *= 1000.0;
-                tokens.check_equal(&["kbps", ";"])?;
-                if tokens.peek()? == "Channel_name" {
-                    state = ParserState::ChannelName;
-                } else {
-                    state = ParserState::Node;
-                }
+        })
+}
+
+// the standard byte signals are assumed for whatever wasn't listed
+fn warn_missing_diagnostic_bytes(
+    tokens: &Tokenizer,
+    sink: &mut dyn DiagnosticSink,
+    what: &str,
+    frame: usize,
+    seen: &[(usize, u16)],
+) {
+    let prefix = DIAGNOSTIC_FRAMES[frame].2;
+    let missing: Vec<String> = (0..8)
+        .filter(|b| !seen.contains(&(frame, *b)))
+        .map(|b| format!("{}{}", prefix, b))
+        .collect();
+    if !missing.is_empty() {
+        sink.warning(Warning::new(
+            WarningKind::DefaultedValue,
+            tokens.span(),
+            format!(
+                "{} missing {}, assuming the standard ones",
+                what,
+                missing.join(", ")
+            ),
+        ));
+    }
+}
+
+fn parse_section(
+    mut state: ParserState,
+    tokens: &mut Tokenizer,
+    ctx: &mut ParserContext,
+    spans: &mut SpanMap,
+    sink: &mut dyn DiagnosticSink,
+) -> Result<ParserState, Error> {
+    let ParserContext {
+        db,
+        data,
+        version,
+        encodings,
+        represented,
+    } = ctx;
+    match state {
+        ParserState::Header => {
+            tokens.check_equal(&["LIN_description_file", ";"])?;
+            state = ParserState::ProtocolVersion;
+        }
+        ParserState::ProtocolVersion => {
+            tokens.check_equal(&["LIN_protocol_version", "="])?;
+            *version = Some(parse_char_string(tokens.peek()?));
+            if tokens.next()? != LIN_VERSION_STR {
+                let message = format!("protocol version not {}", LIN_VERSION_STR);
+                sink.warning(Warning::new(
+                    WarningKind::VersionMismatch,
+                    tokens.span(),
+                    message,
+                ));
             }
-            ParserState::ChannelName => {
-                tokens.check_equal(&["Channel_name", "="])?;
-                data.postfix = tokens.next()?.to_string(); // spec says indentifier, but char_string used
-                tokens.check_equal(&[";"])?;
+            tokens.check_equal(&[";"])?;
+            state = ParserState::LanguageVersion;
+        }
+        ParserState::LanguageVersion => {
+            tokens.check_equal(&["LIN_language_version", "="])?;
+            if tokens.next()? != LIN_VERSION_STR {
+                let message = format!("language version not {}", LIN_VERSION_STR);
+                sink.warning(Warning::new(
+                    WarningKind::VersionMismatch,
+                    tokens.span(),
+                    message,
+                ));
+            }
+            tokens.check_equal(&[";"])?;
+            state = ParserState::Speed;
+        }
+        ParserState::Speed => {
+            tokens.check_equal(&["LIN_speed", "="])?;
+            data.bitrate = parse_real(tokens.next()?)?;
+            data.bitrate *= 1000.0;
+            tokens.check_equal(&["kbps", ";"])?;
+            if tokens.peek()? == "Channel_name" {
+                state = ParserState::ChannelName;
+            } else {
                 state = ParserState::Node;
             }
-            ParserState::Node => {
-                tokens.check_equal(&["Nodes", "{", "Master", ":"])?;
-                data.commander = tokens.next()?.to_string();
-                tokens.check_equal(&[","])?;
-                data.time_base = parse_real_or_integer(tokens.next()?)?;
-                tokens.check_equal(&["ms", ","])?;
-                data.jitter = parse_real_or_integer(tokens.next()?)?;
-                tokens.check_equal(&["ms", ";", "Slaves", ":"])?;
-                loop {
-                    data.responders
-                        .insert(tokens.next()?.to_string(), Default::default());
-                    let delim = tokens.next()?;
-                    if delim == ";" {
-                        break;
-                    } else if delim != "," {
-                        return Err(Error::IncorrectToken);
-                    }
-                }
-                tokens.check_equal(&["}"])?;
-                if tokens.peek()? == "composite" {
-                    state = ParserState::NodeComposition;
-                } else {
-                    state = ParserState::Signal;
+        }
+        ParserState::ChannelName => {
+            tokens.check_equal(&["Channel_name", "="])?;
+            data.postfix = tokens.next()?.to_string(); // spec says indentifier, but char_string used
+            tokens.check_equal(&[";"])?;
+            state = ParserState::Node;
+        }
+        ParserState::Node => {
+            tokens.check_equal(&["Nodes", "{", "Master", ":"])?;
+            data.commander = tokens.next_name()?;
+            tokens.check_equal(&[","])?;
+            data.time_base = parse_real(tokens.next()?)?;
+            tokens.check_equal(&["ms", ","])?;
+            data.jitter = parse_real(tokens.next()?)?;
+            tokens.check_equal(&["ms", ";", "Slaves", ":"])?;
+            loop {
+                data.responders
+                    .insert(tokens.next_name()?, Default::default());
+                let delim = tokens.next()?;
+                if delim == ";" {
+                    break;
+                } else if delim != "," {
+                    return Err(Error::IncorrectToken);
                 }
             }
-            ParserState::NodeComposition => {
-                warn!("node composition not supported yet, ignoring section"); // TODO support?
-                tokens.check_equal(&["composite", "{"])?;
-                let mut depth = 1;
-                while depth > 0 {
-                    match tokens.next()? {
-                        "{" => depth += 1,
-                        "}" => depth -= 1,
-                        _ => (),
-                    }
-                }
+            tokens.check_equal(&["}"])?;
+            if tokens.peek()? == "composite" {
+                state = ParserState::NodeComposition;
+            } else {
                 state = ParserState::Signal;
             }
-            ParserState::Signal => {
-                tokens.check_equal(&["Signals", "{"])?;
-                while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    tokens.check_equal(&[":"])?;
-                    let bit_width = parse_integer(tokens.next()?)? as u16;
-                    if bit_width > MAX_SIGNAL_WIDTH {
-                        return Err(Error::SignalTooWide);
+        }
+        ParserState::NodeComposition => {
+            sink.warning(Warning::new(
+                WarningKind::UnsupportedSection,
+                tokens.span(),
+                "node composition not supported yet, ignoring section".to_string(), // TODO support?
+            ));
+            tokens.check_equal(&["composite", "{"])?;
+            let mut depth = 1;
+            while depth > 0 {
+                match tokens.next()? {
+                    "{" => depth += 1,
+                    "}" => depth -= 1,
+                    _ => (),
+                }
+            }
+            state = ParserState::Signal;
+        }
+        ParserState::Signal => {
+            tokens.check_equal(&["Signals", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let start = tokens.consumed.start;
+                tokens.check_equal(&[":"])?;
+                let bit_width = parse_integer::<u16>(tokens.next()?)?;
+                tokens.check_equal(&[","])?;
+                let (kind, init_value);
+                if tokens.peek()? == "{" {
+                    // byte array, init_value holds the first 8 bytes
+                    tokens.next()?; // "{"
+                    let mut bytes = Vec::new();
+                    loop {
+                        bytes.push(parse_integer::<u8>(tokens.next()?)?);
+                        match tokens.next()? {
+                            "," => (),
+                            "}" => break,
+                            _ => return Err(Error::IncorrectToken),
+                        }
                     }
-                    tokens.check_equal(&[","])?;
-                    let init_value;
-                    if tokens.peek()? == "{" {
-                        warn!("init_value_array not supported yet, defaulting to 0"); // TODO support?
-                        init_value = 0;
-                        while tokens.next()? != "}" {}
-                    } else {
-                        init_value = parse_integer(tokens.next()?)?;
+                    if !bit_width.is_multiple_of(8) || bit_width > MAX_ARRAY_WIDTH {
+                        return Err(Error::SignalTooWide);
+                    } else if bytes.len() != bit_width as usize / 8 {
+                        return Err(Error::IncorrectToken);
                     }
+                    bytes.resize(8, 0);
+                    kind = ValueKind::ByteArray;
+                    init_value = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+                } else {
+                    kind = ValueKind::Unsigned;
+                    init_value = parse_integer::<u64>(tokens.next()?)?;
+                }
+                if kind != ValueKind::ByteArray && bit_width > MAX_SIGNAL_WIDTH {
+                    return Err(Error::SignalTooWide);
+                }
+                tokens.check_equal(&[","])?;
+                let _publisher = tokens.next()?; // unused, determined by Frames field
+                while tokens.peek()? != ";" {
                     tokens.check_equal(&[","])?;
-                    let _publisher = tokens.next()?; // unused, determined by Frames field
-                    while tokens.peek()? != ";" {
-                        tokens.check_equal(&[","])?;
-                        let subscriber = tokens.next()?;
-                        if data.responders.contains_key(subscriber) {
-                            data.responders
-                                .get_mut(subscriber)
-                                .unwrap()
-                                .subscribed_signals
-                                .push(name.clone());
-                        }
+                    let subscriber = tokens.next()?;
+                    if data.responders.contains_key(subscriber) {
+                        data.responders
+                            .get_mut(subscriber)
+                            .unwrap()
+                            .subscribed_signals
+                            .push(name.clone());
                     }
-                    tokens.next()?; // ";"
-                    db.signals.insert(
-                        name,
-                        Signal {
-                            signed: false,
-                            little_endian: true,
-                            bit_start: BIT_START_INVALID, // set later
-                            bit_width,
-                            init_value,
-                            encodings: None,
-                        },
-                    );
                 }
-                tokens.next()?; // "}"
-                if tokens.peek()? == "Diagnostic_signals" {
-                    state = ParserState::DiagnosticSignal;
-                } else {
-                    state = ParserState::Frame;
-                }
-            }
-            ParserState::DiagnosticSignal => {
-                #[rustfmt::skip]
-                tokens.check_equal(&[
-                    "Diagnostic_signals", "{",
-                        "MasterReqB0", ":", "8", ",", "0", ";",
-                        "MasterReqB1", ":", "8", ",", "0", ";",
-                        "MasterReqB2", ":", "8", ",", "0", ";",
-                        "MasterReqB3", ":", "8", ",", "0", ";",
-                        "MasterReqB4", ":", "8", ",", "0", ";",
-                        "MasterReqB5", ":", "8", ",", "0", ";",
-                        "MasterReqB6", ":", "8", ",", "0", ";",
-                        "MasterReqB7", ":", "8", ",", "0", ";",
-                        "SlaveRespB0", ":", "8", ",", "0", ";",
-                        "SlaveRespB1", ":", "8", ",", "0", ";",
-                        "SlaveRespB2", ":", "8", ",", "0", ";",
-                        "SlaveRespB3", ":", "8", ",", "0", ";",
-                        "SlaveRespB4", ":", "8", ",", "0", ";",
-                        "SlaveRespB5", ":", "8", ",", "0", ";",
-                        "SlaveRespB6", ":", "8", ",", "0", ";",
-                        "SlaveRespB7", ":", "8", ",", "0", ";",
-                    "}"
-                ])?;
+                tokens.next()?; // ";"
+                spans.signals.insert(name.clone(), tokens.since(start));
+                db.signals.insert(
+                    name,
+                    Signal {
+                        kind,
+                        little_endian: true,
+                        bit_start: BIT_START_INVALID, // set later
+                        bit_width,
+                        init_value,
+                        encodings: None,
+                        physical_limits: None,
+                        description: None,
+                        attributes: HashMap::new(),
+                    },
+                );
+            }
+            tokens.next()?; // "}"
+            if tokens.peek()? == "Diagnostic_signals" {
+                state = ParserState::DiagnosticSignal;
+            } else {
                 state = ParserState::Frame;
             }
-            ParserState::Frame => {
-                tokens.check_equal(&["Frames", "{"])?;
+        }
+        ParserState::DiagnosticSignal => {
+            // any order, widths must be 8 and missing bytes are implied
+            tokens.check_equal(&["Diagnostic_signals", "{"])?;
+            let mut seen = Vec::new();
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                tokens.check_equal(&[":"])?;
+                let width = parse_integer::<u16>(tokens.next()?)?;
+                tokens.check_equal(&[","])?;
+                let init = parse_integer::<u64>(tokens.next()?)?;
+                tokens.check_equal(&[";"])?;
+                let byte = diagnostic_byte(&name).ok_or(Error::Hint(
+                    format!("{} is not a diagnostic signal", name),
+                    Box::new(Error::UnknownSignal),
+                ))?;
+                if seen.contains(&byte) {
+                    return Err(Error::Hint(
+                        name.to_string(),
+                        Box::new(Error::DuplicateSignal),
+                    ));
+                } else if width != 8 {
+                    return Err(Error::Hint(
+                        format!("{} is {} bits, must be 8", name, width),
+                        Box::new(Error::InconsistentSignal),
+                    ));
+                } else if init != 0 {
+                    sink.warning(Warning::new(
+                        WarningKind::DefaultedValue,
+                        tokens.span(),
+                        format!("{} init value {} ignored, using 0", name, init),
+                    ));
+                }
+                seen.push(byte);
+            }
+            tokens.next()?; // "}"
+            for frame in 0..DIAGNOSTIC_FRAMES.len() {
+                warn_missing_diagnostic_bytes(tokens, sink, "diagnostic signals", frame, &seen);
+            }
+            state = ParserState::Frame;
+        }
+        ParserState::Frame => {
+            tokens.check_equal(&["Frames", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let start = tokens.consumed.start;
+                tokens.check_equal(&[":"])?;
+                let id = parse_integer::<u32>(tokens.next()?)?;
+                tokens.check_equal(&[","])?;
+                let sender = tokens.next_name()?;
+                tokens.check_equal(&[","])?;
+                let byte_width = parse_integer::<u16>(tokens.next()?)?;
+                tokens.check_equal(&["{"])?;
+                let mut signals = Vec::new();
                 while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    tokens.check_equal(&[":"])?;
-                    let id = parse_integer(tokens.next()?)? as u32;
-                    tokens.check_equal(&[","])?;
-                    let sender = tokens.next()?.to_string();
+                    let signal_name = tokens.next_name()?;
                     tokens.check_equal(&[","])?;
-                    let byte_width = parse_integer(tokens.next()?)? as u16;
-                    tokens.check_equal(&["{"])?;
-                    let mut signals = Vec::new();
-                    while tokens.peek()? != "}" {
-                        let signal_name = tokens.next()?.to_string();
-                        tokens.check_equal(&[","])?;
-                        let signal_offset = parse_integer(tokens.next()?)? as u16;
-                        tokens.check_equal(&[";"])?;
-                        if db.signals.contains_key(&signal_name) {
-                            if db.signals[&signal_name].bit_start == BIT_START_INVALID {
-                                db.signals.get_mut(&signal_name).unwrap().bit_start = signal_offset;
-                            } else {
-                                return Err(Error::DuplicateSignal);
-                            }
+                    let signal_offset = parse_integer::<u16>(tokens.next()?)?;
+                    tokens.check_equal(&[";"])?;
+                    if db.signals.contains_key(&signal_name) {
+                        if db.signals[&signal_name].bit_start == BIT_START_INVALID {
+                            db.signals.get_mut(&signal_name).unwrap().bit_start = signal_offset;
                         } else {
-                            return Err(Error::UnknownSignal);
+                            return Err(Error::DuplicateSignal);
                         }
-                        signals.push(signal_name);
+                    } else {
+                        return Err(Error::UnknownSignal);
                     }
-                    tokens.next()?; // "}"
-                    db.messages.insert(
-                        name,
-                        Message {
-                            sender,
-                            id,
-                            byte_width,
-                            signals,
-                            mux_signals: HashMap::new(), // none
-                        },
-                    );
+                    signals.push(signal_name);
                 }
                 tokens.next()?; // "}"
-                match tokens.peek()? {
-                    "Sporadic_frames" => state = ParserState::SporadicFrame,
-                    "Event_triggered_frames" => state = ParserState::EventTriggeredFrame,
-                    "Diagnostic_frames" => state = ParserState::DiagnosticFrame,
-                    _ => state = ParserState::NodeAttributes,
-                }
+                spans.frames.insert(name.clone(), tokens.since(start));
+                db.messages.insert(
+                    name,
+                    Message {
+                        sender,
+                        id,
+                        byte_width,
+                        signals,
+                        mux_signals: HashMap::new(), // none
+                        checksum: None,              // set once node protocols are known
+                        cycle_time: None,
+                        send_type: None,
+                        protocol: Default::default(),
+                        description: None,
+                        attributes: HashMap::new(),
+                        signal_groups: Default::default(),
+                    },
+                );
             }
-            ParserState::SporadicFrame => {
-                tokens.check_equal(&["Sporadic_frames", "{"])?;
-                while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    tokens.check_equal(&[":"])?;
-                    let mut frames = vec![tokens.next()?.to_string()]; // at least one frame
-                    while tokens.peek()? != ";" {
-                        tokens.check_equal(&[","])?;
-                        let f = tokens.next()?.to_string();
-                        if !db.messages.contains_key(&f) {
-                            return Err(Error::UnknownFrame);
-                        } else if db.messages[&f].sender != data.commander {
-                            return Err(Error::SporadicFrameHasResponder);
-                        } else if frames.contains(&f) {
-                            return Err(Error::DuplicateFrame);
-                        }
-                        frames.push(f);
+            tokens.next()?; // "}"
+            match tokens.peek()? {
+                "Sporadic_frames" => state = ParserState::SporadicFrame,
+                "Event_triggered_frames" => state = ParserState::EventTriggeredFrame,
+                "Diagnostic_frames" => state = ParserState::DiagnosticFrame,
+                _ => state = ParserState::NodeAttributes,
+            }
+        }
+        ParserState::SporadicFrame => {
+            tokens.check_equal(&["Sporadic_frames", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let start = tokens.consumed.start;
+                tokens.check_equal(&[":"])?;
+                let mut frames = vec![tokens.next_name()?]; // at least one frame
+                while tokens.peek()? != ";" {
+                    tokens.check_equal(&[","])?;
+                    let f = tokens.next_name()?;
+                    if !db.messages.contains_key(&f) {
+                        return Err(Error::UnknownFrame);
+                    } else if db.messages[&f].sender != data.commander {
+                        return Err(Error::SporadicFrameHasResponder);
+                    } else if frames.contains(&f) {
+                        return Err(Error::DuplicateFrame);
                     }
-                    tokens.next()?; // ";"
-                    if db.messages.contains_key(&name) || data.sporadic_frames.contains_key(&name) {
+                    frames.push(f);
+                }
+                tokens.next()?; // ";"
+                if db.messages.contains_key(&name) || data.sporadic_frames.contains_key(&name) {
+                    return Err(Error::DuplicateFrame);
+                } else {
+                    spans.frames.insert(name.clone(), tokens.since(start));
+                    data.sporadic_frames.insert(name, frames);
+                }
+            }
+            tokens.next()?; // "}"
+            match tokens.peek()? {
+                "Event_triggered_frames" => state = ParserState::EventTriggeredFrame,
+                "Diagnostic_frames" => state = ParserState::DiagnosticFrame,
+                _ => state = ParserState::NodeAttributes,
+            }
+        }
+        ParserState::EventTriggeredFrame => {
+            tokens.check_equal(&["Event_triggered_frames", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let start = tokens.consumed.start;
+                tokens.check_equal(&[":"])?;
+                let resolver = tokens.next_name()?;
+                tokens.check_equal(&[","])?;
+                let id = parse_integer::<u32>(tokens.next()?)?;
+                let mut frames = Vec::new();
+                while tokens.peek()? != ";" {
+                    tokens.check_equal(&[","])?;
+                    let f = tokens.next_name()?;
+                    if frames.contains(&f) {
                         return Err(Error::DuplicateFrame);
+                    } else if db.messages.contains_key(&f) {
+                        frames.push(f);
                     } else {
-                        data.sporadic_frames.insert(name, frames);
+                        return Err(Error::NotUnconditionalFrame);
                     }
                 }
-                tokens.next()?; // "}"
-                match tokens.peek()? {
-                    "Event_triggered_frames" => state = ParserState::EventTriggeredFrame,
-                    "Diagnostic_frames" => state = ParserState::DiagnosticFrame,
-                    _ => state = ParserState::NodeAttributes,
+                tokens.next()?; // ";"
+                let all_same_len = match frames.first() {
+                    Some(first) => {
+                        let width = db.messages[first].byte_width;
+                        frames.iter().all(|f| db.messages[f].byte_width == width)
+                    }
+                    None => true,
+                };
+                if db.messages.contains_key(&name)
+                    || data.sporadic_frames.contains_key(&name)
+                    || data.event_frames.contains_key(&name)
+                {
+                    return Err(Error::DuplicateFrame);
+                } else if all_same_len {
+                    spans.frames.insert(name.clone(), tokens.since(start));
+                    data.event_frames.insert(name, (resolver, id, frames));
+                } else {
+                    return Err(Error::EventFrameDifferentLength);
                 }
             }
-            ParserState::EventTriggeredFrame => {
-                tokens.check_equal(&["Event_triggered_frames", "{"])?;
+            tokens.next()?; // "}"
+            match tokens.peek()? {
+                "Diagnostic_frames" => state = ParserState::DiagnosticFrame,
+                _ => state = ParserState::NodeAttributes,
+            }
+        }
+        ParserState::DiagnosticFrame => {
+            // any order, but ids and offsets are fixed by the spec
+            tokens.check_equal(&["Diagnostic_frames", "{"])?;
+            let mut frames = Vec::new();
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let frame = DIAGNOSTIC_FRAMES
+                    .iter()
+                    .position(|(n, _, _)| **n == *name)
+                    .ok_or(Error::Hint(
+                        format!("{} is not a diagnostic frame", name),
+                        Box::new(Error::UnknownFrame),
+                    ))?;
+                if frames.contains(&frame) {
+                    return Err(Error::Hint(
+                        name.to_string(),
+                        Box::new(Error::DuplicateFrame),
+                    ));
+                }
+                frames.push(frame);
+                tokens.check_equal(&[":"])?;
+                let id = parse_integer::<u32>(tokens.next()?)?;
+                let expected = DIAGNOSTIC_FRAMES[frame].1;
+                if id != expected {
+                    return Err(Error::Hint(
+                        format!("{} is 0x{:02X}, must be 0x{:02X}", name, id, expected),
+                        Box::new(Error::IncorrectToken),
+                    ));
+                }
+                tokens.check_equal(&["{"])?;
+                let mut seen = Vec::new();
                 while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    tokens.check_equal(&[":"])?;
-                    let resolver = tokens.next()?.to_string();
+                    let signal = tokens.next_name()?;
                     tokens.check_equal(&[","])?;
-                    let id = parse_integer(tokens.next()?)? as u32;
-                    let mut frames = Vec::new();
-                    while tokens.peek()? != ";" {
-                        tokens.check_equal(&[","])?;
-                        let f = tokens.next()?.to_string();
-                        if frames.contains(&f) {
-                            return Err(Error::DuplicateFrame);
-                        } else if db.messages.contains_key(&f) {
-                            frames.push(f);
-                        } else {
-                            return Err(Error::NotUnconditionalFrame);
-                        }
-                    }
-                    tokens.next()?; // ";"
-                    let all_same_len;
-                    if frames.is_empty() {
-                        all_same_len = true;
-                    } else {
-                        let first = db.messages[&frames[0]].byte_width;
-                        all_same_len = frames.iter().all(|f| db.messages[f].byte_width == first);
-                    }
-                    if db.messages.contains_key(&name)
-                        || data.sporadic_frames.contains_key(&name)
-                        || data.event_frames.contains_key(&name)
-                    {
-                        return Err(Error::DuplicateFrame);
-                    } else if all_same_len {
-                        data.event_frames.insert(name, (resolver, id, frames));
-                    } else {
-                        return Err(Error::EventFrameDifferentLength);
+                    let offset = parse_integer::<u16>(tokens.next()?)?;
+                    tokens.check_equal(&[";"])?;
+                    let byte = diagnostic_byte(&signal)
+                        .filter(|(f, _)| *f == frame)
+                        .ok_or(Error::Hint(
+                            format!("{} in {}", signal, name),
+                            Box::new(Error::UnknownSignal),
+                        ))?;
+                    if seen.contains(&byte) {
+                        return Err(Error::Hint(
+                            format!("{} in {}", signal, name),
+                            Box::new(Error::DuplicateSignal),
+                        ));
+                    } else if offset != byte.1 * 8 {
+                        return Err(Error::Hint(
+                            format!("{} at bit {}, must be {}", signal, offset, byte.1 * 8),
+                            Box::new(Error::InconsistentSignal),
+                        ));
                     }
+                    seen.push(byte);
                 }
                 tokens.next()?; // "}"
-                match tokens.peek()? {
-                    "Diagnostic_frames" => state = ParserState::DiagnosticFrame,
-                    _ => state = ParserState::NodeAttributes,
-                }
-            }
-            ParserState::DiagnosticFrame => {
-                #[rustfmt::skip]
-                tokens.check_equal(&[
-                    "Diagnostic_frames", "{",
-                        "MasterReq", ":", "60", "{",
-                            "MasterReqB0", ",", "0", ";",
-                            "MasterReqB1", ",", "8", ";",
-                            "MasterReqB2", ",", "16", ";",
-                            "MasterReqB3", ",", "24", ";",
-                            "MasterReqB4", ",", "32", ";",
-                            "MasterReqB5", ",", "40", ";",
-                            "MasterReqB6", ",", "48", ";",
-                            "MasterReqB7", ",", "56", ";",
-                        "}",
-                        "SlaveResp", ":", "61", "{",
-                            "SlaveRespB0", ",", "0", ";",
-                            "SlaveRespB1", ",", "8", ";",
-                            "SlaveRespB2", ",", "16", ";",
-                            "SlaveRespB3", ",", "24", ";",
-                            "SlaveRespB4", ",", "32", ";",
-                            "SlaveRespB5", ",", "40", ";",
-                            "SlaveRespB6", ",", "48", ";",
-                            "SlaveRespB7", ",", "56", ";",
-                        "}",
-                    "}"
-                ])?;
-                state = ParserState::NodeAttributes;
-            }
-            ParserState::NodeAttributes => {
-                tokens.check_equal(&["Node_attributes", "{"])?;
-                while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    if !data.responders.contains_key(&name) {
-                        return Err(Error::UnknownNode);
+                warn_missing_diagnostic_bytes(tokens, sink, &name, frame, &seen);
+            }
+            tokens.next()?; // "}"
+            for (frame, (name, _, _)) in DIAGNOSTIC_FRAMES.iter().enumerate() {
+                if !frames.contains(&frame) {
+                    sink.warning(Warning::new(
+                        WarningKind::DefaultedValue,
+                        tokens.span(),
+                        format!(
+                            "diagnostic frame {} missing, assuming the standard one",
+                            name
+                        ),
+                    ));
+                }
+            }
+            state = ParserState::NodeAttributes;
+        }
+        ParserState::NodeAttributes => {
+            tokens.check_equal(&["Node_attributes", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let start = tokens.consumed.start;
+                if !data.responders.contains_key(&name) {
+                    return Err(Error::UnknownNode);
+                }
+                let resp = data.responders.get_mut(&name).unwrap();
+                tokens.check_equal(&["{", "LIN_protocol", "="])?;
+                resp.protocol = parse_char_string(tokens.next()?);
+                tokens.check_equal(&[";", "configured_NAD", "="])?;
+                resp.configured_nad = parse_integer::<u8>(tokens.next()?)?;
+                tokens.check_equal(&[";"])?;
+                if tokens.peek()? == "initial_NAD" {
+                    tokens.check_equal(&["initial_NAD", "="])?;
+                    resp.initial_nad = Some(parse_integer::<u8>(tokens.next()?)?);
+                    tokens.check_equal(&[";"])?;
+                }
+                if resp.protocol.starts_with("2.") {
+                    tokens.check_equal(&["product_id", "="])?;
+                    let supplier_id = parse_integer::<u16>(tokens.next()?)?;
+                    tokens.check_equal(&[","])?;
+                    let function_id = parse_integer::<u16>(tokens.next()?)?;
+                    let variant = if tokens.peek()? == "," {
+                        tokens.next()?; // ","
+                        parse_integer::<u8>(tokens.next()?)?
+                    } else {
+                        0
+                    };
+                    resp.product_id = Some((supplier_id, function_id, variant));
+                    tokens.check_equal(&[";", "response_error", "="])?;
+                    let response_error = tokens.next_name()?;
+                    if db.signals.contains_key(&response_error) {
+                        resp.response_error = Some(response_error);
+                    } else {
+                        return Err(Error::UnknownSignal);
                     }
-                    let resp = data.responders.get_mut(&name).unwrap();
-                    tokens.check_equal(&["{", "LIN_protocol", "="])?;
-                    let protocol = tokens.next()?.to_string();
-                    tokens.check_equal(&[";", "configured_NAD", "="])?;
-                    resp.configured_nad = parse_integer(tokens.next()?)? as u8;
                     tokens.check_equal(&[";"])?;
-                    if tokens.peek()? == "initial_NAD" {
-                        tokens.check_equal(&["initial_NAD", "="])?;
-                        resp.initial_nad = Some(parse_integer(tokens.next()?)? as u8);
-                        tokens.check_equal(&[";"])?;
+                    for s in [
+                        "fault_state_signals",
+                        "P2_min",
+                        "ST_min",
+                        "N_As_timeout",
+                        "N_Cr_timeout",
+                    ] {
+                        if tokens.peek()? == s {
+                            sink.warning(Warning::new(
+                                WarningKind::UnsupportedAttribute,
+                                tokens.span(),
+                                format!("{} not supported yet, ignoring", s), // TODO support?
+                            ));
+                            tokens.check_equal(&[s, "="])?;
+                            while tokens.next()? != ";" {}
+                        }
                     }
-                    if protocol.starts_with("\"2.") {
-                        tokens.check_equal(&["product_id", "="])?;
-                        let supplier_id = parse_integer(tokens.next()?)? as u16;
-                        tokens.check_equal(&[","])?;
-                        let function_id = parse_integer(tokens.next()?)? as u16;
-                        let variant;
-                        if tokens.peek()? == "," {
-                            tokens.next()?; // ","
-                            variant = parse_integer(tokens.next()?)? as u8;
-                        } else {
-                            variant = 0;
+                    tokens.check_equal(&["configurable_frames", "{"])?;
+                    while tokens.peek()? != "}" {
+                        let frame = tokens.next_name()?;
+                        if !db.messages.contains_key(&frame)
+                            && !data.event_frames.contains_key(&frame)
+                        {
+                            return Err(Error::UnknownFrame);
                         }
-                        resp.product_id = Some((supplier_id, function_id, variant));
-                        tokens.check_equal(&[";", "response_error", "="])?;
-                        let response_error = tokens.next()?.to_string();
-                        if db.signals.contains_key(&response_error) {
-                            resp.response_error = Some(response_error);
+                        let id = if tokens.peek()? == "=" {
+                            tokens.next()?; // "="
+                            Some(parse_integer::<u16>(tokens.next()?)?)
                         } else {
-                            return Err(Error::UnknownSignal);
-                        }
+                            None
+                        };
                         tokens.check_equal(&[";"])?;
-                        for s in [
-                            "fault_state_signals",
-                            "P2_min",
-                            "ST_min",
-                            "N_As_timeout",
-                            "N_Cr_timeout",
-                        ] {
-                            if tokens.peek()? == s {
-                                warn!("{} not supported yet, ignoring", s); // TODO support?
-                                tokens.check_equal(&[s, "="])?;
-                                while tokens.next()? != ";" {}
-                            }
-                        }
-                        tokens.check_equal(&["configurable_frames", "{"])?;
-                        while tokens.peek()? != "}" {
-                            let frame = tokens.next()?.to_string();
-                            if !db.messages.contains_key(&frame)
-                                && !data.event_frames.contains_key(&frame)
-                            {
-                                return Err(Error::UnknownFrame);
-                            }
-                            let id;
-                            if tokens.peek()? == "=" {
-                                tokens.next()?; // "="
-                                id = Some(parse_integer(tokens.next()?)? as u16);
-                            } else {
-                                id = None;
-                            }
-                            tokens.check_equal(&[";"])?;
-                            resp.configurable_frames.push((frame, id));
-                        }
-                        tokens.next()?; // "}"
+                        resp.configurable_frames.push((frame, id));
                     }
                     tokens.next()?; // "}"
                 }
                 tokens.next()?; // "}"
-                state = ParserState::ScheduleTable;
+                spans.nodes.insert(name, tokens.since(start));
             }
-            ParserState::ScheduleTable => {
-                tokens.check_equal(&["Schedule_tables", "{"])?;
+            tokens.next()?; // "}"
+            state = ParserState::ScheduleTable;
+        }
+        ParserState::ScheduleTable => {
+            tokens.check_equal(&["Schedule_tables", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let start = tokens.consumed.start;
+                let mut table = Vec::new();
+                let mut entries = Vec::new();
+                tokens.check_equal(&["{"])?;
                 while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    let mut table = Vec::new();
-                    tokens.check_equal(&["{"])?;
-                    while tokens.peek()? != "}" {
-                        let cmd = tokens.next()?.to_string();
-                        let command;
-                        match cmd.as_str() {
-                            "MasterReq" => command = LDFScheduleCommand::CommanderReq,
-                            "SlaveResp" => command = LDFScheduleCommand::ResponderResp,
-                            "AssignNAD" => {
-                                tokens.check_equal(&["{"])?;
-                                let node = tokens.next()?.to_string();
-                                if !data.responders.contains_key(&node) {
-                                    return Err(Error::UnknownNode);
-                                }
-                                tokens.check_equal(&["}"])?;
-                                command = LDFScheduleCommand::AssignNAD(node);
+                    let cmd = tokens.next_name()?;
+                    let entry_start = tokens.consumed.start;
+                    let command;
+                    match &*cmd {
+                        "MasterReq" => command = LDFScheduleCommand::CommanderReq,
+                        "SlaveResp" => command = LDFScheduleCommand::ResponderResp,
+                        "AssignNAD" => {
+                            tokens.check_equal(&["{"])?;
+                            let node = tokens.next_name()?;
+                            if !data.responders.contains_key(&node) {
+                                return Err(Error::UnknownNode);
                             }
-                            "ConditionalChangeNAD" => {
-                                tokens.check_equal(&["{"])?;
-                                let mut fields = [0; 6];
-                                for i in 0..fields.len() {
-                                    fields[i] = parse_integer(tokens.next()?)? as u8;
-                                    if i != fields.len() - 1 {
-                                        tokens.check_equal(&[","])?;
-                                    } else {
-                                        tokens.check_equal(&["}"])?;
-                                    }
+                            tokens.check_equal(&["}"])?;
+                            command = LDFScheduleCommand::AssignNAD(node);
+                        }
+                        "ConditionalChangeNAD" => {
+                            tokens.check_equal(&["{"])?;
+                            let mut fields = [0; 6];
+                            for i in 0..fields.len() {
+                                fields[i] = parse_integer::<u8>(tokens.next()?)?;
+                                if i != fields.len() - 1 {
+                                    tokens.check_equal(&[","])?;
+                                } else {
+                                    tokens.check_equal(&["}"])?;
                                 }
-                                command = LDFScheduleCommand::ConditionalChangeNAD {
-                                    nad: fields[0],
-                                    id: fields[1],
-                                    byte: fields[2],
-                                    mask: fields[3],
-                                    inv: fields[4],
-                                    new_nad: fields[5],
-                                };
                             }
-                            "DataDump" => {
-                                tokens.check_equal(&["{"])?;
-                                let node = tokens.next()?.to_string();
-                                if !data.responders.contains_key(&node) {
-                                    return Err(Error::UnknownNode);
+                            command = LDFScheduleCommand::ConditionalChangeNAD {
+                                nad: fields[0],
+                                id: fields[1],
+                                byte: fields[2],
+                                mask: fields[3],
+                                inv: fields[4],
+                                new_nad: fields[5],
+                            };
+                        }
+                        "DataDump" => {
+                            tokens.check_equal(&["{"])?;
+                            let node = tokens.next_name()?;
+                            if !data.responders.contains_key(&node) {
+                                return Err(Error::UnknownNode);
+                            }
+                            tokens.check_equal(&[","])?;
+                            let mut d = [0; 5];
+                            for i in 0..d.len() {
+                                d[i] = parse_integer::<u8>(tokens.next()?)?;
+                                if i != d.len() - 1 {
+                                    tokens.check_equal(&[","])?;
+                                } else {
+                                    tokens.check_equal(&["}"])?;
                                 }
-                                tokens.check_equal(&[","])?;
-                                let mut d = [0; 5];
-                                for i in 0..d.len() {
-                                    d[i] = parse_integer(tokens.next()?)? as u8;
-                                    if i != d.len() - 1 {
+                            }
+                            command = LDFScheduleCommand::DataDump {
+                                name: node,
+                                data: d,
+                            };
+                        }
+                        "SaveConfiguration" => {
+                            tokens.check_equal(&["{"])?;
+                            let node = tokens.next_name()?;
+                            if !data.responders.contains_key(&node) {
+                                return Err(Error::UnknownNode);
+                            }
+                            tokens.check_equal(&["}"])?;
+                            command = LDFScheduleCommand::SaveConfiguration(node);
+                        }
+                        "AssignFrameIdRange" => {
+                            tokens.check_equal(&["{"])?;
+                            let node = tokens.next_name()?;
+                            if !data.responders.contains_key(&node) {
+                                return Err(Error::UnknownNode);
+                            }
+                            tokens.check_equal(&[","])?;
+                            let index = parse_integer::<u8>(tokens.next()?)?;
+                            let mut pid = [0; 4];
+                            if tokens.peek()? == "," {
+                                tokens.next()?; // ","
+                                for i in 0..pid.len() {
+                                    pid[i] = parse_integer::<u8>(tokens.next()?)?;
+                                    if i != pid.len() - 1 {
                                         tokens.check_equal(&[","])?;
                                     } else {
                                         tokens.check_equal(&["}"])?;
                                     }
                                 }
-                                command = LDFScheduleCommand::DataDump {
-                                    name: node,
-                                    data: d,
-                                };
-                            }
-                            "SaveConfiguration" => {
-                                tokens.check_equal(&["{"])?;
-                                let node = tokens.next()?.to_string();
-                                if !data.responders.contains_key(&node) {
-                                    return Err(Error::UnknownNode);
-                                }
+                            } else {
+                                sink.warning(Warning::new(
+                                    WarningKind::DefaultedValue,
+                                    tokens.span(),
+                                    "getting PID from configurable_frames not supported yet, default to 0xFF"
+                                        .to_string(), // TODO support?
+                                ));
+                                pid = [0xFF, 0xFF, 0xFF, 0xFF];
                                 tokens.check_equal(&["}"])?;
-                                command = LDFScheduleCommand::SaveConfiguration(node);
                             }
-                            "AssignFrameIdRange" => {
-                                tokens.check_equal(&["{"])?;
-                                let node = tokens.next()?.to_string();
-                                if !data.responders.contains_key(&node) {
-                                    return Err(Error::UnknownNode);
-                                }
-                                tokens.check_equal(&[","])?;
-                                let index = parse_integer(tokens.next()?)? as u8;
-                                let mut pid = [0; 4];
-                                if tokens.peek()? == "," {
-                                    tokens.next()?; // ","
-                                    for i in 0..pid.len() {
-                                        pid[i] = parse_integer(tokens.next()?)? as u8;
-                                        if i != pid.len() - 1 {
-                                            tokens.check_equal(&[","])?;
-                                        } else {
-                                            tokens.check_equal(&["}"])?;
-                                        }
-                                    }
+                            command = LDFScheduleCommand::AssignFrameIdRange {
+                                name: node,
+                                index,
+                                pid,
+                            };
+                        }
+                        "FreeFormat" => {
+                            tokens.check_equal(&["{"])?;
+                            let mut d = [0; 8];
+                            for i in 0..d.len() {
+                                d[i] = parse_integer::<u8>(tokens.next()?)?;
+                                if i != d.len() - 1 {
+                                    tokens.check_equal(&[","])?;
                                 } else {
-                                    warn!("getting PID from configurable_frames not supported yet, default to 0xFF"); // TODO support?
-                                    pid = [0xFF, 0xFF, 0xFF, 0xFF];
                                     tokens.check_equal(&["}"])?;
                                 }
-                                command = LDFScheduleCommand::AssignFrameIdRange {
-                                    name: node,
-                                    index,
-                                    pid,
-                                };
                             }
-                            "FreeFormat" => {
-                                tokens.check_equal(&["{"])?;
-                                let mut d = [0; 8];
-                                for i in 0..d.len() {
-                                    d[i] = parse_integer(tokens.next()?)? as u8;
-                                    if i != d.len() - 1 {
-                                        tokens.check_equal(&[","])?;
-                                    } else {
-                                        tokens.check_equal(&["}"])?;
-                                    }
-                                }
-                                command = LDFScheduleCommand::FreeFormat(d);
+                            command = LDFScheduleCommand::FreeFormat(d);
+                        }
+                        "AssignFrameId" => {
+                            tokens.check_equal(&["{"])?;
+                            let node = tokens.next_name()?;
+                            if !data.responders.contains_key(&node) {
+                                return Err(Error::UnknownNode);
                             }
-                            "AssignFrameId" => {
-                                tokens.check_equal(&["{"])?;
-                                let node = tokens.next()?.to_string();
-                                if !data.responders.contains_key(&node) {
-                                    return Err(Error::UnknownNode);
-                                }
-                                tokens.check_equal(&[","])?;
-                                let frame = tokens.next()?.to_string();
-                                if !db.messages.contains_key(&frame) {
-                                    return Err(Error::UnknownFrame);
-                                }
-                                tokens.check_equal(&["}"])?;
-                                command = LDFScheduleCommand::AssignFrameId { node, frame };
+                            tokens.check_equal(&[","])?;
+                            let frame = tokens.next_name()?;
+                            if !db.messages.contains_key(&frame) {
+                                return Err(Error::UnknownFrame);
                             }
-                            _ => {
-                                if !db.messages.contains_key(&cmd)
-                                    && !data.sporadic_frames.contains_key(&cmd)
-                                    && !data.event_frames.contains_key(&cmd)
-                                {
-                                    return Err(Error::UnknownFrame);
-                                }
-                                command = LDFScheduleCommand::Frame(cmd);
+                            tokens.check_equal(&["}"])?;
+                            command = LDFScheduleCommand::AssignFrameId { node, frame };
+                        }
+                        "AssignNADviaSNPD" | "SNPD" => {
+                            tokens.check_equal(&["{"])?;
+                            let step: SNPDStep = tokens.next()?.parse()?;
+                            tokens.check_equal(&[","])?;
+                            let supplier = parse_integer::<u16>(tokens.next()?)?;
+                            let mut nad = None;
+                            if tokens.peek()? == "," {
+                                tokens.next()?; // ","
+                                nad = Some(Nad::new(parse_integer::<u8>(tokens.next()?)?)?.get());
+                            }
+                            if (step == SNPDStep::AssignNAD) != nad.is_some() {
+                                return Err(Error::Hint(
+                                    "only AssignNAD steps take a NAD".to_string(),
+                                    Box::new(Error::IncorrectToken),
+                                ));
+                            }
+                            tokens.check_equal(&["}"])?;
+                            command = LDFScheduleCommand::AssignNADviaSNPD {
+                                step,
+                                supplier,
+                                nad,
+                            };
+                        }
+                        _ => {
+                            if !db.messages.contains_key(&cmd)
+                                && !data.sporadic_frames.contains_key(&cmd)
+                                && !data.event_frames.contains_key(&cmd)
+                            {
+                                return Err(Error::UnknownFrame);
                             }
+                            command = LDFScheduleCommand::Frame(cmd);
                         }
-                        tokens.check_equal(&["delay"])?;
-                        let frame_time = parse_real_or_integer(tokens.next()?)?;
-                        tokens.check_equal(&["ms", ";"])?;
-                        table.push((command, frame_time));
                     }
-                    tokens.next()?; // "}"
-                    data.schedule_tables.insert(name, table);
+                    tokens.check_equal(&["delay"])?;
+                    let frame_time = parse_real(tokens.next()?)?;
+                    tokens.check_equal(&["ms", ";"])?;
+                    table.push((command, frame_time));
+                    entries.push(tokens.since(entry_start));
                 }
                 tokens.next()?; // "}"
-                if let Ok(tok) = tokens.peek() {
-                    match tok {
-                        "Signal_groups" => state = ParserState::SignalGroups,
-                        "Signal_encoding_types" => state = ParserState::SignalEncodingTypes,
-                        "Signal_representation" => state = ParserState::SignalRepresentation,
-                        _ => return Err(Error::UnexpectedToken),
-                    }
-                } else {
-                    state = ParserState::Done;
+                spans
+                    .schedule_tables
+                    .insert(name.clone(), tokens.since(start));
+                spans.schedule_entries.insert(name.clone(), entries);
+                data.schedule_tables.insert(name, table);
+            }
+            tokens.next()?; // "}"
+            if let Ok(tok) = tokens.peek() {
+                match tok {
+                    "Signal_groups" => state = ParserState::SignalGroups,
+                    "Signal_encoding_types" => state = ParserState::SignalEncodingTypes,
+                    "Signal_representation" => state = ParserState::SignalRepresentation,
+                    _ => return Err(Error::UnexpectedToken),
                 }
+            } else {
+                state = ParserState::Done;
             }
-            ParserState::SignalGroups => {
-                warn!("signal groups deprecated, ignoring section");
-                tokens.check_equal(&["Signal_groups", "{"])?;
-                let mut depth = 1;
-                while depth > 0 {
-                    match tokens.next()? {
-                        "{" => depth += 1,
-                        "}" => depth -= 1,
-                        _ => (),
+        }
+        ParserState::SignalGroups => {
+            // deprecated since LIN 2.0, each group goes to the frame carrying all of its signals
+            tokens.check_equal(&["Signal_groups", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next_name()?;
+                let span = tokens.consumed;
+                tokens.check_equal(&[":"])?;
+                parse_integer::<u16>(tokens.next()?)?; // size, implied by the signals
+                tokens.check_equal(&["{"])?;
+                let mut signals = Vec::new();
+                while tokens.peek()? != "}" {
+                    let signal = tokens.next_name()?;
+                    if !db.signals.contains_key(&signal) {
+                        return Err(Error::UnknownSignal);
                     }
+                    tokens.check_equal(&[","])?;
+                    parse_integer::<u16>(tokens.next()?)?; // offset, implied by the frame
+                    tokens.check_equal(&[";"])?;
+                    signals.push(signal);
                 }
-                if let Ok(tok) = tokens.peek() {
-                    match tok {
-                        "Signal_encoding_types" => state = ParserState::SignalEncodingTypes,
-                        "Signal_representation" => state = ParserState::SignalRepresentation,
-                        _ => return Err(Error::UnexpectedToken),
+                tokens.next()?; // "}"
+                let frame = db
+                    .messages
+                    .values_mut()
+                    .find(|m| signals.iter().all(|s| m.signals.contains(s)));
+                match frame {
+                    Some(msg) => {
+                        msg.signal_groups.insert(
+                            name,
+                            SignalGroup {
+                                signals,
+                                repetitions: 1,
+                            },
+                        );
                     }
-                } else {
-                    state = ParserState::Done;
+                    None => sink.warning(Warning::new(
+                        WarningKind::UnsupportedSection,
+                        span,
+                        format!("no frame carries all of signal group {}, ignoring", name),
+                    )),
+                }
+            }
+            tokens.next()?; // "}"
+            if let Ok(tok) = tokens.peek() {
+                match tok {
+                    "Signal_encoding_types" => state = ParserState::SignalEncodingTypes,
+                    "Signal_representation" => state = ParserState::SignalRepresentation,
+                    _ => return Err(Error::UnexpectedToken),
                 }
+            } else {
+                state = ParserState::Done;
             }
-            ParserState::SignalEncodingTypes => {
-                tokens.check_equal(&["Signal_encoding_types", "{"])?;
+        }
+        ParserState::SignalEncodingTypes => {
+            tokens.check_equal(&["Signal_encoding_types", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next()?.to_string();
+                let start = tokens.consumed.start;
+                if encodings.contains_key(&name) {
+                    return Err(Error::DuplicateEncoding);
+                }
+                encodings.insert(name.clone(), Vec::new());
+                tokens.check_equal(&["{"])?;
+                let header = tokens.trailing_comment(); // the enum's description
+                let mut map = HashMap::new();
+                let mut rev_map = HashMap::new();
                 while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    if encodings.contains_key(&name) {
-                        return Err(Error::DuplicateEncoding);
-                    }
-                    encodings.insert(name.clone(), Vec::new());
-                    tokens.check_equal(&["{"])?;
-                    let mut map = HashMap::new();
-                    let mut rev_map = HashMap::new();
-                    while tokens.peek()? != "}" {
-                        match tokens.next()? {
-                            "logical_value" => {
-                                tokens.check_equal(&[","])?;
-                                let val = parse_integer(tokens.next()?)?;
-                                if tokens.peek()? == "," {
-                                    tokens.next()?; // ","
-                                    let s = tokens.next()?.to_string();
-                                    map.insert(s.clone(), val); // for encoding, just use last val
-                                    if rev_map.contains_key(&val) {
-                                        return Err(Error::DuplicateEncoding); // for decoding, avoid ambiguity
-                                    }
-                                    rev_map.insert(val, s);
-                                } else {
-                                    warn!("logical value w/o text, ignoring"); // opinionated take :)
-                                }
-                            }
-                            "physical_value" => {
-                                tokens.check_equal(&[","])?;
-                                let raw_min = parse_integer(tokens.next()?)?;
-                                tokens.check_equal(&[","])?;
-                                let raw_max = parse_integer(tokens.next()?)?;
-                                tokens.check_equal(&[","])?;
-                                let scale = parse_real_or_integer(tokens.next()?)?;
-                                tokens.check_equal(&[","])?;
-                                let offset = parse_real_or_integer(tokens.next()?)?;
-                                let unit;
-                                if tokens.peek()? == "," {
-                                    tokens.next()?; // ","
-                                    unit = tokens.next()?.to_string();
-                                } else {
-                                    unit = "".to_string();
+                    match tokens.next()? {
+                        "logical_value" => {
+                            tokens.check_equal(&[","])?;
+                            let val = parse_integer::<u64>(tokens.next()?)?;
+                            if tokens.peek()? == "," {
+                                tokens.next()?; // ","
+                                let s = parse_char_string(tokens.next()?);
+                                map.insert(s.clone(), val); // for encoding, just use last val
+                                if rev_map.contains_key(&val) {
+                                    return Err(Error::DuplicateEncoding); // for decoding, avoid ambiguity
                                 }
-                                encodings.get_mut(&name).unwrap().push(Encoding::Scalar {
-                                    raw_min,
-                                    raw_max,
-                                    scale,
-                                    offset,
-                                    unit,
-                                });
-                            }
-                            "bcd_value" => {
-                                warn!("bcd encoding not supported, ignoring");
-                            }
-                            "ascii_value" => {
-                                warn!("ascii encoding not supported, ignoring");
+                                rev_map.insert(val, s);
+                            } else {
+                                sink.warning(Warning::new(
+                                    WarningKind::IgnoredEncoding,
+                                    tokens.span(),
+                                    "logical value w/o text, ignoring".to_string(), // opinionated take :)
+                                ));
                             }
-                            _ => return Err(Error::IncorrectToken),
                         }
-                        tokens.check_equal(&[";"])?;
-                    }
-                    tokens.next()?; // "}"
-                    if !map.is_empty() {
-                        encodings.get_mut(&name).unwrap().push(Encoding::Enum {
-                            name,
-                            map,
-                            rev_map,
-                        });
+                        "physical_value" => {
+                            tokens.check_equal(&[","])?;
+                            let raw_min = parse_integer::<u64>(tokens.next()?)?;
+                            tokens.check_equal(&[","])?;
+                            let raw_max = parse_integer::<u64>(tokens.next()?)?;
+                            tokens.check_equal(&[","])?;
+                            let scale = parse_real(tokens.next()?)?;
+                            tokens.check_equal(&[","])?;
+                            let offset = parse_real(tokens.next()?)?;
+                            let unit = if tokens.peek()? == "," {
+                                tokens.next()?; // ","
+                                parse_char_string(tokens.next()?)
+                            } else {
+                                String::new()
+                            };
+                            // LDF has no place for a description, a comment after the entry is taken as one
+                            tokens.check_equal(&[";"])?;
+                            let description = tokens.trailing_comment().map(|(span, text)| {
+                                spans.descriptions.push(span);
+                                text
+                            });
+                            encodings.get_mut(&name).unwrap().push(Encoding::Scalar {
+                                raw_min,
+                                raw_max,
+                                scale,
+                                offset,
+                                unit,
+                                description,
+                            });
+                            continue;
+                        }
+                        "bcd_value" => {
+                            sink.warning(Warning::new(
+                                WarningKind::IgnoredEncoding,
+                                tokens.span(),
+                                "bcd encoding not supported, ignoring".to_string(),
+                            ));
+                        }
+                        "ascii_value" => {
+                            encodings.get_mut(&name).unwrap().push(Encoding::Ascii);
+                        }
+                        _ => return Err(Error::IncorrectToken),
                     }
+                    tokens.check_equal(&[";"])?;
                 }
                 tokens.next()?; // "}"
-                if let Ok(tok) = tokens.peek() {
-                    match tok {
-                        "Signal_representation" => state = ParserState::SignalRepresentation,
-                        _ => return Err(Error::UnexpectedToken),
-                    }
-                } else {
-                    state = ParserState::Done;
+                spans.encodings.insert(name.clone(), tokens.since(start));
+                if !map.is_empty() {
+                    let description = header.map(|(span, text)| {
+                        spans.descriptions.push(span);
+                        text
+                    });
+                    encodings.get_mut(&name).unwrap().push(Encoding::Enum {
+                        name,
+                        map,
+                        rev_map,
+                        description,
+                    });
                 }
             }
-            ParserState::SignalRepresentation => {
-                tokens.check_equal(&["Signal_representation", "{"])?;
-                while tokens.peek()? != "}" {
-                    let name = tokens.next()?.to_string();
-                    if !encodings.contains_key(&name) {
-                        return Err(Error::UnknownEncoding);
+            tokens.next()?; // "}"
+            if let Ok(tok) = tokens.peek() {
+                match tok {
+                    "Signal_representation" => state = ParserState::SignalRepresentation,
+                    _ => return Err(Error::UnexpectedToken),
+                }
+            } else {
+                state = ParserState::Done;
+            }
+        }
+        ParserState::SignalRepresentation => {
+            tokens.check_equal(&["Signal_representation", "{"])?;
+            while tokens.peek()? != "}" {
+                let name = tokens.next()?.to_string();
+                if !encodings.contains_key(&name) {
+                    return Err(Error::UnknownEncoding);
+                }
+                represented.insert(name.clone());
+                tokens.check_equal(&[":"])?;
+                loop {
+                    let signal = tokens.next()?.to_string();
+                    if !db.signals.contains_key(signal.as_str()) {
+                        return Err(Error::UnknownSignal);
+                    } else if db.signals[signal.as_str()].encodings.is_some() {
+                        return Err(Error::DuplicateEncoding);
                     }
-                    tokens.check_equal(&[":"])?;
-                    loop {
-                        let signal = tokens.next()?;
-                        if !db.signals.contains_key(signal) {
-                            return Err(Error::UnknownSignal);
-                        } else if let Some(_) = db.signals[signal].encodings {
-                            return Err(Error::DuplicateEncoding);
-                        }
-                        db.signals.get_mut(signal).unwrap().encodings =
-                            Some(encodings[&name].clone());
-                        match tokens.next()? {
-                            "," => (),
-                            ";" => break,
-                            _ => return Err(Error::IncorrectToken),
-                        }
+                    db.signals.get_mut(signal.as_str()).unwrap().encodings =
+                        Some(encodings[&name].clone());
+                    match tokens.next()? {
+                        "," => (),
+                        ";" => break,
+                        _ => return Err(Error::IncorrectToken),
                     }
                 }
-                tokens.next()?; // "}"
-                if tokens.peek().is_ok() {
-                    return Err(Error::UnexpectedToken);
-                }
-                state = ParserState::Done;
             }
-            _ => (),
+            tokens.next()?; // "}"
+            if tokens.peek().is_ok() {
+                return Err(Error::UnexpectedToken);
+            }
+            state = ParserState::Done;
         }
+        _ => (),
     }
-
-    // TODO second pass validation
-    /*
-     * - no signal in frame overlap and fit in width (make generic db validate function)
-     * - no message id overlap, include event triggered frames (use db validate)
-     * - event triggered frames have first byte free
-     * - resolver schedule tables exist, no event triggered frames in it!
-     * - no event triggered frames and associated frame in same schedule table
-     */
-    db.extra = DatabaseType::LDF(data);
-    Ok(db)
+    Ok(state)
 }
@@ -0,0 +1,258 @@
+use crate::parsers::encoding::Name;
+use crate::{generate_c_with, Database, Error, Format, Pipeline, WriteOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_NAME: &str = "autodbconv.toml";
+
+// what a validation issue does to a build, by default everything is an error
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Ignore,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodegenLang {
+    #[default]
+    C,
+}
+
+// how signal and frame names become identifiers, macros are upper case either way
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum NameCase {
+    #[default]
+    Preserve,
+    Snake,
+    Camel,
+}
+
+// what struct fields hold, physical values are doubles converted with a signal's first scalar encoding
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Accessors {
+    #[default]
+    Raw,
+    Physical,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CodegenOptions {
+    #[serde(default)]
+    pub lang: CodegenLang,
+    pub name: Option<String>, // base name of generated files, defaults to the input name
+    pub node: Option<String>, // generate for this node, pack frames it publishes and unpack ones it subscribes
+    #[serde(default)]
+    pub messages: Vec<String>, // only frames matching any of these globs or /regexes/
+    #[serde(default)]
+    pub signals: Vec<String>, // only signals matching any of these, frames left without any are dropped
+    #[serde(default)]
+    pub prefix: String, // put before type, function and macro names
+    #[serde(default)]
+    pub case: NameCase,
+    #[serde(default)]
+    pub accessors: Accessors,
+}
+
+// one output of the build, a converted database or generated code when codegen is given
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    pub input: Name,
+    pub output: PathBuf,        // directory for codegen
+    pub format: Option<String>, // inferred from output's extension if not given
+    #[serde(default)]
+    pub sorted: bool,
+    #[serde(default)]
+    pub provenance: bool, // note the input path, format and parse time in the output
+    #[serde(default)]
+    pub vector_attributes: bool, // DBC targets, define Vector's standard attributes even when unused
+    #[serde(default)]
+    pub pipeline: Pipeline, // applied to the input before it's written
+    pub codegen: Option<CodegenOptions>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationOptions {
+    #[serde(default)]
+    pub strict: bool, // also check LIN identifiers
+    #[serde(default)]
+    pub severity: BTreeMap<String, Severity>, // by error kind, e.g. SignalOverlap, whatever its case
+}
+
+impl ValidationOptions {
+    pub fn severity(&self, issue: &Error) -> Severity {
+        let kind = format!("{:?}", issue.kind());
+        let kind = kind.split('(').next().unwrap_or_default();
+        let severity = self
+            .severity
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(kind));
+        severity.map(|(_, s)| *s).unwrap_or_default()
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(Severity::Error),
+            "warning" => Ok(Severity::Warning),
+            "ignore" => Ok(Severity::Ignore),
+            _ => Err(Error::Hint(
+                "expected error, warning or ignore".to_string(),
+                Box::new(Error::UnexpectedToken),
+            )),
+        }
+    }
+}
+
+// autodbconv.toml, paths are relative to it
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Project {
+    #[serde(skip)]
+    pub dir: PathBuf,
+    pub inputs: BTreeMap<Name, PathBuf>,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+    #[serde(default)]
+    pub validation: ValidationOptions,
+}
+
+// what build() did, nothing is written when an issue is an error
+#[derive(Debug, Default)]
+pub struct BuildReport {
+    pub issues: Vec<(Name, Severity, Error)>, // input, severity, issue
+    pub written: Vec<PathBuf>,
+}
+
+impl Project {
+    // a directory means the manifest in it
+    pub fn load(path: impl AsRef<Path>) -> Result<Project, Error> {
+        let mut path = path.as_ref().to_path_buf();
+        if path.is_dir() {
+            path.push(MANIFEST_NAME);
+        }
+        let mut project: Project = toml::from_str(&std::fs::read_to_string(&path)?)?;
+        project.dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        for target in &project.targets {
+            let inputs = std::iter::once(&target.input).chain(target.pipeline.inputs());
+            for input in inputs {
+                if !project.inputs.contains_key(input) {
+                    return Err(Error::Hint(
+                        format!("target {} uses input {}", target.output.display(), input),
+                        Box::new(Error::UnknownBus),
+                    ));
+                }
+            }
+            project.target_format(target)?;
+        }
+        Ok(project)
+    }
+
+    fn target_format(&self, target: &Target) -> Result<Option<Format>, Error> {
+        if target.codegen.is_some() {
+            return Ok(None);
+        }
+        let format = match &target.format {
+            Some(format) => format.parse().ok(),
+            None => Format::from_path(&target.output),
+        };
+        let hint = format!("target {}", target.output.display());
+        format
+            .map(Some)
+            .ok_or(Error::Hint(hint, Box::new(Error::UnknownFormat)))
+    }
+
+    pub fn severity(&self, issue: &Error) -> Severity {
+        self.validation.severity(issue)
+    }
+
+    // every input, parsed
+    pub fn databases(&self) -> Result<BTreeMap<Name, Database>, Error> {
+        self.inputs
+            .iter()
+            .map(|(name, path)| {
+                let path = self.dir.join(path);
+                let format = Format::from_path(&path).ok_or(Error::UnknownFormat)?;
+                let db = format
+                    .parse(&path)
+                    .map_err(|e| Error::Hint(path.display().to_string(), Box::new(e)))?;
+                Ok((name.clone(), db))
+            })
+            .collect()
+    }
+
+    // validates every input, then writes every target unless an issue is an error
+    pub fn build(&self) -> Result<BuildReport, Error> {
+        let databases = self.databases()?;
+        let mut report = BuildReport::default();
+        for (name, db) in &databases {
+            let issues = if self.validation.strict {
+                db.validate_strict()
+            } else {
+                db.validate()
+            };
+            for issue in issues {
+                let severity = self.severity(&issue);
+                if severity != Severity::Ignore {
+                    report.issues.push((name.clone(), severity, issue));
+                }
+            }
+        }
+        if report.issues.iter().any(|(_, s, _)| *s == Severity::Error) {
+            return Ok(report);
+        }
+
+        for target in &self.targets {
+            let output = self.dir.join(&target.output);
+            let db = target
+                .pipeline
+                .run(databases[&target.input].clone(), &databases)
+                .map_err(|e| Error::Hint(format!("target {}", output.display()), Box::new(e)))?;
+            let db = &db;
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match (&target.codegen, self.target_format(target)?) {
+                (Some(codegen), _) => {
+                    let name = codegen.name.as_deref().unwrap_or(&target.input);
+                    std::fs::create_dir_all(&output)?;
+                    match codegen.lang {
+                        CodegenLang::C => {
+                            let (header, source) = generate_c_with(db, name, codegen)?;
+                            for (ext, content) in [("h", header), ("c", source)] {
+                                let path = output.join(format!("{}.{}", name, ext));
+                                std::fs::write(&path, content)?;
+                                report.written.push(path);
+                            }
+                        }
+                    }
+                }
+                (None, Some(format)) => {
+                    let options = WriteOptions {
+                        sorted: target.sorted,
+                        provenance: target.provenance,
+                        vector_attributes: target.vector_attributes,
+                    };
+                    format.write_with(db, &output, &options)?;
+                    report.written.push(output);
+                }
+                (None, None) => unreachable!(), // checked by target_format
+            }
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::parsers::encoding::{DatabaseType, Name, Signal};
+use crate::project::routing::Route;
+use crate::{Database, Error, Format};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+// one database of a project, path is relative to the project file
+#[derive(Debug)]
+pub struct Bus {
+    pub path: PathBuf,
+    pub database: Database,
+}
+
+// several buses of a vehicle, nodes on more than one bus must be declared as gateways
+#[derive(Debug, Default)]
+pub struct NetworkProject {
+    pub buses: BTreeMap<Name, Bus>,
+    pub gateways: Vec<Name>,
+    pub routes: Vec<Route>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProjectFile {
+    buses: BTreeMap<Name, PathBuf>,
+    #[serde(default)]
+    gateways: Vec<Name>,
+    #[serde(default)]
+    routes: Vec<Route>,
+}
+
+fn nodes(db: &Database) -> Vec<&Name> {
+    match &db.extra {
+        DatabaseType::LDF(data) => std::iter::once(&data.commander)
+            .chain(data.responders.keys())
+            .collect(),
+        DatabaseType::DBC(data) => data.nodes.iter().collect(),
+        DatabaseType::NCF => db.messages.values().map(|m| &m.sender).collect(),
+    }
+}
+
+impl NetworkProject {
+    pub fn load(project: impl AsRef<Path>) -> Result<NetworkProject, Error> {
+        let project = project.as_ref();
+        let file: ProjectFile = serde_json::from_reader(std::fs::File::open(project)?)?;
+        let dir = project.parent().unwrap_or(Path::new(""));
+        let mut buses = BTreeMap::new();
+        for (name, path) in file.buses {
+            let format = Format::from_path(&path).ok_or(Error::UnknownFormat)?;
+            let database = format.parse(dir.join(&path))?;
+            buses.insert(name, Bus { path, database });
+        }
+        Ok(NetworkProject {
+            buses,
+            gateways: file.gateways,
+            routes: file.routes,
+        })
+    }
+
+    // writes the project file and every bus database next to it
+    pub fn save(&self, project: impl AsRef<Path>) -> Result<(), Error> {
+        let project = project.as_ref();
+        let dir = project.parent().unwrap_or(Path::new(""));
+        for bus in self.buses.values() {
+            let format = Format::from_path(&bus.path).ok_or(Error::UnknownFormat)?;
+            format.write(&bus.database, dir.join(&bus.path))?;
+        }
+        let file = ProjectFile {
+            buses: self
+                .buses
+                .iter()
+                .map(|(name, bus)| (name.clone(), bus.path.clone()))
+                .collect(),
+            gateways: self.gateways.clone(),
+            routes: self.routes.clone(),
+        };
+        let mut data = serde_json::to_string_pretty(&file)?;
+        data.push('\n');
+        std::fs::write(project, data)?;
+        Ok(())
+    }
+
+    pub fn bus(&self, name: &str) -> Option<&Database> {
+        self.buses.get(name).map(|b| &b.database)
+    }
+
+    // all problems found, each with a hint naming the entities involved
+    pub fn validate(&self) -> Vec<Error> {
+        let mut errors = Vec::new();
+
+        // nodes are unique across buses unless they're gateways
+        let mut seen: HashMap<&Name, &Name> = HashMap::new();
+        for (bus, b) in &self.buses {
+            let mut names = nodes(&b.database);
+            names.sort();
+            names.dedup();
+            for node in names {
+                if node.is_empty() {
+                    continue;
+                }
+                if let Some(other) = seen.insert(node, bus) {
+                    if !self.gateways.contains(node) {
+                        errors.push(Error::Hint(
+                            format!(
+                                "{} is on {} and {}, declare it as a gateway",
+                                node, other, bus
+                            ),
+                            Box::new(Error::DuplicateNode),
+                        ));
+                    }
+                }
+            }
+        }
+        for gateway in &self.gateways {
+            if !seen.contains_key(gateway) {
+                errors.push(Error::Hint(
+                    format!("gateway {} is not on any bus", gateway),
+                    Box::new(Error::UnknownNode),
+                ));
+            }
+        }
+
+        // signals routed between buses keep their name and must agree on layout and encoding
+        let mut signals: HashMap<&Name, (&Name, &Signal)> = HashMap::new();
+        for (bus, b) in &self.buses {
+            let mut names: Vec<_> = b.database.signals.iter().collect();
+            names.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, signal) in names {
+                let Some((other, first)) = signals.get(name) else {
+                    signals.insert(name, (bus, signal));
+                    continue;
+                };
+                let difference = if first.bit_width != signal.bit_width {
+                    Some(format!("width {} vs {}", first.bit_width, signal.bit_width))
+                } else if first.kind != signal.kind {
+                    Some("value kind".to_string())
+                } else if first.encodings != signal.encodings {
+                    Some("encodings".to_string())
+                } else {
+                    None
+                };
+                if let Some(difference) = difference {
+                    errors.push(Error::Hint(
+                        format!(
+                            "{} differs between {} and {}: {}",
+                            name, other, bus, difference
+                        ),
+                        Box::new(Error::InconsistentSignal),
+                    ));
+                }
+            }
+        }
+        for route in &self.routes {
+            if let Err(e) = self.route(route) {
+                errors.push(e);
+            }
+        }
+        errors
+    }
+}
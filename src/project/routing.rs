@@ -0,0 +1,150 @@
+use crate::parsers::encoding::{Encoding, Name, Signal};
+use crate::project::network::NetworkProject;
+use crate::{Database, Error};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignalRef {
+    pub bus: Name,
+    pub signal: Name,
+}
+
+// copies a signal from one bus to another through a gateway
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Route {
+    pub from: SignalRef,
+    pub to: SignalRef,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouteEndpoint {
+    pub bus: Name,
+    pub message: Name,
+    pub id: u32,
+    pub signal: Name,
+    pub bit_start: u16,
+    pub bit_width: u16,
+    pub little_endian: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutingEntry {
+    pub from: RouteEndpoint,
+    pub to: RouteEndpoint,
+    pub scale: f64, // to raw = scale * from raw + offset
+    pub offset: f64,
+}
+
+// first scalar range, or the identity
+fn scaling(signal: &Signal) -> (f64, f64, u64, u64) {
+    signal
+        .encodings
+        .iter()
+        .flatten()
+        .find_map(|e| match e {
+            Encoding::Scalar {
+                raw_min,
+                raw_max,
+                scale,
+                offset,
+                ..
+            } => Some((*scale, *offset, *raw_min, *raw_max)),
+            _ => None,
+        })
+        .unwrap_or((1.0, 0.0, 0, signal.max_raw()))
+}
+
+fn endpoint<'a>(
+    project: &'a NetworkProject,
+    r: &SignalRef,
+) -> Result<(RouteEndpoint, &'a Signal), Error> {
+    let hint = |e: Error| Error::Hint(format!("{} on {}", r.signal, r.bus), Box::new(e));
+    let db: &Database = project.bus(&r.bus).ok_or(hint(Error::UnknownBus))?;
+    let signal = db
+        .signals
+        .get(&r.signal)
+        .ok_or(hint(Error::UnknownSignal))?;
+    let mut messages: Vec<_> = db
+        .messages
+        .iter()
+        .filter(|(_, m)| m.signals.contains(&r.signal) || m.mux_signals.contains_key(&r.signal))
+        .collect();
+    messages.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+    let (message, msg) = messages.first().ok_or(hint(Error::UnknownFrame))?;
+    Ok((
+        RouteEndpoint {
+            bus: r.bus.clone(),
+            message: (*message).clone(),
+            id: msg.id,
+            signal: r.signal.clone(),
+            bit_start: signal.bit_start,
+            bit_width: signal.bit_width,
+            little_endian: signal.little_endian,
+        },
+        signal,
+    ))
+}
+
+impl NetworkProject {
+    // checks that every physical value of the source fits the destination
+    pub fn route(&self, route: &Route) -> Result<RoutingEntry, Error> {
+        let (from, src) = endpoint(self, &route.from)?;
+        let (to, dst) = endpoint(self, &route.to)?;
+        let hint = |msg: String, e: Error| {
+            Error::Hint(
+                format!("{} -> {}: {}", route.from.signal, route.to.signal, msg),
+                Box::new(e),
+            )
+        };
+
+        // names of enum types differ between files, only the values matter
+        let enums = |s: &Signal| {
+            s.encodings
+                .iter()
+                .flatten()
+                .filter_map(|e| match e {
+                    Encoding::Enum { rev_map, .. } => Some(rev_map.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        if enums(src) != enums(dst) {
+            return Err(hint(
+                "logical values differ".to_string(),
+                Error::InconsistentSignal,
+            ));
+        }
+
+        let (src_scale, src_offset, src_min, src_max) = scaling(src);
+        let (dst_scale, dst_offset, _, _) = scaling(dst);
+        if dst_scale == 0.0 {
+            return Err(hint(
+                "destination scale is 0".to_string(),
+                Error::ValueOutOfRange,
+            ));
+        }
+        let scale = src_scale / dst_scale;
+        let offset = (src_offset - dst_offset) / dst_scale;
+        let (lo, hi) = (
+            scale * src_min as f64 + offset,
+            scale * src_max as f64 + offset,
+        );
+        let (lo, hi) = (lo.min(hi), lo.max(hi));
+        if lo < 0.0 || hi > dst.max_raw() as f64 {
+            return Err(hint(
+                format!("raw {}..={} doesn't fit in {} bits", lo, hi, dst.bit_width),
+                Error::ValueOutOfRange,
+            ));
+        }
+        Ok(RoutingEntry {
+            from,
+            to,
+            scale,
+            offset,
+        })
+    }
+
+    pub fn routing_table(&self) -> Result<Vec<RoutingEntry>, Error> {
+        self.routes.iter().map(|r| self.route(r)).collect()
+    }
+}
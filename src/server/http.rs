@@ -0,0 +1,240 @@
+use crate::{parse_hex, Error, Format, FrameStream, FrozenDatabase, Name, SignalValue};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+/*
+ * Endpoints, all responses are JSON.
+ *  POST   /databases?format=dbc                    upload database text, format detected if not given, returns {"id": ...}
+ *  GET    /databases/{id}                          entire database
+ *  DELETE /databases/{id}
+ *  GET    /databases/{id}/messages                 message names, by id
+ *  GET    /databases/{id}/messages/{name}
+ *  GET    /databases/{id}/signals                  signal names
+ *  GET    /databases/{id}/signals/{name}
+ *  POST   /databases/{id}/messages/{name}/decode   {"data": "01FF..."}
+ *  POST   /databases/{id}/messages/{name}/encode   {"signals": {"name": {"physical": 1.0}, ...}}
+ *  POST   /databases/{id}/log/decode               candump -L or ASC text, returns [{"time": ..., "id": ..., "signals": ...}]
+ */
+
+#[derive(Default)]
+struct ServerState {
+    next_id: u64,
+    databases: HashMap<u64, Arc<FrozenDatabase>>,
+}
+
+type SharedState = Arc<RwLock<ServerState>>;
+
+enum ApiError {
+    UnknownDatabase,
+    NotFound(String),
+    BadRequest(String),
+    Database(Error),
+}
+
+// a message or signal named in the path that the database doesn't have
+fn lookup(e: Error) -> ApiError {
+    match e.kind() {
+        Error::UnknownFrame | Error::UnknownSignal => ApiError::NotFound(e.to_string()),
+        _ => ApiError::Database(e),
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(item: Error) -> Self {
+        ApiError::Database(item)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, msg) = match self {
+            ApiError::UnknownDatabase => (StatusCode::NOT_FOUND, "unknown database".to_string()),
+            ApiError::NotFound(s) => (StatusCode::NOT_FOUND, s),
+            ApiError::BadRequest(s) => (StatusCode::BAD_REQUEST, s),
+            ApiError::Database(e) => (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+        };
+        (status, Json(json!({ "error": msg }))).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct UploadParams {
+    format: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DecodeRequest {
+    data: String, // hex
+}
+
+#[derive(Deserialize)]
+struct EncodeRequest {
+    #[serde(default)]
+    signals: HashMap<String, SignalValue>,
+}
+
+fn database(state: &SharedState, id: u64) -> Result<Arc<FrozenDatabase>, ApiError> {
+    state
+        .read()
+        .unwrap()
+        .databases
+        .get(&id)
+        .cloned()
+        .ok_or(ApiError::UnknownDatabase)
+}
+
+async fn upload(
+    State(state): State<SharedState>,
+    Query(params): Query<UploadParams>,
+    body: String,
+) -> Result<Json<Value>, ApiError> {
+    // detected from the text when not given
+    let format = match params.format {
+        Some(f) => f
+            .parse::<Format>()
+            .map_err(|_| ApiError::BadRequest(format!("unsupported format {}", f)))?,
+        None => Format::detect(body.as_bytes())
+            .ok_or(ApiError::BadRequest("unknown format".to_string()))?,
+    };
+    // parsing a large file would hold up the runtime
+    let db = tokio::task::spawn_blocking(move || format.parse_reader(body.as_bytes()))
+        .await
+        .map_err(|e| Error::IO(e.to_string()))??;
+    let mut state = state.write().unwrap();
+    let id = state.next_id;
+    state.next_id += 1;
+    state.databases.insert(id, Arc::new(db.freeze()));
+    Ok(Json(json!({ "id": id })))
+}
+
+async fn get_database(
+    State(state): State<SharedState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Value>, ApiError> {
+    Ok(Json(json!(database(&state, id)?.database())))
+}
+
+async fn delete_database(
+    State(state): State<SharedState>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    match state.write().unwrap().databases.remove(&id) {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err(ApiError::UnknownDatabase),
+    }
+}
+
+async fn list_messages(
+    State(state): State<SharedState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Value>, ApiError> {
+    let db = database(&state, id)?;
+    let names: Vec<&Name> = db.messages().map(|(n, _)| n).collect();
+    Ok(Json(json!(names)))
+}
+
+async fn get_message(
+    State(state): State<SharedState>,
+    Path((id, name)): Path<(u64, String)>,
+) -> Result<Json<Value>, ApiError> {
+    let db = database(&state, id)?;
+    let msg = db
+        .database()
+        .messages
+        .get(name.as_str())
+        .ok_or(ApiError::NotFound(format!("no message {}", name)))?;
+    Ok(Json(json!(msg)))
+}
+
+async fn list_signals(
+    State(state): State<SharedState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Value>, ApiError> {
+    let db = database(&state, id)?;
+    let names: Vec<&Name> = db.signals().map(|(n, _)| n).collect();
+    Ok(Json(json!(names)))
+}
+
+async fn get_signal(
+    State(state): State<SharedState>,
+    Path((id, name)): Path<(u64, String)>,
+) -> Result<Json<Value>, ApiError> {
+    let db = database(&state, id)?;
+    let signal = db
+        .database()
+        .signals
+        .get(name.as_str())
+        .ok_or(ApiError::NotFound(format!("no signal {}", name)))?;
+    Ok(Json(json!(signal)))
+}
+
+async fn decode(
+    State(state): State<SharedState>,
+    Path((id, name)): Path<(u64, String)>,
+    Json(req): Json<DecodeRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let db = database(&state, id)?;
+    let data =
+        parse_hex(&req.data).map_err(|_| ApiError::BadRequest("invalid hex data".to_string()))?;
+    let signals = db.database().decode_message(&name, &data).map_err(lookup)?;
+    Ok(Json(json!(signals)))
+}
+
+async fn encode(
+    State(state): State<SharedState>,
+    Path((id, name)): Path<(u64, String)>,
+    Json(req): Json<EncodeRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let db = database(&state, id)?;
+    let data = db
+        .database()
+        .encode_message(&name, &req.signals)
+        .map_err(lookup)?;
+    let hex: String = data.iter().map(|b| format!("{:02X}", b)).collect();
+    Ok(Json(json!({ "data": hex })))
+}
+
+// frames the database doesn't have are left out, times are ms since decoding started
+async fn decode_log(
+    State(state): State<SharedState>,
+    Path(id): Path<u64>,
+    body: Bytes,
+) -> Result<Json<Value>, ApiError> {
+    let db = database(&state, id)?;
+    let mut frames = FrameStream::lines(std::io::Cursor::new(body));
+    let mut ret = Vec::new();
+    while let Some((frame, signals)) = frames.next_decoded(db.database()).await? {
+        ret.push(json!({ "time": frame.time, "id": frame.id, "signals": signals }));
+    }
+    Ok(Json(json!(ret)))
+}
+
+pub fn http_router() -> Router {
+    let state: SharedState = Default::default();
+    Router::new()
+        .route("/databases", post(upload))
+        .route("/databases/{id}", get(get_database).delete(delete_database))
+        .route("/databases/{id}/messages", get(list_messages))
+        .route("/databases/{id}/messages/{name}", get(get_message))
+        .route("/databases/{id}/messages/{name}/decode", post(decode))
+        .route("/databases/{id}/messages/{name}/encode", post(encode))
+        .route("/databases/{id}/log/decode", post(decode_log))
+        .route("/databases/{id}/signals", get(list_signals))
+        .route("/databases/{id}/signals/{name}", get(get_signal))
+        .with_state(state)
+}
+
+pub async fn serve_http(addr: SocketAddr) -> Result<(), Error> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, http_router()).await?;
+    Ok(())
+}
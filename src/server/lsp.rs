@@ -0,0 +1,517 @@
+use crate::parsers::encoding::Encoding;
+use crate::parsers::error::Span;
+use crate::parsers::ldf::Tokenizer;
+use crate::{parse_dbc_str_with, parse_ldf_str_with, Database, Error, Format};
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Uri,
+};
+use std::collections::HashMap;
+
+const LDF_KEYWORDS: &[&str] = &[
+    "LIN_description_file",
+    "LIN_protocol_version",
+    "LIN_language_version",
+    "LIN_speed",
+    "Channel_name",
+    "Nodes",
+    "Master",
+    "Slaves",
+    "composite",
+    "configuration",
+    "Signals",
+    "Diagnostic_signals",
+    "Frames",
+    "Sporadic_frames",
+    "Event_triggered_frames",
+    "Diagnostic_frames",
+    "MasterReq",
+    "SlaveResp",
+    "Node_attributes",
+    "LIN_protocol",
+    "configured_NAD",
+    "initial_NAD",
+    "product_id",
+    "response_error",
+    "fault_state_signals",
+    "P2_min",
+    "ST_min",
+    "N_As_timeout",
+    "N_Cr_timeout",
+    "configurable_frames",
+    "Schedule_tables",
+    "AssignNAD",
+    "ConditionalChangeNAD",
+    "DataDump",
+    "SaveConfiguration",
+    "AssignFrameIdRange",
+    "FreeFormat",
+    "AssignFrameId",
+    "AssignNADviaSNPD",
+    "delay",
+    "Signal_groups",
+    "Signal_encoding_types",
+    "logical_value",
+    "physical_value",
+    "bcd_value",
+    "ascii_value",
+    "Signal_representation",
+    "kbps",
+    "ms",
+];
+const DBC_KEYWORDS: &[&str] = &[
+    "VERSION",
+    "NS_",
+    "BS_",
+    "BU_",
+    "BO_",
+    "SG_",
+    "SG_MUL_VAL_",
+    "CM_",
+    "BA_DEF_",
+    "BA_DEF_DEF_",
+    "BA_",
+    "VAL_",
+    "VAL_TABLE_",
+    "SIG_VALTYPE_",
+    "SIG_GROUP_",
+    "BO_TX_BU_",
+    "EV_",
+    "Vector__XXX",
+];
+
+#[derive(Default)]
+struct Document {
+    format: Option<Format>, // LDF or DBC, other documents are left alone
+    text: String,
+    db: Option<Database>,        // last successful parse
+    defs: HashMap<String, Span>, // definition locations
+}
+
+fn to_position(text: &str, offset: usize) -> Position {
+    let (line, _) = Span {
+        start: offset,
+        end: offset,
+    }
+    .line_col(text);
+    let line_start = text[..offset.min(text.len())]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let character = text[line_start..offset.min(text.len())]
+        .encode_utf16()
+        .count();
+    Position::new(line as u32, character as u32)
+}
+
+fn to_offset(text: &str, pos: Position) -> usize {
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        if i == pos.line as usize {
+            let mut units = 0;
+            for (j, c) in line.char_indices() {
+                if units >= pos.character as usize {
+                    return offset + j;
+                }
+                units += c.len_utf16();
+            }
+            return offset + line.len();
+        }
+        offset += line.len();
+    }
+    text.len()
+}
+
+fn to_range(text: &str, span: Span) -> Range {
+    Range::new(to_position(text, span.start), to_position(text, span.end))
+}
+
+fn word_at(text: &str, offset: usize) -> Option<&str> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let start = text[..offset]
+        .rfind(|c: char| !is_ident(c))
+        .map_or(0, |i| i + 1);
+    let end = text[offset..]
+        .find(|c: char| !is_ident(c))
+        .map_or(text.len(), |i| offset + i);
+    if start < end {
+        Some(&text[start..end])
+    } else {
+        None
+    }
+}
+
+// LDF or DBC from the extension, or the text for unsaved documents
+fn document_format(uri: &Uri, text: &str) -> Option<Format> {
+    Format::from_path(uri.path().as_str())
+        .or(Format::detect(text.as_bytes()))
+        .filter(|f| matches!(f, Format::LDF | Format::DBC))
+}
+
+// approximate, only needs to find declarations of names and survive broken files
+fn index_definitions(text: &str) -> HashMap<String, Span> {
+    let mut defs = HashMap::new();
+    let mut tokens = Tokenizer::new(text);
+    let mut section = String::new();
+    let mut depth = 0;
+    let mut prev: Option<(String, Span)> = None;
+    let mut node_list = false;
+    while let Ok(tok) = tokens.next() {
+        let tok = tok.to_string();
+        let span = tokens.span();
+        match (tok.as_str(), depth, section.as_str()) {
+            ("{", 0, _) => {
+                section = prev.take().map(|(s, _)| s).unwrap_or_default();
+                depth += 1;
+            }
+            ("{", 1, "Signal_encoding_types" | "Schedule_tables") => {
+                if let Some((name, span)) = prev.take() {
+                    defs.entry(name).or_insert(span);
+                }
+                depth += 1;
+            }
+            ("{", _, _) => depth += 1,
+            ("}", _, _) => depth -= 1,
+            (":", 1, "Nodes") => node_list = true,
+            (";", 1, "Nodes") => node_list = false,
+            (",", 1, "Nodes") => (),
+            (_, 1, "Nodes") if node_list => {
+                defs.entry(tok.clone()).or_insert(span);
+            }
+            (
+                ":",
+                1,
+                "Signals"
+                | "Diagnostic_signals"
+                | "Frames"
+                | "Sporadic_frames"
+                | "Event_triggered_frames"
+                | "Diagnostic_frames",
+            ) => {
+                if let Some((name, span)) = prev.take() {
+                    defs.entry(name).or_insert(span);
+                }
+            }
+            _ => (),
+        }
+        prev = Some((tok, span));
+    }
+    defs
+}
+
+// nodes, frames and signals, each DBC statement declaring one starts a line
+fn index_dbc_definitions(text: &str) -> HashMap<String, Span> {
+    let mut defs = HashMap::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let mut words = Vec::new();
+        let mut start = None;
+        for (i, c) in line.char_indices() {
+            match (start, c.is_whitespace() || c == ':') {
+                (None, false) => start = Some(i),
+                (Some(s), true) => {
+                    words.push((s, &line[s..i]));
+                    start = None;
+                }
+                _ => (),
+            }
+            if c == ':' && words.len() < 2 {
+                words.push((i, ":"));
+            }
+        }
+        if let Some(s) = start {
+            words.push((s, &line[s..]));
+        }
+        let names: Vec<(usize, &str)> = match words.as_slice() {
+            [(_, "BU_"), (_, ":"), nodes @ ..] => nodes.to_vec(),
+            [(_, "BO_"), _, name, ..] | [(_, "SG_"), name, ..] => vec![*name],
+            _ => Vec::new(),
+        };
+        for (i, name) in names {
+            let span = Span {
+                start: offset + i,
+                end: offset + i + name.len(),
+            };
+            defs.entry(name.to_string()).or_insert(span);
+        }
+        offset += line.len();
+    }
+    defs
+}
+
+fn hover_text(db: &Database, name: &str) -> Option<String> {
+    if let Some(signal) = db.signals.get(name) {
+        let mut ret = format!(
+            "**signal** `{}`\n\n{} bit(s), init value {}",
+            name, signal.bit_width, signal.init_value
+        );
+        if let Some((frame, _)) = db
+            .messages
+            .iter()
+            .find(|(_, m)| m.signals.iter().any(|s| **s == *name))
+        {
+            ret += &format!(", bit {} of `{}`", signal.bit_start, frame);
+        }
+        for e in signal.encodings.iter().flatten() {
+            match e {
+                Encoding::Scalar {
+                    raw_min,
+                    raw_max,
+                    scale,
+                    offset,
+                    unit,
+                    description,
+                } => {
+                    ret += &format!(
+                        "\n- raw {}..={}: {} * raw + {} {}",
+                        raw_min, raw_max, scale, offset, unit
+                    );
+                    if let Some(text) = description {
+                        ret += &format!(", {}", text);
+                    }
+                }
+                Encoding::Enum { name, rev_map, .. } => {
+                    let mut values: Vec<_> = rev_map.iter().collect();
+                    values.sort();
+                    ret += &format!("\n- `{}`:", name);
+                    for (raw, label) in values {
+                        ret += &format!(" {} = \"{}\";", raw, label);
+                    }
+                }
+                Encoding::Ascii => ret += "\n- ASCII text",
+            }
+        }
+        if let Some(text) = &signal.description {
+            ret += &format!("\n\n{}", text);
+        }
+        Some(ret)
+    } else {
+        db.messages.get(name).map(|msg| {
+            let mut ret = format!(
+                "**frame** `{}`\n\nid 0x{:02X}, {} byte(s), published by `{}`\n\nsignals: {}",
+                name,
+                msg.id,
+                msg.byte_width,
+                msg.sender,
+                msg.signals.join(", ")
+            );
+            if let Some(text) = &msg.description {
+                ret += &format!("\n\n{}", text);
+            }
+            ret
+        })
+    }
+}
+
+fn diagnostic(
+    doc: &Document,
+    span: Option<Span>,
+    severity: DiagnosticSeverity,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range: span.map_or(Range::default(), |span| to_range(&doc.text, span)),
+        severity: Some(severity),
+        source: Some("autodbconv".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn update(connection: &Connection, docs: &mut HashMap<String, Document>, uri: Uri, text: String) {
+    let doc = docs.entry(uri.as_str().to_string()).or_default();
+    doc.format = document_format(&uri, &text);
+    doc.text = text;
+    let mut warnings = Vec::new();
+    let mut sink = |w| warnings.push(w);
+    let result = match doc.format {
+        Some(Format::LDF) => {
+            doc.defs = index_definitions(&doc.text);
+            parse_ldf_str_with(&doc.text, &mut sink)
+        }
+        Some(_) => {
+            doc.defs = index_dbc_definitions(&doc.text);
+            parse_dbc_str_with(&doc.text, &mut sink)
+        }
+        None => return,
+    };
+    let mut diagnostics: Vec<Diagnostic> = warnings
+        .into_iter()
+        .map(|w| diagnostic(doc, Some(w.span), DiagnosticSeverity::WARNING, w.message))
+        .collect();
+    match result {
+        Ok(db) => doc.db = Some(db),
+        Err(e) => diagnostics.push(diagnostic(
+            doc,
+            e.span(),
+            DiagnosticSeverity::ERROR,
+            e.kind().to_string(),
+        )),
+    }
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+    let _ = connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            params,
+        )));
+}
+
+fn goto_definition(
+    docs: &HashMap<String, Document>,
+    params: GotoDefinitionParams,
+) -> Option<GotoDefinitionResponse> {
+    let pos = params.text_document_position_params;
+    let doc = docs.get(pos.text_document.uri.as_str())?;
+    let name = word_at(&doc.text, to_offset(&doc.text, pos.position))?;
+    let span = doc.defs.get(name)?;
+    Some(GotoDefinitionResponse::Scalar(Location::new(
+        pos.text_document.uri,
+        to_range(&doc.text, *span),
+    )))
+}
+
+fn hover(docs: &HashMap<String, Document>, params: HoverParams) -> Option<Hover> {
+    let pos = params.text_document_position_params;
+    let doc = docs.get(pos.text_document.uri.as_str())?;
+    let name = word_at(&doc.text, to_offset(&doc.text, pos.position))?;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: hover_text(doc.db.as_ref()?, name)?,
+        }),
+        range: None,
+    })
+}
+
+fn completion(
+    docs: &HashMap<String, Document>,
+    params: CompletionParams,
+) -> Option<CompletionResponse> {
+    let item = |label: &str, kind| CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        ..Default::default()
+    };
+    let doc = docs.get(params.text_document_position.text_document.uri.as_str())?;
+    let keywords = match doc.format? {
+        Format::LDF => LDF_KEYWORDS,
+        _ => DBC_KEYWORDS,
+    };
+    let mut items: Vec<CompletionItem> = keywords
+        .iter()
+        .map(|k| item(k, CompletionItemKind::KEYWORD))
+        .collect();
+    if let Some(db) = &doc.db {
+        items.extend(
+            db.signals
+                .keys()
+                .map(|s| item(s, CompletionItemKind::VARIABLE)),
+        );
+        items.extend(
+            db.messages
+                .keys()
+                .map(|s| item(s, CompletionItemKind::STRUCT)),
+        );
+    }
+    Some(CompletionResponse::Array(items))
+}
+
+fn handle_request(docs: &HashMap<String, Document>, req: Request) -> Response {
+    let params = req.params;
+    let result = match req.method.as_str() {
+        GotoDefinition::METHOD => serde_json::from_value(params)
+            .ok()
+            .and_then(|p| goto_definition(docs, p))
+            .map(|r| serde_json::json!(r)),
+        HoverRequest::METHOD => serde_json::from_value(params)
+            .ok()
+            .and_then(|p| hover(docs, p))
+            .map(|r| serde_json::json!(r)),
+        Completion::METHOD => serde_json::from_value(params)
+            .ok()
+            .and_then(|p| completion(docs, p))
+            .map(|r| serde_json::json!(r)),
+        _ => None,
+    };
+    Response::new_ok(req.id, result.unwrap_or(serde_json::Value::Null))
+}
+
+fn handle_notification(
+    connection: &Connection,
+    docs: &mut HashMap<String, Document>,
+    not: Notification,
+) {
+    match not.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            if let Ok(params) = serde_json::from_value::<DidOpenTextDocumentParams>(not.params) {
+                update(
+                    connection,
+                    docs,
+                    params.text_document.uri,
+                    params.text_document.text,
+                );
+            }
+        }
+        DidChangeTextDocument::METHOD => {
+            if let Ok(mut params) =
+                serde_json::from_value::<DidChangeTextDocumentParams>(not.params)
+            {
+                if let Some(change) = params.content_changes.pop() {
+                    update(connection, docs, params.text_document.uri, change.text);
+                    // full sync
+                }
+            }
+        }
+        DidCloseTextDocument::METHOD => {
+            if let Ok(params) = serde_json::from_value::<DidCloseTextDocumentParams>(not.params) {
+                docs.remove(params.text_document.uri.as_str());
+            }
+        }
+        _ => (),
+    }
+}
+
+pub fn serve_lsp_stdio() -> Result<(), Error> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        ..Default::default()
+    };
+    connection
+        .initialize(serde_json::json!(capabilities))
+        .map_err(|e| Error::IO(e.to_string()))?;
+
+    let mut docs = HashMap::new();
+    for msg in &connection.receiver {
+        match msg {
+            Message::Request(req) => {
+                if connection
+                    .handle_shutdown(&req)
+                    .map_err(|e| Error::IO(e.to_string()))?
+                {
+                    break;
+                }
+                let resp = handle_request(&docs, req);
+                let _ = connection.sender.send(Message::Response(resp));
+            }
+            Message::Notification(not) => handle_notification(&connection, &mut docs, not),
+            Message::Response(_) => (),
+        }
+    }
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use crate::codec::payload::SignalValue;
+use crate::codec::vectors::{next_random, SEED};
+use crate::parsers::encoding::{Name, Signal, ValueKind};
+use crate::{diff, Database, Error, Format};
+use std::collections::HashMap;
+
+/*
+ * Checks for tests of format backends and databases, a database should come back the same after being
+ * written and parsed again, and its frames should give back the values they're packed with, e.g.
+ *
+ *   let db = autodbconv::parse_ldf("body.ldf").unwrap();
+ *   autodbconv::testing::assert_round_trip(&db, Format::DBC);
+ *   autodbconv::testing::assert_payloads(&db, 100);
+ *
+ * Databases are compared with PartialEq, provenance is left out since the parser sets its own.
+ */
+
+// db written as format then parsed back
+pub fn round_trip(db: &Database, format: Format) -> Result<Database, Error> {
+    let mut data = Vec::new();
+    format.write_writer(db, &mut data)?;
+    let mut ret = format.parse_reader(data.as_slice())?;
+    ret.provenance = None;
+    Ok(ret)
+}
+
+// panics with what changed if db doesn't survive a round trip through format
+pub fn assert_round_trip(db: &Database, format: Format) {
+    let mut expected = db.clone();
+    expected.provenance = None;
+    let actual = round_trip(db, format)
+        .unwrap_or_else(|e| panic!("round trip through {:?} failed: {}", format, e));
+    if actual != expected {
+        let changes = diff(&expected, &actual);
+        match changes.is_empty() {
+            true => panic!(
+                "round trip through {:?} changed the database in a way diff doesn't show",
+                format
+            ),
+            false => panic!("round trip through {:?} changed:\n{}", format, changes),
+        }
+    }
+}
+
+// parses src as format, then assert_round_trip, giving back the parsed database
+pub fn assert_parse_round_trip(src: &str, format: Format) -> Database {
+    let db = format
+        .parse_reader(src.as_bytes())
+        .unwrap_or_else(|e| panic!("parsing {:?} failed: {}", format, e));
+    assert_round_trip(&db, format);
+    db
+}
+
+fn mismatch(frame: &str, signal: &str, what: String) -> Error {
+    Error::Hint(
+        format!("{} in {}: {}", signal, frame, what),
+        Box::new(Error::InvalidPayload),
+    )
+}
+
+// per frame by id, packs zero, the maximum and count random raw values of every signal but byte arrays,
+// they must unpack to the same raw values, and their physical values must convert back to them unless
+// outside the signal's range
+pub fn check_payloads(db: &Database, count: usize) -> Result<(), Error> {
+    let mut frames: Vec<_> = db.messages.iter().collect();
+    frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+    for (name, msg) in frames {
+        let hint = |e: Error| Error::Hint(name.to_string(), Box::new(e));
+        let signals: Vec<(&Name, &Signal)> = msg
+            .signals
+            .iter()
+            .map(|n| Ok((n, db.signals.get(n).ok_or(Error::UnknownSignal)?)))
+            .filter(|s| !matches!(s, Ok((_, s)) if s.kind == ValueKind::ByteArray))
+            .collect::<Result<_, Error>>()
+            .map_err(hint)?;
+
+        let mut state = SEED ^ msg.id as u64;
+        for case in 0..count + 2 {
+            let raws: Vec<u64> = signals
+                .iter()
+                .map(|(_, s)| match case {
+                    0 => 0,
+                    1 => s.max_raw(),
+                    _ => next_random(&mut state) & s.max_raw(),
+                })
+                .collect();
+            let values: HashMap<String, SignalValue> = signals
+                .iter()
+                .zip(&raws)
+                .map(|((n, _), raw)| (n.to_string(), SignalValue::Raw(*raw)))
+                .collect();
+            let payload = db.encode_message(name, &values).map_err(hint)?;
+            let decoded = db.decode_message(name, &payload).map_err(hint)?;
+            for ((n, s), raw) in signals.iter().zip(&raws) {
+                let Some(d) = decoded.iter().find(|d| *d.name == ***n) else {
+                    return Err(mismatch(name, n, "not decoded".to_string()));
+                };
+                if d.raw != *raw {
+                    return Err(mismatch(
+                        name,
+                        n,
+                        format!(
+                            "raw {:#X} packed as {:02X?} unpacks as {:#X}",
+                            raw, payload, d.raw
+                        ),
+                    ));
+                }
+                // NaN never compares equal, float raws are bits anyway
+                let Some(physical) = d.physical.filter(|_| !s.kind.is_float()) else {
+                    continue;
+                };
+                match s.to_raw(&SignalValue::Physical(physical)) {
+                    Ok(back) if back != *raw => {
+                        return Err(mismatch(
+                            name,
+                            n,
+                            format!(
+                                "raw {:#X} is {} which converts to raw {:#X}",
+                                raw, physical, back
+                            ),
+                        ))
+                    }
+                    Err(e) if !matches!(e.kind(), Error::ValueOutOfRange) => return Err(hint(e)),
+                    _ => (),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// panics with the first failure of check_payloads
+pub fn assert_payloads(db: &Database, count: usize) {
+    if let Err(e) = check_payloads(db, count) {
+        panic!("payload check failed: {}", e);
+    }
+}
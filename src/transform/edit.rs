@@ -0,0 +1,316 @@
+use crate::parsers::encoding::{
+    DatabaseType, FrameProtocol, LDFScheduleCommand, Message, Name, Signal, ValueKind,
+};
+use crate::parsers::ids::{BitWidth, FrameId, Pid};
+use crate::{Database, Error};
+use std::collections::BTreeSet;
+
+const LIN_MAX_ID: u32 = 59; // unconditional frames
+
+/*
+ * The pub fields are the raw escape hatch, anything goes but nothing is checked until a writer or validate()
+ * trips over it. These methods keep every reference resolvable, so edits made through them stay valid.
+ */
+impl Database {
+    pub fn signal(&self, name: &str) -> Option<&Signal> {
+        self.signals.get(name)
+    }
+
+    pub fn message(&self, name: &str) -> Option<&Message> {
+        self.messages.get(name)
+    }
+
+    // plain signals in frame order, then multiplexed ones by name
+    pub fn message_signals(&self, message: &str) -> Result<Vec<(&Name, &Signal)>, Error> {
+        let msg = self.messages.get(message).ok_or(Error::UnknownFrame)?;
+        let mut muxed: Vec<&Name> = msg.mux_signals.keys().collect();
+        muxed.sort();
+        msg.signals
+            .iter()
+            .chain(muxed)
+            .map(|n| Ok((n, self.signals.get(n).ok_or(Error::UnknownSignal)?)))
+            .collect()
+    }
+
+    fn node_exists(&self, node: &str) -> bool {
+        match &self.extra {
+            DatabaseType::LDF(data) => {
+                *data.commander == *node || data.responders.contains_key(node)
+            }
+            DatabaseType::DBC(data) => data.nodes.iter().any(|n| **n == *node),
+            DatabaseType::NCF => true,
+        }
+    }
+
+    pub fn add_signal(&mut self, name: &str, signal: Signal) -> Result<(), Error> {
+        let hint = |e: Error| Error::Hint(name.to_string(), Box::new(e));
+        if self.signals.contains_key(name) {
+            return Err(hint(Error::DuplicateSignal));
+        }
+        match signal.kind {
+            ValueKind::ByteArray => BitWidth::array(signal.bit_width),
+            _ => BitWidth::new(signal.bit_width),
+        }
+        .map_err(hint)?;
+        self.signals.insert(name.into(), signal);
+        Ok(())
+    }
+
+    // empty frame, add signals with add_signal_to_message
+    pub fn add_message(
+        &mut self,
+        name: &str,
+        id: FrameId,
+        byte_width: u16,
+        sender: &str,
+    ) -> Result<(), Error> {
+        let hint = |e: Error| Error::Hint(name.to_string(), Box::new(e));
+        if self.messages.contains_key(name) {
+            return Err(hint(Error::DuplicateFrame));
+        }
+        let protocol = if id.is_extended() {
+            FrameProtocol::Extended
+        } else {
+            FrameProtocol::Standard
+        };
+        let id = id.get();
+        if let Some((other, _)) = self.messages.iter().find(|(_, m)| m.id == id) {
+            return Err(Error::Hint(
+                format!("{} and {} both use 0x{:02X}", other, name, id),
+                Box::new(Error::DuplicateFrameId),
+            ));
+        }
+        if !self.node_exists(sender) {
+            return Err(Error::Hint(
+                sender.to_string(),
+                Box::new(Error::UnknownNode),
+            ));
+        }
+        let mut msg = Message {
+            sender: sender.into(),
+            id,
+            byte_width,
+            signals: Vec::new(),
+            mux_signals: Default::default(),
+            checksum: None,
+            cycle_time: None,
+            send_type: None,
+            protocol,
+            description: None,
+            attributes: Default::default(),
+            signal_groups: Default::default(),
+        };
+        if let DatabaseType::LDF(data) = &self.extra {
+            msg.checksum = Some(data.checksum_model(&msg));
+        }
+        self.messages.insert(name.into(), msg);
+        Ok(())
+    }
+
+    // the signal must fit the frame without sharing bits with its other signals, one without a bit_start
+    // waits for pack_signals
+    pub fn add_signal_to_message(&mut self, message: &str, signal: &str) -> Result<(), Error> {
+        let hint = |e: Error| Error::Hint(format!("{} in {}", signal, message), Box::new(e));
+        let (name, s) = self
+            .signals
+            .get_key_value(signal)
+            .ok_or(hint(Error::UnknownSignal))?;
+        let msg = self
+            .messages
+            .get(message)
+            .ok_or(hint(Error::UnknownFrame))?;
+        if msg.signals.contains(name) || msg.mux_signals.contains_key(name) {
+            return Err(hint(Error::DuplicateSignal));
+        }
+        let bits = s.bit_positions();
+        if bits.iter().any(|b| b.get() >= msg.byte_width * 8) {
+            return Err(hint(Error::SignalOutsideFrame));
+        }
+        for other in msg.signals.iter().chain(msg.mux_signals.keys()) {
+            let o = self.signals.get(other).ok_or(Error::Hint(
+                format!("{} in {}", other, message),
+                Box::new(Error::UnknownSignal),
+            ))?;
+            if o.bit_positions().iter().any(|b| bits.contains(b)) {
+                return Err(Error::Hint(
+                    format!("{}: {} and {}", message, other, signal),
+                    Box::new(Error::SignalOverlap),
+                ));
+            }
+        }
+        let name = name.clone();
+        let checksum = match &self.extra {
+            DatabaseType::LDF(data) => {
+                let mut msg = self.messages[message].clone();
+                msg.signals.push(name.clone());
+                Some(data.checksum_model(&msg))
+            }
+            _ => None,
+        };
+        let msg = self.messages.get_mut(message).unwrap();
+        msg.signals.push(name);
+        msg.checksum = checksum.or(msg.checksum);
+        Ok(())
+    }
+
+    pub fn remove_signal_from_message(&mut self, message: &str, signal: &str) -> Result<(), Error> {
+        let hint = |e: Error| Error::Hint(format!("{} in {}", signal, message), Box::new(e));
+        let msg = self
+            .messages
+            .get_mut(message)
+            .ok_or(hint(Error::UnknownFrame))?;
+        let len = msg.signals.len();
+        msg.signals.retain(|s| **s != *signal);
+        if msg.signals.len() == len && msg.mux_signals.remove(signal).is_none() {
+            return Err(hint(Error::UnknownSignal));
+        }
+        msg.prune_signal_groups();
+        Ok(())
+    }
+
+    // also drops it from frames, subscriptions and receivers, signals it multiplexes leave their frames
+    pub fn remove_signal(&mut self, name: &str) -> Option<Signal> {
+        let signal = self.signals.remove(name)?;
+        for msg in self.messages.values_mut() {
+            msg.signals.retain(|s| **s != *name);
+            msg.mux_signals.remove(name);
+            msg.mux_signals
+                .retain(|_, (_, multiplexors)| !multiplexors.iter().any(|m| **m == *name));
+            msg.prune_signal_groups();
+        }
+        match &mut self.extra {
+            DatabaseType::LDF(data) => {
+                for resp in data.responders.values_mut() {
+                    resp.subscribed_signals.retain(|s| **s != *name);
+                    if resp.response_error.as_deref() == Some(name) {
+                        resp.response_error = None;
+                    }
+                }
+            }
+            DatabaseType::DBC(data) => {
+                data.receivers.remove(name);
+                data.mux_ranges.remove(name);
+            }
+            DatabaseType::NCF => (),
+        }
+        Some(signal)
+    }
+
+    // gives LIN frames unconditional ids, each keeps its own if valid and free and the rest get the lowest free
+    // ones in name order, ids of other frames and event triggered frames stay taken, so the same input always
+    // gives the same ids, returns the ids with their PIDs
+    pub fn assign_frame_ids(
+        &mut self,
+        frames: &[&str],
+    ) -> Result<Vec<(Name, FrameId, Pid)>, Error> {
+        let DatabaseType::LDF(data) = &mut self.extra else {
+            return Err(Error::IncompatibleDatabase);
+        };
+        let mut names: Vec<Name> = frames
+            .iter()
+            .map(|f| {
+                let hint = Error::Hint(f.to_string(), Box::new(Error::UnknownFrame));
+                Ok(self.messages.get_key_value(*f).ok_or(hint)?.0.clone())
+            })
+            .collect::<Result<_, Error>>()?;
+        names.sort();
+        names.dedup();
+        let mut used: BTreeSet<u32> = self
+            .messages
+            .iter()
+            .filter(|(n, _)| !names.contains(n))
+            .map(|(_, m)| m.id)
+            .chain(data.event_frames.values().map(|(_, id, _)| *id))
+            .collect();
+        let mut assigned = Vec::new();
+        let mut pending = Vec::new();
+        for name in names {
+            let id = self.messages[&name].id;
+            if id <= LIN_MAX_ID && used.insert(id) {
+                assigned.push((name, id));
+            } else {
+                pending.push(name);
+            }
+        }
+        for name in pending {
+            let id = (0..=LIN_MAX_ID)
+                .find(|id| !used.contains(id))
+                .ok_or(Error::Hint(
+                    format!("no free LIN id for {}", name),
+                    Box::new(Error::ValueOutOfRange),
+                ))?;
+            used.insert(id);
+            assigned.push((name, id));
+        }
+
+        // PIDs given by AssignFrameIdRange follow the frames they configure, 0xFF leaves a frame unchanged
+        for (name, id) in &assigned {
+            self.messages.get_mut(name).unwrap().id = *id;
+        }
+        for table in data.schedule_tables.values_mut() {
+            for (cmd, _) in table {
+                let LDFScheduleCommand::AssignFrameIdRange { name, index, pid } = cmd else {
+                    continue;
+                };
+                let Some(resp) = data.responders.get(name) else {
+                    continue;
+                };
+                for (i, pid) in pid.iter_mut().enumerate() {
+                    let frame = resp.configurable_frames.get(*index as usize + i);
+                    let id = frame.and_then(|(f, _)| assigned.iter().find(|(n, _)| n == f));
+                    if let Some((_, id)) = id.filter(|_| *pid != 0xFF) {
+                        *pid = Pid::from_id(*id as u8).get();
+                    }
+                }
+            }
+        }
+        assigned.sort_by_key(|(_, id)| *id);
+        assigned
+            .into_iter()
+            .map(|(name, id)| Ok((name, FrameId::lin(id)?, Pid::from_id(id as u8))))
+            .collect()
+    }
+
+    // also drops it from sporadic and event triggered frames, configurable frames and schedule tables, its
+    // signals stay
+    pub fn remove_message(&mut self, name: &str) -> Option<Message> {
+        let msg = self.messages.remove(name)?;
+        if let DatabaseType::LDF(data) = &mut self.extra {
+            for frames in data.sporadic_frames.values_mut() {
+                frames.retain(|f| **f != *name);
+            }
+            for (_, _, frames) in data.event_frames.values_mut() {
+                frames.retain(|f| **f != *name);
+            }
+
+            // sporadic and event triggered frames left empty go too
+            let mut gone: Vec<Name> = vec![name.into()];
+            gone.extend(
+                data.sporadic_frames
+                    .iter()
+                    .filter(|(_, frames)| frames.is_empty())
+                    .map(|(n, _)| n.clone()),
+            );
+            gone.extend(
+                data.event_frames
+                    .iter()
+                    .filter(|(_, (_, _, frames))| frames.is_empty())
+                    .map(|(n, _)| n.clone()),
+            );
+            data.sporadic_frames.retain(|n, _| !gone.contains(n));
+            data.event_frames.retain(|n, _| !gone.contains(n));
+            for resp in data.responders.values_mut() {
+                resp.configurable_frames.retain(|(f, _)| !gone.contains(f));
+            }
+            for table in data.schedule_tables.values_mut() {
+                table.retain(|(cmd, _)| match cmd {
+                    LDFScheduleCommand::Frame(f) => !gone.contains(f),
+                    LDFScheduleCommand::AssignFrameId { frame, .. } => !gone.contains(frame),
+                    _ => true,
+                });
+            }
+            data.schedule_tables.retain(|_, table| !table.is_empty());
+        }
+        Some(msg)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::analysis::query::Pattern;
+use crate::parsers::encoding::{DatabaseType, LDFScheduleCommand, Name};
+use crate::Database;
+use std::collections::HashSet;
+
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub nodes: Vec<String>, // keep frames published or subscribed by any of these
+    pub messages: Vec<String>, // keep frames matching any of these globs or /regexes/
+}
+
+// supports '*' and '?'
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((bp, bt)) = backtrack {
+            pi = bp + 1;
+            ti = bt + 1;
+            backtrack = Some((bp, bt + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+impl Filter {
+    fn involves(&self, db: &Database, node: &str, message: &str) -> bool {
+        db.frames_published_by(node)
+            .chain(db.frames_subscribed_by(node))
+            .any(|(name, _)| &**name == message)
+    }
+
+    fn keep_message(&self, db: &Database, patterns: &[Pattern], message: &str) -> bool {
+        (self.messages.is_empty() || patterns.iter().any(|p| p.matches(message)))
+            && (self.nodes.is_empty() || self.nodes.iter().any(|n| self.involves(db, n, message)))
+    }
+}
+
+impl Database {
+    // invalid patterns match nothing, check them with find_messages first
+    pub fn filter(mut self, filter: &Filter) -> Database {
+        let patterns: Vec<Pattern> = filter
+            .messages
+            .iter()
+            .filter_map(|p| Pattern::parse(p).ok())
+            .collect();
+        let mut messages: HashSet<Name> = self
+            .messages
+            .keys()
+            .filter(|m| filter.keep_message(&self, &patterns, m))
+            .cloned()
+            .collect();
+
+        // responders need their response_error signal to stay valid
+        let mut responders = HashSet::new();
+        if let DatabaseType::LDF(data) = &self.extra {
+            for (name, resp) in &data.responders {
+                let publishes = messages.iter().any(|m| self.messages[m].sender == *name);
+                let subscribes = resp.subscribed_signals.iter().any(|s| {
+                    messages
+                        .iter()
+                        .any(|m| self.messages[m].signals.contains(s))
+                });
+                if filter.nodes.iter().any(|n| **n == **name)
+                    || publishes
+                    || (filter.nodes.is_empty() && subscribes)
+                {
+                    responders.insert(name.clone());
+                    if let Some(s) = &resp.response_error {
+                        messages.extend(
+                            self.messages
+                                .iter()
+                                .filter(|(_, m)| m.signals.contains(s))
+                                .map(|(n, _)| n.clone()),
+                        );
+                    }
+                }
+            }
+        }
+
+        self.messages.retain(|name, _| messages.contains(name));
+        let signals: HashSet<Name> = self
+            .messages
+            .values()
+            .flat_map(|m| m.signals.iter().chain(m.mux_signals.keys()))
+            .chain(
+                self.messages
+                    .values()
+                    .flat_map(|m| m.mux_signals.values().flat_map(|(_, s)| s)),
+            )
+            .cloned()
+            .collect();
+        self.signals.retain(|name, _| signals.contains(name));
+
+        if let DatabaseType::LDF(data) = &mut self.extra {
+            data.responders.retain(|name, _| responders.contains(name));
+            for resp in data.responders.values_mut() {
+                resp.subscribed_signals.retain(|s| signals.contains(s));
+                if resp
+                    .response_error
+                    .as_ref()
+                    .is_some_and(|s| !signals.contains(s))
+                {
+                    resp.response_error = None;
+                }
+            }
+            for frames in data.sporadic_frames.values_mut() {
+                frames.retain(|f| messages.contains(f));
+            }
+            data.sporadic_frames.retain(|_, frames| !frames.is_empty());
+            for (_, _, frames) in data.event_frames.values_mut() {
+                frames.retain(|f| messages.contains(f));
+            }
+            data.event_frames
+                .retain(|_, (_, _, frames)| !frames.is_empty());
+            for resp in data.responders.values_mut() {
+                resp.configurable_frames
+                    .retain(|(f, _)| messages.contains(f) || data.event_frames.contains_key(f));
+            }
+
+            let nodes: HashSet<Name> = data.responders.keys().cloned().collect();
+            let frames: HashSet<Name> = messages
+                .iter()
+                .chain(data.sporadic_frames.keys())
+                .chain(data.event_frames.keys())
+                .cloned()
+                .collect();
+            for table in data.schedule_tables.values_mut() {
+                table.retain(|(cmd, _)| match cmd {
+                    LDFScheduleCommand::Frame(f) => frames.contains(f),
+                    LDFScheduleCommand::AssignNAD(n)
+                    | LDFScheduleCommand::SaveConfiguration(n)
+                    | LDFScheduleCommand::DataDump { name: n, .. }
+                    | LDFScheduleCommand::AssignFrameIdRange { name: n, .. } => nodes.contains(n),
+                    LDFScheduleCommand::AssignFrameId { node, frame } => {
+                        nodes.contains(node) && messages.contains(frame)
+                    }
+                    _ => true,
+                });
+            }
+            data.schedule_tables.retain(|_, table| !table.is_empty());
+        }
+        self
+    }
+}
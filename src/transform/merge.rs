@@ -0,0 +1,106 @@
+use crate::parsers::encoding::{DatabaseType, Name};
+use crate::{Database, Error};
+use std::collections::HashMap;
+
+// adds entries of from missing in into, the same name must mean the same thing in both
+fn merge_map<T: Clone + PartialEq>(
+    into: &mut HashMap<Name, T>,
+    from: &HashMap<Name, T>,
+    error: fn() -> Error,
+) -> Result<(), Error> {
+    for (name, value) in from {
+        match into.get(name) {
+            Some(v) if v != value => {
+                return Err(Error::Hint(
+                    format!("{} differs between the databases", name),
+                    Box::new(error()),
+                ))
+            }
+            Some(_) => (),
+            None => {
+                into.insert(name.clone(), value.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Database {
+    // adds the nodes, frames and signals of other, both must be the same kind of database, nothing changes if
+    // a name or frame id is used differently by the two, bus settings like the bitrate are kept
+    pub fn merge(&mut self, other: &Database) -> Result<(), Error> {
+        let mut db = self.clone();
+        for (name, msg) in &other.messages {
+            let clash = db
+                .messages
+                .iter()
+                .find(|(n, m)| m.id == msg.id && *n != name);
+            if let Some((n, _)) = clash {
+                return Err(Error::Hint(
+                    format!("{} and {}", n, name),
+                    Box::new(Error::DuplicateFrameId),
+                ));
+            }
+        }
+        merge_map(&mut db.messages, &other.messages, || Error::DuplicateFrame)?;
+        merge_map(&mut db.signals, &other.signals, || Error::DuplicateSignal)?;
+        for (key, value) in &other.attributes {
+            db.attributes
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+
+        match (&mut db.extra, &other.extra) {
+            (DatabaseType::LDF(data), DatabaseType::LDF(other)) => {
+                if data.commander != other.commander {
+                    return Err(Error::Hint(
+                        format!("commanders {} and {}", data.commander, other.commander),
+                        Box::new(Error::IncompatibleDatabase),
+                    ));
+                }
+                merge_map(&mut data.responders, &other.responders, || {
+                    Error::DuplicateNode
+                })?;
+                merge_map(&mut data.sporadic_frames, &other.sporadic_frames, || {
+                    Error::DuplicateFrame
+                })?;
+                merge_map(&mut data.event_frames, &other.event_frames, || {
+                    Error::DuplicateFrame
+                })?;
+                merge_map(&mut data.schedule_tables, &other.schedule_tables, || {
+                    Error::DuplicateIdentifier
+                })?;
+            }
+            (DatabaseType::DBC(data), DatabaseType::DBC(other)) => {
+                for node in &other.nodes {
+                    if !data.nodes.contains(node) {
+                        data.nodes.push(node.clone());
+                    }
+                }
+                for (signal, nodes) in &other.receivers {
+                    let receivers = data.receivers.entry(signal.clone()).or_default();
+                    for node in nodes {
+                        if !receivers.contains(node) {
+                            receivers.push(node.clone());
+                        }
+                    }
+                }
+                merge_map(
+                    &mut data.node_descriptions,
+                    &other.node_descriptions,
+                    || Error::DuplicateNode,
+                )?;
+                merge_map(&mut data.node_attributes, &other.node_attributes, || {
+                    Error::DuplicateNode
+                })?;
+                merge_map(&mut data.mux_ranges, &other.mux_ranges, || {
+                    Error::DuplicateSignal
+                })?;
+            }
+            (DatabaseType::NCF, DatabaseType::NCF) => (),
+            _ => return Err(Error::IncompatibleDatabase),
+        }
+        *self = db;
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::analysis::schedule::lin_pid;
+use crate::parsers::encoding::{DatabaseType, LDFScheduleCommand, Message, Name};
+use crate::{Database, Error};
+use std::collections::HashMap;
+
+const LIN_PROTOCOL: &str = "2.2";
+
+type EventFrames = HashMap<Name, (Name, u32, Vec<Name>)>;
+
+fn frame_id(messages: &HashMap<Name, Message>, events: &EventFrames, frame: &str) -> Option<u8> {
+    messages
+        .get(frame)
+        .map(|m| m.id as u8)
+        .or(events.get(frame).map(|e| e.1 as u8))
+}
+
+impl Database {
+    // upgrades LIN 1.x-2.1 node attributes and schedule commands to 2.2, returns the assumptions made
+    pub fn migrate_lin22(mut self) -> Result<(Database, Vec<String>), Error> {
+        let data = match &mut self.extra {
+            DatabaseType::LDF(data) => data,
+            _ => return Err(Error::IncompatibleDatabase),
+        };
+        let mut report = Vec::new();
+
+        let mut names: Vec<Name> = data.responders.keys().cloned().collect();
+        names.sort();
+        for name in &names {
+            let resp = data.responders.get_mut(name).unwrap();
+            if resp.protocol == LIN_PROTOCOL {
+                continue;
+            }
+            if resp.protocol.is_empty() {
+                report.push(format!(
+                    "{}: no LIN_protocol, assumed {}",
+                    name, LIN_PROTOCOL
+                ));
+            } else {
+                report.push(format!(
+                    "{}: LIN_protocol {} -> {}",
+                    name, resp.protocol, LIN_PROTOCOL
+                ));
+            }
+            resp.protocol = LIN_PROTOCOL.to_string();
+
+            if resp.product_id.is_none() {
+                report.push(format!(
+                    "{}: no product_id, assumed 0x0000, 0x0000, 0",
+                    name
+                ));
+                resp.product_id = Some((0, 0, 0));
+            }
+            if resp.response_error.is_none() {
+                // 1-bit signal published by the node, as the spec recommends
+                let mut candidates: Vec<&Name> = self
+                    .messages
+                    .values()
+                    .filter(|m| m.sender == *name)
+                    .flat_map(|m| &m.signals)
+                    .filter(|s| self.signals.get(*s).is_some_and(|s| s.bit_width == 1))
+                    .collect();
+                candidates.sort();
+                match candidates.first() {
+                    Some(signal) => {
+                        report.push(format!("{}: no response_error, assumed {}", name, signal));
+                        resp.response_error = Some((*signal).clone());
+                    }
+                    None => report.push(format!(
+                        "{}: no response_error and no 1-bit signal published, must be added manually",
+                        name
+                    )),
+                }
+            }
+
+            // 2.0 configures frames by message id, 2.1+ by position for AssignFrameIdRange
+            for (i, (frame, id)) in resp.configurable_frames.iter_mut().enumerate() {
+                if let Some(message_id) = id.take() {
+                    let pid = frame_id(&self.messages, &data.event_frames, frame)
+                        .map(|id| format!(", PID 0x{:02X}", lin_pid(id)))
+                        .unwrap_or_default();
+                    report.push(format!(
+                        "{}: configurable frame {} message id 0x{:04X} replaced by index {}{}",
+                        name, frame, message_id, i, pid
+                    ));
+                }
+            }
+        }
+
+        // AssignFrameId is obsolete, assign the single frame through its index instead
+        let mut tables: Vec<Name> = data.schedule_tables.keys().cloned().collect();
+        tables.sort();
+        for table in &tables {
+            for (cmd, _) in data.schedule_tables.get_mut(table).unwrap() {
+                let LDFScheduleCommand::AssignFrameId { node, frame } = cmd else {
+                    continue;
+                };
+                let Some(id) = frame_id(&self.messages, &data.event_frames, frame) else {
+                    report.push(format!(
+                        "{}: AssignFrameId {{{}, {}}} kept, unknown frame",
+                        table, node, frame
+                    ));
+                    continue;
+                };
+                let Some(resp) = data.responders.get_mut(node) else {
+                    report.push(format!(
+                        "{}: AssignFrameId {{{}, {}}} kept, unknown node",
+                        table, node, frame
+                    ));
+                    continue;
+                };
+                let index = match resp
+                    .configurable_frames
+                    .iter()
+                    .position(|(f, _)| f == frame)
+                {
+                    Some(index) => index,
+                    None => {
+                        report.push(format!(
+                            "{}: {} not configurable by {}, appended to its configurable_frames",
+                            table, frame, node
+                        ));
+                        resp.configurable_frames.push((frame.clone(), None));
+                        resp.configurable_frames.len() - 1
+                    }
+                };
+                let Ok(index) = u8::try_from(index) else {
+                    report.push(format!(
+                        "{}: AssignFrameId {{{}, {}}} kept, frame index too large",
+                        table, node, frame
+                    ));
+                    continue;
+                };
+                let pid = lin_pid(id);
+                report.push(format!(
+                    "{}: AssignFrameId {{{}, {}}} -> AssignFrameIdRange {{{}, {}, 0x{:02X}}}",
+                    table, node, frame, node, index, pid
+                ));
+                *cmd = LDFScheduleCommand::AssignFrameIdRange {
+                    name: node.clone(),
+                    index,
+                    pid: [pid, 0xFF, 0xFF, 0xFF], // 0xFF leaves the following frames unchanged
+                };
+            }
+        }
+
+        // LIN 2.x nodes use the enhanced checksum
+        let mut frames: Vec<_> = self.messages.iter_mut().collect();
+        frames.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, msg) in frames {
+            let checksum = data.checksum_model(msg);
+            if msg.checksum.is_some_and(|c| c != checksum) {
+                report.push(format!(
+                    "{}: {:?} checksum -> {:?}",
+                    name,
+                    msg.checksum.unwrap(),
+                    checksum
+                ));
+            }
+            msg.checksum = Some(checksum);
+        }
+        Ok((self, report))
+    }
+}
@@ -0,0 +1,216 @@
+use crate::parsers::encoding::{Message, Name, Signal, BIT_START_INVALID};
+use crate::parsers::ids::{BitPos, FrameId};
+use crate::{Database, Error};
+use std::collections::HashMap;
+
+// choices for placing signals without a bit_start
+#[derive(Clone, Debug)]
+pub struct PackOptions {
+    pub max_byte_width: u16, // frames grow up to this to fit their signals, 8 for LIN and classic CAN
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self { max_byte_width: 8 }
+    }
+}
+
+// bits already taken where a signal could go, multiplexed signals only clash with plain ones and those of
+// the same multiplexor value
+fn used_bits(signals: &HashMap<Name, Signal>, msg: &Message, name: &str) -> Vec<BitPos> {
+    let value = msg.mux_signals.get(name).map(|(v, _)| *v);
+    let muxed = msg
+        .mux_signals
+        .iter()
+        .filter(|(_, (v, _))| value.is_none_or(|value| *v == value))
+        .map(|(n, _)| n);
+    msg.signals
+        .iter()
+        .chain(muxed)
+        .filter(|n| ***n != *name)
+        .filter_map(|n| signals.get(n))
+        .flat_map(|s| s.bit_positions())
+        .collect()
+}
+
+// lowest bit_start fitting in byte_width bytes, spanning no more bytes than the width needs if possible
+fn place(signal: &Signal, used: &[BitPos], byte_width: u16) -> Option<u16> {
+    let fits = |start: u16| {
+        let bits = Signal {
+            bit_start: start,
+            ..signal.clone()
+        }
+        .bit_positions();
+        let fits = bits
+            .iter()
+            .all(|b| b.byte() < byte_width && !used.contains(b));
+        let mut bytes: Vec<u16> = bits.iter().map(|b| b.byte()).collect();
+        bytes.dedup();
+        fits.then_some(bytes.len() as u16)
+    };
+    let least = signal.bit_width.div_ceil(8);
+    let starts = 0..byte_width * 8;
+    starts
+        .clone()
+        .find(|s| fits(*s) == Some(least))
+        .or_else(|| starts.clone().find(|s| fits(*s).is_some()))
+}
+
+// bit_start and byte width for a signal, growing the frame only as much as needed
+fn place_growing(
+    signal: &Signal,
+    used: &[BitPos],
+    byte_width: u16,
+    options: &PackOptions,
+) -> Option<(u16, u16)> {
+    (byte_width..=options.max_byte_width.max(byte_width))
+        .find_map(|width| Some((place(signal, used, width)?, width)))
+}
+
+impl Database {
+    // assigns bit_start to signals of frames without one, widest first, signals already placed stay where
+    // they are, returns what was placed
+    pub fn pack_signals(&mut self, options: &PackOptions) -> Result<Vec<String>, Error> {
+        let mut names: Vec<Name> = self.messages.keys().cloned().collect();
+        names.sort_by(|a, b| (self.messages[a].id, a).cmp(&(self.messages[b].id, b)));
+        let mut signals = self.signals.clone();
+        let mut widths = Vec::new();
+        let mut report = Vec::new();
+        for name in &names {
+            let msg = &self.messages[name];
+            let mut unplaced: Vec<&Name> = msg
+                .signals
+                .iter()
+                .chain(msg.mux_signals.keys())
+                .filter(|s| {
+                    signals
+                        .get(*s)
+                        .is_some_and(|s| s.bit_start == BIT_START_INVALID)
+                })
+                .collect();
+            unplaced.sort_by(|a, b| (signals[*b].bit_width, a).cmp(&(signals[*a].bit_width, b)));
+            let mut byte_width = msg.byte_width;
+            for sig in unplaced {
+                let used = used_bits(&signals, msg, sig);
+                let (start, width) = place_growing(&signals[sig], &used, byte_width, options)
+                    .ok_or(Error::Hint(
+                        format!("no room for {} in {}", sig, name),
+                        Box::new(Error::SignalOutsideFrame),
+                    ))?;
+                signals.get_mut(sig).unwrap().bit_start = start;
+                byte_width = width;
+                report.push(format!("{}: {} at bit {}", name, sig, start));
+            }
+            if byte_width != msg.byte_width {
+                report.push(format!("{}: grown to {} bytes", name, byte_width));
+                widths.push((name, byte_width));
+            }
+        }
+
+        // only once everything fits
+        for (name, byte_width) in widths {
+            self.messages.get_mut(name).unwrap().byte_width = byte_width;
+        }
+        self.signals = signals;
+        Ok(report)
+    }
+
+    // puts signals in no frame into as few new frames of sender as possible, named prefix_1, prefix_2... and
+    // using ids in order, returns the frames made
+    pub fn pack_new_frames(
+        &mut self,
+        signals: &[&str],
+        sender: &str,
+        ids: &[FrameId],
+        prefix: &str,
+        options: &PackOptions,
+    ) -> Result<Vec<Name>, Error> {
+        let mut pending: Vec<(&str, &Signal)> = signals
+            .iter()
+            .map(|n| {
+                let hint = |e: Error| Error::Hint(n.to_string(), Box::new(e));
+                let signal = self.signals.get(*n).ok_or(hint(Error::UnknownSignal))?;
+                if self.messages.values().any(|m| {
+                    m.signals
+                        .iter()
+                        .chain(m.mux_signals.keys())
+                        .any(|s| **s == **n)
+                }) {
+                    return Err(hint(Error::DuplicateSignal));
+                }
+                Ok((*n, signal))
+            })
+            .collect::<Result<_, Error>>()?;
+        pending.sort_by(|a, b| (b.1.bit_width, a.0).cmp(&(a.1.bit_width, b.0)));
+
+        // first fit, fixed positions are kept when they're free
+        let mut frames: Vec<(Vec<(&str, u16)>, u16)> = Vec::new(); // (signals with bit_start, byte width)
+        for (name, signal) in pending {
+            let used = |frame: &[(&str, u16)]| -> Vec<BitPos> {
+                frame
+                    .iter()
+                    .flat_map(|(n, start)| {
+                        Signal {
+                            bit_start: *start,
+                            ..self.signals[*n].clone()
+                        }
+                        .bit_positions()
+                    })
+                    .collect()
+            };
+            let fit = |frame: &[(&str, u16)], byte_width: u16| -> Option<(u16, u16)> {
+                let used = used(frame);
+                if signal.bit_start != BIT_START_INVALID {
+                    let bits = signal.bit_positions();
+                    let width = bits
+                        .iter()
+                        .max()
+                        .map_or(0, |b| b.byte() + 1)
+                        .max(byte_width);
+                    let free = bits.iter().all(|b| !used.contains(b));
+                    return (free && width <= options.max_byte_width)
+                        .then_some((signal.bit_start, width));
+                }
+                place_growing(signal, &used, byte_width, options)
+            };
+            let slot = frames
+                .iter()
+                .enumerate()
+                .find_map(|(i, (frame, width))| Some((i, fit(frame, *width)?)));
+            match slot {
+                Some((i, (start, width))) => {
+                    frames[i].0.push((name, start));
+                    frames[i].1 = width;
+                }
+                None => {
+                    let (start, width) = fit(&[], 0).ok_or(Error::Hint(
+                        format!("{} is wider than {} bytes", name, options.max_byte_width),
+                        Box::new(Error::SignalOutsideFrame),
+                    ))?;
+                    frames.push((vec![(name, start)], width));
+                }
+            }
+        }
+        if frames.len() > ids.len() {
+            return Err(Error::Hint(
+                format!("{} frames needed, {} ids given", frames.len(), ids.len()),
+                Box::new(Error::ValueOutOfRange),
+            ));
+        }
+
+        // on a copy, a name or id already taken leaves nothing half done
+        let mut db = self.clone();
+        let mut ret = Vec::new();
+        for (i, (frame, byte_width)) in frames.into_iter().enumerate() {
+            let name = format!("{}_{}", prefix, i + 1);
+            db.add_message(&name, ids[i], byte_width, sender)?;
+            for (signal, start) in frame {
+                db.signals.get_mut(signal).unwrap().bit_start = start;
+                db.add_signal_to_message(&name, signal)?;
+            }
+            ret.push(name.into());
+        }
+        *self = db;
+        Ok(ret)
+    }
+}
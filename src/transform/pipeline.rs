@@ -0,0 +1,169 @@
+use crate::analysis::diff::EntityKind;
+use crate::parsers::encoding::Name;
+use crate::transform::visit::{walk_mut, VisitorMut};
+use crate::{Database, Error, Filter};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/*
+ * Transformations applied one after another, e.g. before a project target is written:
+ *
+ *   [[targets]]
+ *   input = "body"
+ *   output = "out/body_lsm.dbc"
+ *   pipeline = [
+ *       { step = "filter", nodes = ["LSM"] },
+ *       { step = "rename", signals = { LSMError = "LSM_Error" } },
+ *       { step = "normalize_units", units = ["km/h", "degC"] },
+ *       { step = "merge", input = "diag" },
+ *   ]
+ */
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    // see Filter
+    Filter {
+        #[serde(default)]
+        nodes: Vec<String>,
+        #[serde(default)]
+        messages: Vec<String>,
+    },
+    // old name -> new name, every name must exist, frames include sporadic and event triggered ones
+    Rename {
+        #[serde(default)]
+        nodes: BTreeMap<Name, Name>,
+        #[serde(default)]
+        frames: BTreeMap<Name, Name>,
+        #[serde(default)]
+        signals: BTreeMap<Name, Name>,
+        #[serde(default)]
+        schedule_tables: BTreeMap<Name, Name>,
+    },
+    // see Database::normalize_units
+    NormalizeUnits {
+        units: Vec<String>,
+    },
+    // another input by name, see Database::merge
+    Merge {
+        input: Name,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pipeline {
+    pub steps: Vec<Step>,
+}
+
+// applies a rename step while walking, remembering which names it found
+struct Renamer<'a> {
+    step: &'a Step,
+    found: HashSet<Name>,
+}
+
+impl Renamer<'_> {
+    fn renames(&self, kind: EntityKind) -> Option<&BTreeMap<Name, Name>> {
+        let Step::Rename {
+            nodes,
+            frames,
+            signals,
+            schedule_tables,
+        } = self.step
+        else {
+            return None;
+        };
+        match kind {
+            EntityKind::Node => Some(nodes),
+            EntityKind::Frame | EntityKind::SporadicFrame | EntityKind::EventFrame => Some(frames),
+            EntityKind::Signal => Some(signals),
+            EntityKind::ScheduleTable => Some(schedule_tables),
+            EntityKind::Database => None,
+        }
+    }
+}
+
+impl VisitorMut for Renamer<'_> {
+    fn rename(&mut self, kind: EntityKind, name: &Name) -> Option<Name> {
+        let new = self.renames(kind)?.get(name)?.clone();
+        self.found.insert(name.clone());
+        Some(new)
+    }
+}
+
+fn run_step(
+    mut db: Database,
+    step: &Step,
+    inputs: &BTreeMap<Name, Database>,
+) -> Result<Database, Error> {
+    match step {
+        Step::Filter { nodes, messages } => {
+            let filter = Filter {
+                nodes: nodes.clone(),
+                messages: messages.clone(),
+            };
+            for pattern in &filter.messages {
+                db.find_messages(pattern)?; // reports invalid patterns
+            }
+            db = db.filter(&filter);
+        }
+        Step::Rename {
+            nodes,
+            frames,
+            signals,
+            schedule_tables,
+        } => {
+            let mut renamer = Renamer {
+                step,
+                found: HashSet::new(),
+            };
+            walk_mut(&mut db, &mut renamer)?;
+            let kinds = [
+                (nodes, Error::UnknownNode),
+                (frames, Error::UnknownFrame),
+                (signals, Error::UnknownSignal),
+                (schedule_tables, Error::UnknownScheduleTable),
+            ];
+            for (renames, error) in kinds {
+                if let Some(name) = renames.keys().find(|n| !renamer.found.contains(*n)) {
+                    return Err(Error::Hint(name.to_string(), Box::new(error)));
+                }
+            }
+        }
+        Step::NormalizeUnits { units } => {
+            let units: Vec<&str> = units.iter().map(|u| u.as_str()).collect();
+            db.normalize_units(&units)?;
+        }
+        Step::Merge { input } => {
+            let other = inputs.get(input).ok_or(Error::Hint(
+                format!("merge uses input {}", input),
+                Box::new(Error::UnknownBus),
+            ))?;
+            db.merge(other)?;
+        }
+    }
+    Ok(db)
+}
+
+impl Pipeline {
+    // names of the other inputs merged in
+    pub fn inputs(&self) -> impl Iterator<Item = &Name> {
+        self.steps.iter().filter_map(|s| match s {
+            Step::Merge { input } => Some(input),
+            _ => None,
+        })
+    }
+
+    // inputs holds the databases merge steps refer to
+    pub fn run(
+        &self,
+        mut db: Database,
+        inputs: &BTreeMap<Name, Database>,
+    ) -> Result<Database, Error> {
+        for (i, step) in self.steps.iter().enumerate() {
+            let hint = |e| Error::Hint(format!("pipeline step {}", i + 1), Box::new(e));
+            db = run_step(db, step, inputs).map_err(hint)?;
+        }
+        Ok(db)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::parsers::encoding::{DatabaseType, LDFScheduleCommand, Name};
+use crate::parsers::ldf::parse_char_string;
+use crate::Database;
+use std::collections::HashMap;
+
+fn rename_keys<T>(map: HashMap<Name, T>, rename: &impl Fn(&Name) -> Name) -> HashMap<Name, T> {
+    map.into_iter().map(|(k, v)| (rename(&k), v)).collect()
+}
+
+fn rename_all(list: &mut [Name], rename: &impl Fn(&Name) -> Name) {
+    for n in list {
+        *n = rename(n);
+    }
+}
+
+impl Database {
+    // appends the LDF channel name to signal, frame and schedule table names, no-op without one
+    pub fn apply_postfix(mut self) -> Database {
+        let postfix = match &self.extra {
+            DatabaseType::LDF(data) => parse_char_string(&data.postfix),
+            _ => String::new(),
+        };
+        if postfix.is_empty() {
+            return self;
+        }
+        let rename = |n: &Name| -> Name { format!("{}{}", n, postfix).into() };
+
+        self.signals = rename_keys(std::mem::take(&mut self.signals), &rename);
+        self.messages = rename_keys(std::mem::take(&mut self.messages), &rename);
+        for msg in self.messages.values_mut() {
+            rename_all(&mut msg.signals, &rename);
+            msg.mux_signals = rename_keys(std::mem::take(&mut msg.mux_signals), &rename);
+            for (_, signals) in msg.mux_signals.values_mut() {
+                rename_all(signals, &rename);
+            }
+            msg.signal_groups = rename_keys(std::mem::take(&mut msg.signal_groups), &rename);
+            for group in msg.signal_groups.values_mut() {
+                rename_all(&mut group.signals, &rename);
+            }
+        }
+
+        if let DatabaseType::LDF(data) = &mut self.extra {
+            for resp in data.responders.values_mut() {
+                rename_all(&mut resp.subscribed_signals, &rename);
+                resp.response_error = resp.response_error.as_ref().map(rename);
+                for (frame, _) in &mut resp.configurable_frames {
+                    *frame = rename(frame);
+                }
+            }
+            data.sporadic_frames = rename_keys(std::mem::take(&mut data.sporadic_frames), &rename);
+            for frames in data.sporadic_frames.values_mut() {
+                rename_all(frames, &rename);
+            }
+            data.event_frames = rename_keys(std::mem::take(&mut data.event_frames), &rename);
+            for (resolver, _, frames) in data.event_frames.values_mut() {
+                *resolver = rename(resolver);
+                rename_all(frames, &rename);
+            }
+            data.schedule_tables = rename_keys(std::mem::take(&mut data.schedule_tables), &rename);
+            for table in data.schedule_tables.values_mut() {
+                for (cmd, _) in table {
+                    match cmd {
+                        LDFScheduleCommand::Frame(frame)
+                        | LDFScheduleCommand::AssignFrameId { frame, .. } => *frame = rename(frame),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        self
+    }
+}
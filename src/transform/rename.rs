@@ -0,0 +1,167 @@
+use crate::analysis::diff::EntityKind;
+use crate::parsers::encoding::{DatabaseType, LDFScheduleCommand, Name};
+use crate::{Database, Error};
+use std::collections::HashMap;
+
+fn rename_keys<T>(
+    map: &mut HashMap<Name, T>,
+    renames: &HashMap<Name, Name>,
+    error: fn() -> Error,
+) -> Result<(), Error> {
+    let len = map.len();
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(k, v)| (renames.get(&k).unwrap_or(&k).clone(), v))
+        .collect();
+    if map.len() != len {
+        return Err(Error::Hint(
+            "renamed onto an existing name".to_string(),
+            Box::new(error()),
+        ));
+    }
+    Ok(())
+}
+
+fn rename_one(name: &mut Name, renames: &HashMap<Name, Name>) {
+    if let Some(new) = renames.get(name) {
+        *name = new.clone();
+    }
+}
+
+fn rename_list<'a>(names: impl IntoIterator<Item = &'a mut Name>, renames: &HashMap<Name, Name>) {
+    for name in names {
+        rename_one(name, renames);
+    }
+}
+
+impl Database {
+    // renames entities of a kind and every reference to them, names not in renames stay, nothing changes if
+    // two would end up with the same name, frames, sporadic and event triggered frames share names
+    pub fn rename(&mut self, kind: EntityKind, renames: &HashMap<Name, Name>) -> Result<(), Error> {
+        let mut db = self.clone();
+        match kind {
+            EntityKind::Database => (),
+            EntityKind::Signal => db.rename_signals(renames)?,
+            EntityKind::Frame | EntityKind::SporadicFrame | EntityKind::EventFrame => {
+                db.rename_frames(renames)?
+            }
+            EntityKind::Node => db.rename_nodes(renames)?,
+            EntityKind::ScheduleTable => {
+                if let DatabaseType::LDF(data) = &mut db.extra {
+                    rename_keys(&mut data.schedule_tables, renames, || {
+                        Error::DuplicateIdentifier
+                    })?;
+                    for (resolver, _, _) in data.event_frames.values_mut() {
+                        rename_one(resolver, renames);
+                    }
+                }
+            }
+        }
+        *self = db;
+        Ok(())
+    }
+
+    fn rename_signals(&mut self, renames: &HashMap<Name, Name>) -> Result<(), Error> {
+        rename_keys(&mut self.signals, renames, || Error::DuplicateSignal)?;
+        for msg in self.messages.values_mut() {
+            rename_list(&mut msg.signals, renames);
+            rename_keys(&mut msg.mux_signals, renames, || Error::DuplicateSignal)?;
+            for (_, multiplexors) in msg.mux_signals.values_mut() {
+                rename_list(multiplexors, renames);
+            }
+            for group in msg.signal_groups.values_mut() {
+                rename_list(&mut group.signals, renames);
+            }
+        }
+        match &mut self.extra {
+            DatabaseType::LDF(data) => {
+                for resp in data.responders.values_mut() {
+                    rename_list(&mut resp.subscribed_signals, renames);
+                    rename_list(&mut resp.response_error, renames);
+                }
+            }
+            DatabaseType::DBC(data) => {
+                rename_keys(&mut data.receivers, renames, || Error::DuplicateSignal)?;
+                rename_keys(&mut data.mux_ranges, renames, || Error::DuplicateSignal)?;
+            }
+            DatabaseType::NCF => (),
+        }
+        Ok(())
+    }
+
+    fn rename_frames(&mut self, renames: &HashMap<Name, Name>) -> Result<(), Error> {
+        rename_keys(&mut self.messages, renames, || Error::DuplicateFrame)?;
+        let DatabaseType::LDF(data) = &mut self.extra else {
+            return Ok(());
+        };
+        rename_keys(&mut data.sporadic_frames, renames, || Error::DuplicateFrame)?;
+        rename_keys(&mut data.event_frames, renames, || Error::DuplicateFrame)?;
+        for frames in data.sporadic_frames.values_mut() {
+            rename_list(frames, renames);
+        }
+        for (_, _, frames) in data.event_frames.values_mut() {
+            rename_list(frames, renames);
+        }
+        for resp in data.responders.values_mut() {
+            rename_list(resp.configurable_frames.iter_mut().map(|(f, _)| f), renames);
+        }
+        for (cmd, _) in data.schedule_tables.values_mut().flatten() {
+            match cmd {
+                LDFScheduleCommand::Frame(frame)
+                | LDFScheduleCommand::AssignFrameId { frame, .. } => rename_one(frame, renames),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn rename_nodes(&mut self, renames: &HashMap<Name, Name>) -> Result<(), Error> {
+        for msg in self.messages.values_mut() {
+            rename_one(&mut msg.sender, renames);
+        }
+        match &mut self.extra {
+            DatabaseType::LDF(data) => {
+                rename_one(&mut data.commander, renames);
+                rename_keys(&mut data.responders, renames, || Error::DuplicateNode)?;
+                if data.responders.contains_key(&data.commander) {
+                    return Err(Error::Hint(
+                        data.commander.to_string(),
+                        Box::new(Error::DuplicateNode),
+                    ));
+                }
+                for (cmd, _) in data.schedule_tables.values_mut().flatten() {
+                    match cmd {
+                        LDFScheduleCommand::AssignNAD(node)
+                        | LDFScheduleCommand::SaveConfiguration(node)
+                        | LDFScheduleCommand::DataDump { name: node, .. }
+                        | LDFScheduleCommand::AssignFrameIdRange { name: node, .. }
+                        | LDFScheduleCommand::AssignFrameId { node, .. } => {
+                            rename_one(node, renames)
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            DatabaseType::DBC(data) => {
+                let len = data.nodes.len();
+                rename_list(&mut data.nodes, renames);
+                let mut nodes = data.nodes.clone();
+                nodes.sort();
+                nodes.dedup();
+                if nodes.len() != len {
+                    return Err(Error::Hint(
+                        "renamed onto an existing name".to_string(),
+                        Box::new(Error::DuplicateNode),
+                    ));
+                }
+                rename_list(data.receivers.values_mut().flatten(), renames);
+                rename_keys(&mut data.node_descriptions, renames, || {
+                    Error::DuplicateNode
+                })?;
+                rename_keys(&mut data.node_attributes, renames, || Error::DuplicateNode)?;
+            }
+            DatabaseType::NCF => (),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use crate::analysis::schedule::lin_frame_time;
+use crate::parsers::encoding::{
+    DatabaseType, LDFData, LDFScheduleCommand, LINResponderData, Message, Name, Signal, ValueKind,
+};
+use crate::{Database, Error};
+use std::collections::{BTreeSet, HashMap};
+
+const LIN_MAX_ID: u32 = 59; // unconditional frames
+const LIN_MAX_LENGTH: u16 = 8;
+const CAN_ID_MASK: u32 = 0x1FFFFFFF; // without the DBC extended flag
+const SKELETON_PROTOCOL: &str = "2.2";
+const SKELETON_SCHEDULE: &str = "Normal_Schedule";
+
+// choices for turning a CAN database into a LIN one
+pub struct SkeletonOptions {
+    pub commander: Option<String>, // node publishing the most frames if not given
+    pub bitrate: f64,              // bps
+    pub time_base: f64,            // ms
+    pub jitter: f64,               // ms
+}
+
+impl Default for SkeletonOptions {
+    fn default() -> Self {
+        Self {
+            commander: None,
+            bitrate: 19200.0,
+            time_base: 5.0,
+            jitter: 0.1,
+        }
+    }
+}
+
+impl Database {
+    // approximates a DBC as an LDF, returns what couldn't be carried over and the assumptions made
+    pub fn to_ldf_skeleton(
+        mut self,
+        options: &SkeletonOptions,
+    ) -> Result<(Database, Vec<String>), Error> {
+        let dbc = match std::mem::take(&mut self.extra) {
+            DatabaseType::DBC(dbc) => dbc,
+            _ => return Err(Error::IncompatibleDatabase),
+        };
+        let mut report = Vec::new();
+
+        // frames, LIN payloads are at most 8 bytes of little-endian signals
+        let mut names: Vec<Name> = self.messages.keys().cloned().collect();
+        names.sort_by(|a, b| (self.messages[a].id, a).cmp(&(self.messages[b].id, b)));
+        let mut frames: Vec<(Name, Message)> = Vec::new();
+        for name in names {
+            let mut msg = self.messages.remove(&name).unwrap();
+            if msg.byte_width == 0 || msg.byte_width > LIN_MAX_LENGTH {
+                report.push(format!("{}: {} bytes, dropped", name, msg.byte_width));
+                continue;
+            }
+            if !msg.mux_signals.is_empty() {
+                let mut mux: Vec<&str> = msg.mux_signals.keys().map(|s| &**s).collect();
+                mux.sort();
+                report.push(format!(
+                    "{}: multiplexed signals {} dropped",
+                    name,
+                    mux.join(", ")
+                ));
+                msg.mux_signals.clear();
+            }
+            msg.signals.retain(|s| {
+                let signal = &self.signals[s];
+                if !signal.little_endian {
+                    report.push(format!("{}: big-endian signal {} dropped", name, s));
+                } else if signal.kind != ValueKind::Unsigned {
+                    report.push(format!(
+                        "{}: {} signal {} kept as unsigned",
+                        name, signal.kind, s
+                    ));
+                }
+                signal.little_endian
+            });
+            msg.prune_signal_groups();
+            frames.push((name, msg));
+        }
+
+        // keep CAN ids that are valid LIN ids, assign the lowest free ones to the rest
+        let mut used = BTreeSet::new();
+        let mut reassign = Vec::new();
+        for (i, (_, msg)) in frames.iter_mut().enumerate() {
+            msg.id &= CAN_ID_MASK;
+            if msg.id <= LIN_MAX_ID && used.insert(msg.id) {
+                continue;
+            }
+            reassign.push(i);
+        }
+        let mut free = (0..=LIN_MAX_ID).filter(|id| !used.contains(id));
+        let mut dropped = Vec::new();
+        for i in reassign {
+            let (name, msg) = &mut frames[i];
+            match free.next() {
+                Some(id) => {
+                    report.push(format!("{}: id 0x{:X} -> 0x{:02X}", name, msg.id, id));
+                    msg.id = id;
+                }
+                None => {
+                    report.push(format!("{}: no free LIN id, dropped", name));
+                    dropped.push(i);
+                }
+            }
+        }
+        for i in dropped.into_iter().rev() {
+            frames.remove(i);
+        }
+        frames.sort_by(|a, b| (a.1.id, &a.0).cmp(&(b.1.id, &b.0)));
+
+        // node roles
+        let commander: Name = match &options.commander {
+            Some(name) => {
+                if !dbc.nodes.iter().any(|n| **n == **name) {
+                    return Err(Error::UnknownNode);
+                }
+                name.as_str().into()
+            }
+            None => {
+                let mut counts: HashMap<&Name, usize> = HashMap::new();
+                for (_, msg) in frames.iter().filter(|(_, m)| !m.sender.is_empty()) {
+                    *counts.entry(&msg.sender).or_default() += 1;
+                }
+                let commander = counts
+                    .into_iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+                    .map(|(n, _)| n.clone())
+                    .or(dbc.nodes.first().cloned())
+                    .ok_or(Error::UnknownNode)?;
+                report.push(format!("assumed {} is the commander", commander));
+                commander
+            }
+        };
+        for (name, msg) in &mut frames {
+            if msg.sender.is_empty() {
+                report.push(format!("{}: no publisher, assigned to {}", name, commander));
+                msg.sender = commander.clone();
+            }
+        }
+
+        let mut signals: HashMap<Name, Signal> = frames
+            .iter()
+            .flat_map(|(_, m)| &m.signals)
+            .filter_map(|s| Some((s.clone(), self.signals.remove(s)?)))
+            .collect();
+        for signal in signals.values_mut() {
+            signal.kind = ValueKind::Unsigned;
+        }
+        let mut responders = HashMap::new();
+        let mut nodes: Vec<&Name> = dbc.nodes.iter().filter(|n| **n != commander).collect();
+        nodes.sort();
+        for node in nodes {
+            let mut subscribed: Vec<Name> = signals
+                .keys()
+                .filter(|s| dbc.receivers.get(*s).is_some_and(|r| r.contains(node)))
+                .cloned()
+                .collect();
+            subscribed.sort();
+            let mut configurable: Vec<(Name, Option<u16>)> = frames
+                .iter()
+                .filter(|(_, m)| {
+                    m.sender == *node || m.signals.iter().any(|s| subscribed.contains(s))
+                })
+                .map(|(n, _)| (n.clone(), None))
+                .collect();
+            if configurable.is_empty() {
+                report.push(format!("{}: no LIN frames, dropped", node));
+                continue;
+            }
+            let mut response_error = frames
+                .iter()
+                .filter(|(_, m)| m.sender == *node)
+                .flat_map(|(_, m)| &m.signals)
+                .find(|s| signals[*s].bit_width == 1)
+                .cloned();
+            if response_error.is_none() {
+                // add one in the first free bit of a published frame, or in a new frame
+                let slot = frames.iter().enumerate().find_map(|(i, (_, m))| {
+                    if m.sender != *node {
+                        return None;
+                    }
+                    let used: Vec<_> = m
+                        .signals
+                        .iter()
+                        .map(|s| signals[s].bit_start..signals[s].bit_start + signals[s].bit_width)
+                        .collect();
+                    let bit =
+                        (0..m.byte_width * 8).find(|b| !used.iter().any(|r| r.contains(b)))?;
+                    Some((i, bit))
+                });
+                let slot = slot.or_else(|| {
+                    let id = (0..=LIN_MAX_ID).find(|id| frames.iter().all(|(_, m)| m.id != *id))?;
+                    let name: Name = format!("{}_Status", node).into();
+                    report.push(format!(
+                        "{}: added frame {} with id 0x{:02X}",
+                        node, name, id
+                    ));
+                    configurable.push((name.clone(), None));
+                    frames.push((
+                        name,
+                        Message {
+                            sender: node.clone(),
+                            id,
+                            byte_width: 1,
+                            signals: Vec::new(),
+                            mux_signals: HashMap::new(),
+                            checksum: None,
+                            cycle_time: None,
+                            send_type: None,
+                            protocol: Default::default(),
+                            description: None,
+                            attributes: HashMap::new(),
+                            signal_groups: Default::default(),
+                        },
+                    ));
+                    Some((frames.len() - 1, 0))
+                });
+                match slot {
+                    Some((i, bit)) => {
+                        let name: Name = format!("{}_response_error", node).into();
+                        let (frame, msg) = &mut frames[i];
+                        report.push(format!(
+                            "{}: added {} to {} at bit {}",
+                            node, name, frame, bit
+                        ));
+                        msg.signals.push(name.clone());
+                        signals.insert(
+                            name.clone(),
+                            Signal {
+                                kind: ValueKind::Unsigned,
+                                little_endian: true,
+                                bit_start: bit,
+                                bit_width: 1,
+                                init_value: 0,
+                                encodings: None,
+                                physical_limits: None,
+                                description: None,
+                                attributes: HashMap::new(),
+                            },
+                        );
+                        response_error = Some(name);
+                    }
+                    None => report.push(format!(
+                        "{}: no free bit or id for response_error, must be added manually",
+                        node
+                    )),
+                }
+            }
+            let nad = responders.len() as u8 + 1;
+            report.push(format!("{}: responder with NAD 0x{:02X}", node, nad));
+            responders.insert(
+                node.clone(),
+                LINResponderData {
+                    protocol: SKELETON_PROTOCOL.to_string(),
+                    subscribed_signals: subscribed,
+                    configured_nad: nad,
+                    initial_nad: None,
+                    product_id: Some((0, 0, 0)),
+                    response_error,
+                    configurable_frames: configurable,
+                    description: dbc.node_descriptions.get(node).cloned(),
+                },
+            );
+        }
+
+        // each frame once, slots rounded up to the time base
+        let table = frames
+            .iter()
+            .map(|(name, msg)| {
+                let (_, max) = lin_frame_time(options.bitrate, msg.byte_width);
+                let delay = ((max + options.jitter) / options.time_base).ceil() * options.time_base;
+                (LDFScheduleCommand::Frame(name.clone()), delay)
+            })
+            .collect();
+        report.push(format!(
+            "{}: every frame once, DBC cycle times not used",
+            SKELETON_SCHEDULE
+        ));
+
+        let data = LDFData {
+            bitrate: options.bitrate,
+            postfix: String::new(),
+            commander,
+            time_base: options.time_base,
+            jitter: options.jitter,
+            responders,
+            sporadic_frames: HashMap::new(),
+            event_frames: HashMap::new(),
+            schedule_tables: HashMap::from([(SKELETON_SCHEDULE.into(), table)]),
+        };
+        for (_, msg) in &mut frames {
+            msg.checksum = Some(data.checksum_model(msg));
+        }
+        let db = Database {
+            signals,
+            messages: frames.into_iter().collect(),
+            extra: DatabaseType::LDF(data),
+            provenance: None,
+            attributes: HashMap::new(),
+        };
+        Ok((db, report))
+    }
+}
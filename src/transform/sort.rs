@@ -0,0 +1,33 @@
+use crate::parsers::encoding::DatabaseType;
+use crate::Database;
+
+impl Database {
+    // puts lists whose order carries no meaning in (bit, name) or name order, so writers don't depend on
+    // parse order, priority lists, configurable frames and schedule tables are left alone
+    pub fn sort(&mut self) {
+        for msg in self.messages.values_mut() {
+            let signals = &self.signals;
+            msg.signals.sort_by(|a, b| {
+                let bit = |n| signals.get(n).map(|s| s.bit_start);
+                (bit(a), a).cmp(&(bit(b), b))
+            });
+            for (_, multiplexors) in msg.mux_signals.values_mut() {
+                multiplexors.sort();
+            }
+        }
+        match &mut self.extra {
+            DatabaseType::LDF(data) => {
+                for resp in data.responders.values_mut() {
+                    resp.subscribed_signals.sort();
+                }
+            }
+            DatabaseType::DBC(data) => {
+                data.nodes.sort();
+                for nodes in data.receivers.values_mut() {
+                    nodes.sort();
+                }
+            }
+            DatabaseType::NCF => (),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use crate::parsers::encoding::Encoding;
+use crate::{Database, Error};
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum Quantity {
+    Ratio,
+    Length,
+    Time,
+    Speed,
+    Acceleration,
+    Temperature,
+    Voltage,
+    Current,
+    Power,
+    Pressure,
+    Torque,
+    Angle,
+    RotationalSpeed,
+    Volume,
+    Frequency,
+}
+
+// physical unit, base = factor * value + offset in the quantity's base unit
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Unit {
+    pub symbol: &'static str, // canonical, ASCII so it survives every file format
+    pub quantity: Quantity,
+    pub factor: f64,
+    pub offset: f64,
+}
+
+const fn unit(symbol: &'static str, quantity: Quantity, factor: f64, offset: f64) -> Unit {
+    Unit {
+        symbol,
+        quantity,
+        factor,
+        offset,
+    }
+}
+
+// (unit, other spellings), first of each quantity is its base unit
+const UNITS: &[(Unit, &[&str])] = &[
+    (unit("1", Quantity::Ratio, 1.0, 0.0), &[]),
+    (unit("%", Quantity::Ratio, 0.01, 0.0), &["percent", "pct"]),
+    (unit("ppm", Quantity::Ratio, 1e-6, 0.0), &[]),
+    (unit("m", Quantity::Length, 1.0, 0.0), &["meter", "metre"]),
+    (unit("mm", Quantity::Length, 1e-3, 0.0), &[]),
+    (unit("cm", Quantity::Length, 1e-2, 0.0), &[]),
+    (unit("km", Quantity::Length, 1e3, 0.0), &[]),
+    (unit("mi", Quantity::Length, 1609.344, 0.0), &["mile"]),
+    (unit("s", Quantity::Time, 1.0, 0.0), &["sec"]),
+    (unit("ms", Quantity::Time, 1e-3, 0.0), &[]),
+    (unit("us", Quantity::Time, 1e-6, 0.0), &["µs"]),
+    (unit("min", Quantity::Time, 60.0, 0.0), &[]),
+    (unit("h", Quantity::Time, 3600.0, 0.0), &["hr"]),
+    (unit("m/s", Quantity::Speed, 1.0, 0.0), &["mps"]),
+    (
+        unit("km/h", Quantity::Speed, 1.0 / 3.6, 0.0),
+        &["kmh", "kph", "km/hr"],
+    ),
+    (unit("mph", Quantity::Speed, 0.44704, 0.0), &["mi/h"]),
+    (
+        unit("m/s^2", Quantity::Acceleration, 1.0, 0.0),
+        &["m/s2", "m/s²"],
+    ),
+    (unit("g", Quantity::Acceleration, 9.80665, 0.0), &[]),
+    (unit("K", Quantity::Temperature, 1.0, 0.0), &["kelvin"]),
+    (
+        unit("degC", Quantity::Temperature, 1.0, 273.15),
+        &["°C", "C", "deg C", "celsius"],
+    ),
+    (
+        unit(
+            "degF",
+            Quantity::Temperature,
+            5.0 / 9.0,
+            273.15 - 32.0 * 5.0 / 9.0,
+        ),
+        &["°F", "F", "deg F"],
+    ),
+    (unit("V", Quantity::Voltage, 1.0, 0.0), &["volt"]),
+    (unit("mV", Quantity::Voltage, 1e-3, 0.0), &[]),
+    (unit("A", Quantity::Current, 1.0, 0.0), &["amp"]),
+    (unit("mA", Quantity::Current, 1e-3, 0.0), &[]),
+    (unit("W", Quantity::Power, 1.0, 0.0), &["watt"]),
+    (unit("kW", Quantity::Power, 1e3, 0.0), &[]),
+    (unit("Pa", Quantity::Pressure, 1.0, 0.0), &[]),
+    (unit("hPa", Quantity::Pressure, 1e2, 0.0), &["mbar"]),
+    (unit("kPa", Quantity::Pressure, 1e3, 0.0), &[]),
+    (unit("bar", Quantity::Pressure, 1e5, 0.0), &[]),
+    (unit("psi", Quantity::Pressure, 6894.757, 0.0), &[]),
+    (
+        unit("Nm", Quantity::Torque, 1.0, 0.0),
+        &["N.m", "N*m", "N m"],
+    ),
+    (unit("rad", Quantity::Angle, 1.0, 0.0), &[]),
+    (
+        unit("deg", Quantity::Angle, std::f64::consts::PI / 180.0, 0.0),
+        &["°", "degree"],
+    ),
+    (unit("rad/s", Quantity::RotationalSpeed, 1.0, 0.0), &[]),
+    (
+        unit(
+            "rpm",
+            Quantity::RotationalSpeed,
+            std::f64::consts::PI / 30.0,
+            0.0,
+        ),
+        &["1/min", "U/min"],
+    ),
+    (
+        unit("l", Quantity::Volume, 1.0, 0.0),
+        &["L", "liter", "litre"],
+    ),
+    (unit("ml", Quantity::Volume, 1e-3, 0.0), &["mL"]),
+    (unit("Hz", Quantity::Frequency, 1.0, 0.0), &[]),
+    (unit("kHz", Quantity::Frequency, 1e3, 0.0), &[]),
+];
+
+// drops float noise from conversions, e.g. -40 + 273.15 = 233.14999999999998
+fn tidy(x: f64) -> f64 {
+    const PRECISION: f64 = 1e9;
+    (x * PRECISION).round() / PRECISION
+}
+
+impl Unit {
+    // none for units without a known conversion, e.g. "lux" or ""
+    pub fn parse(s: &str) -> Option<Unit> {
+        let s = s.trim();
+        UNITS
+            .iter()
+            .find(|(u, aliases)| u.symbol == s || aliases.contains(&s))
+            .or_else(|| {
+                UNITS.iter().find(|(u, aliases)| {
+                    u.symbol.eq_ignore_ascii_case(s)
+                        || aliases.iter().any(|a| a.eq_ignore_ascii_case(s))
+                })
+            })
+            .map(|(u, _)| *u)
+    }
+
+    // (a, b) such that value in other = a * value in self + b, none across quantities
+    pub fn conversion(&self, other: &Unit) -> Option<(f64, f64)> {
+        if self.quantity != other.quantity {
+            return None;
+        }
+        let a = self.factor / other.factor;
+        let b = (self.offset - other.offset) / other.factor;
+        Some((a, b))
+    }
+
+    pub fn convert(&self, value: f64, other: &Unit) -> Option<f64> {
+        let (a, b) = self.conversion(other)?;
+        Some(a * value + b)
+    }
+}
+
+impl Encoding {
+    pub fn unit(&self) -> Option<Unit> {
+        match self {
+            Encoding::Scalar { unit, .. } => Unit::parse(unit),
+            Encoding::Enum { .. } | Encoding::Ascii => None,
+        }
+    }
+}
+
+impl Database {
+    // rescales physical values into the target units (e.g. "km/h", "degC"), other known units only get
+    // their canonical spelling, returns the number of encodings changed
+    pub fn normalize_units(&mut self, targets: &[&str]) -> Result<usize, Error> {
+        let targets = targets
+            .iter()
+            .map(|t| Unit::parse(t).ok_or(Error::Hint(t.to_string(), Box::new(Error::UnknownUnit))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut changed = 0;
+        for signal in self.signals.values_mut() {
+            let mut pending = true; // explicit limits are in the unit of the first scalar range
+            for e in signal.encodings.iter_mut().flatten() {
+                let first = matches!(e, Encoding::Scalar { .. }) && std::mem::take(&mut pending);
+                let Some(from) = e.unit() else {
+                    continue;
+                };
+                let Encoding::Scalar {
+                    scale,
+                    offset,
+                    unit,
+                    ..
+                } = e
+                else {
+                    continue;
+                };
+                let to = targets
+                    .iter()
+                    .find(|t| t.quantity == from.quantity)
+                    .unwrap_or(&from);
+                let (a, b) = from.conversion(to).unwrap();
+                if first {
+                    if let Some((min, max)) = &mut signal.physical_limits {
+                        (*min, *max) = (tidy(a * *min + b), tidy(a * *max + b));
+                    }
+                }
+                if to.symbol == unit && a == 1.0 && b == 0.0 {
+                    continue;
+                }
+                *scale = tidy(*scale * a);
+                *offset = tidy(a * *offset + b);
+                *unit = to.symbol.to_string();
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
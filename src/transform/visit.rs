@@ -0,0 +1,203 @@
+use crate::analysis::diff::EntityKind;
+use crate::parsers::encoding::{
+    DatabaseType, Encoding, LDFScheduleCommand, Message, Name, Node, Signal,
+};
+use crate::writers::ldf::sorted;
+use crate::{Database, Error};
+use std::collections::{BTreeMap, HashMap};
+
+/*
+ * Passes over a database without each one walking it by hand. walk and walk_mut go through it in the same
+ * order every time: nodes, then frames by id with their signals (plain ones in frame order, then multiplexed
+ * ones by multiplexor value and name) and each signal's encodings, then signals in no frame by name, then
+ * schedule tables by name with their entries in order.
+ */
+
+// multiplexor value and multiplexors selecting a signal
+pub type Mux<'a> = (u64, &'a [Name]);
+
+// where a signal was found
+#[derive(Clone, Copy, Debug)]
+pub struct SignalSite<'a> {
+    pub message: Option<&'a Name>, // none for signals in no frame
+    pub mux: Option<Mux<'a>>,
+}
+
+// read-only pass, e.g. a lint or statistics, every method does nothing unless overridden
+pub trait Visitor {
+    fn visit_node(&mut self, _node: &Node) {}
+    fn visit_message(&mut self, _name: &Name, _message: &Message) {}
+    fn visit_signal(&mut self, _name: &Name, _signal: &Signal, _site: SignalSite) {}
+    fn visit_encoding(&mut self, _signal: &Name, _encoding: &Encoding) {}
+    fn visit_schedule_entry(&mut self, _table: &Name, _command: &LDFScheduleCommand, _delay: f64) {}
+}
+
+// pass changing what it visits, e.g. a unit normalizer or renamer
+pub trait VisitorMut {
+    fn visit_message_mut(&mut self, _name: &Name, _message: &mut Message) {}
+    fn visit_signal_mut(&mut self, _name: &Name, _signal: &mut Signal, _site: SignalSite) {}
+    fn visit_encoding_mut(&mut self, _signal: &Name, _encoding: &mut Encoding) {}
+    fn visit_schedule_entry_mut(
+        &mut self,
+        _table: &Name,
+        _command: &mut LDFScheduleCommand,
+        _delay: &mut f64,
+    ) {
+    }
+    // a new name for a node, frame, signal or schedule table, given after it's visited and applied to it
+    // and every reference once the walk is done
+    fn rename(&mut self, _kind: EntityKind, _name: &Name) -> Option<Name> {
+        None
+    }
+}
+
+// signals of a frame in walk order
+fn message_signals(message: &Message) -> Vec<(&Name, Option<Mux<'_>>)> {
+    let mut muxed: Vec<(&Name, &(u64, Vec<Name>))> = message.mux_signals.iter().collect();
+    muxed.sort_by(|a, b| (a.1 .0, a.0).cmp(&(b.1 .0, b.0)));
+    let plain = message.signals.iter().map(|n| (n, None));
+    plain
+        .chain(
+            muxed
+                .into_iter()
+                .map(|(n, (v, m))| (n, Some((*v, m.as_slice())))),
+        )
+        .collect()
+}
+
+fn frames_in_order(db: &Database) -> Vec<Name> {
+    let mut frames: Vec<(&Name, &Message)> = db.messages.iter().collect();
+    frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+    frames.into_iter().map(|(n, _)| n.clone()).collect()
+}
+
+fn unframed_signals(db: &Database) -> Vec<Name> {
+    let mut signals: Vec<Name> = db
+        .signals
+        .keys()
+        .filter(|s| {
+            !db.messages
+                .values()
+                .any(|m| m.signals.contains(s) || m.mux_signals.contains_key(*s))
+        })
+        .cloned()
+        .collect();
+    signals.sort();
+    signals
+}
+
+pub fn walk(db: &Database, visitor: &mut impl Visitor) {
+    for node in db.nodes() {
+        visitor.visit_node(&node);
+    }
+    let signal = |visitor: &mut dyn Visitor, name: &Name, site: SignalSite| {
+        let Some(signal) = db.signals.get(name) else {
+            return;
+        };
+        visitor.visit_signal(name, signal, site);
+        for encoding in signal.encodings.iter().flatten() {
+            visitor.visit_encoding(name, encoding);
+        }
+    };
+    for name in frames_in_order(db) {
+        let message = &db.messages[&name];
+        visitor.visit_message(&name, message);
+        for (sig, mux) in message_signals(message) {
+            let site = SignalSite {
+                message: Some(&name),
+                mux,
+            };
+            signal(visitor, sig, site);
+        }
+    }
+    for name in unframed_signals(db) {
+        let site = SignalSite {
+            message: None,
+            mux: None,
+        };
+        signal(visitor, &name, site);
+    }
+    if let DatabaseType::LDF(data) = &db.extra {
+        for (table, entries) in sorted(&data.schedule_tables) {
+            for (command, delay) in entries {
+                visitor.visit_schedule_entry(table, command, *delay);
+            }
+        }
+    }
+}
+
+fn visit_signal_mut(
+    visitor: &mut impl VisitorMut,
+    name: &Name,
+    signal: &mut Signal,
+    site: SignalSite,
+) {
+    visitor.visit_signal_mut(name, signal, site);
+    for encoding in signal.encodings.iter_mut().flatten() {
+        visitor.visit_encoding_mut(name, encoding);
+    }
+}
+
+// renames are checked like Database::rename, on an error db is left as the visitor changed it
+pub fn walk_mut<V: VisitorMut>(db: &mut Database, visitor: &mut V) -> Result<(), Error> {
+    let mut renames: BTreeMap<EntityKind, HashMap<Name, Name>> = BTreeMap::new();
+    let mut rename = |visitor: &mut V, kind: EntityKind, name: &Name| {
+        if let Some(new) = visitor.rename(kind, name).filter(|new| new != name) {
+            renames.entry(kind).or_default().insert(name.clone(), new);
+        }
+    };
+    for node in db.nodes() {
+        rename(visitor, EntityKind::Node, &node.name);
+    }
+
+    for name in frames_in_order(db) {
+        let message = db.messages.get_mut(&name).unwrap();
+        visitor.visit_message_mut(&name, message);
+        rename(visitor, EntityKind::Frame, &name);
+        let message = &db.messages[&name];
+        for (sig, mux) in message_signals(message) {
+            let Some(s) = db.signals.get_mut(sig) else {
+                continue;
+            };
+            let site = SignalSite {
+                message: Some(&name),
+                mux,
+            };
+            visit_signal_mut(visitor, sig, s, site);
+            rename(visitor, EntityKind::Signal, sig);
+        }
+    }
+    for name in unframed_signals(db) {
+        let site = SignalSite {
+            message: None,
+            mux: None,
+        };
+        visit_signal_mut(visitor, &name, db.signals.get_mut(&name).unwrap(), site);
+        rename(visitor, EntityKind::Signal, &name);
+    }
+    if let DatabaseType::LDF(data) = &mut db.extra {
+        for kind in [EntityKind::SporadicFrame, EntityKind::EventFrame] {
+            let mut frames: Vec<&Name> = match kind {
+                EntityKind::SporadicFrame => data.sporadic_frames.keys().collect(),
+                _ => data.event_frames.keys().collect(),
+            };
+            frames.sort();
+            for name in frames {
+                rename(visitor, kind, name);
+            }
+        }
+        let mut tables: Vec<&Name> = data.schedule_tables.keys().collect();
+        tables.sort();
+        for table in tables.into_iter().cloned().collect::<Vec<_>>() {
+            for (command, delay) in data.schedule_tables.get_mut(&table).unwrap() {
+                visitor.visit_schedule_entry_mut(&table, command, delay);
+            }
+            rename(visitor, EntityKind::ScheduleTable, &table);
+        }
+    }
+
+    for (kind, renames) in renames {
+        db.rename(kind, &renames)?;
+    }
+    Ok(())
+}
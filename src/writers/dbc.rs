@@ -0,0 +1,794 @@
+use crate::analysis::schedule::lin_pid;
+use crate::parsers::encoding::{
+    AttributeValue, ChecksumModel, DatabaseType, Encoding, FrameProtocol, LDFData,
+    LDFScheduleCommand, Message, Name, SendType, Signal, ValueKind,
+};
+use crate::parsers::ids::FrameId;
+use crate::writers::ldf::sorted;
+use crate::{Database, Error, WriteOptions};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+const NO_NODE: &str = "Vector__XXX";
+const LIN_ATTRIBUTES: [&str; 10] = [
+    "BusType",
+    "Baudrate",
+    "LIN_TimeBase",
+    "LIN_Jitter",
+    "LIN_NodeType",
+    "LIN_NAD",
+    "LIN_Protocol",
+    "LIN_ProtectedId",
+    "LIN_ChecksumModel",
+    "LIN_ScheduleTable",
+]; // written for LDF databases
+pub(crate) const TYPED_ATTRIBUTES: [&str; 7] = [
+    "GenMsgCycleTime",
+    "GenMsgSendType",
+    "ProtocolType",
+    "VFrameFormat",
+    "GenSigStartValue",
+    "ScaleDescription",
+    "ValueTableDescription",
+]; // from typed fields, the attribute maps can't override them
+const VECTOR_ATTRIBUTES: [(&str, &str); 2] = [("BusType", "CAN"), ("DBName", "")]; // network ones, with defaults
+const FRAME_FORMATS: [&str; 4] = ["StandardCAN", "ExtendedCAN", "reserved", "J1939PG"]; // Vector's indices
+
+pub fn write_dbc(db: &Database, dbc: impl AsRef<Path>) -> Result<(), Error> {
+    write_dbc_writer(db, File::create(dbc)?)
+}
+
+pub fn write_dbc_writer(db: &Database, mut writer: impl io::Write) -> Result<(), Error> {
+    writer.write_all(write_dbc_string(db)?.as_bytes())?;
+    Ok(())
+}
+
+// quoted, with quotes and backslashes escaped
+fn char_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn node(name: &str) -> &str {
+    if name.is_empty() {
+        NO_NODE
+    } else {
+        name
+    }
+}
+
+// nodes subscribing to each signal
+fn receivers(db: &Database) -> HashMap<&Name, Vec<&Name>> {
+    let mut ret: HashMap<&Name, Vec<&Name>> = HashMap::new();
+    match &db.extra {
+        DatabaseType::DBC(data) => {
+            for (signal, nodes) in &data.receivers {
+                ret.insert(signal, nodes.iter().collect());
+            }
+        }
+        DatabaseType::LDF(data) => {
+            // commander assumed to subscribe to everything it doesn't publish, as in the LDF writer
+            for msg in db.messages.values() {
+                for signal in &msg.signals {
+                    let mut nodes: Vec<&Name> = data
+                        .responders
+                        .iter()
+                        .filter(|(_, r)| r.subscribed_signals.contains(signal))
+                        .map(|(n, _)| n)
+                        .collect();
+                    if msg.sender != data.commander {
+                        nodes.push(&data.commander);
+                    }
+                    nodes.sort();
+                    ret.insert(signal, nodes);
+                }
+            }
+        }
+        DatabaseType::NCF => (),
+    }
+    ret
+}
+
+// period of a frame in the first schedule table carrying it, by name
+fn lin_schedule(data: &LDFData, frame: &Name) -> Option<(Name, f64)> {
+    sorted(&data.schedule_tables)
+        .into_iter()
+        .find_map(|(name, table)| {
+            let count = table
+                .iter()
+                .filter(|(cmd, _)| matches!(cmd, LDFScheduleCommand::Frame(f) if f == frame))
+                .count();
+            let total: f64 = table.iter().map(|(_, delay)| delay).sum();
+            (count > 0).then(|| (name.clone(), total / count as f64))
+        })
+}
+
+fn write_signal(
+    s: &mut String,
+    name: &str,
+    signal: &Signal,
+    mux: &str,
+    receivers: Option<&Vec<&Name>>,
+) -> Result<(), Error> {
+    let (mut scale, mut offset, mut unit) = (1.0, 0.0, "");
+    let (mut min, mut max) = signal.raw_span(0, signal.max_raw());
+    if let Some(Encoding::Scalar {
+        raw_min,
+        raw_max,
+        scale: s,
+        offset: o,
+        unit: u,
+        ..
+    }) = signal
+        .encodings
+        .iter()
+        .flatten()
+        .find(|e| matches!(e, Encoding::Scalar { .. }))
+    {
+        (scale, offset, unit) = (*s, *o, u.as_str());
+        let (lo, hi) = signal.raw_span(*raw_min, *raw_max);
+        (min, max) = (lo * s + o, hi * s + o);
+        if min > max {
+            (min, max) = (max, min);
+        }
+    }
+    if signal.kind.is_float() {
+        (min, max) = (0.0, 0.0); // the whole float range, left out
+    }
+    if let Some(limits) = signal.physical_limits {
+        (min, max) = limits;
+    }
+    let receivers: Vec<&str> = receivers
+        .map(|r| r.iter().map(|n| &***n).collect())
+        .unwrap_or_default();
+    writeln!(
+        s,
+        " SG_ {}{} : {}|{}@{}{} ({},{}) [{}|{}] {} {}",
+        name,
+        mux,
+        signal.bit_start,
+        signal.bit_width,
+        if signal.little_endian { 1 } else { 0 },
+        match signal.kind {
+            ValueKind::Unsigned | ValueKind::ByteArray => "+",
+            ValueKind::Signed | ValueKind::Float32 | ValueKind::Float64 => "-",
+        },
+        scale,
+        offset,
+        min,
+        max,
+        char_string(unit),
+        if receivers.is_empty() {
+            NO_NODE.to_string()
+        } else {
+            receivers.join(",")
+        }
+    )?;
+    Ok(())
+}
+
+// BA_DEF_ type of an attribute, ranges cover 0 and the values written
+enum AttributeDef {
+    Int(i64, i64),
+    Float(f64, f64),
+    String,
+    Enum(Vec<String>),
+}
+
+impl AttributeDef {
+    fn new(value: &AttributeValue) -> AttributeDef {
+        match value {
+            AttributeValue::Int(v) => AttributeDef::Int((*v).min(0), (*v).max(0)),
+            AttributeValue::Float(v) => AttributeDef::Float(v.min(0.0), v.max(0.0)),
+            AttributeValue::String(_) => AttributeDef::String,
+            AttributeValue::Enum(l) => AttributeDef::Enum(vec![l.clone()]),
+        }
+    }
+
+    // ints turn into floats when mixed, other mismatches are written as the first value's type
+    fn add(&mut self, value: &AttributeValue) {
+        match (&mut *self, value) {
+            (AttributeDef::Int(min, max), AttributeValue::Int(v)) => {
+                (*min, *max) = ((*min).min(*v), (*max).max(*v));
+            }
+            (AttributeDef::Int(min, max), AttributeValue::Float(v)) => {
+                *self = AttributeDef::Float((*min as f64).min(*v), (*max as f64).max(*v));
+            }
+            (AttributeDef::Float(min, max), AttributeValue::Int(v)) => {
+                (*min, *max) = (min.min(*v as f64), max.max(*v as f64));
+            }
+            (AttributeDef::Float(min, max), AttributeValue::Float(v)) => {
+                (*min, *max) = (min.min(*v), max.max(*v));
+            }
+            (AttributeDef::Enum(labels), AttributeValue::Enum(l) | AttributeValue::String(l))
+                if !labels.contains(l) =>
+            {
+                labels.push(l.clone());
+            }
+            _ => (),
+        }
+    }
+
+    fn format(&self, value: &AttributeValue) -> String {
+        let text = match value {
+            AttributeValue::String(l) | AttributeValue::Enum(l) => l.clone(),
+            v => v.to_string(),
+        };
+        match (self, value) {
+            (AttributeDef::Int(..), AttributeValue::Float(v)) => (v.round() as i64).to_string(),
+            (AttributeDef::Int(..) | AttributeDef::Float(..), _) => value.to_string(),
+            (AttributeDef::String, _) => char_string(&text),
+            (AttributeDef::Enum(labels), _) => {
+                labels
+                    .iter()
+                    .position(|l| *l == text)
+                    .unwrap_or(0)
+                    .to_string() // enum index
+            }
+        }
+    }
+}
+
+fn sorted_attributes(map: &HashMap<String, AttributeValue>) -> Vec<(&String, &AttributeValue)> {
+    let mut ret: Vec<_> = map.iter().collect();
+    ret.sort_by(|a, b| a.0.cmp(b.0));
+    ret
+}
+
+// attributes kept from the source without a typed field
+fn write_attributes(
+    s: &mut String,
+    db: &Database,
+    frames: &[(&Name, &Message)],
+    vector: bool,
+) -> Result<(), Error> {
+    // (object, what the value is for, name, value), in frame order
+    let mut values: Vec<(&str, String, &String, &AttributeValue)> = Vec::new();
+    for (name, value) in sorted_attributes(&db.attributes) {
+        values.push(("", String::new(), name, value));
+    }
+    if let DatabaseType::DBC(data) = &db.extra {
+        for node in &data.nodes {
+            let attributes = data
+                .node_attributes
+                .get(node)
+                .into_iter()
+                .flat_map(sorted_attributes);
+            for (name, value) in attributes {
+                values.push(("BU_", format!("BU_ {} ", node), name, value));
+            }
+        }
+    }
+    for (_, msg) in frames {
+        for (name, value) in sorted_attributes(&msg.attributes) {
+            values.push(("BO_", format!("BO_ {} ", msg.id), name, value));
+        }
+        let muxed = sorted(&msg.mux_signals).into_iter().map(|(n, _)| n);
+        for sig in msg.signals.iter().chain(muxed) {
+            for (name, value) in sorted_attributes(&db.signals[sig].attributes) {
+                values.push(("SG_", format!("SG_ {} {} ", msg.id, sig), name, value));
+            }
+        }
+    }
+    let ldf = matches!(db.extra, DatabaseType::LDF(_));
+    let written = |name: &str| {
+        TYPED_ATTRIBUTES.contains(&name)
+            || (ldf && LIN_ATTRIBUTES.contains(&name))
+            || (vector && VECTOR_ATTRIBUTES.iter().any(|(n, _)| *n == name))
+    };
+    values.retain(|(_, _, name, _)| !written(name));
+    // BA_DEF_DEF_ values from the source, their definitions are kept even if no object has a value
+    let mut defaults: Vec<(&String, &str, &AttributeValue)> = match &db.extra {
+        DatabaseType::DBC(data) => data
+            .attribute_defaults
+            .iter()
+            .filter(|(name, _)| !written(name))
+            .map(|(name, (object, value))| (name, object.as_str(), value))
+            .collect(),
+        _ => Vec::new(),
+    };
+    defaults.sort_by(|a, b| a.0.cmp(b.0));
+    if values.is_empty() && defaults.is_empty() {
+        return Ok(());
+    }
+
+    // one definition per name, values for other objects can't be written
+    let mut defs: Vec<(&String, &str, AttributeDef)> = defaults
+        .iter()
+        .map(|(name, object, value)| (*name, *object, AttributeDef::new(value)))
+        .collect();
+    for (object, _, name, value) in &values {
+        match defs.iter_mut().find(|(n, _, _)| n == name) {
+            Some((_, o, def)) if o == object => def.add(value),
+            Some(_) => (),
+            None => defs.push((name, object, AttributeDef::new(value))),
+        }
+    }
+    values.retain(|(object, _, name, _)| defs.iter().any(|(n, o, _)| n == name && o == object));
+    defs.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, object, def) in &defs {
+        let object = if object.is_empty() {
+            " ".to_string()
+        } else {
+            format!("{} ", object)
+        };
+        match def {
+            AttributeDef::Int(min, max) => {
+                writeln!(s, "BA_DEF_ {}\"{}\" INT {} {};", object, name, min, max)?;
+            }
+            AttributeDef::Float(min, max) => {
+                writeln!(s, "BA_DEF_ {}\"{}\" FLOAT {} {};", object, name, min, max)?;
+            }
+            AttributeDef::String => {
+                writeln!(s, "BA_DEF_ {}\"{}\" STRING ;", object, name)?;
+            }
+            AttributeDef::Enum(labels) => {
+                let labels: Vec<String> = labels.iter().map(|l| char_string(l)).collect();
+                writeln!(
+                    s,
+                    "BA_DEF_ {}\"{}\" ENUM  {};",
+                    object,
+                    name,
+                    labels.join(",")
+                )?;
+            }
+        }
+    }
+    // only the source's, made up ones would come back as if they had been there
+    for (name, _, value) in &defaults {
+        let (_, _, def) = defs.iter().find(|(n, _, _)| n == name).unwrap();
+        let value = match (def, value) {
+            (AttributeDef::Enum(_), AttributeValue::Enum(l) | AttributeValue::String(l)) => {
+                char_string(l) // enum defaults are labels
+            }
+            _ => def.format(value),
+        };
+        writeln!(s, "BA_DEF_DEF_  \"{}\" {};", name, value)?;
+    }
+    for (_, target, name, value) in &values {
+        let (_, _, def) = defs.iter().find(|(n, _, _)| n == name).unwrap();
+        writeln!(s, "BA_ \"{}\" {}{};", name, target, def.format(value))?;
+    }
+    writeln!(s)?;
+    Ok(())
+}
+
+pub fn write_dbc_string(db: &Database) -> Result<String, Error> {
+    write_dbc_string_with(db, &WriteOptions::default())
+}
+
+// provenance and Vector attributes apply, sorting is up to the caller
+pub(crate) fn write_dbc_string_with(
+    db: &Database,
+    options: &WriteOptions,
+) -> Result<String, Error> {
+    let ldf = match &db.extra {
+        DatabaseType::LDF(data) => Some(data),
+        _ => None,
+    };
+    let mut frames: Vec<_> = db.messages.iter().collect();
+    frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+    for (name, msg) in &frames {
+        // looked up by name from here on
+        let mut signals = msg.signals.iter().chain(msg.mux_signals.keys());
+        if let Some(sig) = signals.find(|s| !db.signals.contains_key(*s)) {
+            return Err(Error::Hint(
+                format!("{} in {}", sig, name),
+                Box::new(Error::UnknownSignal),
+            ));
+        }
+    }
+    let receivers = receivers(db);
+    let mut s = String::new();
+
+    // header
+    writeln!(s, "VERSION \"\"\n")?;
+    writeln!(s, "NS_ :")?;
+    for ns in [
+        "NS_DESC_",
+        "CM_",
+        "BA_DEF_",
+        "BA_",
+        "VAL_",
+        "BA_DEF_DEF_",
+        "SIG_GROUP_",
+        "SIG_VALTYPE_",
+    ] {
+        writeln!(s, "\t{}", ns)?;
+    }
+    writeln!(s, "\nBS_:\n")?;
+
+    // nodes
+    let mut nodes: Vec<&str> = match &db.extra {
+        DatabaseType::DBC(data) => data.nodes.iter().map(|n| &**n).collect(),
+        DatabaseType::LDF(data) => {
+            let mut responders: Vec<&str> = data.responders.keys().map(|n| &**n).collect();
+            responders.sort();
+            responders.insert(0, &data.commander);
+            responders
+        }
+        DatabaseType::NCF => Vec::new(),
+    };
+    if nodes.is_empty() {
+        nodes = frames
+            .iter()
+            .map(|(_, m)| &*m.sender)
+            .filter(|n| !n.is_empty())
+            .collect();
+        nodes.sort();
+        nodes.dedup();
+    }
+    writeln!(s, "BU_: {}\n", nodes.join(" "))?;
+
+    // messages, multiplexed signals follow the others
+    for (name, msg) in &frames {
+        writeln!(
+            s,
+            "BO_ {} {}: {} {}",
+            msg.id,
+            name,
+            msg.byte_width,
+            node(&msg.sender)
+        )?;
+        let multiplexors: Vec<&Name> = msg.mux_signals.values().flat_map(|(_, m)| m).collect();
+        for sig in &msg.signals {
+            let mux = if multiplexors.contains(&sig) {
+                " M"
+            } else {
+                ""
+            };
+            write_signal(&mut s, sig, &db.signals[sig], mux, receivers.get(sig))?;
+        }
+        for (sig, (value, _)) in sorted(&msg.mux_signals) {
+            let extended = if multiplexors.contains(&sig) { "M" } else { "" };
+            let mux = format!(" m{}{}", value, extended);
+            write_signal(&mut s, sig, &db.signals[sig], &mux, receivers.get(sig))?;
+        }
+        writeln!(s)?;
+    }
+
+    // comments, provenance as the network one
+    let mut comments = String::new();
+    if let Some(provenance) = db.provenance.as_ref().filter(|_| options.provenance) {
+        writeln!(comments, "CM_ {};", char_string(&provenance.to_string()))?;
+    }
+    let mut nodes: Vec<(&Name, &String)> = match &db.extra {
+        DatabaseType::DBC(data) => data.node_descriptions.iter().collect(),
+        DatabaseType::LDF(data) => data
+            .responders
+            .iter()
+            .filter_map(|(n, r)| Some((n, r.description.as_ref()?)))
+            .collect(),
+        DatabaseType::NCF => Vec::new(),
+    };
+    nodes.sort();
+    for (node, text) in nodes {
+        writeln!(comments, "CM_ BU_ {} {};", node, char_string(text))?;
+    }
+    for (_, msg) in &frames {
+        if let Some(text) = &msg.description {
+            writeln!(comments, "CM_ BO_ {} {};", msg.id, char_string(text))?;
+        }
+        let muxed = sorted(&msg.mux_signals).into_iter().map(|(n, _)| n);
+        for sig in msg.signals.iter().chain(muxed) {
+            if let Some(text) = db.signals.get(sig).and_then(|s| s.description.as_ref()) {
+                writeln!(
+                    comments,
+                    "CM_ SG_ {} {} {};",
+                    msg.id,
+                    sig,
+                    char_string(text)
+                )?;
+            }
+        }
+    }
+    if !comments.is_empty() {
+        writeln!(s, "{}", comments)?;
+    }
+
+    // LIN specifics as attributes
+    if let Some(data) = ldf {
+        writeln!(s, "BA_DEF_  \"BusType\" STRING ;")?;
+        writeln!(s, "BA_DEF_  \"Baudrate\" INT 1000 20000;")?;
+        writeln!(s, "BA_DEF_  \"LIN_TimeBase\" FLOAT 0 1000;")?;
+        writeln!(s, "BA_DEF_  \"LIN_Jitter\" FLOAT 0 1000;")?;
+        writeln!(
+            s,
+            "BA_DEF_ BU_ \"LIN_NodeType\" ENUM  \"Commander\",\"Responder\";"
+        )?;
+        writeln!(s, "BA_DEF_ BU_ \"LIN_NAD\" HEX 0 255;")?;
+        writeln!(s, "BA_DEF_ BU_ \"LIN_Protocol\" STRING ;")?;
+        writeln!(s, "BA_DEF_ BO_ \"LIN_ProtectedId\" HEX 0 255;")?;
+        writeln!(
+            s,
+            "BA_DEF_ BO_ \"LIN_ChecksumModel\" ENUM  \"Classic\",\"Enhanced\";"
+        )?;
+        writeln!(s, "BA_DEF_ BO_ \"LIN_ScheduleTable\" STRING ;")?;
+        writeln!(s, "BA_DEF_DEF_  \"BusType\" \"LIN\";")?;
+        writeln!(s, "BA_DEF_DEF_  \"LIN_NodeType\" \"Responder\";")?;
+        writeln!(s, "BA_DEF_DEF_  \"LIN_ChecksumModel\" \"Enhanced\";")?;
+        writeln!(s, "BA_ \"BusType\" \"LIN\";")?;
+        writeln!(s, "BA_ \"Baudrate\" {};", data.bitrate)?;
+        writeln!(s, "BA_ \"LIN_TimeBase\" {};", data.time_base)?;
+        writeln!(s, "BA_ \"LIN_Jitter\" {};", data.jitter)?;
+        writeln!(s, "BA_ \"LIN_NodeType\" BU_ {} 0;", data.commander)?;
+        for (name, resp) in sorted(&data.responders) {
+            writeln!(s, "BA_ \"LIN_NAD\" BU_ {} {};", name, resp.configured_nad)?;
+            if !resp.protocol.is_empty() {
+                writeln!(
+                    s,
+                    "BA_ \"LIN_Protocol\" BU_ {} {};",
+                    name,
+                    char_string(&resp.protocol)
+                )?;
+            }
+        }
+        for (name, msg) in &frames {
+            writeln!(
+                s,
+                "BA_ \"LIN_ProtectedId\" BO_ {} {};",
+                msg.id,
+                lin_pid(msg.id as u8)
+            )?;
+            writeln!(
+                s,
+                "BA_ \"LIN_ChecksumModel\" BO_ {} {};",
+                msg.id,
+                (msg.checksum.unwrap_or_else(|| data.checksum_model(msg))
+                    == ChecksumModel::Enhanced) as u8 // enum index
+            )?;
+            if let Some((table, _)) = lin_schedule(data, name) {
+                writeln!(
+                    s,
+                    "BA_ \"LIN_ScheduleTable\" BO_ {} {};",
+                    msg.id,
+                    char_string(&table)
+                )?;
+            }
+        }
+        writeln!(s)?;
+    }
+
+    // timing, frame formats and start values, LIN frames default to their schedule period
+    let cycle_times: Vec<(u32, f64)> = frames
+        .iter()
+        .filter_map(|(name, msg)| {
+            let period = msg.cycle_time.or_else(|| {
+                ldf.and_then(|data| lin_schedule(data, name))
+                    .map(|(_, p)| p)
+            });
+            period.map(|p| (msg.id, p))
+        })
+        .collect();
+    let mut send_types: Vec<String> = SendType::LABELS.iter().map(|l| l.to_string()).collect();
+    for (_, msg) in &frames {
+        if let Some(SendType::Other(label)) = &msg.send_type {
+            if !send_types.contains(label) {
+                send_types.push(label.clone());
+            }
+        }
+    }
+    let mut start_values = Vec::new();
+    let mut descriptions = Vec::new();
+    for (_, msg) in &frames {
+        let mut signals: Vec<&Name> = msg.signals.iter().collect();
+        signals.extend(sorted(&msg.mux_signals).into_iter().map(|(n, _)| n));
+        for sig in signals {
+            let signal = &db.signals[sig];
+            if signal.init_value != 0 {
+                start_values.push((msg.id, sig, signal.raw_value(signal.init_value)));
+            }
+            // the first of each, as written
+            let scalar = signal.encodings.iter().flatten().find_map(|e| match e {
+                Encoding::Scalar { description, .. } => Some(description),
+                _ => None,
+            });
+            let table = signal.encodings.iter().flatten().find_map(|e| match e {
+                Encoding::Enum { description, .. } => Some(description),
+                _ => None,
+            });
+            for (key, description) in [
+                ("ScaleDescription", scalar),
+                ("ValueTableDescription", table),
+            ] {
+                if let Some(Some(text)) = description {
+                    descriptions.push((key, msg.id, sig, text));
+                }
+            }
+        }
+    }
+    // every standard definition for tools that insist on them, LIN has its own set
+    let vector = options.vector_attributes && ldf.is_none();
+    let has_send_types = vector || frames.iter().any(|(_, m)| m.send_type.is_some());
+    let j1939 = frames
+        .iter()
+        .any(|(_, m)| m.protocol == FrameProtocol::J1939PG);
+    let has_formats = ldf.is_none()
+        && (vector
+            || frames.iter().any(|(_, m)| {
+                let extended = FrameId::can(m.id).is_ok_and(FrameId::is_extended);
+                m.protocol
+                    != if extended {
+                        FrameProtocol::Extended
+                    } else {
+                        FrameProtocol::Standard
+                    }
+            }));
+    if vector {
+        for (name, default) in VECTOR_ATTRIBUTES {
+            writeln!(s, "BA_DEF_  \"{}\" STRING ;", name)?;
+            writeln!(s, "BA_DEF_DEF_  \"{}\" \"{}\";", name, default)?;
+        }
+    }
+    if vector || !cycle_times.is_empty() {
+        writeln!(s, "BA_DEF_ BO_ \"GenMsgCycleTime\" INT 0 65535;")?;
+        writeln!(s, "BA_DEF_DEF_  \"GenMsgCycleTime\" 0;")?;
+    }
+    if has_send_types {
+        let labels: Vec<String> = send_types.iter().map(|l| char_string(l)).collect();
+        writeln!(
+            s,
+            "BA_DEF_ BO_ \"GenMsgSendType\" ENUM  {};",
+            labels.join(",")
+        )?;
+        writeln!(s, "BA_DEF_DEF_  \"GenMsgSendType\" {};", labels[0])?;
+    }
+    if j1939 {
+        writeln!(s, "BA_DEF_  \"ProtocolType\" STRING ;")?;
+    }
+    if has_formats {
+        let labels: Vec<String> = FRAME_FORMATS.iter().map(|l| format!("\"{}\"", l)).collect();
+        writeln!(
+            s,
+            "BA_DEF_ BO_ \"VFrameFormat\" ENUM  {};",
+            labels.join(",")
+        )?;
+        writeln!(s, "BA_DEF_DEF_  \"VFrameFormat\" \"{}\";", FRAME_FORMATS[0])?;
+    }
+    if vector || !start_values.is_empty() {
+        writeln!(s, "BA_DEF_ SG_ \"GenSigStartValue\" FLOAT -1e+308 1e+308;")?;
+        writeln!(s, "BA_DEF_DEF_  \"GenSigStartValue\" 0;")?;
+    }
+    for key in ["ScaleDescription", "ValueTableDescription"] {
+        if descriptions.iter().any(|d| d.0 == key) {
+            writeln!(s, "BA_DEF_ SG_ \"{}\" STRING ;", key)?;
+            writeln!(s, "BA_DEF_DEF_  \"{}\" \"\";", key)?;
+        }
+    }
+    if vector {
+        // kept from the source if there, the bus type is needed regardless
+        for (name, default) in VECTOR_ATTRIBUTES {
+            match db.attributes.get(name) {
+                Some(value) => writeln!(
+                    s,
+                    "BA_ \"{}\" {};",
+                    name,
+                    AttributeDef::String.format(value)
+                )?,
+                None if !default.is_empty() => writeln!(s, "BA_ \"{}\" \"{}\";", name, default)?,
+                None => (),
+            }
+        }
+    }
+    if j1939 {
+        writeln!(s, "BA_ \"ProtocolType\" \"J1939\";")?;
+    }
+    for (id, period) in &cycle_times {
+        writeln!(s, "BA_ \"GenMsgCycleTime\" BO_ {} {};", id, period.round())?;
+    }
+    for (_, msg) in &frames {
+        if let Some(send_type) = &msg.send_type {
+            let label = send_type.to_string();
+            let index = send_types.iter().position(|l| *l == label).unwrap(); // enum index
+            writeln!(s, "BA_ \"GenMsgSendType\" BO_ {} {};", msg.id, index)?;
+        }
+    }
+    if has_formats {
+        for (_, msg) in &frames {
+            let label = msg.protocol.to_string();
+            let index = FRAME_FORMATS.iter().position(|l| *l == label).unwrap(); // enum index
+            writeln!(s, "BA_ \"VFrameFormat\" BO_ {} {};", msg.id, index)?;
+        }
+    }
+    for (id, sig, value) in &start_values {
+        writeln!(s, "BA_ \"GenSigStartValue\" SG_ {} {} {};", id, sig, value)?;
+    }
+    for (key, id, sig, text) in &descriptions {
+        writeln!(
+            s,
+            "BA_ \"{}\" SG_ {} {} {};",
+            key,
+            id,
+            sig,
+            char_string(text)
+        )?;
+    }
+    if vector
+        || !cycle_times.is_empty()
+        || has_send_types
+        || j1939
+        || has_formats
+        || !start_values.is_empty()
+        || !descriptions.is_empty()
+    {
+        writeln!(s)?;
+    }
+    write_attributes(&mut s, db, &frames, vector)?;
+
+    // logical values
+    for (_, msg) in &frames {
+        let mut signals: Vec<&Name> = msg.signals.iter().collect();
+        signals.extend(sorted(&msg.mux_signals).into_iter().map(|(n, _)| n));
+        for sig in signals {
+            for e in db.signals[sig].encodings.iter().flatten() {
+                if let Encoding::Enum { rev_map, .. } = e {
+                    let mut values: Vec<_> = rev_map.iter().collect();
+                    values.sort();
+                    write!(s, "VAL_ {} {}", msg.id, sig)?;
+                    for (raw, label) in values {
+                        write!(s, " {} {}", raw, char_string(label))?;
+                    }
+                    writeln!(s, " ;")?;
+                }
+            }
+        }
+    }
+
+    // signal groups
+    for (_, msg) in &frames {
+        for (name, group) in sorted(&msg.signal_groups) {
+            let signals: Vec<&str> = group.signals.iter().map(|s| &**s).collect();
+            writeln!(
+                s,
+                "SIG_GROUP_ {} {} {} : {};",
+                msg.id,
+                name,
+                group.repetitions,
+                signals.join(" ")
+            )?;
+        }
+    }
+
+    // float signals
+    for (_, msg) in &frames {
+        let mut signals: Vec<&Name> = msg.signals.iter().collect();
+        signals.extend(sorted(&msg.mux_signals).into_iter().map(|(n, _)| n));
+        for sig in signals {
+            let kind = match db.signals[sig].kind {
+                ValueKind::Float32 => 1,
+                ValueKind::Float64 => 2,
+                _ => continue,
+            };
+            writeln!(s, "SIG_VALTYPE_ {} {} : {};", msg.id, sig, kind)?;
+        }
+    }
+
+    // extended multiplexing, for signals selected by value ranges or by multiplexors that are multiplexed too
+    for (_, msg) in &frames {
+        for (sig, (value, multiplexors)) in sorted(&msg.mux_signals) {
+            let ranged =
+                matches!(&db.extra, DatabaseType::DBC(data) if data.mux_ranges.contains_key(sig));
+            if !ranged && !multiplexors.iter().any(|m| msg.mux_signals.contains_key(m)) {
+                continue;
+            }
+            let ranges: Vec<String> = db
+                .mux_ranges(sig, *value)
+                .iter()
+                .map(|(min, max)| format!("{}-{}", min, max))
+                .collect();
+            for multiplexor in multiplexors {
+                writeln!(
+                    s,
+                    "SG_MUL_VAL_ {} {} {} {};",
+                    msg.id,
+                    sig,
+                    multiplexor,
+                    ranges.join(", ")
+                )?;
+            }
+        }
+    }
+    Ok(s)
+}
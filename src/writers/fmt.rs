@@ -0,0 +1,215 @@
+use crate::analysis::diff::EntityKind;
+use crate::parsers::encoding::Name;
+use crate::parsers::error::Span;
+use crate::parsers::ldf::parse_ldf_str_with_spans;
+use crate::writers::ldf::write_ldf_comments;
+use crate::Error;
+use std::collections::HashMap;
+
+// definitions comments can be attached to
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Entity {
+    Signal(Name),
+    Frame(Name),
+    Node(Name),
+    ScheduleTable(Name),
+    ScheduleEntry(Name, usize),
+    Encoding(String),
+}
+
+// comments to re-emit around definitions, with their source position to keep them ordered
+#[derive(Default)]
+pub(crate) struct Comments<'a> {
+    header: Vec<&'a str>,
+    footer: Vec<(usize, &'a str)>,
+    leading: HashMap<Entity, Vec<(usize, &'a str)>>,
+    trailing: HashMap<Entity, Vec<(usize, &'a str)>>,
+}
+
+impl<'a> Comments<'a> {
+    // comments on their own lines before a definition
+    pub(crate) fn lead(&mut self, s: &mut String, entity: Entity, indent: &str) {
+        for (_, c) in self.leading.remove(&entity).unwrap_or_default() {
+            s.push_str(indent);
+            s.push_str(c);
+            s.push('\n');
+        }
+    }
+
+    // comments at the end of the last line written
+    pub(crate) fn trail(&mut self, s: &mut String, entity: Entity) {
+        if let Some(comments) = self.trailing.remove(&entity) {
+            s.pop(); // "\n"
+            for (_, c) in comments {
+                s.push(' ');
+                s.push_str(c);
+            }
+            s.push('\n');
+        }
+    }
+
+    // follows Database::rename, so comments stay with what they were written at
+    fn rename(&mut self, kind: EntityKind, renames: &HashMap<Name, Name>) {
+        let new = |name: Name| renames.get(&name).cloned().unwrap_or(name);
+        let entity = |e: Entity| match (kind, e) {
+            (EntityKind::Signal, Entity::Signal(n)) => Entity::Signal(new(n)),
+            (
+                EntityKind::Frame | EntityKind::SporadicFrame | EntityKind::EventFrame,
+                Entity::Frame(n),
+            ) => Entity::Frame(new(n)),
+            (EntityKind::Node, Entity::Node(n)) => Entity::Node(new(n)),
+            (EntityKind::ScheduleTable, Entity::ScheduleTable(n)) => Entity::ScheduleTable(new(n)),
+            (EntityKind::ScheduleTable, Entity::ScheduleEntry(n, i)) => {
+                Entity::ScheduleEntry(new(n), i)
+            }
+            (_, e) => e,
+        };
+        for map in [&mut self.leading, &mut self.trailing] {
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(e, c)| (entity(e), c))
+                .collect();
+        }
+    }
+
+    // footer and comments whose definition wasn't written, in source order
+    fn rest(self) -> Vec<&'a str> {
+        let mut ret = self.footer;
+        ret.extend(self.leading.into_values().flatten());
+        ret.extend(self.trailing.into_values().flatten());
+        ret.sort();
+        ret.into_iter().map(|(_, c)| c).collect()
+    }
+}
+
+// spans of "//" and "/* */" comments, skipping char strings
+fn find_comments(src: &str) -> Vec<Span> {
+    let mut ret = Vec::new();
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = match (bytes[i], bytes.get(i + 1)) {
+            (b'"', _) => {
+                let mut j = i + 1;
+                while j < bytes.len() && bytes[j] != b'"' {
+                    j += if bytes[j] == b'\\' { 2 } else { 1 }; // escape
+                }
+                (j + 1).min(bytes.len())
+            }
+            (b'/', Some(b'/')) => src[i..].find('\n').map_or(bytes.len(), |j| i + j),
+            (b'/', Some(b'*')) => src[i + 2..].find("*/").map_or(bytes.len(), |j| i + j + 4),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if bytes[i] == b'/' {
+            ret.push(Span { start: i, end });
+        }
+        i = end;
+    }
+    ret
+}
+
+// re-emits an LDF in canonical form, comments are kept next to the definition they were written at
+pub fn format_ldf(src: &str) -> Result<String, Error> {
+    rename_ldf(src, &[])
+}
+
+// format_ldf after Database::rename with each kind's renames in turn, comments move with what they're at
+pub fn rename_ldf(
+    src: &str,
+    renames: &[(EntityKind, HashMap<Name, Name>)],
+) -> Result<String, Error> {
+    let (mut db, spans) = parse_ldf_str_with_spans(src)?;
+    let mut entities: Vec<(Span, Entity)> = Vec::new();
+    for (name, span) in &spans.signals {
+        entities.push((*span, Entity::Signal(name.clone())));
+    }
+    for (name, span) in &spans.frames {
+        entities.push((*span, Entity::Frame(name.clone())));
+    }
+    for (name, span) in &spans.nodes {
+        entities.push((*span, Entity::Node(name.clone())));
+    }
+    for (name, span) in &spans.schedule_tables {
+        entities.push((*span, Entity::ScheduleTable(name.clone())));
+    }
+    for (name, list) in &spans.schedule_entries {
+        for (i, span) in list.iter().enumerate() {
+            entities.push((*span, Entity::ScheduleEntry(name.clone(), i)));
+        }
+    }
+    for (name, span) in &spans.encodings {
+        entities.push((*span, Entity::Encoding(name.clone())));
+    }
+
+    let mut comments = Comments::default();
+    let mut header_end = 0;
+    for c in find_comments(src) {
+        if spans.descriptions.iter().any(|d| d.start == c.start) {
+            continue; // written back with its encoding
+        }
+        let text = src[c.start..c.end].trim_end();
+        if src[header_end..c.start].trim().is_empty() {
+            // before any token
+            comments.header.push(text);
+            header_end = c.end;
+            continue;
+        }
+        let trailed = entities
+            .iter()
+            .filter(|(e, _)| {
+                e.end <= c.start
+                    && !src[e.end..c.start].contains(|c: char| !c.is_whitespace() || c == '\n')
+            })
+            .max_by_key(|(e, _)| e.end);
+        let inside = entities
+            .iter()
+            .filter(|(e, _)| e.start <= c.start && c.end <= e.end)
+            .min_by_key(|(e, _)| e.end - e.start);
+        let next = entities
+            .iter()
+            .filter(|(e, _)| e.start >= c.end)
+            .min_by_key(|(e, _)| e.start);
+        if let Some((_, entity)) = trailed {
+            comments
+                .trailing
+                .entry(entity.clone())
+                .or_default()
+                .push((c.start, text));
+        } else if let Some((_, entity)) = inside.or(next) {
+            comments
+                .leading
+                .entry(entity.clone())
+                .or_default()
+                .push((c.start, text));
+        } else {
+            comments.footer.push((c.start, text));
+        }
+    }
+
+    for (kind, renames) in renames {
+        db.rename(*kind, renames)?;
+        comments.rename(*kind, renames);
+    }
+
+    let mut s = String::new();
+    for c in std::mem::take(&mut comments.header) {
+        s.push_str(c);
+        s.push('\n');
+    }
+    if !s.is_empty() {
+        s.push('\n');
+    }
+    s.push_str(&write_ldf_comments(&db, &mut comments)?);
+    let rest = comments.rest();
+    if !rest.is_empty() {
+        s.push('\n');
+        for c in rest {
+            s.push_str(c);
+            s.push('\n');
+        }
+    }
+    Ok(s)
+}
@@ -0,0 +1,207 @@
+use crate::parsers::encoding::{Message, Name, Signal};
+use crate::{Database, Error};
+use std::fmt::Write;
+
+const SVG_CELL: usize = 48; // px
+const SVG_MARGIN: usize = 64;
+
+// signals of a message with a key each, plain signals first
+struct Layout<'a> {
+    name: &'a Name,
+    msg: &'a Message,
+    signals: Vec<(char, &'a Name, &'a Signal)>,
+    owners: Vec<Vec<usize>>, // per bit, byte * 8 + bit
+}
+
+fn key(i: usize) -> char {
+    const KEYS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    KEYS.get(i).map_or('?', |k| *k as char)
+}
+
+impl<'a> Layout<'a> {
+    fn new(db: &'a Database, message: &str) -> Result<Layout<'a>, Error> {
+        let (name, msg) = db
+            .messages
+            .get_key_value(message)
+            .ok_or(Error::UnknownFrame)?;
+        let mut names: Vec<&Name> = msg.signals.iter().collect();
+        let mut muxed: Vec<&Name> = msg.mux_signals.keys().collect();
+        muxed.sort_by_key(|s| (msg.mux_signals[*s].0, *s));
+        names.extend(muxed);
+
+        let mut signals = Vec::new();
+        let mut owners = vec![Vec::new(); msg.byte_width as usize * 8];
+        for (i, n) in names.into_iter().enumerate() {
+            let signal = db.signals.get(n).ok_or(Error::UnknownSignal)?;
+            for bit in signal.bit_positions() {
+                if let Some(o) = owners.get_mut(bit.get() as usize) {
+                    o.push(i);
+                }
+            }
+            signals.push((key(i), n, signal));
+        }
+        Ok(Layout {
+            name,
+            msg,
+            signals,
+            owners,
+        })
+    }
+
+    // multiplexed signals with different values never share a payload
+    fn conflict(&self, owners: &[usize]) -> bool {
+        owners.iter().enumerate().any(|(i, a)| {
+            owners[i + 1..].iter().any(|b| {
+                let mux = |s: usize| self.msg.mux_signals.get(self.signals[s].1).map(|m| m.0);
+                !matches!((mux(*a), mux(*b)), (Some(x), Some(y)) if x != y)
+            })
+        })
+    }
+
+    fn cell(&self, bit: usize) -> char {
+        match self.owners[bit].as_slice() {
+            [] => '.',
+            [s] => self.signals[*s].0,
+            o if self.conflict(o) => '#',
+            _ => '*',
+        }
+    }
+
+    fn legend(&self, signal: &(char, &Name, &Signal)) -> String {
+        let (key, name, s) = signal;
+        let mux = match self.msg.mux_signals.get(*name) {
+            Some((value, _)) => format!(", mux {}", value),
+            None => String::new(),
+        };
+        format!(
+            "{}  {}  bits {}|{} {}{}",
+            key,
+            name,
+            s.bit_start,
+            s.bit_width,
+            if s.little_endian {
+                "little-endian"
+            } else {
+                "big-endian"
+            },
+            mux
+        )
+    }
+}
+
+// byte/bit grid with MSB on the left, '*' marks multiplexed signals sharing bits and '#' real overlaps
+pub fn render_layout(db: &Database, message: &str) -> Result<String, Error> {
+    let layout = Layout::new(db, message)?;
+    let mut s = String::new();
+    writeln!(
+        s,
+        "{} (0x{:X}, {} bytes)",
+        layout.name, layout.msg.id, layout.msg.byte_width
+    )?;
+    writeln!(s, "      7  6  5  4  3  2  1  0")?;
+    for byte in 0..layout.msg.byte_width as usize {
+        write!(s, "{:>3} |", byte)?;
+        for bit in (0..8).rev() {
+            write!(s, " {} ", layout.cell(byte * 8 + bit))?;
+        }
+        writeln!(s, "|")?;
+    }
+    for signal in &layout.signals {
+        writeln!(s, "{}", layout.legend(signal))?;
+    }
+    let outside: Vec<&str> = layout
+        .signals
+        .iter()
+        .filter(|(_, _, sig)| {
+            sig.bit_positions()
+                .iter()
+                .any(|b| b.get() as usize >= layout.owners.len())
+        })
+        .map(|(_, n, _)| &***n)
+        .collect();
+    if !outside.is_empty() {
+        writeln!(s, "outside the payload: {}", outside.join(", "))?;
+    }
+    Ok(s)
+}
+
+pub fn render_layout_svg(db: &Database, message: &str) -> Result<String, Error> {
+    let layout = Layout::new(db, message)?;
+    let rows = layout.msg.byte_width as usize;
+    let width = 2 * SVG_MARGIN + 8 * SVG_CELL;
+    let grid = SVG_MARGIN + rows * SVG_CELL;
+    let height = grid + 24 * (layout.signals.len() + 1);
+    let color = |i: usize| format!("hsl({}, 70%, 80%)", (i * 137) % 360);
+    let mut s = String::new();
+
+    writeln!(
+        s,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"14\">",
+        width, height
+    )?;
+    writeln!(
+        s,
+        "  <text x=\"{}\" y=\"24\">{} (0x{:X}, {} bytes)</text>",
+        SVG_MARGIN, layout.name, layout.msg.id, layout.msg.byte_width
+    )?;
+    for bit in 0..8 {
+        writeln!(
+            s,
+            "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            SVG_MARGIN + (7 - bit) * SVG_CELL + SVG_CELL / 2,
+            SVG_MARGIN - 8,
+            bit
+        )?;
+    }
+    for byte in 0..rows {
+        let y = SVG_MARGIN + byte * SVG_CELL;
+        writeln!(
+            s,
+            "  <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            SVG_MARGIN - 8,
+            y + SVG_CELL / 2 + 5,
+            byte
+        )?;
+        for bit in 0..8 {
+            let x = SVG_MARGIN + (7 - bit) * SVG_CELL;
+            let owners = &layout.owners[byte * 8 + bit];
+            let fill = match owners.as_slice() {
+                [] => "white".to_string(),
+                [o] => color(*o),
+                o if layout.conflict(o) => "red".to_string(),
+                _ => "lightgray".to_string(),
+            };
+            writeln!(
+                s,
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"black\"/>",
+                x, y, SVG_CELL, SVG_CELL, fill
+            )?;
+            writeln!(
+                s,
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x + SVG_CELL / 2,
+                y + SVG_CELL / 2 + 5,
+                layout.cell(byte * 8 + bit)
+            )?;
+        }
+    }
+    for (i, signal) in layout.signals.iter().enumerate() {
+        let y = grid + 24 * (i + 1);
+        writeln!(
+            s,
+            "  <rect x=\"{}\" y=\"{}\" width=\"16\" height=\"16\" fill=\"{}\" stroke=\"black\"/>",
+            SVG_MARGIN,
+            y - 13,
+            color(i)
+        )?;
+        writeln!(
+            s,
+            "  <text x=\"{}\" y=\"{}\">{}</text>",
+            SVG_MARGIN + 24,
+            y,
+            layout.legend(signal)
+        )?;
+    }
+    writeln!(s, "</svg>")?;
+    Ok(s)
+}
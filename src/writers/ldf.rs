@@ -0,0 +1,340 @@
+use crate::parsers::encoding::{
+    DatabaseType, Encoding, Name, Signal, ValueKind, BIT_START_INVALID,
+};
+use crate::writers::fmt::{Comments, Entity};
+use crate::{Database, Error, WriteOptions};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+const LIN_VERSION_STR: &str = "\"2.2\"";
+
+// quoted, with quotes and backslashes escaped
+fn char_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// ends the line, with an encoding's description as a comment the parser takes back
+fn description(s: &mut String, text: Option<&str>) -> Result<(), Error> {
+    match text {
+        Some(text) => writeln!(s, " // {}", text.replace(['\r', '\n'], " "))?,
+        None => writeln!(s)?,
+    }
+    Ok(())
+}
+
+pub(crate) fn sorted<T>(map: &HashMap<Name, T>) -> Vec<(&Name, &T)> {
+    let mut ret: Vec<_> = map.iter().collect();
+    ret.sort_by(|a, b| a.0.cmp(b.0));
+    ret
+}
+
+pub fn write_ldf(db: &Database, ldf: impl AsRef<Path>) -> Result<(), Error> {
+    write_ldf_writer(db, File::create(ldf)?)
+}
+
+pub fn write_ldf_writer(db: &Database, mut writer: impl io::Write) -> Result<(), Error> {
+    writer.write_all(write_ldf_string(db)?.as_bytes())?;
+    Ok(())
+}
+
+pub fn write_ldf_string(db: &Database) -> Result<String, Error> {
+    write_ldf_comments(db, &mut Comments::default())
+}
+
+// only provenance applies, sorting is up to the caller
+pub(crate) fn write_ldf_string_with(
+    db: &Database,
+    options: &WriteOptions,
+) -> Result<String, Error> {
+    let mut s = String::new();
+    if let Some(provenance) = db.provenance.as_ref().filter(|_| options.provenance) {
+        writeln!(s, "// {}\n", provenance)?;
+    }
+    s.push_str(&write_ldf_string(db)?);
+    Ok(s)
+}
+
+pub(crate) fn write_ldf_comments(db: &Database, comments: &mut Comments) -> Result<String, Error> {
+    let data = match &db.extra {
+        DatabaseType::LDF(data) => data,
+        _ => return Err(Error::IncompatibleDatabase),
+    };
+    let mut frames: Vec<_> = db.messages.iter().collect();
+    frames.sort_by(|a, b| (a.1.id, a.0).cmp(&(b.1.id, b.0)));
+    let mut responders: Vec<_> = sorted(&data.responders);
+    responders.sort_by(|a, b| (a.1.configured_nad, a.0).cmp(&(b.1.configured_nad, b.0)));
+    let mut s = String::new();
+
+    // header
+    writeln!(s, "LIN_description_file;")?;
+    writeln!(s, "LIN_protocol_version = {};", LIN_VERSION_STR)?;
+    writeln!(s, "LIN_language_version = {};", LIN_VERSION_STR)?;
+    writeln!(s, "LIN_speed = {} kbps;", data.bitrate / 1000.0)?;
+    if !data.postfix.is_empty() {
+        writeln!(s, "Channel_name = {};", data.postfix)?;
+    }
+
+    // nodes
+    writeln!(s, "\nNodes {{")?;
+    writeln!(
+        s,
+        "    Master: {}, {} ms, {} ms;",
+        data.commander, data.time_base, data.jitter
+    )?;
+    let names: Vec<&str> = responders.iter().map(|(n, _)| &***n).collect();
+    writeln!(s, "    Slaves: {};", names.join(", "))?;
+    writeln!(s, "}}")?;
+
+    // signals, publisher is determined by frame and commander assumed to subscribe to the rest
+    let mut publishers = HashMap::new();
+    for (_, msg) in &frames {
+        for sig in &msg.signals {
+            publishers.insert(&**sig, &*msg.sender);
+        }
+    }
+    writeln!(s, "\nSignals {{")?;
+    for (name, signal) in sorted(&db.signals) {
+        let publisher = publishers.get(&**name).copied().unwrap_or(&data.commander);
+        let mut subscribers: Vec<&str> = responders
+            .iter()
+            .filter(|(_, r)| r.subscribed_signals.contains(name))
+            .map(|(n, _)| &***n)
+            .collect();
+        if *publisher != *data.commander {
+            subscribers.insert(0, &data.commander);
+        }
+        comments.lead(&mut s, Entity::Signal(name.clone()), "    ");
+        let init_value = match signal.kind {
+            ValueKind::ByteArray => {
+                let bytes: Vec<String> = signal
+                    .init_bytes()
+                    .iter()
+                    .map(|b| format!("0x{:02X}", b))
+                    .collect();
+                format!("{{{}}}", bytes.join(", "))
+            }
+            _ => signal.init_value.to_string(),
+        };
+        write!(
+            s,
+            "    {}: {}, {}, {}",
+            name, signal.bit_width, init_value, publisher
+        )?;
+        for sub in subscribers {
+            write!(s, ", {}", sub)?;
+        }
+        writeln!(s, ";")?;
+        comments.trail(&mut s, Entity::Signal(name.clone()));
+    }
+    writeln!(s, "}}")?;
+
+    // frames
+    writeln!(s, "\nFrames {{")?;
+    for (name, msg) in &frames {
+        comments.lead(&mut s, Entity::Frame((*name).clone()), "    ");
+        writeln!(
+            s,
+            "    {}: 0x{:02X}, {}, {} {{",
+            name, msg.id, msg.sender, msg.byte_width
+        )?;
+        for sig in &msg.signals {
+            let bit_start = db
+                .signals
+                .get(sig)
+                .map_or(BIT_START_INVALID, |s| s.bit_start);
+            writeln!(s, "        {}, {};", sig, bit_start)?;
+        }
+        writeln!(s, "    }}")?;
+        comments.trail(&mut s, Entity::Frame((*name).clone()));
+    }
+    writeln!(s, "}}")?;
+
+    if !data.sporadic_frames.is_empty() {
+        writeln!(s, "\nSporadic_frames {{")?;
+        for (name, list) in sorted(&data.sporadic_frames) {
+            comments.lead(&mut s, Entity::Frame(name.clone()), "    ");
+            writeln!(s, "    {}: {};", name, list.join(", "))?;
+            comments.trail(&mut s, Entity::Frame(name.clone()));
+        }
+        writeln!(s, "}}")?;
+    }
+
+    if !data.event_frames.is_empty() {
+        writeln!(s, "\nEvent_triggered_frames {{")?;
+        for (name, (resolver, id, list)) in sorted(&data.event_frames) {
+            comments.lead(&mut s, Entity::Frame(name.clone()), "    ");
+            write!(s, "    {}: {}, 0x{:02X}", name, resolver, id)?;
+            for f in list {
+                write!(s, ", {}", f)?;
+            }
+            writeln!(s, ";")?;
+            comments.trail(&mut s, Entity::Frame(name.clone()));
+        }
+        writeln!(s, "}}")?;
+    }
+
+    // node attributes
+    writeln!(s, "\nNode_attributes {{")?;
+    for (name, resp) in &responders {
+        let protocol = if resp.protocol.is_empty() {
+            "2.2"
+        } else {
+            &resp.protocol
+        };
+        comments.lead(&mut s, Entity::Node((*name).clone()), "    ");
+        writeln!(s, "    {} {{", name)?;
+        writeln!(s, "        LIN_protocol = {};", char_string(protocol))?;
+        writeln!(s, "        configured_NAD = 0x{:02X};", resp.configured_nad)?;
+        if let Some(nad) = resp.initial_nad {
+            writeln!(s, "        initial_NAD = 0x{:02X};", nad)?;
+        }
+        if protocol.starts_with("2.") {
+            if let Some((supplier, function, variant)) = resp.product_id {
+                writeln!(
+                    s,
+                    "        product_id = 0x{:04X}, 0x{:04X}, {};",
+                    supplier, function, variant
+                )?;
+            }
+            if let Some(response_error) = &resp.response_error {
+                writeln!(s, "        response_error = {};", response_error)?;
+            }
+            writeln!(s, "        configurable_frames {{")?;
+            for (frame, id) in &resp.configurable_frames {
+                match id {
+                    Some(id) => writeln!(s, "            {} = 0x{:02X};", frame, id)?,
+                    None => writeln!(s, "            {};", frame)?,
+                }
+            }
+            writeln!(s, "        }}")?;
+        }
+        writeln!(s, "    }}")?;
+        comments.trail(&mut s, Entity::Node((*name).clone()));
+    }
+    writeln!(s, "}}")?;
+
+    // schedule tables
+    writeln!(s, "\nSchedule_tables {{")?;
+    for (name, table) in sorted(&data.schedule_tables) {
+        comments.lead(&mut s, Entity::ScheduleTable(name.clone()), "    ");
+        writeln!(s, "    {} {{", name)?;
+        for (i, (cmd, delay)) in table.iter().enumerate() {
+            let entry = Entity::ScheduleEntry(name.clone(), i);
+            comments.lead(&mut s, entry.clone(), "        ");
+            writeln!(s, "        {} delay {} ms;", cmd, delay)?;
+            comments.trail(&mut s, entry);
+        }
+        writeln!(s, "    }}")?;
+        comments.trail(&mut s, Entity::ScheduleTable(name.clone()));
+    }
+    writeln!(s, "}}")?;
+
+    // signal groups, deprecated since LIN 2.0 but still understood, offsets are from the group's first bit
+    let groups: Vec<_> = frames
+        .iter()
+        .flat_map(|(_, msg)| sorted(&msg.signal_groups))
+        .collect();
+    if !groups.is_empty() {
+        writeln!(s, "\nSignal_groups {{")?;
+        for (name, group) in groups {
+            let signals: Vec<(&Name, &Signal)> = group
+                .signals
+                .iter()
+                .filter_map(|n| Some((n, db.signals.get(n)?)))
+                .collect();
+            let start = signals
+                .iter()
+                .map(|(_, sig)| sig.bit_start)
+                .min()
+                .unwrap_or(0);
+            let end = signals
+                .iter()
+                .map(|(_, sig)| sig.bit_start + sig.bit_width)
+                .max()
+                .unwrap_or(0);
+            writeln!(s, "    {} : {} {{", name, end - start)?;
+            for (signal, sig) in signals {
+                writeln!(s, "        {}, {};", signal, sig.bit_start - start)?;
+            }
+            writeln!(s, "    }}")?;
+        }
+        writeln!(s, "}}")?;
+    }
+
+    // encodings, signals sharing identical encodings are grouped under one type
+    let mut types: Vec<(String, &Vec<Encoding>, Vec<&Name>)> = Vec::new();
+    for (name, signal) in sorted(&db.signals) {
+        if let Some(encodings) = &signal.encodings {
+            if let Some(t) = types.iter_mut().find(|t| t.1 == encodings) {
+                t.2.push(name);
+            } else {
+                let type_name = encodings
+                    .iter()
+                    .find_map(|e| match e {
+                        Encoding::Enum { name, .. } => Some(name.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or(format!("{}_Encoding", name));
+                types.push((type_name, encodings, vec![name]));
+            }
+        }
+    }
+    if !types.is_empty() {
+        types.sort_by(|a, b| a.0.cmp(&b.0));
+        writeln!(s, "\nSignal_encoding_types {{")?;
+        for (name, encodings, _) in &types {
+            comments.lead(&mut s, Entity::Encoding(name.clone()), "    ");
+            write!(s, "    {} {{", name)?;
+            let enum_description = encodings.iter().find_map(|e| match e {
+                Encoding::Enum { description, .. } => description.as_deref(),
+                _ => None,
+            });
+            description(&mut s, enum_description)?;
+            for e in encodings.iter() {
+                match e {
+                    Encoding::Enum { rev_map, .. } => {
+                        let mut values: Vec<_> = rev_map.iter().collect();
+                        values.sort();
+                        for (raw, label) in values {
+                            writeln!(s, "        logical_value, {}, {};", raw, char_string(label))?;
+                        }
+                    }
+                    Encoding::Scalar {
+                        raw_min,
+                        raw_max,
+                        scale,
+                        offset,
+                        unit,
+                        description: text,
+                    } => {
+                        write!(
+                            s,
+                            "        physical_value, {}, {}, {}, {}",
+                            raw_min, raw_max, scale, offset
+                        )?;
+                        if !unit.is_empty() {
+                            write!(s, ", {}", char_string(unit))?;
+                        }
+                        write!(s, ";")?;
+                        description(&mut s, text.as_deref())?;
+                    }
+                    Encoding::Ascii => writeln!(s, "        ascii_value;")?,
+                }
+            }
+            writeln!(s, "    }}")?;
+            comments.trail(&mut s, Entity::Encoding(name.clone()));
+        }
+        writeln!(s, "}}")?;
+
+        writeln!(s, "\nSignal_representation {{")?;
+        for (name, _, signals) in &types {
+            let signals: Vec<&str> = signals.iter().map(|s| &***s).collect();
+            writeln!(s, "    {}: {};", name, signals.join(", "))?;
+        }
+        writeln!(s, "}}")?;
+    }
+    Ok(s)
+}
@@ -0,0 +1,92 @@
+use autodbconv::{parse_ldf_str, Database, Format};
+use std::path::PathBuf;
+
+const LDF: &str = include_str!("ldf/LIN_2.2A.ldf");
+
+// a fresh path in the temp directory for each test
+fn cache_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("autodbconv-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn cache_round_trip() {
+    let cache = cache_path("round-trip.cache");
+    let db = parse_ldf_str(LDF).unwrap();
+    db.save_cache(&cache, LDF.as_bytes()).unwrap();
+    let loaded = Database::load_cache(&cache, LDF.as_bytes()).unwrap();
+    assert_eq!(loaded, Some(db));
+    std::fs::remove_file(&cache).unwrap();
+}
+
+#[test]
+fn stale_cache() {
+    let cache = cache_path("stale.cache");
+    assert_eq!(Database::load_cache(&cache, LDF.as_bytes()).unwrap(), None); // missing
+
+    let db = parse_ldf_str(LDF).unwrap();
+    db.save_cache(&cache, LDF.as_bytes()).unwrap();
+    let edited = LDF.replace("19.2 kbps", "10.4 kbps");
+    assert_eq!(
+        Database::load_cache(&cache, edited.as_bytes()).unwrap(),
+        None
+    );
+
+    // another version's header
+    let mut data = std::fs::read(&cache).unwrap();
+    data[4] ^= 0xFF;
+    std::fs::write(&cache, data).unwrap();
+    assert_eq!(Database::load_cache(&cache, LDF.as_bytes()).unwrap(), None);
+    std::fs::remove_file(&cache).unwrap();
+}
+
+#[test]
+fn parse_cached_rebuilds() {
+    let (source, cache) = (cache_path("source.ldf"), cache_path("source.cache"));
+    std::fs::write(&source, LDF).unwrap();
+    let db = Format::LDF.parse_cached(&source, &cache).unwrap();
+    assert!(cache.exists());
+    assert_eq!(Format::LDF.parse_cached(&source, &cache).unwrap(), db);
+
+    let edited = LDF.replace("19.2 kbps", "10.4 kbps");
+    std::fs::write(&source, &edited).unwrap();
+    let rebuilt = Format::LDF.parse_cached(&source, &cache).unwrap();
+    assert_eq!(rebuilt, parse_ldf_str(&edited).unwrap());
+    assert_eq!(
+        Database::load_cache(&cache, edited.as_bytes()).unwrap(),
+        Some(rebuilt)
+    );
+    std::fs::remove_file(&source).unwrap();
+    std::fs::remove_file(&cache).unwrap();
+}
+
+#[test]
+fn fingerprint() {
+    let db = parse_ldf_str(LDF).unwrap();
+    let fingerprint = db.fingerprint();
+    assert_eq!(parse_ldf_str(LDF).unwrap().fingerprint(), fingerprint);
+
+    // signal order within a frame
+    let mut reordered = db.clone();
+    reordered
+        .messages
+        .get_mut("LSM_Frm2")
+        .unwrap()
+        .signals
+        .reverse();
+    assert_eq!(reordered.fingerprint(), fingerprint);
+
+    // where and when it was parsed
+    let mut moved = db.clone();
+    let provenance = moved.provenance.as_mut().unwrap();
+    provenance.source = Some("elsewhere.ldf".to_string());
+    provenance.parsed_at += 60;
+    assert_eq!(moved.fingerprint(), fingerprint);
+
+    let mut edited = db.clone();
+    edited.signals.get_mut("IntTest").unwrap().init_value = 1;
+    assert_ne!(edited.fingerprint(), fingerprint);
+    let edited = parse_ldf_str(&LDF.replace("19.2 kbps", "10.4 kbps")).unwrap();
+    assert_ne!(edited.fingerprint(), fingerprint);
+}
@@ -0,0 +1,79 @@
+use autodbconv::testing::assert_round_trip;
+use autodbconv::{format_ldf, parse_ldf_str, Database, DatabaseType, Encoding, Format};
+
+const LDF: &str = include_str!("ldf/LIN_2.2A.ldf");
+
+// the example with char_strings full of quotes, delimiters and comment starts
+fn ldf() -> String {
+    LDF.replace(r#""lux""#, r#""l\"u/x\\""#)
+        .replace(r#""No test result""#, r#""a, b; c: d = {e} /* f */""#)
+        .replace(r#""not used""#, r#""// not a comment""#)
+        .replace(r#"Channel_name = "DB";"#, r#"Channel_name = "D\"B";"#)
+}
+
+fn encoding(encodings: &[Encoding], raw: u64) -> &str {
+    encodings
+        .iter()
+        .find_map(|e| match e {
+            Encoding::Enum { rev_map, .. } => rev_map.get(&raw).map(|l| l.as_str()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn escaped_quotes() {
+    let db = parse_ldf_str(&ldf()).unwrap();
+    let unit = db.signals["LeftIntLightsSwitch"]
+        .encodings
+        .iter()
+        .flatten()
+        .find_map(|e| match e {
+            Encoding::Scalar { unit, .. } => Some(unit.as_str()),
+            _ => None,
+        });
+    assert_eq!(unit, Some(r#"l"u/x\"#));
+    let DatabaseType::LDF(data) = &db.extra else {
+        panic!("not an LDF");
+    };
+    assert_eq!(data.postfix, r#""D\"B""#); // kept as written
+}
+
+#[test]
+fn embedded_delimiters() {
+    let db = parse_ldf_str(&ldf()).unwrap();
+    let encodings = db.signals["IntTest"].encodings.as_ref().unwrap();
+    assert_eq!(encoding(encodings, 0), "a, b; c: d = {e} /* f */");
+    assert_eq!(encoding(encodings, 1), "failed");
+    assert_eq!(encoding(encodings, 3), "// not a comment");
+}
+
+#[test]
+fn postfix_unescaped() {
+    let db = parse_ldf_str(&ldf()).unwrap().apply_postfix();
+    assert!(db.signals.contains_key(r#"IntTestD"B"#));
+}
+
+#[test]
+fn round_trip() {
+    let db = parse_ldf_str(&ldf()).unwrap();
+    assert_round_trip(&db, Format::LDF);
+}
+
+#[test]
+fn fmt_keeps_strings() {
+    let formatted = format_ldf(&ldf()).unwrap();
+    assert!(formatted.contains(r#""// not a comment""#));
+    assert!(formatted.contains(r#""l\"u/x\\""#));
+    let parse = |src: &str| Database {
+        provenance: None, // parsed at different times
+        ..parse_ldf_str(src).unwrap()
+    };
+    assert_eq!(parse(&formatted), parse(&ldf()));
+}
+
+#[test]
+fn unterminated() {
+    let src = ldf().replace(r#""failed";"#, r#""failed;"#);
+    assert!(parse_ldf_str(&src).is_err());
+}
@@ -0,0 +1,52 @@
+use autodbconv::{generate_c, generate_c_with, parse_dbc_str, CodegenOptions};
+
+const MUX: &str = include_str!("dbc/mux.dbc");
+
+#[test]
+fn multiplexed_fields() {
+    let db = parse_dbc_str(MUX).unwrap();
+    let (h, c) = generate_c(&db, "mux").unwrap();
+    for field in ["Page", "Speed", "Temp", "Sub", "Deep", "Deeper"] {
+        assert!(
+            h.contains(&format!(" {};", field)),
+            "{} missing:\n{}",
+            field,
+            h
+        );
+    }
+
+    // packed and unpacked only when selected, multiplexors first
+    assert!(c.contains("    if (msg->Page == 0u) {\n        set_bits(data, 8, 16, msg->Speed);"));
+    assert!(c.contains(
+        "    if ((msg->Sub >= 2u && msg->Sub <= 4u) && msg->Page == 1u) {\n        msg->Deep = "
+    ));
+    let unpack = &c[c.find("Status_unpack").unwrap()..];
+    assert!(unpack.find("msg->Page =").unwrap() < unpack.find("msg->Sub =").unwrap());
+    assert!(unpack.find("msg->Sub =").unwrap() < unpack.find("msg->Deep =").unwrap());
+}
+
+#[test]
+fn multiplexors_follow_selected_signals() {
+    let db = parse_dbc_str(MUX).unwrap();
+    let options = CodegenOptions {
+        signals: vec!["Deep".to_string()],
+        ..Default::default()
+    };
+    let (h, _) = generate_c_with(&db, "mux", &options).unwrap();
+    for field in ["Page", "Sub", "Deep"] {
+        assert!(
+            h.contains(&format!(" {};", field)),
+            "{} missing:\n{}",
+            field,
+            h
+        );
+    }
+    for field in ["Speed", "Temp", "Deeper"] {
+        assert!(
+            !h.contains(&format!(" {};", field)),
+            "{} kept:\n{}",
+            field,
+            h
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use autodbconv::{
+    parse_dbc_str, parse_dbc_str_with, parse_ldf_str, write_dbc_string, AttributeValue,
+    DatabaseType, Encoding, Error, FrameProtocol, SendType, SignalValue, Warning, WarningKind,
+};
+use std::collections::HashMap;
+
+const DBC: &str = r#"VERSION ""
+
+NS_ :
+
+BS_:
+
+BU_: ECU
+
+BO_ 2147484160 Status: 8 ECU
+ SG_ Speed : 0|16@1+ (0.1,0) [0|6553.5] "km/h" ECU
+
+BA_DEF_ BO_ "GenMsgCycleTime" INT 0 65535;
+BA_DEF_ BO_ "GenMsgSendType" ENUM "Cyclic","Spontaneous";
+BA_DEF_ BO_ "VFrameFormat" ENUM "StandardCAN","ExtendedCAN";
+BA_DEF_ SG_ "GenSigStartValue" FLOAT 0 65535;
+BA_ "GenMsgCycleTime" BO_ 2147484160 100;
+BA_ "GenMsgSendType" BO_ 2147484160 0;
+BA_ "VFrameFormat" BO_ 2147484160 1;
+BA_ "GenSigStartValue" SG_ 2147484160 Speed 120;
+"#;
+
+#[test]
+fn typed_attributes() {
+    let db = parse_dbc_str(DBC).unwrap();
+    let msg = &db.messages["Status"];
+    assert_eq!(msg.cycle_time, Some(100.0));
+    assert_eq!(msg.send_type, Some(SendType::Cyclic));
+    assert_eq!(msg.protocol, FrameProtocol::Extended);
+    assert_eq!(db.signals["Speed"].init_value, 120);
+
+    // kept in the attribute maps too
+    let attr = |name: &str| msg.attributes.get(name);
+    assert_eq!(attr("GenMsgCycleTime"), Some(&AttributeValue::Int(100)));
+    assert_eq!(
+        attr("GenMsgSendType"),
+        Some(&AttributeValue::Enum("Cyclic".to_string()))
+    );
+    assert_eq!(
+        attr("VFrameFormat"),
+        Some(&AttributeValue::Enum("ExtendedCAN".to_string()))
+    );
+    assert_eq!(
+        db.signals["Speed"].attributes.get("GenSigStartValue"),
+        Some(&AttributeValue::Float(120.0))
+    );
+}
+
+const MUX: &str = include_str!("dbc/mux.dbc");
+
+#[test]
+fn decode_multiplexed() {
+    let db = parse_dbc_str(MUX).unwrap();
+    let names = |data: &[u8]| -> Vec<(String, u64)> {
+        let decoded = db.decode_message("Status", data).unwrap();
+        decoded.into_iter().map(|s| (s.name, s.raw)).collect()
+    };
+    let expected = |signals: &[(&str, u64)]| -> Vec<(String, u64)> {
+        signals.iter().map(|(n, r)| (n.to_string(), *r)).collect()
+    };
+
+    assert_eq!(
+        names(&[0, 0x10, 0x27, 0]),
+        expected(&[("Page", 0), ("Speed", 0x2710)])
+    );
+    assert_eq!(
+        names(&[1, 0xE8, 3, 7]),
+        expected(&[("Page", 1), ("Deep", 7), ("Sub", 3), ("Temp", 0xE8)])
+    );
+    assert_eq!(
+        names(&[1, 0xE8, 5, 7]),
+        expected(&[("Page", 1), ("Deeper", 7), ("Sub", 5), ("Temp", 0xE8)])
+    );
+    assert_eq!(
+        names(&[1, 0xE8, 7, 7]),
+        expected(&[("Page", 1), ("Sub", 7), ("Temp", 0xE8)])
+    );
+
+    let (_, decoded) = db.decode_frame(300, &[1, 0xE8, 3, 7]).unwrap();
+    let temp = decoded.iter().find(|s| s.name == "Temp").unwrap();
+    assert_eq!(temp.physical, Some(-64.0));
+}
+
+#[test]
+fn encode_multiplexed() {
+    let db = parse_dbc_str(MUX).unwrap();
+    let values = HashMap::from([
+        ("Page".to_string(), SignalValue::Raw(1)),
+        ("Temp".to_string(), SignalValue::Physical(-64.0)),
+        ("Sub".to_string(), SignalValue::Raw(3)),
+        ("Deep".to_string(), SignalValue::Raw(7)),
+    ]);
+    assert_eq!(
+        db.encode_message("Status", &values).unwrap(),
+        [1, 0xE8, 3, 7]
+    );
+
+    let values = HashMap::from([
+        ("Page".to_string(), SignalValue::Raw(0)),
+        ("Temp".to_string(), SignalValue::Physical(-64.0)),
+    ]);
+    let e = db.encode_message("Status", &values).unwrap_err();
+    assert!(matches!(e.kind(), Error::UnselectedSignal), "{}", e);
+}
+
+#[test]
+fn escaped_value_labels() {
+    let labels = [r#"say "hi""#, r"back\slash", r"ends in \", "plain"];
+    let src = DBC.replace(
+        "BA_DEF_ BO_",
+        &format!(
+            "VAL_ 2147484160 Speed 0 {:?} 1 {:?} 2 {:?} 3 {:?} ;\n\nBA_DEF_ BO_",
+            labels[0], labels[1], labels[2], labels[3]
+        ),
+    );
+    let db = parse_dbc_str(&src).unwrap();
+    let read = |db: &autodbconv::Database| -> Vec<String> {
+        let rev_map = db.signals["Speed"]
+            .encodings
+            .iter()
+            .flatten()
+            .find_map(|e| match e {
+                Encoding::Enum { rev_map, .. } => Some(rev_map.clone()),
+                _ => None,
+            })
+            .unwrap();
+        (0..4).map(|raw| rev_map[&raw].clone()).collect()
+    };
+    assert_eq!(read(&db), labels);
+
+    let written = write_dbc_string(&db).unwrap();
+    assert_eq!(read(&parse_dbc_str(&written).unwrap()), labels);
+}
+
+#[test]
+fn escaped_units() {
+    let unit = |db: &autodbconv::Database, signal: &str| -> String {
+        db.signals[signal]
+            .encodings
+            .iter()
+            .flatten()
+            .find_map(|e| match e {
+                Encoding::Scalar { unit, .. } => Some(unit.clone()),
+                _ => None,
+            })
+            .unwrap()
+    };
+    let db = parse_dbc_str(&DBC.replace(r#""km/h""#, r#""k\"m\\h" "#)).unwrap();
+    assert_eq!(unit(&db, "Speed"), r#"k"m\h"#);
+    let written = write_dbc_string(&db).unwrap();
+    assert_eq!(unit(&parse_dbc_str(&written).unwrap(), "Speed"), r#"k"m\h"#);
+
+    // LIN units and attributes written as DBC
+    let ldf = include_str!("ldf/LIN_2.2A.ldf").replace(r#""lux""#, r#""l\"ux""#);
+    let mut db = parse_ldf_str(&ldf).unwrap();
+    let DatabaseType::LDF(data) = &mut db.extra else {
+        panic!("not an LDF");
+    };
+    data.responders.get_mut("RSM").unwrap().protocol = r#"2"0"#.to_string();
+    let dbc = parse_dbc_str(&write_dbc_string(&db).unwrap()).unwrap();
+    assert_eq!(unit(&dbc, "LeftIntLightsSwitch"), r#"l"ux"#);
+    let DatabaseType::DBC(data) = &dbc.extra else {
+        panic!("not a DBC");
+    };
+    assert_eq!(
+        data.node_attributes["RSM"].get("LIN_Protocol"),
+        Some(&AttributeValue::String(r#"2"0"#.to_string()))
+    );
+}
+
+#[test]
+fn decode_plan_multiplexed() {
+    let db = parse_dbc_str(MUX).unwrap();
+    let e = db.decode_plan("Status").err().unwrap();
+    assert!(matches!(e.kind(), Error::NotImplemented), "{}", e);
+    assert!(db.freeze().decode_plan(300).is_none());
+
+    let mut db = parse_dbc_str(DBC).unwrap();
+    let msg = db.messages.get_mut("Status").unwrap();
+    msg.signals.push("Ghost".into());
+    let e = db.decode_plan("Status").err().unwrap();
+    assert!(matches!(e.kind(), Error::UnknownSignal), "{}", e);
+}
+
+// statements for objects the model has no place for
+#[test]
+fn unsupported_statements() {
+    let src = format!(
+        "{}{}",
+        DBC,
+        r#"BU_SG_REL_ "Rx" SG_ ECU 2147484160 Speed 1;
+BU_BO_REL_ "Tx" BU_ ECU 2147484160 1;
+BU_EV_REL_ "Rx" ECU Env 1;
+SGTYPE_VAL_ Speeds 0 "Stop";
+BA_DEF_SGTYPE_ "Kind" INT 0 1;
+CAT_DEF_ 1 "Body" 0;
+FILTER 0 "Body" ECU;
+"#
+    );
+    let mut warnings: Vec<Warning> = Vec::new();
+    let db = parse_dbc_str_with(&src, &mut |w| warnings.push(w)).unwrap();
+    assert!(db.messages.contains_key("Status"));
+    let kinds: Vec<WarningKind> = warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(kinds, [WarningKind::UnsupportedSection; 7]);
+}
+
+#[test]
+fn attribute_defaults() {
+    let src = format!(
+        "{}{}",
+        DBC,
+        r#"BA_DEF_ BO_ "Priority" INT 0 7;
+BA_DEF_ BU_ "Role" ENUM "Gateway","Sensor";
+BA_DEF_  "Owner" STRING ;
+BA_DEF_DEF_ "Priority" 3;
+BA_DEF_DEF_ "Role" "Sensor";
+BA_DEF_DEF_ "Owner" "body";
+"#
+    );
+    let defaults = |db: &autodbconv::Database| {
+        let DatabaseType::DBC(data) = &db.extra else {
+            panic!("not a DBC");
+        };
+        data.attribute_defaults.clone()
+    };
+    let db = parse_dbc_str(&src).unwrap();
+    let default = |object: &str, value| (object.to_string(), value);
+    let expected = HashMap::from([
+        (
+            "Priority".to_string(),
+            default("BO_", AttributeValue::Int(3)),
+        ),
+        (
+            "Role".to_string(),
+            default("BU_", AttributeValue::Enum("Sensor".into())),
+        ),
+        (
+            "Owner".to_string(),
+            default("", AttributeValue::String("body".into())),
+        ),
+    ]);
+    assert_eq!(defaults(&db), expected);
+
+    let written = write_dbc_string(&db).unwrap();
+    assert!(
+        written.contains(r#"BA_DEF_DEF_  "Priority" 3;"#),
+        "{}",
+        written
+    );
+    assert_eq!(defaults(&parse_dbc_str(&written).unwrap()), defaults(&db));
+}
+
+#[test]
+fn mux_signal_without_multiplexor() {
+    let src = DBC.replace("SG_ Speed :", "SG_ Speed m1 :");
+    let e = parse_dbc_str(&src).unwrap_err();
+    assert!(matches!(e.kind(), Error::UnknownSignal), "{}", e);
+    let span = e.span().unwrap();
+    assert!(src[span.start..span.end].contains("SG_ Speed m1"));
+}
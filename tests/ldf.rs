@@ -0,0 +1,156 @@
+use autodbconv::{
+    format_ldf, ldf_sections, parse_dbc_str, parse_ldf_sections, parse_ldf_str, write_dbc_string,
+    write_ldf_string, Database, DatabaseType, Encoding, Error, LDFScheduleCommand, Pid,
+};
+
+const LDF: &str = include_str!("ldf/LIN_2.2A.ldf");
+
+#[test]
+fn sections() {
+    let (sections, errors) = ldf_sections(&format!("{}\n// trailing comment\n", LDF));
+    assert!(errors.is_empty(), "{:?}", errors);
+    let keywords: Vec<&str> = sections.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keywords.first(), Some(&"LIN_description_file"));
+    assert!(keywords.contains(&"Schedule_tables"));
+    assert!(keywords.contains(&"Signal_representation"));
+}
+
+// a stray '/' in Node_attributes
+fn broken_attributes() -> String {
+    LDF.replace("response_error = RSMerror;", "response_error = RSMerror; /")
+}
+
+#[test]
+fn sections_resync_after_tokenizer_errors() {
+    let (sections, errors) = ldf_sections(&broken_attributes());
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(
+        matches!(errors[0].kind(), Error::ExpectedComment),
+        "{}",
+        errors[0]
+    );
+    assert!(errors[0].span().is_some());
+    let keywords: Vec<&str> = sections.iter().map(|(k, _)| k.as_str()).collect();
+    assert!(!keywords.contains(&"Node_attributes"));
+    assert!(keywords.contains(&"Event_triggered_frames"));
+    assert!(keywords.contains(&"Schedule_tables"));
+}
+
+#[test]
+fn broken_section_leaves_others() {
+    let (db, errors) = parse_ldf_sections(
+        &broken_attributes(),
+        &["Node_attributes", "Schedule_tables"],
+    )
+    .unwrap();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(
+        matches!(errors[0].kind(), Error::ExpectedComment),
+        "{}",
+        errors[0]
+    );
+    assert!(db.messages.contains_key("CEM_Frm1"));
+    let DatabaseType::LDF(data) = &db.extra else {
+        panic!("not an LDF");
+    };
+    assert_eq!(data.schedule_tables["Normal_Schedule"].len(), 4);
+    assert!(data.event_frames.contains_key("Node_Status_Event"));
+}
+
+// trailing comments in an encoding block describe the enum, on its header line, or a physical range
+fn described() -> String {
+    LDF.replace("LightEncoding {", "LightEncoding { // interior light")
+        .replace(r#""lux";"#, r#""lux"; /* ambient */"#)
+}
+
+fn descriptions(db: &Database) -> Vec<Option<String>> {
+    db.signals["LeftIntLightsSwitch"]
+        .encodings
+        .iter()
+        .flatten()
+        .filter_map(|e| match e {
+            Encoding::Scalar { description, .. } | Encoding::Enum { description, .. } => {
+                Some(description.clone())
+            }
+            Encoding::Ascii => None,
+        })
+        .collect()
+}
+
+#[test]
+fn encoding_descriptions() {
+    let db = parse_ldf_str(&described()).unwrap();
+    let mut expected = vec![
+        Some("ambient".to_string()),
+        Some("interior light".to_string()),
+    ];
+    let mut found = descriptions(&db);
+    expected.sort();
+    found.sort();
+    assert_eq!(found, expected);
+    assert_eq!(descriptions(&parse_ldf_str(LDF).unwrap()), vec![None, None]);
+
+    let ldf = parse_ldf_str(&write_ldf_string(&db).unwrap()).unwrap();
+    assert_eq!(descriptions(&ldf), descriptions(&db));
+    let dbc = parse_dbc_str(&write_dbc_string(&db).unwrap()).unwrap();
+    assert_eq!(descriptions(&dbc), descriptions(&db));
+
+    // written back once, as descriptions
+    let formatted = format_ldf(&described()).unwrap();
+    assert_eq!(formatted.matches("interior light").count(), 1);
+    assert_eq!(formatted.matches("ambient").count(), 1);
+    assert_eq!(
+        descriptions(&parse_ldf_str(&formatted).unwrap()),
+        descriptions(&db)
+    );
+}
+
+#[test]
+fn assign_frame_ids() {
+    let src = LDF
+        .replace("LSM_Frm1: 0x02", "LSM_Frm1: 0x01")
+        .replace("RSM_Frm1: 0x04", "RSM_Frm1: 0x02")
+        .replace("RSM_Frm2: 0x05", "RSM_Frm2: 0x01")
+        .replace("Collision_resolver, 0x06", "Collision_resolver, 0x04")
+        .replace(
+            "AssignFrameIdRange {LSM, 0}",
+            "AssignFrameIdRange {LSM, 0, 0x06, 0x01, 0x02, 0xFF}",
+        );
+    let mut db = parse_ldf_str(&src).unwrap();
+    db.messages.get_mut("CEM_Frm1").unwrap().id = 0x3C; // not an unconditional id
+    let frames = ["RSM_Frm2", "LSM_Frm2", "CEM_Frm1"];
+    let assigned = db.assign_frame_ids(&frames).unwrap();
+
+    // LSM_Frm2 keeps its free id, the rest get the lowest free ones in name order, skipping ids taken by
+    // other frames (1, 2) and the event triggered frame (4)
+    let ids: Vec<(&str, u32, u8)> = assigned
+        .iter()
+        .map(|(n, id, pid)| (&**n, id.get(), pid.get()))
+        .collect();
+    let pid = |id: u8| Pid::from_id(id).get();
+    assert_eq!(
+        ids,
+        [
+            ("CEM_Frm1", 0, pid(0)),
+            ("LSM_Frm2", 3, pid(3)),
+            ("RSM_Frm2", 5, pid(5)),
+        ]
+    );
+    assert_eq!(db.messages["RSM_Frm2"].id, 5);
+    assert_eq!(db.messages["LSM_Frm1"].id, 1);
+
+    // PIDs of the frames assigned follow, 0xFF stays
+    let DatabaseType::LDF(data) = &db.extra else {
+        panic!("not an LDF");
+    };
+    let range = data.schedule_tables["Configuration_Schedule"]
+        .iter()
+        .find_map(|(cmd, _)| match cmd {
+            LDFScheduleCommand::AssignFrameIdRange { pid, .. } => Some(*pid),
+            _ => None,
+        });
+    assert_eq!(range, Some([0x06, pid(0), 0x02, 0xFF]));
+
+    // the same again
+    assert_eq!(db.assign_frame_ids(&frames).unwrap(), assigned);
+}
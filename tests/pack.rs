@@ -0,0 +1,124 @@
+use autodbconv::{
+    parse_dbc_str, BitPos, Database, Error, FrameId, PackOptions, Signal, ValueKind,
+    BIT_START_INVALID, MAX_ARRAY_WIDTH,
+};
+
+const DBC: &str = r#"VERSION ""
+
+NS_ :
+
+BS_:
+
+BU_: ECU
+
+BO_ 100 Status: 2 ECU
+ SG_ A : 0|8@1+ (1,0) [0|255] "" ECU
+ SG_ B : 8|4@1+ (1,0) [0|15] "" ECU
+ SG_ C : 12|8@1+ (1,0) [0|255] "" ECU
+"#;
+
+// B and C without positions
+fn unplaced() -> Database {
+    let mut db = parse_dbc_str(DBC).unwrap();
+    for name in ["B", "C"] {
+        db.signals.get_mut(name).unwrap().bit_start = BIT_START_INVALID;
+    }
+    db
+}
+
+#[test]
+fn pack_signals() {
+    let mut db = unplaced();
+    let report = db.pack_signals(&PackOptions::default()).unwrap();
+    assert_eq!(
+        report,
+        [
+            "Status: C at bit 8",
+            "Status: B at bit 16",
+            "Status: grown to 3 bytes"
+        ]
+    );
+    assert_eq!(db.signals["A"].bit_start, 0); // placed already
+    assert_eq!(db.signals["C"].bit_start, 8); // in one byte, not straddling
+    assert_eq!(db.messages["Status"].byte_width, 3);
+
+    // nothing changes if something doesn't fit
+    let mut db = unplaced();
+    let options = PackOptions { max_byte_width: 2 };
+    let e = db.pack_signals(&options).unwrap_err();
+    assert!(matches!(e.kind(), Error::SignalOutsideFrame), "{}", e);
+    assert_eq!(db.signals["C"].bit_start, BIT_START_INVALID);
+    assert_eq!(db.messages["Status"].byte_width, 2);
+}
+
+#[test]
+fn pack_new_frames() {
+    let mut db = unplaced();
+    db.messages.remove("Status");
+    let ids = [FrameId::can(0x200).unwrap()];
+    let frames = db
+        .pack_new_frames(
+            &["B", "C", "A"],
+            "ECU",
+            &ids,
+            "Packed",
+            &PackOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(frames.len(), 1);
+    let msg = &db.messages["Packed_1"];
+    assert_eq!((msg.id, msg.byte_width), (0x200, 3));
+    let starts: Vec<u16> = ["A", "B", "C"]
+        .iter()
+        .map(|s| db.signals[*s].bit_start)
+        .collect();
+    assert_eq!(starts, [0, 16, 8]); // A keeps its fixed position
+
+    // a signal already in a frame can't be packed again
+    let e = db
+        .pack_new_frames(&["A"], "ECU", &ids, "Again", &PackOptions::default())
+        .unwrap_err();
+    assert!(matches!(e.kind(), Error::DuplicateSignal), "{}", e);
+}
+
+#[test]
+fn add_array_signal() {
+    let mut db = parse_dbc_str(DBC).unwrap();
+    let array = Signal {
+        kind: ValueKind::ByteArray,
+        bit_width: MAX_ARRAY_WIDTH,
+        ..db.signals["A"].clone()
+    };
+    db.add_signal("Blob", array.clone()).unwrap();
+    let odd = Signal {
+        bit_width: 12,
+        ..array.clone()
+    };
+    let e = db.add_signal("Odd", odd).unwrap_err();
+    assert!(matches!(e.kind(), Error::SignalTooWide), "{}", e);
+    let wide = Signal {
+        kind: ValueKind::Unsigned,
+        bit_width: 128,
+        ..array
+    };
+    let e = db.add_signal("Wide", wide).unwrap_err();
+    assert!(matches!(e.kind(), Error::SignalTooWide), "{}", e);
+}
+
+#[test]
+fn bit_positions() {
+    let db = parse_dbc_str(DBC).unwrap();
+    let big_endian = Signal {
+        little_endian: false,
+        bit_start: 12,
+        ..db.signals["C"].clone()
+    };
+    let bits: Vec<u16> = big_endian.bit_positions().iter().map(|b| b.get()).collect();
+    assert_eq!(bits, [12, 11, 10, 9, 8, 23, 22, 21]);
+    let msb = big_endian.bit_positions()[0];
+    assert_eq!((msb.byte(), msb.bit()), (1, 4));
+
+    assert_eq!(BitPos::new(511).unwrap().byte(), 63);
+    let e = BitPos::new(512).unwrap_err();
+    assert!(matches!(e.kind(), Error::ValueOutOfRange), "{}", e);
+}
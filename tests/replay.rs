@@ -0,0 +1,49 @@
+use autodbconv::{parse_candump, parse_dbc_str, SeriesOptions};
+
+const MUX: &str = include_str!("dbc/mux.dbc");
+
+const LOG: &str = "(1.000) can0 12C#00102700
+(1.010) can0 12C#01E80307
+(1.020) can0 12C#01E80507
+(1.030) can0 7FF#00
+(1.040) can0 12C#01
+";
+
+#[test]
+fn multiplexed_series() {
+    let db = parse_dbc_str(MUX).unwrap();
+    let frames = parse_candump(LOG);
+    let series = db
+        .signal_series(&frames, &SeriesOptions::default())
+        .unwrap();
+    let columns: Vec<&str> = series.signals.iter().map(|s| &**s).collect();
+    assert_eq!(columns, ["Deep", "Deeper", "Page", "Speed", "Sub", "Temp"]);
+    assert_eq!((series.unknown, series.invalid), (1, 1));
+    assert_eq!(series.rows.len(), 3);
+
+    // only the signals the multiplexors select have values
+    let [page0, page1, sub5] = &series.rows[..] else {
+        unreachable!()
+    };
+    assert_eq!(page0[2], Some(0.0));
+    assert!(page0[3].is_some());
+    assert_eq!(page0[..2], [None, None]);
+    assert_eq!(page0[4..], [None, None]);
+    assert_eq!(
+        page1,
+        &[Some(7.0), None, Some(1.0), None, Some(3.0), Some(-64.0)]
+    );
+    assert_eq!(
+        sub5,
+        &[None, Some(7.0), Some(1.0), None, Some(5.0), Some(-64.0)]
+    );
+
+    // held values carry over from frames that selected them
+    let options = SeriesOptions {
+        hold: true,
+        ..Default::default()
+    };
+    let held = db.signal_series(&frames, &options).unwrap();
+    assert_eq!(held.rows[2][0], Some(7.0));
+    assert_eq!(held.rows[2][3], page0[3]);
+}
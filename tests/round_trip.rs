@@ -0,0 +1,16 @@
+use autodbconv::{parse_ldf, testing, Format};
+
+#[test]
+fn ldf_round_trips() {
+    let db = parse_ldf("tests/ldf/LIN_2.2A.ldf").unwrap();
+    testing::assert_round_trip(&db, Format::LDF);
+    testing::assert_payloads(&db, 20);
+}
+
+#[test]
+fn dbc_round_trips() {
+    let db = parse_ldf("tests/ldf/LIN_2.2A.ldf").unwrap();
+    let db = testing::round_trip(&db, Format::DBC).unwrap();
+    testing::assert_round_trip(&db, Format::DBC);
+    testing::assert_payloads(&db, 20);
+}
@@ -0,0 +1,130 @@
+use autodbconv::{parse_dbc_str, Signal, SignalValue, ValueKind};
+use std::collections::HashMap;
+
+const DBC: &str = r#"VERSION ""
+
+NS_ :
+
+BS_:
+
+BU_: ECU
+
+BO_ 256 Status: 8 ECU
+ SG_ Temp : 0|8@1- (0.5,0) [0|0] "degC" ECU
+ SG_ Torque : 15|16@0- (0.1,-100) [0|0] "Nm" ECU
+ SG_ Flag : 24|1@1- (1,0) [0|0] "" ECU
+ SG_ Raw : 32|32@1- (1,0) [0|0] "" ECU
+"#;
+
+fn signal(width: u16, little_endian: bool) -> Signal {
+    Signal {
+        kind: ValueKind::Signed,
+        little_endian,
+        bit_start: 3,
+        bit_width: width,
+        init_value: 0,
+        encodings: None,
+        physical_limits: None,
+        description: None,
+        attributes: HashMap::new(),
+    }
+}
+
+fn bounds(width: u16) -> (i64, i64) {
+    if width == 64 {
+        (i64::MIN, i64::MAX)
+    } else {
+        (-(1 << (width - 1)), (1 << (width - 1)) - 1)
+    }
+}
+
+#[test]
+fn round_trip_all_widths() {
+    for little_endian in [true, false] {
+        for width in 1..=64 {
+            let s = signal(width, little_endian);
+            let (min, max) = bounds(width);
+            for value in [min, min / 2, -1, 0, 1, max / 2, max] {
+                if value < min || value > max {
+                    continue; // 1 bit signals only hold -1 and 0
+                }
+                let raw = s.from_signed(value).unwrap();
+                let mut data = [0; 9];
+                s.insert(&mut data, raw).unwrap();
+                let extracted = s.extract(&data).unwrap();
+                assert_eq!(extracted, raw, "width {} value {}", width, value);
+                assert_eq!(s.sign_extend(extracted), value, "width {}", width);
+            }
+        }
+    }
+}
+
+#[test]
+fn out_of_range() {
+    for width in 1..64 {
+        let s = signal(width, true);
+        let (min, max) = bounds(width);
+        assert_eq!(s.from_signed(min - 1), None, "width {}", width);
+        assert_eq!(s.from_signed(max + 1), None, "width {}", width);
+    }
+    let unsigned = Signal {
+        kind: ValueKind::Unsigned,
+        ..signal(8, true)
+    };
+    assert_eq!(unsigned.from_signed(-1), None);
+    assert_eq!(unsigned.from_signed(255), Some(255));
+    assert_eq!(unsigned.sign_extend(0xFF), 255);
+}
+
+#[test]
+fn sign_bit_boundaries() {
+    let s = signal(8, true);
+    assert_eq!(s.sign_extend(0x7F), 127);
+    assert_eq!(s.sign_extend(0x80), -128);
+    assert_eq!(s.sign_extend(0xFF), -1);
+    let s = signal(1, true);
+    assert_eq!(s.sign_extend(1), -1);
+    let s = signal(64, true);
+    assert_eq!(s.sign_extend(u64::MAX), -1);
+    assert_eq!(s.sign_extend(1 << 63), i64::MIN);
+}
+
+#[test]
+fn decode_physical() {
+    let db = parse_dbc_str(DBC).unwrap();
+    let data = [0xF6, 0x00, 0x00, 0x01, 0xFE, 0xFF, 0xFF, 0xFF];
+    let decoded = db.decode_message("Status", &data).unwrap();
+    let get = |n: &str| decoded.iter().find(|s| s.name == n).unwrap();
+
+    assert_eq!(get("Temp").raw, 0xF6);
+    assert_eq!(get("Temp").signed, Some(-10));
+    assert_eq!(get("Temp").physical, Some(-5.0));
+    assert_eq!(get("Torque").signed, Some(0));
+    assert_eq!(get("Torque").physical, Some(-100.0));
+    assert_eq!(get("Flag").signed, Some(-1));
+    assert_eq!(get("Raw").signed, Some(-2));
+
+    let plan = db.decode_plan("Status").unwrap();
+    let mut out = vec![0.0; plan.len()];
+    plan.decode(&data, &mut out).unwrap();
+    for (name, value) in plan.signals().zip(out) {
+        assert_eq!(get(name).physical.unwrap_or(value), value, "{}", name);
+    }
+}
+
+#[test]
+fn encode_physical() {
+    let db = parse_dbc_str(DBC).unwrap();
+    let values = HashMap::from([
+        ("Temp".to_string(), SignalValue::Physical(-5.0)),
+        ("Torque".to_string(), SignalValue::Physical(-110.0)),
+    ]);
+    let data = db.encode_message("Status", &values).unwrap();
+    assert_eq!(data[0], 0xF6);
+    assert_eq!([data[1], data[2]], [0xFF, 0x9C]); // -100 big-endian
+
+    let temp = &db.signals["Temp"];
+    assert_eq!(temp.physical_range(), Some((-64.0, 63.5)));
+    assert!(temp.to_raw(&SignalValue::Physical(64.0)).is_err());
+    assert_eq!(temp.to_raw(&SignalValue::Physical(-64.0)).unwrap(), 0x80);
+}
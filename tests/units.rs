@@ -0,0 +1,91 @@
+use autodbconv::{parse_dbc_str, Database, Encoding, Error, Quantity, Unit};
+
+const DBC: &str = r#"VERSION ""
+
+NS_ :
+
+BS_:
+
+BU_: ECU
+
+BO_ 256 Status: 8 ECU
+ SG_ Speed : 0|16@1+ (0.1,0) [0|250] "km/h" ECU
+ SG_ Temp : 16|8@1+ (1,-40) [0|0] "C" ECU
+ SG_ Light : 24|8@1+ (10,0) [0|0] "lux" ECU
+"#;
+
+// (scale, offset, unit) of a signal's first scalar range
+fn scalar(db: &Database, signal: &str) -> (f64, f64, String) {
+    db.signals[signal]
+        .encodings
+        .iter()
+        .flatten()
+        .find_map(|e| match e {
+            Encoding::Scalar {
+                scale,
+                offset,
+                unit,
+                ..
+            } => Some((*scale, *offset, unit.clone())),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn parse_units() {
+    let kmh = Unit::parse("km/h").unwrap();
+    assert_eq!(kmh.quantity, Quantity::Speed);
+    assert_eq!(Unit::parse(" kph ").unwrap(), kmh);
+    assert_eq!(Unit::parse("KM/H").unwrap(), kmh); // case only matters when it tells units apart
+    assert_eq!(Unit::parse("mV").unwrap().symbol, "mV");
+    assert_eq!(Unit::parse("°C").unwrap().symbol, "degC");
+    assert_eq!(Unit::parse("lux"), None);
+    assert_eq!(Unit::parse(""), None);
+}
+
+#[test]
+fn conversions() {
+    let unit = |s| Unit::parse(s).unwrap();
+    let convert = |value, from, to| unit(from).convert(value, &unit(to)).unwrap();
+    for (value, from, to, expected) in [
+        (100.0, "degC", "degF", 212.0),
+        (-40.0, "degF", "degC", -40.0),
+        (0.0, "degC", "K", 273.15),
+        (36.0, "km/h", "m/s", 10.0),
+        (1.0, "bar", "kPa", 100.0),
+    ] {
+        let actual = convert(value, from, to);
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} {} is {} {}",
+            value,
+            from,
+            actual,
+            to
+        );
+    }
+    assert_eq!(unit("km/h").convert(1.0, &unit("V")), None);
+}
+
+#[test]
+fn normalize_units() {
+    let mut db = parse_dbc_str(DBC).unwrap();
+    assert_eq!(db.normalize_units(&["m/s", "K"]).unwrap(), 2);
+    let (scale, offset, unit) = scalar(&db, "Speed");
+    assert!((scale - 0.1 / 3.6).abs() < 1e-9, "{}", scale);
+    assert_eq!((offset, unit.as_str()), (0.0, "m/s"));
+    let limits = db.signals["Speed"].physical_limits.unwrap();
+    assert!((limits.1 - 250.0 / 3.6).abs() < 1e-9, "{:?}", limits);
+    assert_eq!(scalar(&db, "Temp"), (1.0, 233.15, "K".to_string()));
+    assert_eq!(scalar(&db, "Light"), (10.0, 0.0, "lux".to_string())); // unknown, left alone
+
+    // without targets known units only get their canonical spelling
+    let mut db = parse_dbc_str(DBC).unwrap();
+    assert_eq!(db.normalize_units(&[]).unwrap(), 1);
+    assert_eq!(scalar(&db, "Temp"), (1.0, -40.0, "degC".to_string()));
+    assert_eq!(scalar(&db, "Speed"), (0.1, 0.0, "km/h".to_string()));
+
+    let e = db.normalize_units(&["furlongs"]).unwrap_err();
+    assert!(matches!(e.kind(), Error::UnknownUnit), "{}", e);
+}
@@ -0,0 +1,305 @@
+use autodbconv::{
+    parse_dbc_str, parse_ldf_str, write_dbc_string, BitPos, Database, DatabaseType, Error, LDFData,
+    Name,
+};
+use std::collections::HashMap;
+
+const MUX: &str = include_str!("dbc/mux.dbc");
+const LDF: &str = include_str!("ldf/LIN_2.2A.ldf");
+
+// multiplexing issues found, as "kind, hint"
+fn mux_issues(db: &Database) -> Vec<String> {
+    db.validate()
+        .iter()
+        .filter(|e| {
+            matches!(
+                e.kind(),
+                Error::MultiplexorCount | Error::MuxRangeGap | Error::UnknownSignal
+            )
+        })
+        .map(|e| e.to_string())
+        .collect()
+}
+
+fn set_multiplexors(db: &mut Database, signal: &str, multiplexors: &[&str]) {
+    let msg = db.messages.get_mut("Status").unwrap();
+    msg.mux_signals.get_mut(signal).unwrap().1 =
+        multiplexors.iter().map(|m| Name::from(*m)).collect();
+}
+
+#[test]
+fn valid_multiplexing() {
+    let db = parse_dbc_str(MUX).unwrap();
+    assert_eq!(mux_issues(&db), Vec::<String>::new());
+    assert!(db.overlaps().is_empty()); // only different selector values share bits
+}
+
+#[test]
+fn multiplexor_count() {
+    let mut db = parse_dbc_str(MUX).unwrap();
+    set_multiplexors(&mut db, "Speed", &["Page", "Temp"]);
+    assert_eq!(
+        mux_issues(&db),
+        ["not exactly one multiplexor, Status: Speed has multiplexors [Page, Temp]"]
+    );
+
+    // a second multiplexor outside the others
+    let mut db = parse_dbc_str(MUX).unwrap();
+    let msg = db.messages.get_mut("Status").unwrap();
+    msg.mux_signals.remove("Deeper");
+    msg.signals.push("Deeper".into());
+    set_multiplexors(&mut db, "Deep", &["Deeper"]);
+    assert_eq!(
+        mux_issues(&db),
+        ["not exactly one multiplexor, Status: multiplexors Deeper, Page"]
+    );
+}
+
+#[test]
+fn multiplexor_not_in_frame() {
+    let mut db = parse_dbc_str(MUX).unwrap();
+    set_multiplexors(&mut db, "Temp", &["Gone"]);
+    let issues = mux_issues(&db);
+    assert_eq!(issues.len(), 1, "{:?}", issues);
+    assert!(issues[0].ends_with("Status: Temp has multiplexor Gone, not in the frame"));
+}
+
+#[test]
+fn multiplexor_loop() {
+    let mut db = parse_dbc_str(MUX).unwrap();
+    set_multiplexors(&mut db, "Sub", &["Deep"]);
+    let issues = mux_issues(&db);
+    assert!(
+        issues.iter().any(|i| i.ends_with("Sub multiplexes itself")),
+        "{:?}",
+        issues
+    );
+}
+
+#[test]
+fn mux_range_gap() {
+    let src = MUX.replace("Deeper Sub 5-6", "Deeper Sub 7-9");
+    let db = parse_dbc_str(&src).unwrap();
+    assert_eq!(
+        mux_issues(&db),
+        ["gap in multiplexor values, Status: Sub 5-6 selects nothing"]
+    );
+}
+
+#[test]
+fn overlapping_selectors() {
+    let src = MUX.replace("Deeper Sub 5-6", "Deeper Sub 4-6");
+    let db = parse_dbc_str(&src).unwrap();
+    let overlaps = db.overlaps();
+    assert_eq!(overlaps.len(), 1, "{:?}", overlaps);
+    assert_eq!(
+        (&*overlaps[0].signals.0, &*overlaps[0].signals.1),
+        ("Deep", "Deeper")
+    );
+    let bit = |b| BitPos::new(b).unwrap();
+    assert_eq!(overlaps[0].bits, [(bit(24), bit(31))]);
+}
+
+// a frame referring to a signal that isn't defined, possible when editing the fields directly
+#[test]
+fn dangling_signal() {
+    let mut db = parse_dbc_str(MUX).unwrap();
+    db.messages
+        .get_mut("Status")
+        .unwrap()
+        .signals
+        .push("Ghost".into());
+    let errors = db.validate();
+    let unknown: Vec<String> = errors
+        .iter()
+        .filter(|e| matches!(e.kind(), Error::UnknownSignal))
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(unknown, ["unknown signal, Status: Ghost isn't defined"]);
+    assert!(db.overlaps().is_empty());
+
+    let unknown = |e: Error| assert!(matches!(e.kind(), Error::UnknownSignal), "{}", e);
+    unknown(write_dbc_string(&db).unwrap_err());
+    unknown(db.decode_message("Status", &[0; 4]).unwrap_err());
+    unknown(db.encode_message("Status", &HashMap::new()).unwrap_err());
+    db.messages
+        .get_mut("Status")
+        .unwrap()
+        .signals
+        .retain(|s| &**s != "Page");
+    unknown(db.add_signal_to_message("Status", "Page").unwrap_err());
+}
+
+// issues of the kinds given found, as "kind, hint"
+fn issues(db: &Database, kind: fn(&Error) -> bool) -> Vec<String> {
+    db.validate()
+        .iter()
+        .filter(|e| kind(e.kind()))
+        .map(|e| e.to_string())
+        .collect()
+}
+
+fn ldf_data(db: &mut Database) -> &mut LDFData {
+    match &mut db.extra {
+        DatabaseType::LDF(data) => data,
+        _ => panic!("not an LDF"),
+    }
+}
+
+fn nad_issue(e: &Error) -> bool {
+    matches!(
+        e,
+        Error::InvalidNAD | Error::DuplicateNAD | Error::UnknownNode
+    )
+}
+
+#[test]
+fn valid_nads() {
+    let db = parse_ldf_str(LDF).unwrap();
+    assert_eq!(issues(&db, nad_issue), Vec::<String>::new());
+}
+
+#[test]
+fn nad_issues() {
+    let mut db = parse_ldf_str(LDF).unwrap();
+    let data = ldf_data(&mut db);
+    data.responders.get_mut("RSM").unwrap().configured_nad = 0x21; // LSM's
+    data.responders.get_mut("LSM").unwrap().initial_nad = Some(0x7E);
+    assert_eq!(
+        issues(&db, nad_issue),
+        [
+            "NAD outside 0x01-0x7D, LSM initial NAD 0x7E",
+            "duplicate NAD, LSM and RSM both have configured NAD 0x21"
+        ]
+    );
+
+    // AssignNAD {LSM} goes to LSM's initial NAD
+    let mut db = parse_ldf_str(LDF).unwrap();
+    ldf_data(&mut db)
+        .responders
+        .get_mut("RSM")
+        .unwrap()
+        .initial_nad = Some(0x01);
+    assert_eq!(
+        issues(&db, nad_issue),
+        [
+            "duplicate NAD, LSM and RSM both have initial NAD 0x01",
+            "duplicate NAD, Configuration_Schedule: AssignNAD {LSM} reaches RSM too"
+        ]
+    );
+}
+
+fn frame_id_issue(e: &Error) -> bool {
+    matches!(e, Error::InvalidFrameId | Error::ReservedFrameId)
+}
+
+#[test]
+fn valid_frame_ids() {
+    let db = parse_ldf_str(LDF).unwrap();
+    assert_eq!(issues(&db, frame_id_issue), Vec::<String>::new());
+}
+
+#[test]
+fn frame_id_ranges() {
+    let mut db = parse_ldf_str(LDF).unwrap();
+    for (frame, id) in [("CEM_Frm1", 0x40), ("LSM_Frm1", 0x3C), ("RSM_Frm2", 0x3F)] {
+        db.messages.get_mut(frame).unwrap().id = id;
+    }
+    ldf_data(&mut db)
+        .event_frames
+        .get_mut("Node_Status_Event")
+        .unwrap()
+        .1 = 0x3E;
+    assert_eq!(
+        issues(&db, frame_id_issue),
+        [
+            "frame id outside 0-59, frame CEM_Frm1 uses 0x40",
+            "reserved frame id, frame LSM_Frm1 uses 0x3C, only for diagnostic frames",
+            "reserved frame id, frame RSM_Frm2 uses 0x3F",
+            "reserved frame id, event triggered frame Node_Status_Event uses 0x3E"
+        ]
+    );
+}
+
+fn collision_issue(e: &Error) -> bool {
+    matches!(
+        e,
+        Error::DuplicateFrameId | Error::SporadicFrameInEventFrame
+    )
+}
+
+#[test]
+fn no_frame_collisions() {
+    let mut db = parse_ldf_str(LDF).unwrap();
+    let sporadic = vec![Name::from("LSM_Frm2"), Name::from("RSM_Frm2")];
+    ldf_data(&mut db)
+        .sporadic_frames
+        .insert("Sporadic".into(), sporadic);
+    assert_eq!(issues(&db, collision_issue), Vec::<String>::new());
+}
+
+#[test]
+fn frame_collisions() {
+    let mut db = parse_ldf_str(LDF).unwrap();
+    db.messages.get_mut("LSM_Frm2").unwrap().id = 0x02; // LSM_Frm1's
+    let data = ldf_data(&mut db);
+    data.event_frames.get_mut("Node_Status_Event").unwrap().1 = 0x01; // CEM_Frm1's
+    let sporadic = vec![Name::from("LSM_Frm2"), Name::from("RSM_Frm1")];
+    data.sporadic_frames.insert("Sporadic".into(), sporadic);
+    assert_eq!(
+        issues(&db, collision_issue),
+        [
+            "duplicate frame id, frame LSM_Frm1 and frame LSM_Frm2 both use 0x02",
+            "duplicate frame id, frame CEM_Frm1 and event triggered frame Node_Status_Event both use 0x01",
+            "frame in both a sporadic and an event triggered frame, RSM_Frm1 is in sporadic frame Sporadic and event triggered frame Node_Status_Event"
+        ]
+    );
+}
+
+fn slot_issue(e: &Error) -> bool {
+    matches!(e, Error::ScheduleSlotTooShort)
+}
+
+#[test]
+fn feasible_schedules() {
+    let db = parse_ldf_str(LDF).unwrap();
+    assert_eq!(issues(&db, slot_issue), Vec::<String>::new());
+}
+
+#[test]
+fn slot_too_short() {
+    let mut db = parse_ldf_str(LDF).unwrap();
+    let normal = ldf_data(&mut db)
+        .schedule_tables
+        .get_mut("Normal_Schedule")
+        .unwrap();
+    normal[1].1 = 2.0; // LSM_Frm2
+    assert_eq!(
+        issues(&db, slot_issue),
+        ["schedule slot shorter than its frame, Normal_Schedule slot 1 (LSM_Frm2) is 2 ms, needs 4.037 ms"]
+    );
+}
+
+fn first_byte_issue(e: &Error) -> bool {
+    matches!(e, Error::EventFrameFirstByteUsed)
+}
+
+#[test]
+fn event_frames_keep_first_byte() {
+    let db = parse_ldf_str(LDF).unwrap();
+    assert_eq!(issues(&db, first_byte_issue), Vec::<String>::new());
+}
+
+#[test]
+fn event_frame_first_byte_used() {
+    let mut db = parse_ldf_str(LDF).unwrap();
+    db.signals
+        .get_mut("RightIntLightsSwitch")
+        .unwrap()
+        .bit_start = 4;
+    db.signals.get_mut("RSMerror").unwrap().bit_start = 1; // RSM_Frm2 isn't carried
+    assert_eq!(
+        issues(&db, first_byte_issue),
+        ["event triggered frame has a signal in its first byte, RightIntLightsSwitch in RSM_Frm1 of Node_Status_Event starts at bit 4"]
+    );
+}